@@ -0,0 +1,176 @@
+//! Command line interface of the library, a thin wrapper of the [`translate_dir_lib::project`]
+//! API. Built with the `cli_support` feature.
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+use clap::{Parser, Subcommand};
+use translate_dir_lib::{
+    errors::{
+        project_errors::{SetSourceDirError, TranslateFileError},
+        ErrorInfo,
+    },
+    project, shutdown, Language,
+};
+
+#[derive(Parser)]
+#[command(
+    name = "translate-dir",
+    about = "Translates directories of documents with LLMs"
+)]
+struct Cli {
+    /// directory of the project or any directory inside of it
+    #[arg(short, long, global = true, default_value = ".")]
+    project: PathBuf,
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Initializes a project in the directory
+    Init {
+        name: String,
+        #[arg(default_value = ".")]
+        path: PathBuf,
+    },
+    /// Sets the source directory of the project and its language, the language is detected
+    /// from the files if it isn't given
+    SetSource { dir: String, lang: Option<Language> },
+    /// Adds another directory in the source language, mirrored in the directory of its name
+    /// inside every target directory
+    AddSource { dir: String },
+    /// Adds a target language, its directory is created. A language that isn't built in is
+    /// given as `<code>:<name>` (e.g. `haw:Hawaiian`).
+    AddLang {
+        lang: Language,
+        /// directory of the language relative to the project root (e.g. `content/fr`),
+        /// `<project name><language suffix>` if it isn't given
+        #[arg(long)]
+        dir: Option<String>,
+    },
+    /// Syncs the untranslatable files into the target directories
+    Sync {
+        /// overwrite the copies edited by hand
+        #[arg(long)]
+        force: bool,
+    },
+    /// Translates the files into the language, all the translatable files if none is given
+    Translate {
+        /// code of a language of the project (e.g. `fr`, `haw`) or the name of a built-in one
+        lang: String,
+        files: Vec<PathBuf>,
+        /// overwrite the translations of the given files edited by hand
+        #[arg(long, requires = "files")]
+        force: bool,
+    },
+    /// Shows the state of the translations
+    Status {
+        /// print the status as JSON
+        #[arg(long)]
+        json: bool,
+    },
+}
+
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+    match run(cli) {
+        Ok(code) => code,
+        Err((code, message)) => {
+            eprintln!("error [{}]: {}", code, message);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+/// Runs the command, the error is its stable code and its message
+fn run(cli: Cli) -> Result<ExitCode, (&'static str, String)> {
+    if let Command::Init { name, path } = &cli.command {
+        project::init(name, path.clone()).map_err(describe)?;
+        return Ok(ExitCode::SUCCESS);
+    }
+    let mut project = project::load(std::path::absolute(&cli.project).unwrap_or(cli.project))
+        .map_err(describe)?;
+    match cli.command {
+        Command::Init { .. } => unreachable!("handled before loading the project"),
+        Command::SetSource { dir, lang } => {
+            let lang = match lang {
+                Some(lang) => lang,
+                None => {
+                    let detected = project.detect_source_language(&dir).map_err(describe)?;
+                    let lang = detected
+                        .ok_or(SetSourceDirError::UndetectedLanguage)
+                        .map_err(describe)?;
+                    println!("detected language: {}", lang);
+                    lang
+                }
+            };
+            project.set_source_dir(&dir, lang).map_err(describe)?
+        }
+        Command::AddSource { dir } => project.add_source_dir(&dir).map_err(describe)?,
+        Command::AddLang { lang, dir } => match dir {
+            Some(dir) => project.add_lang_with_dir(lang, &dir).map_err(describe)?,
+            None => project.add_lang(lang).map_err(describe)?,
+        },
+        Command::Sync { force } => {
+            let report = project.sync_files(force).map_err(describe)?;
+            println!(
+                "{} copied, {} up to date, {} removed",
+                report.copied.len(),
+                report.skipped.len(),
+                report.removed.len()
+            );
+            for (path, error) in &report.errors {
+                eprintln!("{}: {}", path.display(), error);
+            }
+            if !report.is_success() {
+                return Ok(ExitCode::FAILURE);
+            }
+        }
+        Command::Translate { lang, files, force } => {
+            // a custom language is only known to the project by its code, the parsed one has no
+            // name and wouldn't be equal to it
+            let lang = project
+                .get_config_as_ref()
+                .get_lang_by_code(&lang)
+                .or_else(|| lang.parse().ok())
+                .ok_or(TranslateFileError::TargetLanguageNotInProject)
+                .map_err(describe)?;
+            shutdown::install_signal_handlers();
+            if files.is_empty() {
+                project.translate_all(lang.clone()).map_err(describe)?;
+            }
+            for file in files {
+                let path = std::path::absolute(&file).unwrap_or(file);
+                project
+                    .translate_file(path, lang.clone(), force)
+                    .map_err(describe)?;
+            }
+        }
+        Command::Status { json } => {
+            let status = project.status().map_err(describe)?;
+            if json {
+                println!(
+                    "{}",
+                    serde_json::to_string_pretty(&status).unwrap_or_default()
+                );
+            } else {
+                for entry in &status.entries {
+                    println!(
+                        "{:<10} {} {}",
+                        entry.state.get_name(),
+                        entry.lang.get_code(),
+                        entry.path.display()
+                    );
+                }
+            }
+            if !status.is_clean() {
+                return Ok(ExitCode::FAILURE);
+            }
+        }
+    }
+    Ok(ExitCode::SUCCESS)
+}
+
+fn describe<E: ErrorInfo + std::fmt::Display>(e: E) -> (&'static str, String) {
+    (e.code(), e.to_string())
+}
@@ -0,0 +1,110 @@
+//! A portable bundle of a project: its config, its prompts, its state (e.g. the chunk
+//! boundaries) and optionally its content in one JSON file with paths relative to the project
+//! root, to move the project to another machine or to hand it to a collaborator intact.
+use std::path::{Path, PathBuf};
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+
+use crate::{errors::bundle_errors::BundleError, helper::write_atomic};
+
+/// Version of the bundle format written by this library
+pub const BUNDLE_VERSION: u32 = 1;
+
+#[derive(Debug, Clone, Default)]
+/// What is put into a bundle besides the config, the prompts and the state
+pub struct BundleOptions {
+    /// if true, the files of the source and the target directories are included
+    pub include_content: bool,
+}
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+/// A file of the bundle
+pub struct BundleEntry {
+    /// path relative to the project root, with `/` separators
+    pub path: String,
+    /// contents of the file in base64
+    pub data: String,
+}
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct Bundle {
+    pub version: u32,
+    /// time of the export, RFC 3339
+    pub created_at: String,
+    pub entries: Vec<BundleEntry>,
+}
+
+impl Bundle {
+    pub fn new() -> Self {
+        Self {
+            version: BUNDLE_VERSION,
+            created_at: chrono::Utc::now().to_rfc3339(),
+            entries: Vec::new(),
+        }
+    }
+    /// Adds a file with the given contents under the path relative to the project root
+    pub fn add(&mut self, path: &Path, contents: &[u8]) {
+        let path = path
+            .components()
+            .map(|c| c.as_os_str().to_string_lossy())
+            .collect::<Vec<_>>()
+            .join("/");
+        self.entries.retain(|e| e.path != path);
+        self.entries.push(BundleEntry {
+            path,
+            data: STANDARD.encode(contents),
+        });
+    }
+    /// Adds the file of the project, _path_ must be inside of _root_
+    pub fn add_file(&mut self, root: &Path, path: &Path) -> std::io::Result<()> {
+        let contents = std::fs::read(path)?;
+        let rel = path.strip_prefix(root).map_err(std::io::Error::other)?;
+        self.add(rel, &contents);
+        Ok(())
+    }
+    /// Adds every file under the directory recursively, symlinks are skipped
+    pub fn add_dir(&mut self, root: &Path, dir: &Path) -> std::io::Result<()> {
+        for entry in std::fs::read_dir(dir)? {
+            let entry = entry?;
+            let file_type = entry.file_type()?;
+            if file_type.is_dir() {
+                self.add_dir(root, &entry.path())?;
+            } else if file_type.is_file() {
+                self.add_file(root, &entry.path())?;
+            }
+        }
+        Ok(())
+    }
+    /// Returns the decoded contents of the entries with their relative paths
+    pub fn decode_entries(&self) -> Result<Vec<(PathBuf, Vec<u8>)>, BundleError> {
+        self.entries
+            .iter()
+            .map(|e| {
+                let data = STANDARD
+                    .decode(&e.data)
+                    .map_err(|err| BundleError::InvalidBundle(format!("{}: {}", e.path, err)))?;
+                Ok((PathBuf::from(&e.path), data))
+            })
+            .collect()
+    }
+    /// Reads the bundle, fails if its version isn't supported
+    pub fn read(path: &Path) -> Result<Self, BundleError> {
+        let contents = std::fs::read_to_string(path).map_err(BundleError::IoError)?;
+        let bundle: Bundle = serde_json::from_str(&contents)
+            .map_err(|e| BundleError::InvalidBundle(e.to_string()))?;
+        if bundle.version > BUNDLE_VERSION {
+            return Err(BundleError::UnsupportedVersion(bundle.version));
+        }
+        Ok(bundle)
+    }
+    pub fn write(&self, path: &Path) -> std::io::Result<()> {
+        let contents = serde_json::to_string(self).map_err(std::io::Error::other)?;
+        write_atomic(path, contents)
+    }
+}
+
+impl Default for Bundle {
+    fn default() -> Self {
+        Self::new()
+    }
+}
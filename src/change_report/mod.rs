@@ -0,0 +1,105 @@
+//! Machine readable summary of what a run changed, written to a well-known path of the state
+//! directory ([`CHANGE_REPORT_FILE`]) if enabled in the project config, so CI pipelines can post
+//! it to pull requests without re-deriving it from the file system.
+use std::path::{Path, PathBuf};
+
+use crate::{
+    state::{get_state_dir, write_json},
+    Language,
+};
+
+/// Name of the file with the report of the last run, in the state directory
+pub const CHANGE_REPORT_FILE: &str = "last_run.json";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub enum ChangeKind {
+    /// an untranslatable file was copied into a target directory
+    Copied,
+    /// a file or a directory that isn't in the source directory was removed
+    Removed,
+    /// a translatable file was translated
+    Translated,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct Change {
+    /// path of the changed file in the target directory, relative to the project root
+    pub path: PathBuf,
+    pub lang: Language,
+    pub kind: ChangeKind,
+}
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+/// Everything a sync or a translation run changed
+pub struct ChangeReport {
+    /// the operation that was run, `sync` or `translate`
+    pub operation: String,
+    /// start and end of the run, RFC 3339
+    pub started_at: String,
+    pub finished_at: String,
+    /// false if the run stopped with an error, the changes made before it are still listed
+    pub succeeded: bool,
+    pub changes: Vec<Change>,
+}
+
+impl ChangeReport {
+    pub fn new(operation: &str) -> Self {
+        Self {
+            operation: operation.to_string(),
+            started_at: chrono::Utc::now().to_rfc3339(),
+            finished_at: String::new(),
+            succeeded: false,
+            changes: Vec::new(),
+        }
+    }
+    /// Records the change of the file, the path is made relative to the project _root_
+    pub fn push(&mut self, root: &Path, path: &Path, lang: Language, kind: ChangeKind) {
+        self.changes.push(Change {
+            path: path.strip_prefix(root).unwrap_or(path).to_path_buf(),
+            lang,
+            kind,
+        });
+    }
+    /// returns the number of changes of the kind
+    pub fn count(&self, kind: ChangeKind) -> usize {
+        self.changes.iter().filter(|c| c.kind == kind).count()
+    }
+    /// Marks the end of the run and writes the report to [`get_change_report_path`]
+    pub fn finish(&mut self, root: &Path, succeeded: bool) -> std::io::Result<()> {
+        self.finished_at = chrono::Utc::now().to_rfc3339();
+        self.succeeded = succeeded;
+        write_json(&get_change_report_path(root), self)
+    }
+}
+
+/// Returns the path the report of the last run of the project is written to
+pub fn get_change_report_path(project_root: &Path) -> PathBuf {
+    get_state_dir(project_root).join(CHANGE_REPORT_FILE)
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+/// What a sync did, returned by [`crate::project::Project::sync_files`]
+pub struct SyncReport {
+    /// untranslatable files copied into the target directories
+    pub copied: Vec<PathBuf>,
+    /// untranslatable files whose copy was already up to date
+    pub skipped: Vec<PathBuf>,
+    /// files and directories removed from the target directories
+    pub removed: Vec<PathBuf>,
+    /// files that couldn't be copied, with the reason
+    pub errors: Vec<(PathBuf, String)>,
+}
+
+impl SyncReport {
+    /// Adds the entries of the _other_ report to this one
+    pub fn extend(&mut self, other: SyncReport) {
+        self.copied.extend(other.copied);
+        self.skipped.extend(other.skipped);
+        self.removed.extend(other.removed);
+        self.errors.extend(other.errors);
+    }
+    /// returns true if every file was synced
+    pub fn is_success(&self) -> bool {
+        self.errors.is_empty()
+    }
+}
@@ -0,0 +1,36 @@
+//! Confirmation of the destructive operations of a [`crate::project::Project`]. An embedding
+//! application sets a [`ConfirmHandler`] on the project to prompt the user or to enforce its
+//! policies before anything is deleted, the operation fails if the handler declines it.
+use std::path::{Path, PathBuf};
+
+use crate::Language;
+
+#[derive(Debug, Clone, Copy)]
+/// A destructive operation the handler is asked to confirm
+pub enum DestructiveOp<'a> {
+    /// the target directory of the language is removed with the language
+    RemoveLang { lang: &'a Language, dir: &'a Path },
+    /// the sync removes the files and directories of the target directories that aren't in
+    /// the source directory anymore
+    SyncRemovals { paths: &'a [PathBuf] },
+}
+
+/// Consulted before a destructive operation, returns true to let it proceed
+pub trait ConfirmHandler: Send + Sync {
+    fn confirm(&self, op: &DestructiveOp) -> bool;
+}
+
+impl<F> ConfirmHandler for F
+where
+    F: Fn(&DestructiveOp) -> bool + Send + Sync,
+{
+    fn confirm(&self, op: &DestructiveOp) -> bool {
+        self(op)
+    }
+}
+
+impl std::fmt::Debug for dyn ConfirmHandler {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("ConfirmHandler")
+    }
+}
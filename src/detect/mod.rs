@@ -0,0 +1,271 @@
+//! Local detection of the language of a text, without a request to the model: the writing
+//! system picks the language when only one built-in language uses it, the languages written in
+//! the Latin or the Cyrillic script are told apart by their most frequent words.
+use std::{
+    io::Read,
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    format::{handler_for_path, Segment},
+    glossary::extract::is_stop_word,
+    project_config::{is_excluded, SymlinkPolicy},
+    Language,
+};
+
+/// Maximal number of characters of the sources sampled by [`sample_dir`]
+pub const SAMPLE_CHARS: usize = 64 * 1024;
+/// Maximal number of files sampled by [`sample_dir`]
+pub const SAMPLE_FILES: usize = 20;
+/// Minimal number of letters of a text its language is detected in
+const MIN_LETTERS: usize = 20;
+/// Minimal number of the frequent words of the detected language in a text in the Latin script
+const MIN_STOP_WORDS: usize = 3;
+
+/// Languages written in the Latin script, told apart by their frequent words
+const LATIN_LANGUAGES: [Language; 9] = [
+    Language::English,
+    Language::French,
+    Language::German,
+    Language::Spanish,
+    Language::Italian,
+    Language::Portuguese,
+    Language::Dutch,
+    Language::Polish,
+    Language::Turkish,
+];
+
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+/// The detected language of a text
+pub struct Detection {
+    pub language: Language,
+    /// share of the evidence pointing at the language, from 0 to 1
+    pub confidence: f64,
+}
+
+#[derive(Debug, Default)]
+/// Number of letters of the text in every writing system
+struct ScriptCounts {
+    latin: usize,
+    cyrillic: usize,
+    arabic: usize,
+    hangul: usize,
+    kana: usize,
+    han: usize,
+}
+
+impl ScriptCounts {
+    fn count(text: &str) -> Self {
+        let mut res = ScriptCounts::default();
+        for c in text.chars().filter(|c| c.is_alphabetic()) {
+            match c as u32 {
+                0x0041..=0x024F => res.latin += 1,
+                0x0400..=0x04FF => res.cyrillic += 1,
+                0x0600..=0x06FF | 0x0750..=0x077F => res.arabic += 1,
+                0x1100..=0x11FF | 0x3130..=0x318F | 0xAC00..=0xD7AF => res.hangul += 1,
+                0x3040..=0x30FF => res.kana += 1,
+                0x3400..=0x4DBF | 0x4E00..=0x9FFF => res.han += 1,
+                _ => {}
+            }
+        }
+        res
+    }
+    fn total(&self) -> usize {
+        self.latin + self.cyrillic + self.arabic + self.hangul + self.kana + self.han
+    }
+}
+
+/// Detects the language of the text among the built-in languages, `None` if the text is too
+/// short or doesn't look like any of them
+pub fn detect_language(text: &str) -> Option<Detection> {
+    let counts = ScriptCounts::count(text);
+    let total = counts.total();
+    if total < MIN_LETTERS {
+        return None;
+    }
+    let share = |n: usize| n as f64 / total as f64;
+    // the Japanese text mixes the kana with the Chinese characters
+    let cjk = counts.kana + counts.han;
+    let (language, confidence) = if counts.kana > 0 && cjk * 2 > total {
+        (Language::Japanese, share(cjk))
+    } else if counts.han * 2 > total {
+        (Language::Chinese, share(counts.han))
+    } else if counts.hangul * 2 > total {
+        (Language::Korean, share(counts.hangul))
+    } else if counts.arabic * 2 > total {
+        // Persian and Urdu are written with the same script and extra letters
+        if text
+            .chars()
+            .any(|c| matches!(c, 'پ' | 'چ' | 'ژ' | 'گ' | 'ڈ' | 'ٹ'))
+        {
+            return None;
+        }
+        (Language::Arabic, share(counts.arabic))
+    } else if counts.cyrillic * 2 > total {
+        detect_cyrillic(text)?
+    } else if counts.latin * 2 > total {
+        let (language, confidence) = best_by_stop_words(text, &LATIN_LANGUAGES)?;
+        (language, confidence * share(counts.latin))
+    } else {
+        return None;
+    };
+    Some(Detection {
+        language,
+        confidence,
+    })
+}
+
+/// Tells Ukrainian from Russian by the letters only one of them has, by their frequent words if
+/// there are none
+fn detect_cyrillic(text: &str) -> Option<(Language, f64)> {
+    let ukrainian = text
+        .chars()
+        .filter(|c| matches!(c.to_lowercase().next(), Some('і' | 'ї' | 'є' | 'ґ')))
+        .count();
+    let russian = text
+        .chars()
+        .filter(|c| matches!(c.to_lowercase().next(), Some('ы' | 'э' | 'ъ' | 'ё')))
+        .count();
+    match ukrainian + russian {
+        0 => best_by_stop_words(text, &[Language::Ukrainian, Language::Russian]),
+        letters => {
+            let language = if ukrainian >= russian {
+                Language::Ukrainian
+            } else {
+                Language::Russian
+            };
+            Some((language, ukrainian.max(russian) as f64 / letters as f64))
+        }
+    }
+}
+
+/// Returns the language of _candidates_ with the most frequent words in the text and its share
+/// of the frequent words found, `None` if there are too few or there's a tie
+fn best_by_stop_words(text: &str, candidates: &[Language]) -> Option<(Language, f64)> {
+    let mut hits = vec![0usize; candidates.len()];
+    for word in text.split(|c: char| !c.is_alphabetic()) {
+        if word.is_empty() {
+            continue;
+        }
+        for (lang, count) in candidates.iter().zip(hits.iter_mut()) {
+            if is_stop_word(word, lang.clone()) {
+                *count += 1;
+            }
+        }
+    }
+    let mut ranked: Vec<(Language, usize)> = candidates.iter().cloned().zip(hits).collect();
+    ranked.sort_by_key(|r| std::cmp::Reverse(r.1));
+    let (best, best_hits) = ranked[0].clone();
+    let second_hits = ranked.get(1).map(|r| r.1).unwrap_or_default();
+    if best_hits < MIN_STOP_WORDS || best_hits == second_hits {
+        return None;
+    }
+    Some((best, best_hits as f64 / (best_hits + second_hits) as f64))
+}
+
+/// Returns the translatable parts of the text files of the directory, the sampling stops after
+/// [`SAMPLE_FILES`] files or [`SAMPLE_CHARS`] characters. The hidden and the _exclude_d files are
+/// skipped, see [`is_excluded`]. The links are only followed with [`SymlinkPolicy::Follow`], a
+/// link loop is cut where it closes.
+pub fn sample_dir(dir: &Path, exclude: &[String], links: SymlinkPolicy) -> std::io::Result<String> {
+    let mut sample = String::new();
+    let mut files = 0;
+    let mut ancestors = vec![std::fs::canonicalize(dir)?];
+    let mut ctx = SampleContext {
+        root: dir,
+        exclude,
+        links,
+        sample: &mut sample,
+        files: &mut files,
+        ancestors: &mut ancestors,
+    };
+    sample_dir_rec(&mut ctx, dir)?;
+    Ok(sample)
+}
+
+/// The state of a [`sample_dir`] walk
+struct SampleContext<'a> {
+    root: &'a Path,
+    exclude: &'a [String],
+    links: SymlinkPolicy,
+    sample: &'a mut String,
+    files: &'a mut usize,
+    /// the resolved paths of the directories being walked, to cut the link loops
+    ancestors: &'a mut Vec<PathBuf>,
+}
+
+fn sample_dir_rec(ctx: &mut SampleContext, dir: &Path) -> std::io::Result<()> {
+    let mut entries = Vec::new();
+    for entry in std::fs::read_dir(dir)? {
+        entries.push(entry?.path());
+    }
+    entries.sort();
+    for path in entries {
+        if *ctx.files >= SAMPLE_FILES || ctx.sample.chars().count() >= SAMPLE_CHARS {
+            break;
+        }
+        let hidden = path
+            .file_name()
+            .is_some_and(|n| n.to_string_lossy().starts_with('.'));
+        let mut meta = std::fs::symlink_metadata(&path)?;
+        if meta.file_type().is_symlink() {
+            if ctx.links != SymlinkPolicy::Follow {
+                continue;
+            }
+            // a dangling link has nothing to sample
+            let Ok(resolved) = std::fs::metadata(&path) else {
+                continue;
+            };
+            meta = resolved;
+        }
+        let relative = path.strip_prefix(ctx.root).unwrap_or(&path);
+        if hidden || is_excluded(ctx.exclude, relative, meta.is_dir()) {
+            continue;
+        }
+        if meta.is_dir() {
+            let real = std::fs::canonicalize(&path)?;
+            if ctx.ancestors.contains(&real) {
+                continue;
+            }
+            ctx.ancestors.push(real);
+            let res = sample_dir_rec(ctx, &path);
+            ctx.ancestors.pop();
+            res?;
+            continue;
+        }
+        if !meta.is_file() {
+            continue;
+        }
+        // a binary file isn't a text to sample
+        let Some(contents) = read_start(&path)? else {
+            continue;
+        };
+        for segment in handler_for_path(&path).extract(&contents) {
+            if let Segment::Translatable(text) = segment {
+                ctx.sample.push_str(&text);
+            }
+        }
+        ctx.sample.push('\n');
+        *ctx.files += 1;
+    }
+    Ok(())
+}
+
+/// Reads the first [`SAMPLE_CHARS`] bytes of the file, `None` if it isn't a UTF-8 text
+fn read_start(path: &Path) -> std::io::Result<Option<String>> {
+    let mut bytes = Vec::new();
+    std::fs::File::open(path)?
+        .take(SAMPLE_CHARS as u64)
+        .read_to_end(&mut bytes)?;
+    match String::from_utf8(bytes) {
+        Ok(text) => Ok(Some(text)),
+        // the limit may cut a character in two
+        Err(e) if e.utf8_error().error_len().is_none() => {
+            let valid = e.utf8_error().valid_up_to();
+            let mut bytes = e.into_bytes();
+            bytes.truncate(valid);
+            Ok(String::from_utf8(bytes).ok())
+        }
+        Err(_) => Ok(None),
+    }
+}
@@ -0,0 +1,52 @@
+//! Diagnostics of a project and of its environment, run before a long translation so a broken
+//! setup fails fast instead of in the middle of the run.
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+/// Outcome of a single check
+pub enum CheckStatus {
+    Ok,
+    /// the run can start but something is likely wrong
+    Warning,
+    /// the run will fail
+    Failed,
+}
+
+#[derive(Debug, Clone, serde::Serialize)]
+/// Result of a single check
+pub struct DoctorCheck {
+    /// short stable name of the check, e.g. `credentials`
+    pub name: &'static str,
+    pub status: CheckStatus,
+    /// human readable explanation, tells what to do if the check didn't pass
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Default, serde::Serialize)]
+/// Results of all the checks of [`crate::project::Project::doctor`]
+pub struct DoctorReport {
+    checks: Vec<DoctorCheck>,
+}
+
+impl DoctorReport {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    pub(crate) fn push(&mut self, name: &'static str, status: CheckStatus, message: String) {
+        self.checks.push(DoctorCheck {
+            name,
+            status,
+            message,
+        });
+    }
+    pub fn get_checks_as_ref(&self) -> &Vec<DoctorCheck> {
+        &self.checks
+    }
+    /// returns only the checks that didn't pass
+    pub fn problems(&self) -> impl Iterator<Item = &DoctorCheck> {
+        self.checks.iter().filter(|c| c.status != CheckStatus::Ok)
+    }
+    /// returns true if no check failed, warnings are allowed
+    pub fn is_ok(&self) -> bool {
+        self.checks.iter().all(|c| c.status != CheckStatus::Failed)
+    }
+}
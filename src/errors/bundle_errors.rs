@@ -0,0 +1,54 @@
+use std::path::PathBuf;
+
+use thiserror::Error;
+
+use super::{
+    io_error_category, project_config_errors::WriteConfigError, project_errors::LoadProjectError,
+    sandbox_errors::SandboxError, ErrorCategory, ErrorInfo,
+};
+
+#[derive(Error, Debug)]
+pub enum BundleError {
+    #[error("the file isn't a project bundle: {0}")]
+    InvalidBundle(String),
+    #[error("the bundle has the version {0} that isn't supported")]
+    UnsupportedVersion(u32),
+    #[error("the destination already contains a project")]
+    AlreadyAProject,
+    #[error("the destination isn't a directory")]
+    InvalidDestination,
+    #[error("the bundle would overwrite the existing file {}", .0.display())]
+    WouldOverwrite(PathBuf),
+    #[error("the bundle contains a path outside of the project: {0}")]
+    PathOutsideProject(SandboxError),
+    #[error("config writing error {0}")]
+    ConfigWritingError(WriteConfigError),
+    #[error("couldn't load the imported project: {0}")]
+    LoadProjectError(LoadProjectError),
+    #[error("io error: {0}")]
+    IoError(std::io::Error),
+}
+
+impl ErrorInfo for BundleError {
+    fn code(&self) -> &'static str {
+        match self {
+            BundleError::InvalidBundle(_) => "bundle.invalid",
+            BundleError::UnsupportedVersion(_) => "bundle.unsupported_version",
+            BundleError::AlreadyAProject => "bundle.already_a_project",
+            BundleError::InvalidDestination => "bundle.invalid_destination",
+            BundleError::WouldOverwrite(_) => "bundle.would_overwrite",
+            BundleError::PathOutsideProject(_) => "bundle.path_outside_project",
+            BundleError::ConfigWritingError(_) => "bundle.config_writing",
+            BundleError::LoadProjectError(_) => "bundle.load_project",
+            BundleError::IoError(_) => "bundle.io",
+        }
+    }
+    fn category(&self) -> ErrorCategory {
+        match self {
+            BundleError::ConfigWritingError(e) => e.category(),
+            BundleError::LoadProjectError(e) => e.category(),
+            BundleError::IoError(e) => io_error_category(e),
+            _ => ErrorCategory::UserError,
+        }
+    }
+}
@@ -0,0 +1,64 @@
+use thiserror::Error;
+
+use super::{
+    format_errors::ReinsertError, io_error_category, lock_errors::LockError,
+    project_errors::GetTranslatableFilesError, sandbox_errors::SandboxError, ErrorCategory,
+    ErrorInfo,
+};
+
+#[derive(Error, Debug)]
+pub enum ExchangeError {
+    #[error("no source language to translate from")]
+    NoSourceLang,
+    #[error("couldn't load the translatable files: {0}")]
+    TranslatableFilesError(GetTranslatableFilesError),
+    #[error("there's no such target language")]
+    TargetLanguageNotInProject,
+    #[error("the document is translated from {0}, not from the source language of the project")]
+    SourceLanguageMismatch(String),
+    #[error("the file isn't a valid XLIFF file: {0}")]
+    InvalidXliff(String),
+    #[error("the file isn't a valid PO file: {0}")]
+    InvalidPo(String),
+    #[error("the source file {0} changed since the segments were extracted")]
+    SourceChanged(String),
+    #[error("the unit {0} has no translation")]
+    MissingTarget(String),
+    #[error("the file {0} isn't a file of the source directory")]
+    FileNotInSource(String),
+    #[error("the file points outside of the source directory: {0}")]
+    PathOutsideProject(SandboxError),
+    #[error("the translation doesn't keep the protected regions: {0}")]
+    ReinsertError(ReinsertError),
+    #[error("io error: {0}")]
+    IoError(std::io::Error),
+    #[error("{0}")]
+    Locked(LockError),
+}
+
+impl ErrorInfo for ExchangeError {
+    fn code(&self) -> &'static str {
+        match self {
+            ExchangeError::NoSourceLang => "exchange.no_source_lang",
+            ExchangeError::TranslatableFilesError(_) => "exchange.translatable_files",
+            ExchangeError::TargetLanguageNotInProject => "exchange.lang_not_in_project",
+            ExchangeError::SourceLanguageMismatch(_) => "exchange.source_lang_mismatch",
+            ExchangeError::InvalidXliff(_) => "exchange.invalid_xliff",
+            ExchangeError::InvalidPo(_) => "exchange.invalid_po",
+            ExchangeError::SourceChanged(_) => "exchange.source_changed",
+            ExchangeError::MissingTarget(_) => "exchange.missing_target",
+            ExchangeError::FileNotInSource(_) => "exchange.file_not_in_source",
+            ExchangeError::PathOutsideProject(_) => "exchange.path_outside_project",
+            ExchangeError::ReinsertError(_) => "exchange.reinsert",
+            ExchangeError::IoError(_) => "exchange.io",
+            ExchangeError::Locked(_) => "exchange.locked",
+        }
+    }
+    fn category(&self) -> ErrorCategory {
+        match self {
+            ExchangeError::IoError(e) => io_error_category(e),
+            ExchangeError::Locked(e) => e.category(),
+            _ => ErrorCategory::UserError,
+        }
+    }
+}
@@ -0,0 +1,64 @@
+use thiserror::Error;
+
+use crate::Language;
+
+use super::{
+    io_error_category,
+    project_errors::{
+        AddLanguageError, AddTranslatableFileError, InitProjectError, LoadProjectError,
+        SetSourceDirError, SyncFilesError,
+    },
+    ErrorCategory, ErrorInfo,
+};
+
+#[derive(Error, Debug)]
+pub enum FixtureError {
+    #[error("the path of the fixture file is outside of its directory: {0}")]
+    InvalidPath(String),
+    #[error("couldn't init the project: {0}")]
+    InitProjectError(InitProjectError),
+    #[error("couldn't load the project: {0}")]
+    LoadProjectError(LoadProjectError),
+    #[error("couldn't set the source directory: {0}")]
+    SetSourceDirError(SetSourceDirError),
+    #[error("couldn't add the language: {0}")]
+    AddLanguageError(AddLanguageError),
+    #[error("couldn't make the file translatable: {0}")]
+    AddTranslatableFileError(AddTranslatableFileError),
+    #[error("couldn't sync the files: {0}")]
+    SyncFilesError(SyncFilesError),
+    #[error("{0} isn't a target language of the project")]
+    LangNotInProject(Language),
+    #[error("io error: {0}")]
+    IoError(std::io::Error),
+}
+
+impl ErrorInfo for FixtureError {
+    fn code(&self) -> &'static str {
+        match self {
+            FixtureError::InvalidPath(_) => "fixture.invalid_path",
+            FixtureError::InitProjectError(_) => "fixture.init_project",
+            FixtureError::LoadProjectError(_) => "fixture.load_project",
+            FixtureError::SetSourceDirError(_) => "fixture.set_source_dir",
+            FixtureError::AddLanguageError(_) => "fixture.add_language",
+            FixtureError::AddTranslatableFileError(_) => "fixture.add_translatable_file",
+            FixtureError::SyncFilesError(_) => "fixture.sync_files",
+            FixtureError::LangNotInProject(_) => "fixture.lang_not_in_project",
+            FixtureError::IoError(_) => "fixture.io",
+        }
+    }
+    fn category(&self) -> ErrorCategory {
+        match self {
+            FixtureError::InvalidPath(_) | FixtureError::LangNotInProject(_) => {
+                ErrorCategory::UserError
+            }
+            FixtureError::InitProjectError(e) => e.category(),
+            FixtureError::LoadProjectError(e) => e.category(),
+            FixtureError::SetSourceDirError(e) => e.category(),
+            FixtureError::AddLanguageError(e) => e.category(),
+            FixtureError::AddTranslatableFileError(e) => e.category(),
+            FixtureError::SyncFilesError(e) => e.category(),
+            FixtureError::IoError(e) => io_error_category(e),
+        }
+    }
+}
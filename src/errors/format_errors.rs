@@ -0,0 +1,52 @@
+use thiserror::Error;
+
+use super::{io_error_category, ErrorCategory, ErrorInfo};
+
+#[derive(Error, Debug)]
+pub enum ReinsertError {
+    #[error("placeholder of the protected region {0} is missing in the translation")]
+    MissingPlaceholder(usize),
+    #[error("placeholder of the protected region {0} appears more than once in the translation")]
+    DuplicatePlaceholder(usize),
+}
+
+#[derive(Error, Debug)]
+pub enum RoundTripError {
+    #[error("io error: {0}")]
+    IoError(std::io::Error),
+    #[error("the handler's segments don't add up to the original contents")]
+    LossyExtraction,
+    #[error("reinsert error: {0}")]
+    ReinsertError(ReinsertError),
+}
+
+impl ErrorInfo for ReinsertError {
+    fn code(&self) -> &'static str {
+        match self {
+            ReinsertError::MissingPlaceholder(_) => "format.missing_placeholder",
+            ReinsertError::DuplicatePlaceholder(_) => "format.duplicate_placeholder",
+        }
+    }
+    fn category(&self) -> ErrorCategory {
+        // the model mangled the placeholders, another attempt may go better
+        ErrorCategory::TransientProviderError
+    }
+}
+
+impl ErrorInfo for RoundTripError {
+    fn code(&self) -> &'static str {
+        match self {
+            RoundTripError::IoError(_) => "round_trip.io",
+            RoundTripError::LossyExtraction => "round_trip.lossy_extraction",
+            RoundTripError::ReinsertError(_) => "round_trip.reinsert",
+        }
+    }
+    fn category(&self) -> ErrorCategory {
+        match self {
+            RoundTripError::IoError(e) => io_error_category(e),
+            RoundTripError::LossyExtraction | RoundTripError::ReinsertError(_) => {
+                ErrorCategory::UserError
+            }
+        }
+    }
+}
@@ -0,0 +1,52 @@
+use thiserror::Error;
+
+use super::{
+    io_error_category, project_config_errors::WriteConfigError,
+    project_errors::GetTranslatableFilesError, ErrorCategory, ErrorInfo,
+};
+
+#[derive(Error, Debug)]
+pub enum GlossaryError {
+    #[error("\"{term}\" is already translated as \"{existing}\", can't add \"{new}\"")]
+    Conflict {
+        term: String,
+        existing: String,
+        new: String,
+    },
+    #[error("no such glossary entry")]
+    NoSuchEntry,
+    #[error("no shared store is set in the library config")]
+    NoSharedStore,
+    #[error("no source language to translate from")]
+    NoSourceLang,
+    #[error("couldn't load the translatable files: {0}")]
+    TranslatableFilesError(GetTranslatableFilesError),
+    #[error("there's no such target language")]
+    TargetLanguageNotInProject,
+    #[error("config writing error {0}")]
+    ConfigWritingError(WriteConfigError),
+    #[error("io error: {0}")]
+    IoError(std::io::Error),
+}
+
+impl ErrorInfo for GlossaryError {
+    fn code(&self) -> &'static str {
+        match self {
+            GlossaryError::Conflict { .. } => "glossary.conflict",
+            GlossaryError::NoSuchEntry => "glossary.no_such_entry",
+            GlossaryError::NoSharedStore => "glossary.no_shared_store",
+            GlossaryError::NoSourceLang => "glossary.no_source_lang",
+            GlossaryError::TranslatableFilesError(_) => "glossary.translatable_files",
+            GlossaryError::TargetLanguageNotInProject => "glossary.lang_not_in_project",
+            GlossaryError::ConfigWritingError(_) => "glossary.config_writing",
+            GlossaryError::IoError(_) => "glossary.io",
+        }
+    }
+    fn category(&self) -> ErrorCategory {
+        match self {
+            GlossaryError::ConfigWritingError(e) => e.category(),
+            GlossaryError::IoError(e) => io_error_category(e),
+            _ => ErrorCategory::UserError,
+        }
+    }
+}
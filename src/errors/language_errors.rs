@@ -0,0 +1,32 @@
+use thiserror::Error;
+
+use super::{ErrorCategory, ErrorInfo};
+
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum LanguageError {
+    #[error("'{0}' isn't a BCP-47 code")]
+    InvalidCode(String),
+    #[error("'{0}' is the code of a built-in language")]
+    BuiltInCode(String),
+    #[error("the language has no name")]
+    EmptyName,
+    #[error("'{0}' can't be the suffix of a directory name")]
+    InvalidSuffix(String),
+    #[error("'{0}' isn't the name or the code of a language")]
+    UnknownLanguage(String),
+}
+
+impl ErrorInfo for LanguageError {
+    fn code(&self) -> &'static str {
+        match self {
+            LanguageError::InvalidCode(_) => "language.invalid_code",
+            LanguageError::BuiltInCode(_) => "language.built_in_code",
+            LanguageError::EmptyName => "language.empty_name",
+            LanguageError::InvalidSuffix(_) => "language.invalid_suffix",
+            LanguageError::UnknownLanguage(_) => "language.unknown",
+        }
+    }
+    fn category(&self) -> ErrorCategory {
+        ErrorCategory::UserError
+    }
+}
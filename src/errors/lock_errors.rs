@@ -0,0 +1,30 @@
+use thiserror::Error;
+
+use super::{io_error_category, project_config_errors::LoadConfigError, ErrorCategory, ErrorInfo};
+
+#[derive(Error, Debug)]
+pub enum LockError {
+    #[error("the project is being changed by another process")]
+    Locked,
+    #[error("couldn't lock the project: {0}")]
+    IoError(std::io::Error),
+    #[error("couldn't reload the config of the locked project: {0}")]
+    ConfigError(LoadConfigError),
+}
+
+impl ErrorInfo for LockError {
+    fn code(&self) -> &'static str {
+        match self {
+            LockError::Locked => "lock.locked",
+            LockError::IoError(_) => "lock.io",
+            LockError::ConfigError(_) => "lock.config",
+        }
+    }
+    fn category(&self) -> ErrorCategory {
+        match self {
+            LockError::Locked => ErrorCategory::UserError,
+            LockError::IoError(e) => io_error_category(e),
+            LockError::ConfigError(e) => e.category(),
+        }
+    }
+}
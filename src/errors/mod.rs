@@ -1,2 +1,65 @@
+//! Error types of the library. Every error exposes a stable code and a category through the
+//! [`ErrorInfo`] trait, so frontends can decide how to react without matching on messages.
+pub mod bundle_errors;
+pub mod exchange_errors;
+pub mod fixture_errors;
+pub mod format_errors;
+pub mod glossary_errors;
+pub mod language_errors;
+pub mod lock_errors;
 pub mod project_config_errors;
 pub mod project_errors;
+pub mod prompt_errors;
+pub mod sandbox_errors;
+pub mod status_errors;
+pub mod translation_memory_errors;
+pub mod translator_errors;
+pub mod watch_errors;
+pub mod workspace_errors;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+/// The broad kind of an error
+pub enum ErrorCategory {
+    /// the operation was called with arguments or in a state that doesn't allow it, the user
+    /// has to change something
+    UserError,
+    /// a temporary problem of the provider (rate limit, overload, network), retrying later
+    /// should help
+    TransientProviderError,
+    /// a problem of the provider that won't go away by retrying (e.g. a rejected request)
+    ProviderError,
+    /// the config or the directories of the project are in an unexpected state
+    ProjectCorruption,
+    /// the file system failed
+    IoError,
+    /// the operation was stopped on request
+    Interrupted,
+}
+
+impl ErrorCategory {
+    /// returns true if repeating the operation later may succeed
+    pub fn is_retryable(&self) -> bool {
+        matches!(
+            self,
+            ErrorCategory::TransientProviderError | ErrorCategory::Interrupted
+        )
+    }
+}
+
+/// Stable description of an error
+pub trait ErrorInfo {
+    /// a code that identifies the error and doesn't change between versions
+    fn code(&self) -> &'static str;
+    fn category(&self) -> ErrorCategory;
+}
+
+/// Returns the category of an io error
+pub(crate) fn io_error_category(e: &std::io::Error) -> ErrorCategory {
+    match e.kind() {
+        std::io::ErrorKind::Interrupted => ErrorCategory::Interrupted,
+        std::io::ErrorKind::TimedOut | std::io::ErrorKind::ConnectionReset => {
+            ErrorCategory::TransientProviderError
+        }
+        _ => ErrorCategory::IoError,
+    }
+}
@@ -1,5 +1,7 @@
 use thiserror::Error;
 
+use super::{io_error_category, lock_errors::LockError, ErrorCategory, ErrorInfo};
+
 #[derive(Error, Debug)]
 pub enum LoadConfigError {
     #[error("open config file error {0}")]
@@ -14,4 +16,38 @@ pub enum WriteConfigError {
     IoError(std::io::Error),
     #[error("serialisation error: {0}")]
     SerialisationError(String),
+    #[error("{0}")]
+    Locked(LockError),
+}
+
+impl ErrorInfo for LoadConfigError {
+    fn code(&self) -> &'static str {
+        match self {
+            LoadConfigError::OpenConfigFileError(_) => "config.open",
+            LoadConfigError::IncorrectConfigFileFormat => "config.format",
+        }
+    }
+    fn category(&self) -> ErrorCategory {
+        match self {
+            LoadConfigError::OpenConfigFileError(e) => io_error_category(e),
+            LoadConfigError::IncorrectConfigFileFormat => ErrorCategory::ProjectCorruption,
+        }
+    }
+}
+
+impl ErrorInfo for WriteConfigError {
+    fn code(&self) -> &'static str {
+        match self {
+            WriteConfigError::IoError(_) => "config.write_io",
+            WriteConfigError::SerialisationError(_) => "config.serialisation",
+            WriteConfigError::Locked(_) => "config.locked",
+        }
+    }
+    fn category(&self) -> ErrorCategory {
+        match self {
+            WriteConfigError::IoError(e) => io_error_category(e),
+            WriteConfigError::SerialisationError(_) => ErrorCategory::ProjectCorruption,
+            WriteConfigError::Locked(e) => e.category(),
+        }
+    }
 }
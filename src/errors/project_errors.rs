@@ -1,9 +1,13 @@
-use std::path::StripPrefixError;
+use std::path::{PathBuf, StripPrefixError};
 
-use crate::errors::project_config_errors::LoadConfigError;
+use crate::{errors::project_config_errors::LoadConfigError, translator::budget::RunUsage};
 use thiserror::Error;
 
-use super::project_config_errors::WriteConfigError;
+use super::{
+    exchange_errors::ExchangeError, io_error_category, lock_errors::LockError,
+    project_config_errors::WriteConfigError, prompt_errors::PromptError,
+    sandbox_errors::SandboxError, translator_errors::BatchError, ErrorCategory, ErrorInfo,
+};
 
 #[derive(Error, Debug)]
 pub enum InitProjectError {
@@ -21,6 +25,12 @@ pub enum LoadProjectError {
     NoConfig,
     #[error("load config error {0}")]
     LoadConfigError(LoadConfigError),
+    #[error("prompt error {0}")]
+    PromptError(PromptError),
+    #[error("couldn't load the shared store: {0}")]
+    SharedStoreError(std::io::Error),
+    #[error("couldn't open the lock file: {0}")]
+    LockFileError(std::io::Error),
 }
 #[derive(Error, Debug)]
 pub enum SetSourceDirError {
@@ -34,6 +44,22 @@ pub enum SetSourceDirError {
     AnalyzeDirError(std::io::Error),
     #[error("language already in the project")]
     LangAlreadyInTheProj,
+    #[error("path outside of the project: {0}")]
+    PathOutsideProject(SandboxError),
+    #[error("couldn't detect the language of the directory")]
+    UndetectedLanguage,
+    #[error("can't add a source directory without the main one")]
+    NoSourceLang,
+    #[error("the directory is already a directory of the project")]
+    AlreadyInProject,
+    #[error("the main source directory already has an entry named {0}")]
+    NameConflict(String),
+    #[error("the directory isn't an extra source directory of the project")]
+    NotSourceDir,
+    #[error("the language is the pivot language of a target language")]
+    PivotLanguage,
+    #[error("config writing error {0}")]
+    ConfigWritingError(WriteConfigError),
 }
 
 #[derive(Error, Debug)]
@@ -42,10 +68,20 @@ pub enum AddLanguageError {
     LangAlreadyInTheProj,
     #[error("io error {0}")]
     IoError(std::io::Error),
-    #[error("can't set translate language without source language")]
+    #[error("the project has no source directory")]
     NoSourceLang,
     #[error("language directory already exists")]
     LangDirExists,
+    #[error("{0}")]
+    Locked(LockError),
+    #[error("path outside of the project: {0}")]
+    PathOutsideProject(SandboxError),
+    #[error("the language directory overlaps with a directory of the project")]
+    DirOverlaps,
+    #[error("the language was added but its directory couldn't be synced: {0}")]
+    SyncError(SyncFilesError),
+    #[error("config writing error {0}")]
+    ConfigWritingError(WriteConfigError),
 }
 
 #[derive(Error, Debug)]
@@ -56,11 +92,15 @@ pub enum RemoveLangaugeError {
     LangDirDoesNotExist,
     #[error("there's no such target language")]
     TargetLanguageNotInProject,
+    #[error("the removal of the language directory was declined")]
+    Declined,
+    #[error("{0}")]
+    Locked(LockError),
 }
 
 #[derive(Error, Debug)]
 pub enum SyncFilesError {
-    #[error("can't set translate language without source language")]
+    #[error("the project has no source directory")]
     NoSourceLang,
     #[error("no languages to translate into")]
     NoTransLangs,
@@ -74,6 +114,20 @@ pub enum SyncFilesError {
     ConfigWritingError(WriteConfigError),
     #[error("update structure error {0}")]
     UpdateStructureError(UpdateSourceDirConfig),
+    #[error("couldn't write the change report: {0}")]
+    ReportWritingError(std::io::Error),
+    #[error("target files were edited since they were written: {}", display_paths(.0))]
+    TargetModified(Vec<PathBuf>),
+    #[error("the sync failed and couldn't be rolled back: {0}")]
+    RollbackError(std::io::Error),
+    #[error("files couldn't be copied, the sync was rolled back: {}", display_errors(.0))]
+    FilesNotCopied(Vec<(PathBuf, String)>),
+    #[error("the removal of the untracked files was declined: {}", display_paths(.0))]
+    RemovalDeclined(Vec<PathBuf>),
+    #[error("the sync was cancelled, the files synced before are kept")]
+    Interrupted,
+    #[error("{0}")]
+    Locked(LockError),
 }
 
 #[derive(Error, Debug)]
@@ -86,17 +140,33 @@ pub enum CopyFileDirError {
 
 #[derive(Error, Debug)]
 pub enum AddTranslatableFileError {
-    #[error("can't set translate language without source language")]
+    #[error("the project has no source directory")]
+    NoSourceLang,
+    #[error("there is no such file")]
+    NoFile,
+    #[error("config writing error {0}")]
+    ConfigWritingError(WriteConfigError),
+    #[error("path outside of the project: {0}")]
+    PathOutsideProject(SandboxError),
+}
+
+#[derive(Error, Debug)]
+pub enum SetReviewStateError {
+    #[error("can't review translations without source language")]
     NoSourceLang,
     #[error("there is no such file")]
     NoFile,
+    #[error("there's no such target language")]
+    TargetLanguageNotInProject,
     #[error("config writing error {0}")]
     ConfigWritingError(WriteConfigError),
+    #[error("path outside of the project: {0}")]
+    PathOutsideProject(SandboxError),
 }
 
 #[derive(Error, Debug)]
 pub enum GetTranslatableFilesError {
-    #[error("can't set translate language without source language")]
+    #[error("the project has no source directory")]
     NoSourceLang,
 }
 #[derive(Error, Debug)]
@@ -115,6 +185,94 @@ pub enum TranslateFileError {
     TargetLanguageNotInProject,
     #[error("io error: {0}")]
     IoError(std::io::Error),
+    #[error("batch error: {0}")]
+    BatchError(BatchError),
+    #[error("interrupted by a shutdown request or a cancellation")]
+    Interrupted,
+    #[error("timeout: {0}")]
+    Timeout(std::io::Error),
+    #[error("path outside of the project: {0}")]
+    PathOutsideProject(SandboxError),
+    #[error("exchange error: {0}")]
+    ExchangeError(ExchangeError),
+    #[error("target files were edited since they were written: {}", display_paths(.0))]
+    TargetModified(Vec<PathBuf>),
+    #[error("the file has no translation to merge into")]
+    NoMergeBase,
+    #[error("config writing error {0}")]
+    ConfigWritingError(WriteConfigError),
+    #[error("the budget of the run is exhausted after {translated} files, {remaining} left")]
+    BudgetExceeded {
+        usage: RunUsage,
+        translated: usize,
+        remaining: usize,
+    },
+    #[error("{0}")]
+    Locked(LockError),
+}
+
+#[derive(Error, Debug)]
+pub enum SetPromptFileError {
+    #[error("prompt error: {0}")]
+    PromptError(PromptError),
+    #[error("there's no such target language")]
+    TargetLanguageNotInProject,
+    #[error("config writing error {0}")]
+    ConfigWritingError(WriteConfigError),
+}
+
+#[derive(Error, Debug)]
+pub enum SetTranslatorOptionsError {
+    #[error("invalid time window in the schedule")]
+    InvalidSchedule,
+    #[error("config writing error {0}")]
+    ConfigWritingError(WriteConfigError),
+}
+
+#[derive(Error, Debug)]
+pub enum LangSettingsError {
+    #[error("there's no such target language")]
+    TargetLanguageNotInProject,
+    #[error("the pivot language can't be the source or the target language")]
+    InvalidPivot,
+    #[error("config writing error {0}")]
+    ConfigWritingError(WriteConfigError),
+}
+
+#[derive(Error, Debug)]
+pub enum QaCheckError {
+    #[error("no source language to translate from")]
+    NoSourceLang,
+    #[error("couldn't load the translatable files: {0}")]
+    TranslatableFilesError(GetTranslatableFilesError),
+    #[error("there's no such target language")]
+    TargetLanguageNotInProject,
+    #[error("io error: {0}")]
+    IoError(std::io::Error),
+}
+
+#[derive(Error, Debug)]
+pub enum EstimateError {
+    #[error("no source language to translate from")]
+    NoSourceLang,
+    #[error("couldn't load the translatable files: {0}")]
+    TranslatableFilesError(GetTranslatableFilesError),
+    #[error("there's no such target language")]
+    TargetLanguageNotInProject,
+    #[error("io error: {0}")]
+    IoError(std::io::Error),
+}
+
+#[derive(Error, Debug)]
+pub enum BackupError {
+    #[error("there is no such backup")]
+    NoBackup,
+    #[error("path outside of the project: {0}")]
+    PathOutsideProject(SandboxError),
+    #[error("io error: {0}")]
+    IoError(std::io::Error),
+    #[error("{0}")]
+    Locked(LockError),
 }
 
 #[derive(Error, Debug)]
@@ -124,3 +282,395 @@ pub enum UpdateSourceDirConfig {
     #[error("couldn't analyze directory {0}")]
     AnalyzeDirError(std::io::Error),
 }
+
+impl ErrorInfo for InitProjectError {
+    fn code(&self) -> &'static str {
+        match self {
+            InitProjectError::InvalidPath => "init.invalid_path",
+            InitProjectError::ProjectAlreadyInitialized => "init.already_initialized",
+            InitProjectError::ConfigWritingError(_) => "init.config_writing",
+        }
+    }
+    fn category(&self) -> ErrorCategory {
+        match self {
+            InitProjectError::InvalidPath | InitProjectError::ProjectAlreadyInitialized => {
+                ErrorCategory::UserError
+            }
+            InitProjectError::ConfigWritingError(e) => e.category(),
+        }
+    }
+}
+
+impl ErrorInfo for LoadProjectError {
+    fn code(&self) -> &'static str {
+        match self {
+            LoadProjectError::NoConfig => "load.no_config",
+            LoadProjectError::LoadConfigError(_) => "load.config",
+            LoadProjectError::PromptError(_) => "load.prompt",
+            LoadProjectError::SharedStoreError(_) => "load.shared_store",
+            LoadProjectError::LockFileError(_) => "load.lock_file",
+        }
+    }
+    fn category(&self) -> ErrorCategory {
+        match self {
+            LoadProjectError::NoConfig => ErrorCategory::UserError,
+            LoadProjectError::LoadConfigError(e) => e.category(),
+            LoadProjectError::PromptError(e) => e.category(),
+            LoadProjectError::SharedStoreError(e) | LoadProjectError::LockFileError(e) => {
+                io_error_category(e)
+            }
+        }
+    }
+}
+
+impl ErrorInfo for SetSourceDirError {
+    fn code(&self) -> &'static str {
+        match self {
+            SetSourceDirError::DirectoryDoesNotExist => "source_dir.does_not_exist",
+            SetSourceDirError::IncorrectPath => "source_dir.incorrect_path",
+            SetSourceDirError::NotDirectory => "source_dir.not_directory",
+            SetSourceDirError::AnalyzeDirError(_) => "source_dir.analyze",
+            SetSourceDirError::LangAlreadyInTheProj => "source_dir.lang_already_in_project",
+            SetSourceDirError::PathOutsideProject(_) => "source_dir.outside_project",
+            SetSourceDirError::UndetectedLanguage => "source_dir.undetected_language",
+            SetSourceDirError::NoSourceLang => "source_dir.no_source_lang",
+            SetSourceDirError::AlreadyInProject => "source_dir.already_in_project",
+            SetSourceDirError::NameConflict(_) => "source_dir.name_conflict",
+            SetSourceDirError::NotSourceDir => "source_dir.not_source_dir",
+            SetSourceDirError::PivotLanguage => "source_dir.pivot_language",
+            SetSourceDirError::ConfigWritingError(_) => "source_dir.config_writing",
+        }
+    }
+    fn category(&self) -> ErrorCategory {
+        match self {
+            SetSourceDirError::AnalyzeDirError(e) => io_error_category(e),
+            SetSourceDirError::PathOutsideProject(e) => e.category(),
+            SetSourceDirError::ConfigWritingError(e) => e.category(),
+            _ => ErrorCategory::UserError,
+        }
+    }
+}
+
+impl ErrorInfo for AddLanguageError {
+    fn code(&self) -> &'static str {
+        match self {
+            AddLanguageError::LangAlreadyInTheProj => "add_lang.already_in_project",
+            AddLanguageError::IoError(_) => "add_lang.io",
+            AddLanguageError::NoSourceLang => "add_lang.no_source_lang",
+            AddLanguageError::LangDirExists => "add_lang.dir_exists",
+            AddLanguageError::Locked(_) => "add_lang.locked",
+            AddLanguageError::PathOutsideProject(_) => "add_lang.outside_project",
+            AddLanguageError::DirOverlaps => "add_lang.dir_overlaps",
+            AddLanguageError::SyncError(_) => "add_lang.sync",
+            AddLanguageError::ConfigWritingError(_) => "add_lang.config_writing",
+        }
+    }
+    fn category(&self) -> ErrorCategory {
+        match self {
+            AddLanguageError::IoError(e) => io_error_category(e),
+            AddLanguageError::Locked(e) => e.category(),
+            AddLanguageError::PathOutsideProject(e) => e.category(),
+            AddLanguageError::SyncError(e) => e.category(),
+            AddLanguageError::ConfigWritingError(e) => e.category(),
+            _ => ErrorCategory::UserError,
+        }
+    }
+}
+
+impl ErrorInfo for RemoveLangaugeError {
+    fn code(&self) -> &'static str {
+        match self {
+            RemoveLangaugeError::IoError(_) => "remove_lang.io",
+            RemoveLangaugeError::LangDirDoesNotExist => "remove_lang.dir_does_not_exist",
+            RemoveLangaugeError::TargetLanguageNotInProject => "remove_lang.not_in_project",
+            RemoveLangaugeError::Declined => "remove_lang.declined",
+            RemoveLangaugeError::Locked(_) => "remove_lang.locked",
+        }
+    }
+    fn category(&self) -> ErrorCategory {
+        match self {
+            RemoveLangaugeError::IoError(e) => io_error_category(e),
+            RemoveLangaugeError::Locked(e) => e.category(),
+            // the language is in the config but its directory is gone
+            RemoveLangaugeError::LangDirDoesNotExist => ErrorCategory::ProjectCorruption,
+            RemoveLangaugeError::TargetLanguageNotInProject | RemoveLangaugeError::Declined => {
+                ErrorCategory::UserError
+            }
+        }
+    }
+}
+
+impl ErrorInfo for SyncFilesError {
+    fn code(&self) -> &'static str {
+        match self {
+            SyncFilesError::NoSourceLang => "sync.no_source_lang",
+            SyncFilesError::NoTransLangs => "sync.no_trans_langs",
+            SyncFilesError::CopyError(_) => "sync.copy",
+            SyncFilesError::BuildingConfigError(_) => "sync.building_config",
+            SyncFilesError::RemoveUntrackedError(_) => "sync.remove_untracked",
+            SyncFilesError::ConfigWritingError(_) => "sync.config_writing",
+            SyncFilesError::UpdateStructureError(_) => "sync.update_structure",
+            SyncFilesError::ReportWritingError(_) => "sync.report_writing",
+            SyncFilesError::TargetModified(_) => "sync.target_modified",
+            SyncFilesError::RollbackError(_) => "sync.rollback",
+            SyncFilesError::FilesNotCopied(_) => "sync.files_not_copied",
+            SyncFilesError::RemovalDeclined(_) => "sync.removal_declined",
+            SyncFilesError::Interrupted => "sync.interrupted",
+            SyncFilesError::Locked(_) => "sync.locked",
+        }
+    }
+    fn category(&self) -> ErrorCategory {
+        match self {
+            SyncFilesError::NoSourceLang
+            | SyncFilesError::NoTransLangs
+            | SyncFilesError::TargetModified(_)
+            | SyncFilesError::RemovalDeclined(_) => ErrorCategory::UserError,
+            SyncFilesError::CopyError(e) => e.category(),
+            SyncFilesError::BuildingConfigError(e)
+            | SyncFilesError::RemoveUntrackedError(e)
+            | SyncFilesError::ReportWritingError(e)
+            | SyncFilesError::RollbackError(e) => io_error_category(e),
+            SyncFilesError::FilesNotCopied(_) => ErrorCategory::IoError,
+            SyncFilesError::ConfigWritingError(e) => e.category(),
+            SyncFilesError::UpdateStructureError(e) => e.category(),
+            SyncFilesError::Interrupted => ErrorCategory::Interrupted,
+            SyncFilesError::Locked(e) => e.category(),
+        }
+    }
+}
+
+impl ErrorInfo for CopyFileDirError {
+    fn code(&self) -> &'static str {
+        match self {
+            CopyFileDirError::IoError(_) => "copy.io",
+            CopyFileDirError::StripPathError(_) => "copy.strip_path",
+        }
+    }
+    fn category(&self) -> ErrorCategory {
+        match self {
+            CopyFileDirError::IoError(e) => io_error_category(e),
+            CopyFileDirError::StripPathError(_) => ErrorCategory::ProjectCorruption,
+        }
+    }
+}
+
+impl ErrorInfo for AddTranslatableFileError {
+    fn code(&self) -> &'static str {
+        match self {
+            AddTranslatableFileError::NoSourceLang => "translatable.no_source_lang",
+            AddTranslatableFileError::NoFile => "translatable.no_file",
+            AddTranslatableFileError::ConfigWritingError(_) => "translatable.config_writing",
+            AddTranslatableFileError::PathOutsideProject(_) => "translatable.outside_project",
+        }
+    }
+    fn category(&self) -> ErrorCategory {
+        match self {
+            AddTranslatableFileError::ConfigWritingError(e) => e.category(),
+            AddTranslatableFileError::PathOutsideProject(e) => e.category(),
+            _ => ErrorCategory::UserError,
+        }
+    }
+}
+
+impl ErrorInfo for SetReviewStateError {
+    fn code(&self) -> &'static str {
+        match self {
+            SetReviewStateError::NoSourceLang => "review.no_source_lang",
+            SetReviewStateError::NoFile => "review.no_file",
+            SetReviewStateError::TargetLanguageNotInProject => "review.lang_not_in_project",
+            SetReviewStateError::ConfigWritingError(_) => "review.config_writing",
+            SetReviewStateError::PathOutsideProject(_) => "review.outside_project",
+        }
+    }
+    fn category(&self) -> ErrorCategory {
+        match self {
+            SetReviewStateError::ConfigWritingError(e) => e.category(),
+            SetReviewStateError::PathOutsideProject(e) => e.category(),
+            _ => ErrorCategory::UserError,
+        }
+    }
+}
+
+impl ErrorInfo for GetTranslatableFilesError {
+    fn code(&self) -> &'static str {
+        match self {
+            GetTranslatableFilesError::NoSourceLang => "translatable.no_source_lang",
+        }
+    }
+    fn category(&self) -> ErrorCategory {
+        ErrorCategory::UserError
+    }
+}
+
+impl ErrorInfo for TranslateFileError {
+    fn code(&self) -> &'static str {
+        match self {
+            TranslateFileError::NoSourceLang => "translate.no_source_lang",
+            TranslateFileError::NoTransLangs => "translate.no_trans_langs",
+            TranslateFileError::FileNotExist => "translate.file_not_exist",
+            TranslateFileError::UntranslatableFile => "translate.untranslatable_file",
+            TranslateFileError::TranslatableFilesError(_) => "translate.translatable_files",
+            TranslateFileError::TargetLanguageNotInProject => "translate.lang_not_in_project",
+            TranslateFileError::IoError(_) => "translate.io",
+            TranslateFileError::BatchError(_) => "translate.batch",
+            TranslateFileError::Interrupted => "translate.interrupted",
+            TranslateFileError::Timeout(_) => "translate.timeout",
+            TranslateFileError::PathOutsideProject(_) => "translate.outside_project",
+            TranslateFileError::ExchangeError(_) => "translate.exchange",
+            TranslateFileError::TargetModified(_) => "translate.target_modified",
+            TranslateFileError::NoMergeBase => "translate.no_merge_base",
+            TranslateFileError::ConfigWritingError(_) => "translate.config_writing",
+            TranslateFileError::BudgetExceeded { .. } => "translate.budget_exceeded",
+            TranslateFileError::Locked(_) => "translate.locked",
+        }
+    }
+    fn category(&self) -> ErrorCategory {
+        match self {
+            TranslateFileError::PathOutsideProject(e) => e.category(),
+            TranslateFileError::TranslatableFilesError(e) => e.category(),
+            TranslateFileError::IoError(e) => io_error_category(e),
+            TranslateFileError::BatchError(e) => e.category(),
+            TranslateFileError::ExchangeError(e) => e.category(),
+            TranslateFileError::ConfigWritingError(e) => e.category(),
+            TranslateFileError::Interrupted => ErrorCategory::Interrupted,
+            TranslateFileError::Timeout(_) => ErrorCategory::TransientProviderError,
+            TranslateFileError::Locked(e) => e.category(),
+            _ => ErrorCategory::UserError,
+        }
+    }
+}
+
+impl ErrorInfo for SetTranslatorOptionsError {
+    fn code(&self) -> &'static str {
+        match self {
+            SetTranslatorOptionsError::InvalidSchedule => "translator_options.invalid_schedule",
+            SetTranslatorOptionsError::ConfigWritingError(_) => "translator_options.config_writing",
+        }
+    }
+    fn category(&self) -> ErrorCategory {
+        match self {
+            SetTranslatorOptionsError::InvalidSchedule => ErrorCategory::UserError,
+            SetTranslatorOptionsError::ConfigWritingError(e) => e.category(),
+        }
+    }
+}
+
+impl ErrorInfo for SetPromptFileError {
+    fn code(&self) -> &'static str {
+        match self {
+            SetPromptFileError::PromptError(_) => "prompt_file.prompt",
+            SetPromptFileError::TargetLanguageNotInProject => "prompt_file.lang_not_in_project",
+            SetPromptFileError::ConfigWritingError(_) => "prompt_file.config_writing",
+        }
+    }
+    fn category(&self) -> ErrorCategory {
+        match self {
+            SetPromptFileError::PromptError(e) => e.category(),
+            SetPromptFileError::TargetLanguageNotInProject => ErrorCategory::UserError,
+            SetPromptFileError::ConfigWritingError(e) => e.category(),
+        }
+    }
+}
+
+impl ErrorInfo for LangSettingsError {
+    fn code(&self) -> &'static str {
+        match self {
+            LangSettingsError::TargetLanguageNotInProject => "lang_settings.not_in_project",
+            LangSettingsError::InvalidPivot => "lang_settings.invalid_pivot",
+            LangSettingsError::ConfigWritingError(_) => "lang_settings.config_writing",
+        }
+    }
+    fn category(&self) -> ErrorCategory {
+        match self {
+            LangSettingsError::TargetLanguageNotInProject | LangSettingsError::InvalidPivot => {
+                ErrorCategory::UserError
+            }
+            LangSettingsError::ConfigWritingError(e) => e.category(),
+        }
+    }
+}
+
+impl ErrorInfo for QaCheckError {
+    fn code(&self) -> &'static str {
+        match self {
+            QaCheckError::NoSourceLang => "qa.no_source_lang",
+            QaCheckError::TranslatableFilesError(_) => "qa.translatable_files",
+            QaCheckError::TargetLanguageNotInProject => "qa.lang_not_in_project",
+            QaCheckError::IoError(_) => "qa.io",
+        }
+    }
+    fn category(&self) -> ErrorCategory {
+        match self {
+            QaCheckError::IoError(e) => io_error_category(e),
+            _ => ErrorCategory::UserError,
+        }
+    }
+}
+
+impl ErrorInfo for EstimateError {
+    fn code(&self) -> &'static str {
+        match self {
+            EstimateError::NoSourceLang => "estimate.no_source_lang",
+            EstimateError::TranslatableFilesError(_) => "estimate.translatable_files",
+            EstimateError::TargetLanguageNotInProject => "estimate.lang_not_in_project",
+            EstimateError::IoError(_) => "estimate.io",
+        }
+    }
+    fn category(&self) -> ErrorCategory {
+        match self {
+            EstimateError::IoError(e) => io_error_category(e),
+            _ => ErrorCategory::UserError,
+        }
+    }
+}
+
+impl ErrorInfo for BackupError {
+    fn code(&self) -> &'static str {
+        match self {
+            BackupError::NoBackup => "backup.no_backup",
+            BackupError::PathOutsideProject(_) => "backup.outside_project",
+            BackupError::IoError(_) => "backup.io",
+            BackupError::Locked(_) => "backup.locked",
+        }
+    }
+    fn category(&self) -> ErrorCategory {
+        match self {
+            BackupError::NoBackup => ErrorCategory::UserError,
+            BackupError::PathOutsideProject(e) => e.category(),
+            BackupError::IoError(e) => io_error_category(e),
+            BackupError::Locked(e) => e.category(),
+        }
+    }
+}
+
+impl ErrorInfo for UpdateSourceDirConfig {
+    fn code(&self) -> &'static str {
+        match self {
+            UpdateSourceDirConfig::NoSourceLang => "update_source_dir.no_source_lang",
+            UpdateSourceDirConfig::AnalyzeDirError(_) => "update_source_dir.analyze",
+        }
+    }
+    fn category(&self) -> ErrorCategory {
+        match self {
+            UpdateSourceDirConfig::NoSourceLang => ErrorCategory::UserError,
+            UpdateSourceDirConfig::AnalyzeDirError(e) => io_error_category(e),
+        }
+    }
+}
+
+/// Lists the paths separated by commas
+fn display_paths(paths: &[PathBuf]) -> String {
+    paths
+        .iter()
+        .map(|p| p.display().to_string())
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+fn display_errors(errors: &[(PathBuf, String)]) -> String {
+    errors
+        .iter()
+        .map(|(p, e)| format!("{}: {e}", p.display()))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
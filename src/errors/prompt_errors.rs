@@ -0,0 +1,35 @@
+use thiserror::Error;
+
+use super::{io_error_category, sandbox_errors::SandboxError, ErrorCategory, ErrorInfo};
+
+#[derive(Error, Debug)]
+pub enum PromptError {
+    #[error("couldn't read the prompt file: {0}")]
+    IoError(std::io::Error),
+    #[error("the prompt file is outside of the project: {0}")]
+    PathOutsideProject(SandboxError),
+    #[error("the prompt doesn't contain the {0} placeholder")]
+    MissingPlaceholder(String),
+    #[error("unknown placeholder {0} in the prompt")]
+    UnknownPlaceholder(String),
+}
+
+impl ErrorInfo for PromptError {
+    fn code(&self) -> &'static str {
+        match self {
+            PromptError::IoError(_) => "prompt.io",
+            PromptError::PathOutsideProject(_) => "prompt.outside_project",
+            PromptError::MissingPlaceholder(_) => "prompt.missing_placeholder",
+            PromptError::UnknownPlaceholder(_) => "prompt.unknown_placeholder",
+        }
+    }
+    fn category(&self) -> ErrorCategory {
+        match self {
+            PromptError::IoError(e) => io_error_category(e),
+            PromptError::PathOutsideProject(e) => e.category(),
+            PromptError::MissingPlaceholder(_) | PromptError::UnknownPlaceholder(_) => {
+                ErrorCategory::UserError
+            }
+        }
+    }
+}
@@ -0,0 +1,32 @@
+use thiserror::Error;
+
+use super::{io_error_category, ErrorCategory, ErrorInfo};
+
+#[derive(Error, Debug)]
+pub enum SandboxError {
+    #[error("the path leaves the project root through `..`")]
+    ParentTraversal,
+    #[error("the path is outside of the project root")]
+    OutsideRoot,
+    #[error("the path goes through a symlink pointing outside of the project root")]
+    SymlinkEscape,
+    #[error("io error: {0}")]
+    IoError(std::io::Error),
+}
+
+impl ErrorInfo for SandboxError {
+    fn code(&self) -> &'static str {
+        match self {
+            SandboxError::ParentTraversal => "sandbox.parent_traversal",
+            SandboxError::OutsideRoot => "sandbox.outside_root",
+            SandboxError::SymlinkEscape => "sandbox.symlink_escape",
+            SandboxError::IoError(_) => "sandbox.io",
+        }
+    }
+    fn category(&self) -> ErrorCategory {
+        match self {
+            SandboxError::IoError(e) => io_error_category(e),
+            _ => ErrorCategory::UserError,
+        }
+    }
+}
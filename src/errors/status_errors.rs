@@ -0,0 +1,32 @@
+use thiserror::Error;
+
+use super::{
+    io_error_category, project_errors::GetTranslatableFilesError, ErrorCategory, ErrorInfo,
+};
+
+#[derive(Error, Debug)]
+pub enum StatusError {
+    #[error("the project has no source directory")]
+    NoSourceLang,
+    #[error("couldn't load the translatable files: {0}")]
+    TranslatableFilesError(GetTranslatableFilesError),
+    #[error("io error: {0}")]
+    IoError(std::io::Error),
+}
+
+impl ErrorInfo for StatusError {
+    fn code(&self) -> &'static str {
+        match self {
+            StatusError::NoSourceLang => "status.no_source_lang",
+            StatusError::TranslatableFilesError(_) => "status.translatable_files",
+            StatusError::IoError(_) => "status.io",
+        }
+    }
+    fn category(&self) -> ErrorCategory {
+        match self {
+            StatusError::NoSourceLang => ErrorCategory::UserError,
+            StatusError::TranslatableFilesError(e) => e.category(),
+            StatusError::IoError(e) => io_error_category(e),
+        }
+    }
+}
@@ -0,0 +1,36 @@
+use thiserror::Error;
+
+use super::{io_error_category, lock_errors::LockError, ErrorCategory, ErrorInfo};
+
+#[derive(Error, Debug)]
+pub enum TranslationMemoryError {
+    #[error("no source language to translate from")]
+    NoSourceLang,
+    #[error("there's no such target language")]
+    TargetLanguageNotInProject,
+    #[error("the file isn't a valid TMX file: {0}")]
+    InvalidTmx(String),
+    #[error("io error: {0}")]
+    IoError(std::io::Error),
+    #[error("{0}")]
+    Locked(LockError),
+}
+
+impl ErrorInfo for TranslationMemoryError {
+    fn code(&self) -> &'static str {
+        match self {
+            TranslationMemoryError::NoSourceLang => "memory.no_source_lang",
+            TranslationMemoryError::TargetLanguageNotInProject => "memory.lang_not_in_project",
+            TranslationMemoryError::InvalidTmx(_) => "memory.invalid_tmx",
+            TranslationMemoryError::IoError(_) => "memory.io",
+            TranslationMemoryError::Locked(_) => "memory.locked",
+        }
+    }
+    fn category(&self) -> ErrorCategory {
+        match self {
+            TranslationMemoryError::IoError(e) => io_error_category(e),
+            TranslationMemoryError::Locked(e) => e.category(),
+            _ => ErrorCategory::UserError,
+        }
+    }
+}
@@ -0,0 +1,123 @@
+use thiserror::Error;
+
+use super::{io_error_category, ErrorCategory, ErrorInfo};
+
+#[derive(Error, Debug)]
+pub enum BatchError {
+    #[error("request error: {0}")]
+    RequestError(String),
+    #[error("remote error {status}: {message}")]
+    RemoteError { status: u16, message: String },
+    #[error("unexpected response: {0}")]
+    UnexpectedResponse(String),
+    #[error("batch job ended in the {0} state")]
+    JobNotSucceeded(String),
+    #[error("no result for the request {0}")]
+    MissingResult(String),
+    #[error("the batch mode can't translate through a pivot language")]
+    PivotNotSupported,
+    #[error("io error: {0}")]
+    IoError(std::io::Error),
+}
+
+impl ErrorInfo for BatchError {
+    fn code(&self) -> &'static str {
+        match self {
+            BatchError::RequestError(_) => "batch.request",
+            BatchError::RemoteError { .. } => "batch.remote",
+            BatchError::UnexpectedResponse(_) => "batch.unexpected_response",
+            BatchError::JobNotSucceeded(_) => "batch.job_not_succeeded",
+            BatchError::MissingResult(_) => "batch.missing_result",
+            BatchError::PivotNotSupported => "batch.pivot_not_supported",
+            BatchError::IoError(_) => "batch.io",
+        }
+    }
+    fn category(&self) -> ErrorCategory {
+        match self {
+            BatchError::RequestError(_) => ErrorCategory::TransientProviderError,
+            BatchError::RemoteError { status, .. } => provider_status_category(*status),
+            BatchError::UnexpectedResponse(_)
+            | BatchError::JobNotSucceeded(_)
+            | BatchError::MissingResult(_) => ErrorCategory::ProviderError,
+            BatchError::PivotNotSupported => ErrorCategory::UserError,
+            BatchError::IoError(e) => io_error_category(e),
+        }
+    }
+}
+
+/// Returns the category of an HTTP status returned by the provider
+pub(crate) fn provider_status_category(status: u16) -> ErrorCategory {
+    match status {
+        408 | 429 | 500..=599 => ErrorCategory::TransientProviderError,
+        // bad key or no access
+        401 | 403 => ErrorCategory::UserError,
+        _ => ErrorCategory::ProviderError,
+    }
+}
+
+#[derive(Error, Debug)]
+pub enum CredentialError {
+    #[error("the {0} environment variable isn't set, export the API key of the provider")]
+    MissingKey(&'static str),
+    #[error("the API key was rejected, check that it's copied correctly and not revoked: {0}")]
+    InvalidKey(String),
+    #[error("the API key has no access to the API, enable it for the key's project: {0}")]
+    PermissionDenied(String),
+    #[error("the endpoint {0} doesn't exist, check the base URL of the provider")]
+    WrongEndpoint(String),
+    #[error("the quota of the key is exhausted, wait for it to reset or raise it: {0}")]
+    QuotaExhausted(String),
+    #[error("the provider can't be reached, check the network connection: {0}")]
+    Unreachable(String),
+    #[error("the provider answered {status}: {message}")]
+    RemoteError { status: u16, message: String },
+}
+
+impl ErrorInfo for CredentialError {
+    fn code(&self) -> &'static str {
+        match self {
+            CredentialError::MissingKey(_) => "credentials.missing_key",
+            CredentialError::InvalidKey(_) => "credentials.invalid_key",
+            CredentialError::PermissionDenied(_) => "credentials.permission_denied",
+            CredentialError::WrongEndpoint(_) => "credentials.wrong_endpoint",
+            CredentialError::QuotaExhausted(_) => "credentials.quota_exhausted",
+            CredentialError::Unreachable(_) => "credentials.unreachable",
+            CredentialError::RemoteError { .. } => "credentials.remote",
+        }
+    }
+    fn category(&self) -> ErrorCategory {
+        match self {
+            CredentialError::MissingKey(_)
+            | CredentialError::InvalidKey(_)
+            | CredentialError::PermissionDenied(_)
+            | CredentialError::WrongEndpoint(_) => ErrorCategory::UserError,
+            CredentialError::QuotaExhausted(_) | CredentialError::Unreachable(_) => {
+                ErrorCategory::TransientProviderError
+            }
+            CredentialError::RemoteError { status, .. } => provider_status_category(*status),
+        }
+    }
+}
+
+#[derive(Error, Debug)]
+pub enum ResponseError {
+    #[error("the response has no <output> tag")]
+    MissingOutput,
+    #[error("the translation failed the quality checks: {0}")]
+    QaFailed(String),
+}
+
+impl ErrorInfo for ResponseError {
+    fn code(&self) -> &'static str {
+        match self {
+            ResponseError::MissingOutput => "response.missing_output",
+            ResponseError::QaFailed(_) => "response.qa_failed",
+        }
+    }
+    fn category(&self) -> ErrorCategory {
+        match self {
+            ResponseError::MissingOutput => ErrorCategory::ProviderError,
+            ResponseError::QaFailed(_) => ErrorCategory::ProviderError,
+        }
+    }
+}
@@ -0,0 +1,26 @@
+use thiserror::Error;
+
+use super::{ErrorCategory, ErrorInfo};
+
+#[derive(Error, Debug)]
+pub enum WatchError {
+    #[error("no source directory to watch")]
+    NoSourceLang,
+    #[error("couldn't watch the source directory: {0}")]
+    WatcherError(notify::Error),
+}
+
+impl ErrorInfo for WatchError {
+    fn code(&self) -> &'static str {
+        match self {
+            WatchError::NoSourceLang => "watch.no_source_lang",
+            WatchError::WatcherError(_) => "watch.watcher",
+        }
+    }
+    fn category(&self) -> ErrorCategory {
+        match self {
+            WatchError::NoSourceLang => ErrorCategory::UserError,
+            WatchError::WatcherError(_) => ErrorCategory::IoError,
+        }
+    }
+}
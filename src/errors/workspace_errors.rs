@@ -0,0 +1,35 @@
+use std::path::PathBuf;
+
+use thiserror::Error;
+
+use super::{io_error_category, project_errors::LoadProjectError, ErrorCategory, ErrorInfo};
+
+#[derive(Error, Debug)]
+pub enum WorkspaceError {
+    #[error("no project was found in the workspace")]
+    NoProjects,
+    #[error("couldn't search the workspace for projects: {0}")]
+    IoError(std::io::Error),
+    #[error("couldn't search the directory {}: {}", .0.display(), .1)]
+    ReadDirError(PathBuf, std::io::Error),
+    #[error("couldn't load the project {}: {}", .0.display(), .1)]
+    LoadProjectError(PathBuf, LoadProjectError),
+}
+
+impl ErrorInfo for WorkspaceError {
+    fn code(&self) -> &'static str {
+        match self {
+            WorkspaceError::NoProjects => "workspace.no_projects",
+            WorkspaceError::IoError(_) => "workspace.io",
+            WorkspaceError::ReadDirError(_, _) => "workspace.read_dir",
+            WorkspaceError::LoadProjectError(_, _) => "workspace.load_project",
+        }
+    }
+    fn category(&self) -> ErrorCategory {
+        match self {
+            WorkspaceError::NoProjects => ErrorCategory::UserError,
+            WorkspaceError::IoError(e) | WorkspaceError::ReadDirError(_, e) => io_error_category(e),
+            WorkspaceError::LoadProjectError(_, e) => e.category(),
+        }
+    }
+}
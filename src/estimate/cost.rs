@@ -0,0 +1,98 @@
+//! Cost of a translation run: the tokens of an estimate priced with the rates of the model. The
+//! rates of the common models are built in, a project can add or override them.
+use super::TranslationEstimate;
+
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+/// Rates of a model in US dollars per million tokens
+pub struct ModelPricing {
+    /// name of the model, versioned names (e.g. `gemini-2.0-flash-001`) use the rates of the
+    /// longest name they start with
+    pub model: String,
+    pub input_per_million: f64,
+    pub output_per_million: f64,
+}
+
+/// Built-in rates: (model, input, output) in US dollars per million tokens
+const DEFAULT_PRICING: &[(&str, f64, f64)] = &[
+    ("gemini-1.5-flash", 0.075, 0.30),
+    ("gemini-1.5-pro", 1.25, 5.00),
+    ("gemini-2.0-flash", 0.10, 0.40),
+    ("gemini-2.0-flash-lite", 0.075, 0.30),
+    ("gemini-2.5-flash", 0.30, 2.50),
+    ("gemini-2.5-flash-lite", 0.10, 0.40),
+    ("gemini-2.5-pro", 1.25, 10.00),
+];
+
+impl ModelPricing {
+    pub fn new(model: &str, input_per_million: f64, output_per_million: f64) -> Self {
+        Self {
+            model: model.to_string(),
+            input_per_million,
+            output_per_million,
+        }
+    }
+    /// Returns the price in US dollars of the tokens
+    pub fn get_cost(&self, input_tokens: u64, output_tokens: u64) -> f64 {
+        (input_tokens as f64 * self.input_per_million
+            + output_tokens as f64 * self.output_per_million)
+            / 1_000_000.0
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+/// Estimated cost of a translation run
+pub struct CostEstimate {
+    pub model: String,
+    /// requests sent to the model, one per chunk
+    pub requests: usize,
+    /// tokens sent to the model, the prompt of every request included
+    pub input_tokens: u64,
+    /// tokens of the answers, estimated as long as the translated text
+    pub output_tokens: u64,
+    /// rates the cost is computed with, `None` if the model has no known rates
+    pub pricing: Option<ModelPricing>,
+    /// cost in US dollars, `None` if the model has no known rates
+    pub cost: Option<f64>,
+}
+
+impl CostEstimate {
+    /// Prices the _estimate_ of a run with _model_, _prompt_tokens_ are sent with every request
+    pub fn new(
+        estimate: &TranslationEstimate,
+        model: &str,
+        prompt_tokens: u64,
+        pricing: Option<ModelPricing>,
+    ) -> Self {
+        let requests = estimate.get_total_requests();
+        let text_tokens = estimate.get_total_tokens();
+        let input_tokens = text_tokens + prompt_tokens * requests as u64;
+        let output_tokens = text_tokens;
+        Self {
+            model: model.to_string(),
+            requests,
+            input_tokens,
+            output_tokens,
+            cost: pricing
+                .as_ref()
+                .map(|p| p.get_cost(input_tokens, output_tokens)),
+            pricing,
+        }
+    }
+}
+
+/// Returns the rates of _model_, the ones of _custom_ take precedence over the built-in ones
+pub fn get_model_pricing(model: &str, custom: &[ModelPricing]) -> Option<ModelPricing> {
+    let builtin: Vec<ModelPricing> = DEFAULT_PRICING
+        .iter()
+        .map(|(name, input, output)| ModelPricing::new(name, *input, *output))
+        .collect();
+    find_pricing(model, custom).or_else(|| find_pricing(model, &builtin))
+}
+
+fn find_pricing(model: &str, table: &[ModelPricing]) -> Option<ModelPricing> {
+    table
+        .iter()
+        .filter(|p| model.starts_with(&p.model))
+        .max_by_key(|p| p.model.len())
+        .cloned()
+}
@@ -0,0 +1,128 @@
+//! Estimation of a translation run before it's started: how many requests and tokens every file
+//! needs, with warnings for the files that are likely to be slow, costly or translated badly
+//! because they are split into many chunks, and the cost of the run.
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+};
+
+pub mod cost;
+pub mod plan;
+pub mod stats;
+
+use crate::{
+    format::{handler_for_path, protect},
+    translator::{
+        chunks::{split_into_chunks, strategy_for_path},
+        LINES_PER_CHUNK,
+    },
+};
+
+/// Rough number of characters per token, good enough for an estimate
+const CHARS_PER_TOKEN: usize = 4;
+
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+/// Limits above which a file is flagged in the estimate, `None` disables a limit
+pub struct BudgetThresholds {
+    /// maximal size of a file in bytes
+    #[serde(default)]
+    pub max_file_bytes: Option<u64>,
+    /// maximal number of tokens sent to the model for a file
+    #[serde(default)]
+    pub max_file_tokens: Option<u64>,
+    /// maximal number of chunks a file is split into
+    #[serde(default)]
+    pub max_file_chunks: Option<usize>,
+}
+
+impl Default for BudgetThresholds {
+    fn default() -> Self {
+        Self {
+            max_file_bytes: Some(256 * 1024),
+            max_file_tokens: Some(50_000),
+            max_file_chunks: Some(40),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+/// A limit exceeded by a file
+pub enum BudgetWarning {
+    TooLarge { bytes: u64, limit: u64 },
+    TooManyTokens { tokens: u64, limit: u64 },
+    TooManyChunks { chunks: usize, limit: usize },
+}
+
+#[derive(Debug, Clone, serde::Serialize)]
+/// Estimate of the translation of one file
+pub struct FileEstimate {
+    pub file: PathBuf,
+    pub bytes: u64,
+    /// estimated tokens of the translatable text, the prompt isn't included
+    pub tokens: u64,
+    /// number of chunks, each of them is one request
+    pub chunks: usize,
+    pub warnings: Vec<BudgetWarning>,
+}
+
+#[derive(Debug, Clone, Default, serde::Serialize)]
+/// Estimate of a translation run
+pub struct TranslationEstimate {
+    pub files: Vec<FileEstimate>,
+}
+
+impl TranslationEstimate {
+    pub fn get_total_requests(&self) -> usize {
+        self.files.iter().map(|f| f.chunks).sum()
+    }
+    pub fn get_total_tokens(&self) -> u64 {
+        self.files.iter().map(|f| f.tokens).sum()
+    }
+    /// Returns the files exceeding one of the thresholds
+    pub fn get_flagged_files(&self) -> Vec<&FileEstimate> {
+        self.files
+            .iter()
+            .filter(|f| !f.warnings.is_empty())
+            .collect()
+    }
+}
+
+/// Returns the estimated number of tokens of the text
+pub fn estimate_tokens(text: &str) -> u64 {
+    text.chars().count().div_ceil(CHARS_PER_TOKEN) as u64
+}
+
+/// Estimates the translation of one file and checks it against the thresholds, the file is
+/// split with the strategy _chunking_ selects for it as in
+/// [`crate::translator::TranslatorOptions::chunking`]
+pub fn estimate_file(
+    path: &Path,
+    contents: &str,
+    thresholds: &BudgetThresholds,
+    chunking: &HashMap<String, String>,
+) -> FileEstimate {
+    let handler = handler_for_path(path);
+    let (text, _) = protect(handler.as_ref(), contents);
+    let tokens = estimate_tokens(&text);
+    let strategy = strategy_for_path(path, chunking);
+    let chunks = split_into_chunks(&text, LINES_PER_CHUNK, &[], strategy.as_ref()).len();
+    let bytes = contents.len() as u64;
+
+    let mut warnings = Vec::new();
+    if let Some(limit) = thresholds.max_file_bytes.filter(|l| bytes > *l) {
+        warnings.push(BudgetWarning::TooLarge { bytes, limit });
+    }
+    if let Some(limit) = thresholds.max_file_tokens.filter(|l| tokens > *l) {
+        warnings.push(BudgetWarning::TooManyTokens { tokens, limit });
+    }
+    if let Some(limit) = thresholds.max_file_chunks.filter(|l| chunks > *l) {
+        warnings.push(BudgetWarning::TooManyChunks { chunks, limit });
+    }
+    FileEstimate {
+        file: path.to_path_buf(),
+        bytes,
+        tokens,
+        chunks,
+        warnings,
+    }
+}
@@ -0,0 +1,68 @@
+//! Dry run of a translation: what `translate_all` would do with every file of the source
+//! directory, computed without any request to the model and without writing anything.
+use std::path::PathBuf;
+
+use crate::Language;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PlannedAction {
+    /// the file has no translation yet, it's created
+    Create,
+    /// the translation is replaced
+    Overwrite,
+    /// the translation was edited by hand since it was written, the edits would be lost
+    OverwriteEdited,
+    /// the file isn't translatable, it's left to the sync
+    Skip,
+}
+
+impl PlannedAction {
+    pub fn get_name(&self) -> &'static str {
+        match self {
+            PlannedAction::Create => "create",
+            PlannedAction::Overwrite => "overwrite",
+            PlannedAction::OverwriteEdited => "overwrite_edited",
+            PlannedAction::Skip => "skip",
+        }
+    }
+    /// Returns true if the file is sent to the model
+    pub fn is_translated(&self) -> bool {
+        !matches!(self, PlannedAction::Skip)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct PlannedFile {
+    pub source: PathBuf,
+    /// the file the translation is written to, `None` for a skipped file
+    pub target: Option<PathBuf>,
+    pub action: PlannedAction,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+/// What a translation run into _lang_ would do
+pub struct TranslatePlan {
+    pub lang: Language,
+    pub files: Vec<PlannedFile>,
+}
+
+impl TranslatePlan {
+    pub fn get_files_with(&self, action: PlannedAction) -> Vec<&PlannedFile> {
+        self.files.iter().filter(|f| f.action == action).collect()
+    }
+    /// Returns the source files that would be translated
+    pub fn get_translated(&self) -> Vec<&PathBuf> {
+        self.files
+            .iter()
+            .filter(|f| f.action.is_translated())
+            .map(|f| &f.source)
+            .collect()
+    }
+    /// Returns true if the run would lose edits made by hand
+    pub fn has_edited_overwrites(&self) -> bool {
+        !self
+            .get_files_with(PlannedAction::OverwriteEdited)
+            .is_empty()
+    }
+}
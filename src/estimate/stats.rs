@@ -0,0 +1,112 @@
+//! Word and character counts of the translatable text of a project, to size a translation job
+//! before it's started. Only the text sent to the model is counted, the regions protected by the
+//! format of a file (e.g. code blocks) aren't.
+use std::path::{Path, PathBuf};
+
+use crate::{
+    format::{handler_for_path, Segment},
+    Language,
+};
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct TextCounts {
+    pub words: usize,
+    /// characters including the whitespace
+    pub chars: usize,
+}
+
+impl TextCounts {
+    pub fn add(&mut self, other: &TextCounts) {
+        self.words += other.words;
+        self.chars += other.chars;
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct FileStats {
+    /// path of the file relative to the source directory
+    pub file: PathBuf,
+    pub counts: TextCounts,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+/// Counts of the translatable files of a directory and of its subdirectories
+pub struct DirStats {
+    /// path of the directory relative to the source directory, empty for the source directory
+    pub dir: PathBuf,
+    pub files: usize,
+    pub counts: TextCounts,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct LangStats {
+    pub lang: Language,
+    /// counts of all the translatable files
+    pub total: TextCounts,
+    /// counts of the files whose translation is missing or out of date
+    pub remaining: TextCounts,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct ProjectStats {
+    pub files: Vec<FileStats>,
+    /// one entry per directory with translatable files, parents before their subdirectories
+    pub dirs: Vec<DirStats>,
+    /// one entry per target language
+    pub langs: Vec<LangStats>,
+    pub total: TextCounts,
+}
+
+impl ProjectStats {
+    /// Builds the stats from the counts of the _files_, the directories are derived from their
+    /// paths
+    pub fn new(files: Vec<FileStats>) -> Self {
+        let mut dirs: Vec<DirStats> = Vec::new();
+        let mut total = TextCounts::default();
+        for file in &files {
+            total.add(&file.counts);
+            for dir in file.file.ancestors().skip(1) {
+                match dirs.iter_mut().find(|d| d.dir == dir) {
+                    Some(stats) => {
+                        stats.files += 1;
+                        stats.counts.add(&file.counts);
+                    }
+                    None => dirs.push(DirStats {
+                        dir: dir.to_path_buf(),
+                        files: 1,
+                        counts: file.counts,
+                    }),
+                }
+            }
+        }
+        dirs.sort_by(|a, b| a.dir.cmp(&b.dir));
+        Self {
+            files,
+            dirs,
+            langs: Vec::new(),
+            total,
+        }
+    }
+    /// Returns the counts of the file at _path_ relative to the source directory
+    pub fn get_file_counts(&self, path: &Path) -> Option<TextCounts> {
+        self.files.iter().find(|f| f.file == path).map(|f| f.counts)
+    }
+}
+
+/// Counts the words and the characters of the translatable text of the file at _path_
+pub fn count_file(path: &Path, contents: &str) -> TextCounts {
+    let mut counts = TextCounts::default();
+    for segment in handler_for_path(path).extract(contents) {
+        if let Segment::Translatable(text) = segment {
+            counts.add(&count_text(&text));
+        }
+    }
+    counts
+}
+
+pub fn count_text(text: &str) -> TextCounts {
+    TextCounts {
+        words: text.split_whitespace().count(),
+        chars: text.chars().count(),
+    }
+}
@@ -0,0 +1,158 @@
+//! Events of the operations of a [`crate::project::Project`] for async consumers: a frontend
+//! subscribes to the broadcast of the project, see [`crate::project::Project::subscribe`], and
+//! receives a [`ProjectEvent`] for every step of the runs instead of polling their state.
+use std::{path::PathBuf, sync::Arc};
+
+use futures_util::Stream;
+use tokio::sync::broadcast;
+
+use crate::{
+    progress::{ProgressEvent, ProgressHandler},
+    Language,
+};
+
+/// Number of events kept for a subscriber that doesn't keep up, the older ones are dropped
+pub const EVENT_CAPACITY: usize = 1024;
+
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+/// A step of an operation of the project, the owned form of a [`ProgressEvent`] plus the start
+/// and the end of the runs
+pub enum ProjectEvent {
+    /// a sync (`lang` is `None`), translation or merge run starts
+    OperationStarted {
+        operation: String,
+        lang: Option<Language>,
+    },
+    SourceScanned {
+        dir: PathBuf,
+        files: usize,
+    },
+    FileSynced {
+        path: PathBuf,
+        lang: Language,
+        done: usize,
+        total: usize,
+    },
+    FileStarted {
+        file: PathBuf,
+        lang: Language,
+        index: usize,
+        total: usize,
+    },
+    ChunkCompleted {
+        file: PathBuf,
+        lang: Language,
+        chunk: usize,
+        chunks: usize,
+    },
+    FileWritten {
+        file: PathBuf,
+        path: PathBuf,
+        lang: Language,
+        index: usize,
+        total: usize,
+    },
+    /// the run ended, _changes_ files were written or removed
+    OperationFinished {
+        operation: String,
+        succeeded: bool,
+        changes: usize,
+    },
+}
+
+impl From<&ProgressEvent<'_>> for ProjectEvent {
+    fn from(event: &ProgressEvent) -> Self {
+        match *event {
+            ProgressEvent::SourceScanned { dir, files } => ProjectEvent::SourceScanned {
+                dir: dir.to_path_buf(),
+                files,
+            },
+            ProgressEvent::FileSynced {
+                path,
+                lang,
+                done,
+                total,
+            } => ProjectEvent::FileSynced {
+                path: path.to_path_buf(),
+                lang: lang.clone(),
+                done,
+                total,
+            },
+            ProgressEvent::FileStarted {
+                file,
+                lang,
+                index,
+                total,
+            } => ProjectEvent::FileStarted {
+                file: file.to_path_buf(),
+                lang: lang.clone(),
+                index,
+                total,
+            },
+            ProgressEvent::ChunkCompleted {
+                file,
+                lang,
+                chunk,
+                chunks,
+            } => ProjectEvent::ChunkCompleted {
+                file: file.to_path_buf(),
+                lang: lang.clone(),
+                chunk,
+                chunks,
+            },
+            ProgressEvent::FileWritten {
+                file,
+                path,
+                lang,
+                index,
+                total,
+            } => ProjectEvent::FileWritten {
+                file: file.to_path_buf(),
+                path: path.to_path_buf(),
+                lang: lang.clone(),
+                index,
+                total,
+            },
+        }
+    }
+}
+
+/// Sends the event to the subscribers, nothing is done if there are none
+pub(crate) fn send(sender: &broadcast::Sender<ProjectEvent>, event: ProjectEvent) {
+    if sender.receiver_count() > 0 {
+        let _ = sender.send(event);
+    }
+}
+
+/// Turns the receiver into a stream of the events, the ones a slow consumer missed are skipped.
+/// The stream ends when the project is dropped.
+pub fn into_stream(
+    receiver: broadcast::Receiver<ProjectEvent>,
+) -> impl Stream<Item = ProjectEvent> {
+    futures_util::stream::unfold(receiver, |mut receiver| async move {
+        loop {
+            match receiver.recv().await {
+                Ok(event) => return Some((event, receiver)),
+                Err(broadcast::error::RecvError::Lagged(_)) => continue,
+                Err(broadcast::error::RecvError::Closed) => return None,
+            }
+        }
+    })
+}
+
+/// Passes the progress of the translations to the handler of the project and to the
+/// subscribers of its events
+pub(crate) struct EventForwarder {
+    pub(crate) handler: Option<Arc<dyn ProgressHandler>>,
+    pub(crate) sender: broadcast::Sender<ProjectEvent>,
+}
+
+impl ProgressHandler for EventForwarder {
+    fn on_progress(&self, event: &ProgressEvent) {
+        if let Some(handler) = &self.handler {
+            handler.on_progress(event);
+        }
+        send(&self.sender, event.into());
+    }
+}
@@ -0,0 +1,18 @@
+//! Exchange of the translations with human reviewers. The translatable files are exported as
+//! XLIFF 2.1 documents, segmented into the chunks the model translated with their machine
+//! translation from the translation memory, and the reviewed documents are imported back into
+//! the target directories. Teams using gettext can instead keep the translations in PO files,
+//! see [`Workflow`].
+pub mod po;
+pub mod xliff;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+/// What a translation run produces
+pub enum Workflow {
+    /// the translated files in the target directories
+    #[default]
+    Files,
+    /// a PO file per target language in the [`po::PO_DIR`] directory, the target files are
+    /// written when the reviewed PO file is compiled
+    Gettext,
+}
@@ -0,0 +1,203 @@
+//! gettext PO files. Every segment of a translatable file is an entry whose context is the path
+//! of the file and the index of the segment (e.g. `docs/intro.md#3`), so the files can be put
+//! back together from a reviewed PO file.
+use crate::{errors::exchange_errors::ExchangeError, Language};
+
+/// Name of the directory with the PO files, in the project root
+pub const PO_DIR: &str = "po";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PoEntry {
+    /// path of the file relative to the source directory
+    pub file: String,
+    /// index of the segment in the file
+    pub index: usize,
+    pub msgid: String,
+    /// empty if the segment isn't translated
+    pub msgstr: String,
+    /// true if the translation needs a review (e.g. a machine translation)
+    pub fuzzy: bool,
+}
+
+impl PoEntry {
+    pub fn get_context(&self) -> String {
+        format!("{}#{}", self.file, self.index)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PoFile {
+    /// BCP-47 code of the language of the translations, `None` for a template
+    pub lang: Option<String>,
+    pub entries: Vec<PoEntry>,
+}
+
+/// Writes the entries as a PO file, a template (`.pot`) if _lang_ is `None`
+pub fn write_po(project_name: &str, lang: Option<Language>, entries: &[PoEntry]) -> String {
+    let mut res = String::from("msgid \"\"\nmsgstr \"\"\n");
+    let header = [
+        format!("Project-Id-Version: {}\\n", project_name),
+        format!(
+            "POT-Creation-Date: {}\\n",
+            chrono::Utc::now().format("%Y-%m-%d %H:%M+0000")
+        ),
+        format!(
+            "Language: {}\\n",
+            lang.as_ref().map(|l| l.get_code()).unwrap_or_default()
+        ),
+        "MIME-Version: 1.0\\n".to_string(),
+        "Content-Type: text/plain; charset=UTF-8\\n".to_string(),
+        "Content-Transfer-Encoding: 8bit\\n".to_string(),
+        format!(
+            "X-Generator: translate-dir-lib {}\\n",
+            env!("CARGO_PKG_VERSION")
+        ),
+    ];
+    for line in header {
+        res.push_str(&format!("\"{}\"\n", line));
+    }
+    for entry in entries {
+        res.push('\n');
+        res.push_str(&format!("#: {}\n", entry.file));
+        if entry.fuzzy && lang.is_some() {
+            res.push_str("#, fuzzy\n");
+        }
+        res.push_str(&format!("msgctxt {}\n", quote(&entry.get_context())));
+        res.push_str(&format!("msgid {}\n", quote(&entry.msgid)));
+        let msgstr = if lang.is_some() {
+            entry.msgstr.as_str()
+        } else {
+            ""
+        };
+        res.push_str(&format!("msgstr {}\n", quote(msgstr)));
+    }
+    res
+}
+
+/// Reads a PO file written by [`write_po`], the entries of other tools (without a context of the
+/// form `file#index`) and the obsolete entries are left out
+pub fn parse_po(text: &str) -> Result<PoFile, ExchangeError> {
+    let mut file = PoFile {
+        lang: None,
+        entries: Vec::new(),
+    };
+    for (line_id, block) in split_entries(text).into_iter().enumerate() {
+        let mut fuzzy = false;
+        let mut fields: Vec<(&str, String)> = Vec::new();
+        for line in block {
+            let line = line.trim();
+            if let Some(flags) = line.strip_prefix("#,") {
+                fuzzy |= flags.split(',').any(|f| f.trim() == "fuzzy");
+            } else if line.starts_with('#') {
+                continue;
+            } else if line.starts_with('"') {
+                let last = fields
+                    .last_mut()
+                    .ok_or_else(|| ExchangeError::InvalidPo(format!("stray string: {}", line)))?;
+                last.1.push_str(&unquote(line)?);
+            } else if let Some((keyword, value)) = line.split_once(char::is_whitespace) {
+                fields.push((keyword, unquote(value.trim())?));
+            } else {
+                return Err(ExchangeError::InvalidPo(format!(
+                    "unexpected line: {}",
+                    line
+                )));
+            }
+        }
+        let get = |name: &str| fields.iter().find(|(k, _)| *k == name).map(|(_, v)| v);
+        let (Some(msgid), Some(msgstr)) = (get("msgid"), get("msgstr")) else {
+            continue;
+        };
+        if line_id == 0 && msgid.is_empty() {
+            file.lang = msgstr
+                .lines()
+                .find_map(|l| l.strip_prefix("Language:"))
+                .map(|l| l.trim().to_string());
+            continue;
+        }
+        let Some((path, index)) = get("msgctxt").and_then(|c| c.rsplit_once('#')) else {
+            continue;
+        };
+        let Ok(index) = index.parse() else {
+            continue;
+        };
+        file.entries.push(PoEntry {
+            file: path.to_string(),
+            index,
+            msgid: msgid.clone(),
+            msgstr: msgstr.clone(),
+            fuzzy,
+        });
+    }
+    Ok(file)
+}
+
+/// Splits the file into the blocks of lines of its entries
+fn split_entries(text: &str) -> Vec<Vec<&str>> {
+    let mut blocks = vec![Vec::new()];
+    for line in text.lines() {
+        if line.trim().is_empty() {
+            if blocks.last().is_some_and(|b| !b.is_empty()) {
+                blocks.push(Vec::new());
+            }
+        // obsolete entries
+        } else if !line.starts_with("#~") {
+            blocks.last_mut().unwrap().push(line);
+        }
+    }
+    blocks.retain(|b| !b.is_empty());
+    blocks
+}
+
+/// Writes the text as a PO string, a text of several lines is split after its newlines
+fn quote(text: &str) -> String {
+    let escape = |line: &str| {
+        let mut res = String::from("\"");
+        for c in line.chars() {
+            match c {
+                '\\' => res.push_str("\\\\"),
+                '"' => res.push_str("\\\""),
+                '\n' => res.push_str("\\n"),
+                '\t' => res.push_str("\\t"),
+                '\r' => res.push_str("\\r"),
+                c => res.push(c),
+            }
+        }
+        res.push('"');
+        res
+    };
+    let lines: Vec<&str> = text.split_inclusive('\n').collect();
+    if lines.len() <= 1 {
+        return escape(text);
+    }
+    let mut res = String::from("\"\"");
+    for line in lines {
+        res.push('\n');
+        res.push_str(&escape(line));
+    }
+    res
+}
+
+/// Reads a PO string
+fn unquote(value: &str) -> Result<String, ExchangeError> {
+    let inner = value
+        .strip_prefix('"')
+        .and_then(|v| v.strip_suffix('"'))
+        .ok_or_else(|| ExchangeError::InvalidPo(format!("not a string: {}", value)))?;
+    let mut res = String::new();
+    let mut chars = inner.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            res.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') => res.push('\n'),
+            Some('t') => res.push('\t'),
+            Some('r') => res.push('\r'),
+            Some(c) => res.push(c),
+            None => return Err(ExchangeError::InvalidPo(format!("not a string: {}", value))),
+        }
+    }
+    Ok(res)
+}
@@ -0,0 +1,237 @@
+//! XLIFF 2.1 documents. The protected regions of a file (e.g. code blocks) are kept in the
+//! `<originalData>` of the units and shown as `<ph>` placeholders, so a review tool can't alter
+//! them.
+use crate::{
+    errors::exchange_errors::ExchangeError,
+    format::placeholder,
+    helper::xml::{escape, get_attribute, next_element, unescape},
+    Language,
+};
+
+/// Namespace of XLIFF 2.x documents
+const XLIFF_NAMESPACE: &str = "urn:oasis:names:tc:xliff:document:2.0";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// A segment of a file, its texts hold the placeholders of the protected regions
+pub struct XliffSegment {
+    pub source: String,
+    /// `None` if the segment isn't translated yet
+    pub target: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct XliffFile {
+    /// path of the file relative to the source directory
+    pub original: String,
+    /// the protected regions of the file, see [`crate::format::protect`]
+    pub protected: Vec<String>,
+    pub segments: Vec<XliffSegment>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct XliffDocument {
+    /// BCP-47 codes of the languages, the project resolves them (see
+    /// [`crate::project_config::ProjectConfig::get_lang_by_code`])
+    pub src_lang: Option<String>,
+    pub tgt_lang: Option<String>,
+    pub files: Vec<XliffFile>,
+}
+
+/// Writes the files as an XLIFF 2.1 document
+pub fn write_xliff(files: &[XliffFile], src_lang: Language, tgt_lang: Language) -> String {
+    let mut res = format!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<xliff xmlns=\"{}\" version=\"2.1\" \
+         srcLang=\"{}\" trgLang=\"{}\">\n",
+        XLIFF_NAMESPACE,
+        src_lang.get_code(),
+        tgt_lang.get_code()
+    );
+    for (file_id, file) in files.iter().enumerate() {
+        res.push_str(&format!(
+            "  <file id=\"f{}\" original=\"{}\">\n",
+            file_id + 1,
+            escape(&file.original)
+        ));
+        for (unit_id, segment) in file.segments.iter().enumerate() {
+            res.push_str(&format!("    <unit id=\"u{}\">\n", unit_id + 1));
+            let used = get_placeholders(&segment.source, &file.protected);
+            if !used.is_empty() {
+                res.push_str("      <originalData>\n");
+                for id in used {
+                    res.push_str(&format!(
+                        "        <data id=\"d{}\" xml:space=\"preserve\">{}</data>\n",
+                        id,
+                        escape(&file.protected[id])
+                    ));
+                }
+                res.push_str("      </originalData>\n");
+            }
+            let state = match segment.target {
+                Some(_) => "translated",
+                None => "initial",
+            };
+            res.push_str(&format!("      <segment state=\"{}\">\n", state));
+            res.push_str(&format!(
+                "        <source xml:space=\"preserve\">{}</source>\n",
+                to_inline(&segment.source, &file.protected)
+            ));
+            if let Some(target) = &segment.target {
+                res.push_str(&format!(
+                    "        <target xml:space=\"preserve\">{}</target>\n",
+                    to_inline(target, &file.protected)
+                ));
+            }
+            res.push_str("      </segment>\n    </unit>\n");
+        }
+        res.push_str("  </file>\n");
+    }
+    res.push_str("</xliff>\n");
+    res
+}
+
+/// Reads an XLIFF 2.x document
+pub fn parse_xliff(text: &str) -> Result<XliffDocument, ExchangeError> {
+    let root = next_element(text, "xliff")
+        .map_err(ExchangeError::InvalidXliff)?
+        .ok_or_else(|| ExchangeError::InvalidXliff("no <xliff> element".into()))?;
+    let version = get_attribute(root.attributes, "version").unwrap_or_default();
+    if !version.starts_with("2.") {
+        return Err(ExchangeError::InvalidXliff(format!(
+            "the version {:?} isn't supported",
+            version
+        )));
+    }
+    let mut doc = XliffDocument {
+        src_lang: get_attribute(root.attributes, "srcLang"),
+        tgt_lang: get_attribute(root.attributes, "trgLang"),
+        files: Vec::new(),
+    };
+    let mut files = root.inner;
+    while let Some(file) = next_element(files, "file").map_err(ExchangeError::InvalidXliff)? {
+        let original = get_attribute(file.attributes, "original")
+            .ok_or_else(|| ExchangeError::InvalidXliff("a file has no original path".into()))?;
+        let mut res = XliffFile {
+            original,
+            protected: Vec::new(),
+            segments: Vec::new(),
+        };
+        let mut units = file.inner;
+        while let Some(unit) = next_element(units, "unit").map_err(ExchangeError::InvalidXliff)? {
+            parse_unit(unit.attributes, unit.inner, &mut res)?;
+            units = unit.rest;
+        }
+        doc.files.push(res);
+        files = file.rest;
+    }
+    Ok(doc)
+}
+
+/// Adds the segments and the protected regions of the unit to the file
+fn parse_unit(attributes: &str, unit: &str, file: &mut XliffFile) -> Result<(), ExchangeError> {
+    let unit_id = get_attribute(attributes, "id").unwrap_or_default();
+    if let Some(data) = next_element(unit, "originalData").map_err(ExchangeError::InvalidXliff)? {
+        let mut rest = data.inner;
+        while let Some(data) = next_element(rest, "data").map_err(ExchangeError::InvalidXliff)? {
+            let id = get_attribute(data.attributes, "id")
+                .and_then(|id| id.strip_prefix('d').and_then(|n| n.parse::<usize>().ok()))
+                .ok_or_else(|| {
+                    ExchangeError::InvalidXliff(format!("unknown data in the unit {}", unit_id))
+                })?;
+            if file.protected.len() <= id {
+                file.protected.resize(id + 1, String::new());
+            }
+            file.protected[id] = unescape(data.inner);
+            rest = data.rest;
+        }
+    }
+    let mut rest = unit;
+    while let Some(segment) = next_element(rest, "segment").map_err(ExchangeError::InvalidXliff)? {
+        let source = next_element(segment.inner, "source")
+            .map_err(ExchangeError::InvalidXliff)?
+            .ok_or_else(|| {
+                ExchangeError::InvalidXliff(format!("the unit {} has no source", unit_id))
+            })?;
+        let target = next_element(segment.inner, "target").map_err(ExchangeError::InvalidXliff)?;
+        file.segments.push(XliffSegment {
+            source: from_inline(source.inner, &file.protected)?,
+            target: target
+                .map(|t| from_inline(t.inner, &file.protected))
+                .transpose()?,
+        });
+        rest = segment.rest;
+    }
+    Ok(())
+}
+
+/// Returns the ids of the protected regions whose placeholders are in the text, in their order
+fn get_placeholders(text: &str, protected: &[String]) -> Vec<usize> {
+    (0..protected.len())
+        .filter(|id| text.contains(&placeholder(*id)))
+        .collect()
+}
+
+/// Escapes the text and replaces the placeholders of the protected regions by `<ph>` elements
+fn to_inline(text: &str, protected: &[String]) -> String {
+    let mut res = String::new();
+    let mut rest = text;
+    loop {
+        let next = (0..protected.len())
+            .filter_map(|id| rest.find(&placeholder(id)).map(|pos| (pos, id)))
+            .min();
+        let Some((pos, id)) = next else {
+            break;
+        };
+        res.push_str(&escape(&rest[..pos]));
+        res.push_str(&format!("<ph id=\"p{}\" dataRef=\"d{}\"/>", id, id));
+        rest = &rest[pos + placeholder(id).len()..];
+        // the newline after the placeholder is a part of the region
+        if protected[id].ends_with('\n') {
+            rest = rest.strip_prefix('\n').unwrap_or(rest);
+        }
+    }
+    res.push_str(&escape(rest));
+    res
+}
+
+/// Reads the text of a source or a target, the `<ph>` elements become the placeholders of the
+/// protected regions and the other inline markup added by the review tools is left out
+fn from_inline(inline: &str, protected: &[String]) -> Result<String, ExchangeError> {
+    let mut res = String::new();
+    let mut rest = inline;
+    while let Some(id) = rest.find('<') {
+        res.push_str(&unescape(&rest[..id]));
+        let tag_end = rest[id..]
+            .find('>')
+            .map(|end| id + end)
+            .ok_or_else(|| ExchangeError::InvalidXliff("unclosed inline tag".into()))?;
+        let tag = &rest[id + 1..tag_end];
+        let name = tag
+            .split(|c: char| c.is_whitespace() || c == '/')
+            .next()
+            .unwrap_or_default();
+        match name {
+            "ph" => {
+                let id = get_attribute(tag, "dataRef")
+                    .and_then(|d| d.strip_prefix('d').and_then(|n| n.parse::<usize>().ok()))
+                    .filter(|id| *id < protected.len())
+                    .ok_or_else(|| {
+                        ExchangeError::InvalidXliff(format!("unknown placeholder <{}>", tag))
+                    })?;
+                res.push_str(&placeholder(id));
+                if protected[id].ends_with('\n') {
+                    res.push('\n');
+                }
+            }
+            "cp" => {
+                let c = get_attribute(tag, "hex")
+                    .and_then(|hex| u32::from_str_radix(&hex, 16).ok())
+                    .and_then(char::from_u32);
+                res.extend(c);
+            }
+            _ => {}
+        }
+        rest = &rest[tag_end + 1..];
+    }
+    res.push_str(&unescape(rest));
+    Ok(res)
+}
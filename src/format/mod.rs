@@ -0,0 +1,222 @@
+//! A module describing file formats: which parts of a file are translatable and which parts are
+//! protected and must reach the translated file unchanged (e.g. code blocks).
+//!
+//! Protected regions are replaced by placeholders before the text is sent to the model and put
+//! back afterwards.
+use std::path::Path;
+
+use crate::errors::format_errors::ReinsertError;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// A part of a file
+pub enum Segment {
+    /// text that is sent to the model
+    Translatable(String),
+    /// text that must be kept byte-for-byte
+    Protected(String),
+}
+
+impl Segment {
+    pub fn get_text_as_ref(&self) -> &str {
+        match self {
+            Segment::Translatable(t) | Segment::Protected(t) => t,
+        }
+    }
+    pub fn is_protected(&self) -> bool {
+        matches!(self, Segment::Protected(_))
+    }
+}
+
+/// Splits the contents of a file into translatable and protected segments. Concatenating the
+/// returned segments must give back exactly the original contents.
+pub trait FormatHandler {
+    /// name of the format, used in reports
+    fn name(&self) -> &str;
+    fn extract(&self, contents: &str) -> Vec<Segment>;
+}
+
+/// Format where everything is translatable
+pub struct PlainTextHandler;
+
+impl FormatHandler for PlainTextHandler {
+    fn name(&self) -> &str {
+        "plain text"
+    }
+    fn extract(&self, contents: &str) -> Vec<Segment> {
+        vec![Segment::Translatable(contents.to_string())]
+    }
+}
+
+/// Markdown format, fenced code blocks are protected
+pub struct MarkdownHandler;
+
+impl FormatHandler for MarkdownHandler {
+    fn name(&self) -> &str {
+        "markdown"
+    }
+    fn extract(&self, contents: &str) -> Vec<Segment> {
+        let mut res = Vec::new();
+        let mut current = String::new();
+        let mut fence: Option<String> = None;
+
+        for line in contents.split_inclusive('\n') {
+            let trimmed = line.trim_start();
+            match &fence {
+                None if trimmed.starts_with("```") || trimmed.starts_with("~~~") => {
+                    if !current.is_empty() {
+                        res.push(Segment::Translatable(std::mem::take(&mut current)));
+                    }
+                    let marker: String = trimmed
+                        .chars()
+                        .take_while(|c| *c == '`' || *c == '~')
+                        .collect();
+                    fence = Some(marker);
+                    current.push_str(line);
+                }
+                None => current.push_str(line),
+                Some(marker) => {
+                    current.push_str(line);
+                    if trimmed.trim_end().starts_with(marker.as_str())
+                        && trimmed.trim_end().chars().all(|c| c == '`' || c == '~')
+                    {
+                        res.push(Segment::Protected(std::mem::take(&mut current)));
+                        fence = None;
+                    }
+                }
+            }
+        }
+        if !current.is_empty() {
+            // an unclosed fence protects the rest of the file
+            match fence {
+                Some(_) => res.push(Segment::Protected(current)),
+                None => res.push(Segment::Translatable(current)),
+            }
+        }
+        res
+    }
+}
+
+/// Returns the handler for the file by its extension
+pub fn handler_for_path(path: &Path) -> Box<dyn FormatHandler> {
+    let ext = path
+        .extension()
+        .map(|e| e.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+    match ext.as_str() {
+        "md" | "markdown" => Box::new(MarkdownHandler),
+        _ => Box::new(PlainTextHandler),
+    }
+}
+
+/// Returns the placeholder that replaces the protected region number _id_
+pub fn placeholder(id: usize) -> String {
+    format!("[[PROTECTED_{}]]", id)
+}
+
+/// Replaces every protected region by a placeholder. Returns the text to translate and the
+/// protected regions in their order.
+pub fn protect(handler: &dyn FormatHandler, contents: &str) -> (String, Vec<String>) {
+    let mut text = String::new();
+    let mut protected = Vec::new();
+    for segment in handler.extract(contents) {
+        match segment {
+            Segment::Translatable(t) => text.push_str(&t),
+            Segment::Protected(p) => {
+                text.push_str(&placeholder(protected.len()));
+                // keep the line structure so the chunking stays sensible
+                if p.ends_with('\n') {
+                    text.push('\n');
+                }
+                protected.push(p);
+            }
+        }
+    }
+    (text, protected)
+}
+
+/// Puts the protected regions back in place of their placeholders
+pub fn reinsert(translated: &str, protected: &[String]) -> Result<String, ReinsertError> {
+    let mut reinserter = Reinserter::new(protected);
+    let mut res = reinserter.push(translated)?;
+    res.push_str(&reinserter.finish()?);
+    Ok(res)
+}
+
+/// Puts the protected regions back in place of the placeholders of a part of the translation,
+/// unlike [`reinsert`] the part doesn't have to contain every placeholder
+pub fn reinsert_part(translated: &str, protected: &[String]) -> Result<String, ReinsertError> {
+    Reinserter::new(protected).process(translated)
+}
+
+/// Puts the protected regions back into a text that arrives piece by piece. Only complete lines
+/// are processed, so a placeholder split between two pieces is still recognized.
+pub struct Reinserter<'a> {
+    protected: &'a [String],
+    used: Vec<bool>,
+    buffer: String,
+}
+
+impl<'a> Reinserter<'a> {
+    pub fn new(protected: &'a [String]) -> Self {
+        Self {
+            protected,
+            used: vec![false; protected.len()],
+            buffer: String::new(),
+        }
+    }
+
+    /// Adds the next piece of the translation, returns the text that is ready to be written
+    pub fn push(&mut self, piece: &str) -> Result<String, ReinsertError> {
+        self.buffer.push_str(piece);
+        let ready_len = match self.buffer.rfind('\n') {
+            Some(id) => id + 1,
+            None => return Ok(String::new()),
+        };
+        let ready: String = self.buffer.drain(..ready_len).collect();
+        self.process(&ready)
+    }
+
+    /// Processes the rest of the text and verifies every protected region was put back
+    pub fn finish(&mut self) -> Result<String, ReinsertError> {
+        let rest = std::mem::take(&mut self.buffer);
+        let res = self.process(&rest)?;
+        match self.used.iter().position(|u| !u) {
+            Some(id) => Err(ReinsertError::MissingPlaceholder(id)),
+            None => Ok(res),
+        }
+    }
+
+    fn process(&mut self, text: &str) -> Result<String, ReinsertError> {
+        const PREFIX: &str = "[[PROTECTED_";
+        let mut res = String::new();
+        let mut rest = text;
+
+        while let Some(start) = rest.find(PREFIX) {
+            res.push_str(&rest[..start]);
+            let after = &rest[start + PREFIX.len()..];
+            let id = after
+                .find("]]")
+                .and_then(|end| after[..end].parse::<usize>().ok().map(|id| (id, end)));
+            match id {
+                Some((id, end)) if id < self.protected.len() => {
+                    if self.used[id] {
+                        return Err(ReinsertError::DuplicatePlaceholder(id));
+                    }
+                    self.used[id] = true;
+                    res.push_str(&self.protected[id]);
+                    rest = &after[end + 2..];
+                    // the newline after the placeholder is a part of the region
+                    if self.protected[id].ends_with('\n') {
+                        rest = rest.strip_prefix('\n').unwrap_or(rest);
+                    }
+                }
+                _ => {
+                    res.push_str(PREFIX);
+                    rest = after;
+                }
+            }
+        }
+        res.push_str(rest);
+        Ok(res)
+    }
+}
@@ -0,0 +1,606 @@
+//! File system abstraction: the walks of the language directories (the analysis of their tree,
+//! the search of the untracked files of the sync) and the changes of the sync go through a
+//! [`FileSystem`], so they run on the disk with [`RealFs`] or on a virtual project with
+//! [`MemoryFs`], e.g. in tests.
+use std::{
+    collections::BTreeMap,
+    path::{Component, Path, PathBuf},
+    sync::{Arc, Mutex},
+    time::SystemTime,
+};
+
+/// Maximal number of links followed to resolve a path, a longer chain is a loop
+const MAX_LINK_HOPS: usize = 40;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FsMetadata {
+    pub is_dir: bool,
+    pub is_file: bool,
+    pub is_symlink: bool,
+    /// size of the file in bytes
+    pub len: u64,
+    pub modified: Option<SystemTime>,
+    pub accessed: Option<SystemTime>,
+}
+
+impl From<std::fs::Metadata> for FsMetadata {
+    fn from(meta: std::fs::Metadata) -> Self {
+        Self {
+            is_dir: meta.is_dir(),
+            is_file: meta.is_file(),
+            is_symlink: meta.is_symlink(),
+            len: meta.len(),
+            modified: meta.modified().ok(),
+            accessed: meta.accessed().ok(),
+        }
+    }
+}
+
+/// The operations of the file system the project uses, with the semantics of the functions of
+/// `std::fs` of the same names
+pub trait FileSystem: Send + Sync {
+    /// Returns the paths of the entries of the directory
+    fn read_dir(&self, path: &Path) -> std::io::Result<Vec<PathBuf>>;
+    /// Returns the metadata of what the path points to, the links are followed
+    fn metadata(&self, path: &Path) -> std::io::Result<FsMetadata>;
+    /// Returns the metadata of the path itself, a link isn't followed
+    fn symlink_metadata(&self, path: &Path) -> std::io::Result<FsMetadata>;
+    fn canonicalize(&self, path: &Path) -> std::io::Result<PathBuf>;
+    fn read(&self, path: &Path) -> std::io::Result<Vec<u8>>;
+    fn write(&self, path: &Path, contents: &[u8]) -> std::io::Result<()>;
+    fn create_dir_all(&self, path: &Path) -> std::io::Result<()>;
+    fn remove_file(&self, path: &Path) -> std::io::Result<()>;
+    fn remove_dir_all(&self, path: &Path) -> std::io::Result<()>;
+    fn rename(&self, from: &Path, to: &Path) -> std::io::Result<()>;
+    /// Copies the contents of the file, returns the number of bytes copied
+    fn copy(&self, from: &Path, to: &Path) -> std::io::Result<u64>;
+    fn hard_link(&self, original: &Path, link: &Path) -> std::io::Result<()>;
+    /// Creates a symbolic link at _link_ pointing to _original_, a relative _original_ is
+    /// relative to the directory of the link
+    fn symlink(&self, original: &Path, link: &Path) -> std::io::Result<()>;
+    fn read_link(&self, path: &Path) -> std::io::Result<PathBuf>;
+    /// Sets the access and the modification times of the file or the directory
+    fn set_times(
+        &self,
+        path: &Path,
+        accessed: Option<SystemTime>,
+        modified: SystemTime,
+    ) -> std::io::Result<()>;
+    /// Gives _target_ the permissions of _source_ if they differ
+    fn copy_permissions(&self, source: &Path, target: &Path) -> std::io::Result<()>;
+    /// returns true if both paths are the same file, e.g. hard links of each other
+    fn is_same_file(&self, a: &Path, b: &Path) -> bool;
+    /// returns true if something is at the path, a dangling link too
+    fn exists(&self, path: &Path) -> bool {
+        self.symlink_metadata(path).is_ok()
+    }
+}
+
+impl std::fmt::Debug for dyn FileSystem {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("FileSystem")
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+/// The file system of the disk
+pub struct RealFs;
+
+impl FileSystem for RealFs {
+    fn read_dir(&self, path: &Path) -> std::io::Result<Vec<PathBuf>> {
+        let mut entries = Vec::new();
+        for entry in std::fs::read_dir(path)? {
+            entries.push(entry?.path());
+        }
+        Ok(entries)
+    }
+    fn metadata(&self, path: &Path) -> std::io::Result<FsMetadata> {
+        std::fs::metadata(path).map(FsMetadata::from)
+    }
+    fn symlink_metadata(&self, path: &Path) -> std::io::Result<FsMetadata> {
+        std::fs::symlink_metadata(path).map(FsMetadata::from)
+    }
+    fn canonicalize(&self, path: &Path) -> std::io::Result<PathBuf> {
+        std::fs::canonicalize(path)
+    }
+    fn read(&self, path: &Path) -> std::io::Result<Vec<u8>> {
+        std::fs::read(path)
+    }
+    fn write(&self, path: &Path, contents: &[u8]) -> std::io::Result<()> {
+        std::fs::write(path, contents)
+    }
+    fn create_dir_all(&self, path: &Path) -> std::io::Result<()> {
+        std::fs::create_dir_all(path)
+    }
+    fn remove_file(&self, path: &Path) -> std::io::Result<()> {
+        std::fs::remove_file(path)
+    }
+    fn remove_dir_all(&self, path: &Path) -> std::io::Result<()> {
+        std::fs::remove_dir_all(path)
+    }
+    fn rename(&self, from: &Path, to: &Path) -> std::io::Result<()> {
+        std::fs::rename(from, to)
+    }
+    fn copy(&self, from: &Path, to: &Path) -> std::io::Result<u64> {
+        crate::helper::reflink_or_copy(from, to)
+    }
+    fn hard_link(&self, original: &Path, link: &Path) -> std::io::Result<()> {
+        std::fs::hard_link(original, link)
+    }
+    fn symlink(&self, original: &Path, link: &Path) -> std::io::Result<()> {
+        create_symlink(original, link)
+    }
+    fn read_link(&self, path: &Path) -> std::io::Result<PathBuf> {
+        std::fs::read_link(path)
+    }
+    fn set_times(
+        &self,
+        path: &Path,
+        accessed: Option<SystemTime>,
+        modified: SystemTime,
+    ) -> std::io::Result<()> {
+        let mut times = std::fs::FileTimes::new().set_modified(modified);
+        if let Some(accessed) = accessed {
+            times = times.set_accessed(accessed);
+        }
+        open_for_times(path)?.set_times(times)
+    }
+    fn copy_permissions(&self, source: &Path, target: &Path) -> std::io::Result<()> {
+        let permissions = std::fs::metadata(source)?.permissions();
+        if std::fs::metadata(target)?.permissions() != permissions {
+            std::fs::set_permissions(target, permissions)?;
+        }
+        Ok(())
+    }
+    fn is_same_file(&self, a: &Path, b: &Path) -> bool {
+        is_same_file(a, b)
+    }
+}
+
+#[cfg(unix)]
+fn create_symlink(original: &Path, link: &Path) -> std::io::Result<()> {
+    std::os::unix::fs::symlink(original, link)
+}
+
+#[cfg(windows)]
+fn create_symlink(original: &Path, link: &Path) -> std::io::Result<()> {
+    // a relative original is resolved from the directory of the link
+    let resolved = link.parent().unwrap_or(link).join(original);
+    if resolved.is_dir() {
+        std::os::windows::fs::symlink_dir(original, link)
+    } else {
+        std::os::windows::fs::symlink_file(original, link)
+    }
+}
+
+#[cfg(unix)]
+fn is_same_file(a: &Path, b: &Path) -> bool {
+    use std::os::unix::fs::MetadataExt;
+    match (std::fs::metadata(a), std::fs::metadata(b)) {
+        (Ok(a), Ok(b)) => a.dev() == b.dev() && a.ino() == b.ino(),
+        _ => false,
+    }
+}
+
+#[cfg(not(unix))]
+fn is_same_file(_a: &Path, _b: &Path) -> bool {
+    false
+}
+
+/// Opens the file or the directory to set its times. A read-only file or a directory can't be
+/// opened for writing, the owner may set them without it on Unix.
+fn open_for_times(path: &Path) -> std::io::Result<std::fs::File> {
+    std::fs::File::options()
+        .write(true)
+        .open(path)
+        .or_else(|_| std::fs::File::open(path))
+}
+
+#[derive(Debug)]
+struct FileData {
+    contents: Vec<u8>,
+    modified: SystemTime,
+}
+
+#[derive(Debug, Clone)]
+enum Node {
+    Dir,
+    /// the hard links of a file share its data
+    File(Arc<Mutex<FileData>>),
+    Symlink(PathBuf),
+}
+
+impl Node {
+    fn file(contents: Vec<u8>) -> Self {
+        Node::File(Arc::new(Mutex::new(FileData {
+            contents,
+            modified: SystemTime::now(),
+        })))
+    }
+}
+
+#[derive(Debug, Default)]
+/// A file system kept in memory, the paths are absolute and the root always exists
+pub struct MemoryFs {
+    nodes: Mutex<BTreeMap<PathBuf, Node>>,
+}
+
+impl MemoryFs {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, BTreeMap<PathBuf, Node>> {
+        self.nodes.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Adds the _node_ at the new path _at_, fails if something is there already
+    fn insert_new(&self, at: &Path, node: Node) -> std::io::Result<()> {
+        let at = normalize(at);
+        let mut nodes = self.lock();
+        check_parent(&nodes, &at)?;
+        if is_root(&at) || nodes.contains_key(&at) {
+            return Err(already_exists(&at));
+        }
+        nodes.insert(at, node);
+        Ok(())
+    }
+
+    /// Returns the data of the file at the path, the links are followed
+    fn file_data(&self, path: &Path) -> std::io::Result<Arc<Mutex<FileData>>> {
+        let nodes = self.lock();
+        let resolved = resolve(&nodes, path, true)?;
+        match nodes.get(&resolved) {
+            Some(Node::File(data)) => Ok(data.clone()),
+            _ => Err(is_a_directory(path)),
+        }
+    }
+}
+
+impl FileSystem for MemoryFs {
+    fn read_dir(&self, path: &Path) -> std::io::Result<Vec<PathBuf>> {
+        let nodes = self.lock();
+        let dir = resolve(&nodes, path, true)?;
+        if !is_root(&dir) && !matches!(nodes.get(&dir), Some(Node::Dir)) {
+            return Err(not_a_directory(path));
+        }
+        // the entries are listed under the path as it was given, as the disk does
+        let given = normalize(path);
+        Ok(children(&nodes, &dir)
+            .map(|child| given.join(child.file_name().unwrap_or_default()))
+            .collect())
+    }
+    fn metadata(&self, path: &Path) -> std::io::Result<FsMetadata> {
+        let nodes = self.lock();
+        let resolved = resolve(&nodes, path, true)?;
+        Ok(node_metadata(nodes.get(&resolved)))
+    }
+    fn symlink_metadata(&self, path: &Path) -> std::io::Result<FsMetadata> {
+        let nodes = self.lock();
+        let resolved = resolve(&nodes, path, false)?;
+        Ok(node_metadata(nodes.get(&resolved)))
+    }
+    fn canonicalize(&self, path: &Path) -> std::io::Result<PathBuf> {
+        resolve(&self.lock(), path, true)
+    }
+    fn read(&self, path: &Path) -> std::io::Result<Vec<u8>> {
+        let data = self.file_data(path)?;
+        let contents = lock_data(&data).contents.clone();
+        Ok(contents)
+    }
+    fn write(&self, path: &Path, contents: &[u8]) -> std::io::Result<()> {
+        let mut nodes = self.lock();
+        // writing through a link writes the file it points to
+        let path = match resolve(&nodes, path, true) {
+            Ok(resolved) => resolved,
+            Err(_) => normalize(path),
+        };
+        check_parent(&nodes, &path)?;
+        match nodes.get(&path) {
+            _ if is_root(&path) => Err(is_a_directory(&path)),
+            Some(Node::Dir) => Err(is_a_directory(&path)),
+            // the file is truncated in place, its hard links see the new contents
+            Some(Node::File(data)) => {
+                let mut data = lock_data(data);
+                data.contents = contents.to_vec();
+                data.modified = SystemTime::now();
+                Ok(())
+            }
+            _ => {
+                nodes.insert(path, Node::file(contents.to_vec()));
+                Ok(())
+            }
+        }
+    }
+    fn create_dir_all(&self, path: &Path) -> std::io::Result<()> {
+        let mut nodes = self.lock();
+        let mut current = PathBuf::new();
+        for component in normalize(path).components() {
+            current.push(component);
+            if is_root(&current) {
+                continue;
+            }
+            match resolve(&nodes, &current, true) {
+                Ok(resolved) if is_root(&resolved) => {}
+                Ok(resolved) => match nodes.get(&resolved) {
+                    Some(Node::Dir) => current = resolved,
+                    _ => return Err(not_a_directory(&current)),
+                },
+                Err(_) => {
+                    nodes.insert(current.clone(), Node::Dir);
+                }
+            }
+        }
+        Ok(())
+    }
+    fn remove_file(&self, path: &Path) -> std::io::Result<()> {
+        let mut nodes = self.lock();
+        let path = resolve(&nodes, path, false)?;
+        match nodes.get(&path) {
+            Some(Node::File { .. } | Node::Symlink(_)) => {
+                nodes.remove(&path);
+                Ok(())
+            }
+            _ => Err(is_a_directory(&path)),
+        }
+    }
+    fn remove_dir_all(&self, path: &Path) -> std::io::Result<()> {
+        let mut nodes = self.lock();
+        let path = resolve(&nodes, path, false)?;
+        if is_root(&path) || !matches!(nodes.get(&path), Some(Node::Dir)) {
+            return Err(not_a_directory(&path));
+        }
+        nodes.retain(|p, _| !p.starts_with(&path));
+        Ok(())
+    }
+    fn rename(&self, from: &Path, to: &Path) -> std::io::Result<()> {
+        let mut nodes = self.lock();
+        let from = resolve(&nodes, from, false)?;
+        let to = normalize(to);
+        check_parent(&nodes, &to)?;
+        if is_root(&from) || to.starts_with(&from) {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                "can't move a directory into itself",
+            ));
+        }
+        if matches!(nodes.get(&to), Some(Node::Dir)) {
+            return Err(already_exists(&to));
+        }
+        let moved: Vec<PathBuf> = nodes
+            .keys()
+            .filter(|p| p.starts_with(&from))
+            .cloned()
+            .collect();
+        for path in moved {
+            if let Some(node) = nodes.remove(&path) {
+                let relative = path.strip_prefix(&from).unwrap_or(Path::new(""));
+                nodes.insert(to.join(relative), node);
+            }
+        }
+        Ok(())
+    }
+    fn copy(&self, from: &Path, to: &Path) -> std::io::Result<u64> {
+        let contents = self.read(from)?;
+        self.write(to, &contents)?;
+        Ok(contents.len() as u64)
+    }
+    fn hard_link(&self, original: &Path, link: &Path) -> std::io::Result<()> {
+        let data = self.file_data(original)?;
+        self.insert_new(link, Node::File(data))
+    }
+    fn symlink(&self, original: &Path, link: &Path) -> std::io::Result<()> {
+        self.insert_new(link, Node::Symlink(original.to_path_buf()))
+    }
+    fn read_link(&self, path: &Path) -> std::io::Result<PathBuf> {
+        let nodes = self.lock();
+        let resolved = resolve(&nodes, path, false)?;
+        match nodes.get(&resolved) {
+            Some(Node::Symlink(target)) => Ok(target.clone()),
+            _ => Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                format!("not a symbolic link: {}", path.display()),
+            )),
+        }
+    }
+    /// Only the modification time of a file is kept
+    fn set_times(
+        &self,
+        path: &Path,
+        _accessed: Option<SystemTime>,
+        modified: SystemTime,
+    ) -> std::io::Result<()> {
+        let nodes = self.lock();
+        let resolved = resolve(&nodes, path, true)?;
+        if let Some(Node::File(data)) = nodes.get(&resolved) {
+            lock_data(data).modified = modified;
+        }
+        Ok(())
+    }
+    /// The files have no permissions, both paths only have to exist
+    fn copy_permissions(&self, source: &Path, target: &Path) -> std::io::Result<()> {
+        self.metadata(source)?;
+        self.metadata(target)?;
+        Ok(())
+    }
+    fn is_same_file(&self, a: &Path, b: &Path) -> bool {
+        match (self.file_data(a), self.file_data(b)) {
+            (Ok(a), Ok(b)) => Arc::ptr_eq(&a, &b),
+            _ => false,
+        }
+    }
+}
+
+fn lock_data(data: &Mutex<FileData>) -> std::sync::MutexGuard<'_, FileData> {
+    data.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Resolves the links of the path, the last component is only followed if _follow_last_ is
+/// set. Fails if the path doesn't exist.
+fn resolve(
+    nodes: &BTreeMap<PathBuf, Node>,
+    path: &Path,
+    follow_last: bool,
+) -> std::io::Result<PathBuf> {
+    let mut pending: Vec<PathBuf> = normalize(path)
+        .components()
+        .rev()
+        .map(|c| PathBuf::from(c.as_os_str()))
+        .collect();
+    let mut current = PathBuf::new();
+    let mut hops = 0;
+    while let Some(component) = pending.pop() {
+        let next = normalize(&current.join(&component));
+        if is_root(&next) {
+            current = next;
+            continue;
+        }
+        match nodes.get(&next) {
+            None => return Err(not_found(path)),
+            Some(Node::Symlink(target)) if follow_last || !pending.is_empty() => {
+                hops += 1;
+                if hops > MAX_LINK_HOPS {
+                    return Err(std::io::Error::other(format!(
+                        "too many levels of symbolic links: {}",
+                        path.display()
+                    )));
+                }
+                // the rest of the path continues from where the link points
+                let target = current.join(target);
+                pending.extend(
+                    normalize(&target)
+                        .components()
+                        .rev()
+                        .map(|c| PathBuf::from(c.as_os_str())),
+                );
+                current = PathBuf::new();
+            }
+            Some(_) => current = next,
+        }
+    }
+    Ok(current)
+}
+
+/// Resolves `.` and `..` without following the links
+fn normalize(path: &Path) -> PathBuf {
+    let mut res = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                res.pop();
+            }
+            _ => res.push(component),
+        }
+    }
+    res
+}
+
+fn is_root(path: &Path) -> bool {
+    path.parent().is_none()
+}
+
+fn children<'a>(
+    nodes: &'a BTreeMap<PathBuf, Node>,
+    dir: &'a Path,
+) -> impl Iterator<Item = &'a PathBuf> {
+    nodes.keys().filter(move |p| p.parent() == Some(dir))
+}
+
+fn check_parent(nodes: &BTreeMap<PathBuf, Node>, path: &Path) -> std::io::Result<()> {
+    let Some(parent) = path.parent() else {
+        return Ok(());
+    };
+    let parent = resolve(nodes, parent, true)?;
+    if is_root(&parent) || matches!(nodes.get(&parent), Some(Node::Dir)) {
+        Ok(())
+    } else {
+        Err(not_a_directory(&parent))
+    }
+}
+
+fn node_metadata(node: Option<&Node>) -> FsMetadata {
+    let (is_dir, is_file, is_symlink, len, modified) = match node {
+        // the root isn't stored
+        None | Some(Node::Dir) => (true, false, false, 0, None),
+        Some(Node::File(data)) => {
+            let data = lock_data(data);
+            (
+                false,
+                true,
+                false,
+                data.contents.len() as u64,
+                Some(data.modified),
+            )
+        }
+        Some(Node::Symlink(_)) => (false, false, true, 0, None),
+    };
+    FsMetadata {
+        is_dir,
+        is_file,
+        is_symlink,
+        len,
+        modified,
+        accessed: None,
+    }
+}
+
+fn not_found(path: &Path) -> std::io::Error {
+    std::io::Error::new(
+        std::io::ErrorKind::NotFound,
+        format!("no such file or directory: {}", path.display()),
+    )
+}
+
+fn not_a_directory(path: &Path) -> std::io::Error {
+    std::io::Error::other(format!("not a directory: {}", path.display()))
+}
+
+fn is_a_directory(path: &Path) -> std::io::Error {
+    std::io::Error::other(format!("is a directory: {}", path.display()))
+}
+
+fn already_exists(path: &Path) -> std::io::Error {
+    std::io::Error::new(
+        std::io::ErrorKind::AlreadyExists,
+        format!("already exists: {}", path.display()),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hard_links_share_their_contents() {
+        let fs = MemoryFs::new();
+        fs.create_dir_all(Path::new("/a/b")).unwrap();
+        fs.write(Path::new("/a/file"), b"one").unwrap();
+        fs.hard_link(Path::new("/a/file"), Path::new("/a/b/link"))
+            .unwrap();
+        fs.write(Path::new("/a/b/link"), b"two").unwrap();
+        assert_eq!(fs.read(Path::new("/a/file")).unwrap(), b"two");
+
+        fs.remove_file(Path::new("/a/file")).unwrap();
+        assert_eq!(fs.read(Path::new("/a/b/link")).unwrap(), b"two");
+    }
+
+    #[test]
+    fn follows_relative_links() {
+        let fs = MemoryFs::new();
+        fs.create_dir_all(Path::new("/a/b")).unwrap();
+        fs.write(Path::new("/a/file"), b"contents").unwrap();
+        fs.symlink(Path::new("../file"), Path::new("/a/b/link"))
+            .unwrap();
+        assert_eq!(fs.read(Path::new("/a/b/link")).unwrap(), b"contents");
+        assert!(
+            fs.symlink_metadata(Path::new("/a/b/link"))
+                .unwrap()
+                .is_symlink
+        );
+        assert_eq!(
+            fs.canonicalize(Path::new("/a/b/link")).unwrap(),
+            Path::new("/a/file")
+        );
+    }
+}
@@ -0,0 +1,364 @@
+//! Extraction of a draft glossary from the source files: frequent words and short phrases that
+//! look like terms are collected, optionally with translations suggested by the model, and the
+//! user edits the draft before committing it to the glossary of the project.
+use std::collections::{HashMap, HashSet};
+
+use crate::{
+    format::{handler_for_path, Segment},
+    Language,
+};
+
+use super::{Glossary, GlossaryEntry};
+
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+/// Options of the term extraction
+pub struct ExtractOptions {
+    /// minimal number of occurrences of a term in all the files
+    pub min_occurrences: usize,
+    /// maximal number of terms in the draft
+    pub max_terms: usize,
+    /// maximal number of words in a term
+    pub max_phrase_words: usize,
+    /// minimal number of characters of a single word term
+    pub min_word_len: usize,
+}
+
+impl Default for ExtractOptions {
+    fn default() -> Self {
+        Self {
+            min_occurrences: 3,
+            max_terms: 100,
+            max_phrase_words: 2,
+            min_word_len: 4,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// A term found in the source files
+pub struct TermCandidate {
+    /// the term in its most frequent spelling
+    pub term: String,
+    pub occurrences: usize,
+    /// number of files the term appears in
+    pub files: usize,
+}
+
+/// Extracts the candidate terms from the translatable parts of the files, every file is a
+/// (path, contents) pair. The most frequent terms come first.
+pub fn extract_terms(
+    files: &[(std::path::PathBuf, String)],
+    lang: Language,
+    options: &ExtractOptions,
+) -> Vec<TermCandidate> {
+    // normalized term -> (spellings, occurrences, files)
+    let mut found: HashMap<String, (HashMap<String, usize>, usize, usize)> = HashMap::new();
+    for (path, contents) in files {
+        let handler = handler_for_path(path);
+        let mut in_file: HashSet<String> = HashSet::new();
+        for segment in handler.extract(contents) {
+            let text = match segment {
+                Segment::Translatable(t) => t,
+                Segment::Protected(_) => continue,
+            };
+            for sentence in text.split(['.', '!', '?', ';', ':', '\n', '(', ')', ',']) {
+                let words: Vec<&str> = sentence
+                    .split(|c: char| !(c.is_alphanumeric() || c == '-' || c == '_'))
+                    .map(|w| w.trim_matches('-'))
+                    .filter(|w| !w.is_empty())
+                    .collect();
+                for len in 1..=options.max_phrase_words.max(1) {
+                    for phrase in words.windows(len) {
+                        if !is_term(phrase, lang.clone(), options) {
+                            continue;
+                        }
+                        let spelling = phrase.join(" ");
+                        let key = spelling.to_lowercase();
+                        let entry = found.entry(key.clone()).or_default();
+                        *entry.0.entry(spelling).or_default() += 1;
+                        entry.1 += 1;
+                        if in_file.insert(key) {
+                            entry.2 += 1;
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    let mut res: Vec<TermCandidate> = found
+        .into_values()
+        .filter(|(_, occurrences, _)| *occurrences >= options.min_occurrences)
+        .map(|(spellings, occurrences, files)| {
+            let term = spellings
+                .into_iter()
+                .max_by(|a, b| a.1.cmp(&b.1).then_with(|| b.0.cmp(&a.0)))
+                .map(|(s, _)| s)
+                .unwrap_or_default();
+            TermCandidate {
+                term,
+                occurrences,
+                files,
+            }
+        })
+        .collect();
+    res.sort_by(|a, b| {
+        b.occurrences
+            .cmp(&a.occurrences)
+            .then_with(|| a.term.cmp(&b.term))
+    });
+    res.truncate(options.max_terms);
+    res
+}
+
+/// returns true if the words could be a term: no stop word at the ends, no numbers, long enough
+fn is_term(words: &[&str], lang: Language, options: &ExtractOptions) -> bool {
+    let (first, last) = match (words.first(), words.last()) {
+        (Some(f), Some(l)) => (f, l),
+        _ => return false,
+    };
+    if is_stop_word(first, lang.clone()) || is_stop_word(last, lang) {
+        return false;
+    }
+    if words
+        .iter()
+        .any(|w| w.chars().all(|c| c.is_numeric() || c == '-' || c == '_'))
+    {
+        return false;
+    }
+    words.len() > 1 || first.chars().count() >= options.min_word_len
+}
+
+pub(crate) fn is_stop_word(word: &str, lang: Language) -> bool {
+    let word = word.to_lowercase();
+    let list: &[&str] = match lang {
+        Language::English => &[
+            "the", "a", "an", "and", "or", "but", "of", "to", "in", "on", "at", "for", "with",
+            "by", "from", "as", "is", "are", "was", "were", "be", "been", "it", "its", "this",
+            "that", "these", "those", "you", "your", "we", "our", "they", "their", "can", "will",
+            "not", "if", "then", "than", "which", "when", "what", "how", "all", "any", "some",
+            "each", "into", "also", "more", "most", "use", "used", "using", "have", "has", "do",
+            "does", "may", "should", "must", "only", "there", "here", "about",
+        ],
+        Language::French => &[
+            "le", "la", "les", "un", "une", "des", "du", "de", "et", "ou", "mais", "à", "au",
+            "aux", "en", "dans", "sur", "pour", "par", "avec", "est", "sont", "ce", "cette", "ces",
+            "il", "elle", "ils", "elles", "vous", "nous", "on", "qui", "que", "quoi", "ne", "pas",
+            "plus", "se", "sa", "son", "ses", "leur", "leurs", "peut", "être", "avoir",
+        ],
+        Language::German => &[
+            "der", "die", "das", "den", "dem", "des", "ein", "eine", "einen", "einem", "einer",
+            "und", "oder", "aber", "zu", "in", "im", "an", "am", "auf", "für", "mit", "von", "vom",
+            "aus", "ist", "sind", "war", "es", "sie", "wir", "ihr", "sich", "nicht", "auch",
+            "wenn", "dann", "als", "wie", "was", "kann", "können", "wird", "werden", "diese",
+            "dieser", "dieses",
+        ],
+        Language::Spanish => &[
+            "el", "la", "los", "las", "un", "una", "unos", "unas", "de", "del", "y", "o", "pero",
+            "a", "al", "en", "con", "por", "para", "es", "son", "se", "su", "sus", "que", "como",
+            "este", "esta", "estos", "estas", "no", "más", "puede", "ser", "lo", "le",
+        ],
+        Language::Ukrainian => &[
+            "і",
+            "й",
+            "та",
+            "а",
+            "але",
+            "або",
+            "в",
+            "у",
+            "на",
+            "з",
+            "із",
+            "зі",
+            "до",
+            "від",
+            "для",
+            "по",
+            "за",
+            "це",
+            "цей",
+            "ця",
+            "ці",
+            "що",
+            "як",
+            "не",
+            "є",
+            "ви",
+            "ми",
+            "він",
+            "вона",
+            "воно",
+            "вони",
+            "його",
+            "її",
+            "їх",
+            "може",
+            "також",
+        ],
+        Language::Italian => &[
+            "il", "lo", "la", "i", "gli", "le", "un", "uno", "una", "di", "del", "della", "e", "o",
+            "ma", "a", "al", "in", "nel", "su", "per", "con", "da", "è", "sono", "che", "non",
+            "si", "questo", "questa", "come", "più", "può", "essere",
+        ],
+        Language::Portuguese => &[
+            "o", "a", "os", "as", "um", "uma", "de", "do", "da", "dos", "das", "e", "ou", "mas",
+            "em", "no", "na", "por", "para", "com", "é", "são", "que", "não", "se", "seu", "sua",
+            "este", "esta", "como", "mais", "pode", "ser",
+        ],
+        Language::Dutch => &[
+            "de", "het", "een", "en", "of", "maar", "van", "in", "op", "te", "voor", "met", "door",
+            "aan", "is", "zijn", "was", "dat", "die", "dit", "niet", "ook", "als", "kan", "worden",
+            "wordt", "u", "je", "we", "zij",
+        ],
+        Language::Polish => &[
+            "i", "w", "z", "na", "do", "nie", "się", "to", "jest", "są", "że", "o", "a", "lub",
+            "ale", "od", "po", "przez", "dla", "jak", "ten", "ta", "te", "może", "być", "czy",
+        ],
+        Language::Russian => &[
+            "и",
+            "в",
+            "во",
+            "не",
+            "на",
+            "с",
+            "со",
+            "что",
+            "как",
+            "а",
+            "но",
+            "или",
+            "по",
+            "к",
+            "у",
+            "из",
+            "за",
+            "для",
+            "от",
+            "это",
+            "этот",
+            "эта",
+            "эти",
+            "он",
+            "она",
+            "они",
+            "вы",
+            "мы",
+            "быть",
+            "может",
+            "также",
+        ],
+        Language::Turkish => &[
+            "ve", "veya", "ama", "bir", "bu", "şu", "o", "da", "de", "ile", "için", "gibi", "daha",
+            "en", "çok", "ne", "mi", "değil", "olarak", "olan", "var", "yok",
+        ],
+        Language::Arabic => &[
+            "في", "من", "على", "إلى", "عن", "مع", "و", "أو", "لكن", "أن", "إن", "هذا", "هذه",
+            "ذلك", "التي", "الذي", "هو", "هي", "كان", "لا", "ما", "كل",
+        ],
+        // Japanese and Chinese words aren't separated by spaces and the Korean particles are
+        // attached to the words, a list of words wouldn't match. No stop words are known for
+        // the custom languages.
+        Language::Japanese | Language::Chinese | Language::Korean | Language::Custom(_) => &[],
+    };
+    list.contains(&word.as_str())
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// A term of a draft glossary
+pub struct DraftEntry {
+    pub term: String,
+    pub occurrences: usize,
+    /// the translation, suggested by the model or filled in by the user, empty if unknown
+    pub translation: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// A glossary for one language pair to be reviewed by the user before it's committed
+pub struct GlossaryDraft {
+    pub source: Language,
+    pub target: Language,
+    pub entries: Vec<DraftEntry>,
+}
+
+impl GlossaryDraft {
+    /// Creates a draft of the candidates without translations, the terms already in the glossary
+    /// for the pair are left out
+    pub fn new(
+        source: Language,
+        target: Language,
+        candidates: Vec<TermCandidate>,
+        existing: &Glossary,
+    ) -> Self {
+        let known = existing.entries_for_pair(source.clone(), target.clone());
+        let entries = candidates
+            .into_iter()
+            .filter(|c| {
+                !known
+                    .iter()
+                    .any(|e| e.term.to_lowercase() == c.term.to_lowercase())
+            })
+            .map(|c| DraftEntry {
+                term: c.term,
+                occurrences: c.occurrences,
+                translation: String::new(),
+            })
+            .collect();
+        Self {
+            source,
+            target,
+            entries,
+        }
+    }
+
+    /// Writes the draft as tab separated values to be edited by the user: term, translation and
+    /// the number of occurrences
+    pub fn to_tsv(&self) -> String {
+        let mut res = String::from("term\ttranslation\toccurrences\n");
+        for entry in &self.entries {
+            res.push_str(&format!(
+                "{}\t{}\t{}\n",
+                entry.term, entry.translation, entry.occurrences
+            ));
+        }
+        res
+    }
+
+    /// Reads a draft edited by the user, the lines without a term are skipped
+    pub fn from_tsv(source: Language, target: Language, tsv: &str) -> Self {
+        let entries = tsv
+            .lines()
+            .skip(1)
+            .filter_map(|line| {
+                let mut cols = line.split('\t');
+                let term = cols.next()?.trim();
+                if term.is_empty() {
+                    return None;
+                }
+                Some(DraftEntry {
+                    term: term.to_string(),
+                    translation: cols.next().unwrap_or("").trim().to_string(),
+                    occurrences: cols.next().and_then(|o| o.trim().parse().ok()).unwrap_or(0),
+                })
+            })
+            .collect();
+        Self {
+            source,
+            target,
+            entries,
+        }
+    }
+
+    /// Returns the glossary entries of the translated terms, scoped to the language pair of the
+    /// draft
+    pub fn to_entries(&self) -> Vec<GlossaryEntry> {
+        self.entries
+            .iter()
+            .filter(|e| !e.translation.is_empty())
+            .map(|e| {
+                GlossaryEntry::new(&e.term, &e.translation)
+                    .with_pair(Some(self.source.clone()), Some(self.target.clone()))
+            })
+            .collect()
+    }
+}
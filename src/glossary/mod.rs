@@ -0,0 +1,280 @@
+//! Glossary of approved translations of terms. An entry can be scoped to a source language, a
+//! target language or both, so a term like "stack" can have different approved translations in
+//! German and in Ukrainian. For a language pair the most specific entry of a term wins.
+use crate::{errors::glossary_errors::GlossaryError, Language};
+
+pub mod extract;
+
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+/// An approved translation of a term
+pub struct GlossaryEntry {
+    /// the term in the source language
+    term: String,
+    /// its approved translation
+    translation: String,
+    /// source language the entry applies to, `None` for any
+    #[serde(default)]
+    source: Option<Language>,
+    /// target language the entry applies to, `None` for any
+    #[serde(default)]
+    target: Option<Language>,
+    /// optional explanation for the translators
+    #[serde(default)]
+    note: Option<String>,
+}
+
+impl GlossaryEntry {
+    /// Creates an entry that applies to every language pair
+    pub fn new(term: &str, translation: &str) -> Self {
+        Self {
+            term: term.to_string(),
+            translation: translation.to_string(),
+            source: None,
+            target: None,
+            note: None,
+        }
+    }
+    /// Restricts the entry to the _source_ → _target_ pair, `None` keeps a side unrestricted
+    pub fn with_pair(mut self, source: Option<Language>, target: Option<Language>) -> Self {
+        self.source = source;
+        self.target = target;
+        self
+    }
+    pub fn with_note(mut self, note: &str) -> Self {
+        self.note = Some(note.to_string());
+        self
+    }
+    pub fn get_term(&self) -> String {
+        self.term.clone()
+    }
+    pub fn get_translation(&self) -> String {
+        self.translation.clone()
+    }
+    pub fn get_source(&self) -> Option<Language> {
+        self.source.clone()
+    }
+    pub fn get_target(&self) -> Option<Language> {
+        self.target.clone()
+    }
+    pub fn get_note_as_ref(&self) -> &Option<String> {
+        &self.note
+    }
+    /// returns true if the entry applies to the _source_ → _target_ pair
+    pub fn applies_to(&self, source: Language, target: Language) -> bool {
+        self.source.clone().is_none_or(|l| l == source)
+            && self.target.clone().is_none_or(|l| l == target)
+    }
+    /// number of languages the entry is restricted to, the higher the more specific
+    fn specificity(&self) -> usize {
+        self.source.is_some() as usize + self.target.is_some() as usize
+    }
+    fn same_term(&self, other: &GlossaryEntry) -> bool {
+        self.term.to_lowercase() == other.term.to_lowercase()
+    }
+    /// returns true if there's a language pair both entries apply to with the same specificity,
+    /// so none of them would win over the other
+    fn clashes_with(&self, other: &GlossaryEntry) -> bool {
+        let overlap = |a: Option<Language>, b: Option<Language>| match (a, b) {
+            (Some(a), Some(b)) => a == b,
+            _ => true,
+        };
+        self.same_term(other)
+            && self.translation != other.translation
+            && overlap(self.source.clone(), other.source.clone())
+            && overlap(self.target.clone(), other.target.clone())
+            && self.specificity() == other.specificity()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// Two entries that give different translations of the same term for the same language pair
+pub struct GlossaryConflict {
+    pub first: GlossaryEntry,
+    pub second: GlossaryEntry,
+}
+
+#[derive(Debug, Clone, Default, serde::Serialize, serde::Deserialize)]
+pub struct Glossary {
+    entries: Vec<GlossaryEntry>,
+}
+
+impl Glossary {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    pub fn get_entries_as_ref(&self) -> &Vec<GlossaryEntry> {
+        &self.entries
+    }
+    /// Adds the entry, fails if it conflicts with an existing one. An entry equal to an existing
+    /// one is ignored.
+    pub fn add_entry(&mut self, entry: GlossaryEntry) -> Result<(), GlossaryError> {
+        if self.entries.contains(&entry) {
+            return Ok(());
+        }
+        if let Some(existing) = self.entries.iter().find(|e| e.clashes_with(&entry)) {
+            return Err(GlossaryError::Conflict {
+                term: entry.get_term(),
+                existing: existing.get_translation(),
+                new: entry.get_translation(),
+            });
+        }
+        self.entries.push(entry);
+        Ok(())
+    }
+    /// Removes the entries of the term with exactly this scope, returns true if one was removed
+    pub fn remove_entry(
+        &mut self,
+        term: &str,
+        source: Option<Language>,
+        target: Option<Language>,
+    ) -> bool {
+        let term = term.to_lowercase();
+        let len = self.entries.len();
+        self.entries.retain(|e| {
+            !(e.term.to_lowercase() == term && e.source == source && e.target == target)
+        });
+        len != self.entries.len()
+    }
+    /// Returns all the conflicting entries, e.g. of a glossary edited by hand
+    pub fn validate(&self) -> Vec<GlossaryConflict> {
+        let mut res = Vec::new();
+        for (id, first) in self.entries.iter().enumerate() {
+            for second in &self.entries[id + 1..] {
+                if first.clashes_with(second) {
+                    res.push(GlossaryConflict {
+                        first: first.clone(),
+                        second: second.clone(),
+                    });
+                }
+            }
+        }
+        res
+    }
+    /// Returns the glossary with the entries of _overrides_ and the entries of this glossary
+    /// whose terms and language pairs none of them covers, e.g. a shared glossary overridden by
+    /// the one of a project
+    pub fn overridden_by(&self, overrides: &Glossary) -> Glossary {
+        let overlap = |a: Option<Language>, b: Option<Language>| match (a, b) {
+            (Some(a), Some(b)) => a == b,
+            _ => true,
+        };
+        let mut entries = overrides.entries.clone();
+        for entry in &self.entries {
+            let overridden = overrides.entries.iter().any(|o| {
+                o.same_term(entry)
+                    && overlap(o.source.clone(), entry.source.clone())
+                    && overlap(o.target.clone(), entry.target.clone())
+            });
+            if !overridden {
+                entries.push(entry.clone());
+            }
+        }
+        Glossary { entries }
+    }
+    /// Returns the entries that apply to the _source_ → _target_ pair, one per term: the most
+    /// specific one
+    pub fn entries_for_pair(&self, source: Language, target: Language) -> Vec<&GlossaryEntry> {
+        self.select_entries(Some(source), target)
+    }
+    /// Same as [`Glossary::entries_for_pair`], an unknown source language only matches the
+    /// entries that aren't restricted to a source language
+    fn select_entries(&self, source: Option<Language>, target: Language) -> Vec<&GlossaryEntry> {
+        let applies = |e: &GlossaryEntry| match source {
+            Some(source) => e.applies_to(source, target),
+            None => e.source.is_none() && e.target.clone().is_none_or(|l| l == target),
+        };
+        let mut res: Vec<&GlossaryEntry> = Vec::new();
+        for entry in self.entries.iter().filter(|e| applies.clone()(e)) {
+            match res.iter_mut().find(|e| e.same_term(entry)) {
+                Some(chosen) if chosen.specificity() < entry.specificity() => *chosen = entry,
+                Some(_) => {}
+                None => res.push(entry),
+            }
+        }
+        res
+    }
+    /// Returns the entries of the pair as a list for the prompt, one `term → translation` per
+    /// line, empty if there are none
+    pub fn format_for_prompt(&self, source: Option<Language>, target: Language) -> String {
+        self.select_entries(source, target)
+            .iter()
+            .map(|e| match &e.note {
+                Some(note) => format!("- {} → {} ({})\n", e.term, e.translation, note),
+                None => format!("- {} → {}\n", e.term, e.translation),
+            })
+            .collect()
+    }
+    /// Returns the glossary of the translation from the _pivot_ language into _target_ of a text
+    /// translated from _source_ into _pivot_ first: the entries of the _source_ → _target_ pair,
+    /// then those of the _pivot_ → _target_ pair for the other terms, all restricted to the
+    /// _pivot_ → _target_ pair
+    pub fn for_pivot(&self, source: Option<Language>, pivot: Language, target: Language) -> Self {
+        let mut entries: Vec<GlossaryEntry> = Vec::new();
+        let selected = self
+            .select_entries(source, target.clone())
+            .into_iter()
+            .chain(self.select_entries(Some(pivot.clone()), target.clone()));
+        for entry in selected {
+            if !entries.iter().any(|e| e.same_term(entry)) {
+                entries.push(
+                    entry
+                        .clone()
+                        .with_pair(Some(pivot.clone()), Some(target.clone())),
+                );
+            }
+        }
+        Glossary { entries }
+    }
+    /// Exports the entries of the _source_ → _target_ pair as tab separated values with a
+    /// header line: term, translation and note
+    pub fn export_pair(&self, source: Language, target: Language) -> String {
+        let mut res = String::from("term\ttranslation\tnote\n");
+        for entry in self.entries_for_pair(source, target) {
+            let clean = |s: &str| s.replace(['\t', '\n'], " ");
+            res.push_str(&format!(
+                "{}\t{}\t{}\n",
+                clean(&entry.term),
+                clean(&entry.translation),
+                clean(entry.note.as_deref().unwrap_or(""))
+            ));
+        }
+        res
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn the_pivot_glossary_keeps_the_entries_of_the_source() {
+        let mut glossary = Glossary::new();
+        let entries = [
+            GlossaryEntry::new("ліс", "bosque").with_pair(Some(Language::Ukrainian), None),
+            GlossaryEntry::new("forest", "selva")
+                .with_pair(Some(Language::English), Some(Language::Spanish)),
+            GlossaryEntry::new("forest", "arboleda")
+                .with_pair(Some(Language::English), Some(Language::French)),
+        ];
+        for entry in entries {
+            glossary.add_entry(entry).unwrap();
+        }
+        let pivot = glossary.for_pivot(
+            Some(Language::Ukrainian),
+            Language::English,
+            Language::Spanish,
+        );
+        let terms: Vec<(String, String)> = pivot
+            .entries_for_pair(Language::English, Language::Spanish)
+            .iter()
+            .map(|e| (e.get_term(), e.get_translation()))
+            .collect();
+        assert_eq!(
+            terms,
+            vec![
+                ("ліс".to_string(), "bosque".to_string()),
+                ("forest".to_string(), "selva".to_string()),
+            ]
+        );
+    }
+}
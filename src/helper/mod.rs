@@ -1,5 +1,9 @@
 //! A module with helper functions. Most of functions aim to work with files and text.
-use std::io::{Read, Write};
+use std::io::Read;
+
+use crate::errors::translator_errors::ResponseError;
+
+pub mod xml;
 
 /// Search the given directory and each parent directory for `file_name`.
 /// Returns the full path to the first match, or `None` if nothing is found.
@@ -43,7 +47,7 @@ pub fn divide_into_chunks(text: String, lines_per_chunk: usize) -> Vec<String> {
         temp_res.push_str(part);
         temp_res.push('\n');
 
-        if (id > 0 && id % lines_per_chunk == 0) {
+        if id > 0 && id % lines_per_chunk == 0 {
             res.push(temp_res);
             temp_res = String::new();
         }
@@ -55,15 +59,16 @@ pub fn divide_into_chunks(text: String, lines_per_chunk: usize) -> Vec<String> {
     res
 }
 
-/// Takes a text into parameter and returns the content written in the `<document>` tag.
-pub fn extract_translated_from_response(message: String) -> String {
+/// Takes a text into parameter and returns the content written in the `<output>` tag, fails if
+/// there is no such tag.
+pub fn extract_translated_from_response(message: String) -> Result<String, ResponseError> {
     if !message.contains("<output>") {
-        return String::new();
+        return Err(ResponseError::MissingOutput);
     }
     let mut res = String::new();
     let mut chunks_iter = message.split("<output>");
     let _ = chunks_iter.next();
-    while let Some(chunk) = chunks_iter.next() {
+    for chunk in chunks_iter {
         let chunk = chunk.strip_prefix("\n").unwrap_or(chunk);
         let mut chunk_string = chunk;
 
@@ -72,7 +77,66 @@ pub fn extract_translated_from_response(message: String) -> String {
         }
         res.push_str(chunk_string);
     }
-    res
+    Ok(res)
+}
+
+/// Extracts the contents of the `<output>` tags from a response that arrives piece by piece.
+/// Text that could be the beginning of a tag is held back until the next piece arrives.
+#[derive(Default)]
+pub struct OutputExtractor {
+    buffer: String,
+    inside: bool,
+    /// true right after an opening tag, the newline that follows it isn't a part of the output
+    after_open: bool,
+    /// true once an opening tag was received
+    opened: bool,
+}
+
+impl OutputExtractor {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// returns true if the response received so far has an `<output>` tag
+    pub fn has_output(&self) -> bool {
+        self.opened
+    }
+
+    /// Adds the next piece of the response, returns the newly extracted text
+    pub fn push(&mut self, piece: &str) -> String {
+        self.buffer.push_str(piece);
+        let mut res = String::new();
+        loop {
+            if self.after_open && !self.buffer.is_empty() {
+                if self.buffer.starts_with('\n') {
+                    self.buffer.remove(0);
+                }
+                self.after_open = false;
+            }
+            let tag = if self.inside { "</output>" } else { "<output>" };
+            if let Some(id) = self.buffer.find(tag) {
+                if self.inside {
+                    res.push_str(&self.buffer[..id]);
+                }
+                self.buffer.drain(..id + tag.len());
+                self.inside = !self.inside;
+                self.after_open = self.inside;
+                self.opened |= self.inside;
+                continue;
+            }
+            // keep what could be a partially received tag
+            let keep = (1..tag.len())
+                .rev()
+                .find(|n| self.buffer.ends_with(&tag[..*n]))
+                .unwrap_or(0);
+            let emit_len = self.buffer.len() - keep;
+            let emitted: String = self.buffer.drain(..emit_len).collect();
+            if self.inside {
+                res.push_str(&emitted);
+            }
+            return res;
+        }
+    }
 }
 
 /// Reads file and returns its contents in the String format
@@ -83,3 +147,199 @@ pub fn read_string_file(path: impl Into<std::path::PathBuf>) -> String {
     let _ = file.read_to_string(&mut contents);
     contents
 }
+
+/// Returns the 64-bit FNV-1a hash of the bytes. Unlike `DefaultHasher` the result is stable
+/// between runs and compiler versions, so it can be persisted.
+pub fn fnv1a_64(bytes: &[u8]) -> u64 {
+    let mut hash: u64 = 0xcbf29ce484222325;
+    for byte in bytes {
+        hash ^= *byte as u64;
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+    hash
+}
+
+/// Returns a stable hash of the text as a hex string
+pub fn content_hash(text: &str) -> String {
+    bytes_hash(text.as_bytes())
+}
+
+/// Returns a stable hash of the bytes as a hex string
+pub fn bytes_hash(bytes: &[u8]) -> String {
+    format!("{:016x}", fnv1a_64(bytes))
+}
+
+/// Matches a path with `/` separators against a glob pattern: `*` matches any part of a name,
+/// `?` a single character of it and `**` any number of directories
+pub fn glob_match(pattern: &str, path: &str) -> bool {
+    let pattern: Vec<&str> = pattern.split('/').filter(|s| !s.is_empty()).collect();
+    let path: Vec<&str> = path.split('/').filter(|s| !s.is_empty()).collect();
+    match_segments(&pattern, &path)
+}
+
+fn match_segments(pattern: &[&str], path: &[&str]) -> bool {
+    match pattern.first() {
+        None => path.is_empty(),
+        Some(&"**") => (0..=path.len()).any(|i| match_segments(&pattern[1..], &path[i..])),
+        Some(segment) => {
+            !path.is_empty()
+                && match_name(
+                    &segment.chars().collect::<Vec<_>>(),
+                    &path[0].chars().collect::<Vec<_>>(),
+                )
+                && match_segments(&pattern[1..], &path[1..])
+        }
+    }
+}
+
+fn match_name(pattern: &[char], name: &[char]) -> bool {
+    match pattern.first() {
+        None => name.is_empty(),
+        Some('*') => (0..=name.len()).any(|i| match_name(&pattern[1..], &name[i..])),
+        Some('?') => !name.is_empty() && match_name(&pattern[1..], &name[1..]),
+        Some(c) => name.first() == Some(c) && match_name(&pattern[1..], &name[1..]),
+    }
+}
+
+/// Copies the file as a reflink, a copy-on-write clone sharing the blocks of the source, where
+/// the file system supports it (Btrfs, XFS), as a regular copy otherwise. On macOS the standard
+/// copy already clones the file on APFS.
+pub fn reflink_or_copy(from: &std::path::Path, to: &std::path::Path) -> std::io::Result<u64> {
+    #[cfg(target_os = "linux")]
+    if let Ok(size) = reflink(from, to) {
+        return Ok(size);
+    }
+    std::fs::copy(from, to)
+}
+
+#[cfg(target_os = "linux")]
+fn reflink(from: &std::path::Path, to: &std::path::Path) -> std::io::Result<u64> {
+    use std::os::fd::AsRawFd;
+
+    let source = std::fs::File::open(from)?;
+    let meta = source.metadata()?;
+    let target = std::fs::File::create(to)?;
+    // SAFETY: both descriptors are open for the duration of the call
+    let res = unsafe { libc::ioctl(target.as_raw_fd(), libc::FICLONE, source.as_raw_fd()) };
+    if res != 0 {
+        return Err(std::io::Error::last_os_error());
+    }
+    target.set_permissions(meta.permissions())?;
+    Ok(meta.len())
+}
+
+/// Applies _func_ to every item on up to _workers_ threads, returns the results in the order
+/// of the items
+pub fn run_parallel<T, R, F>(items: &[T], workers: usize, func: F) -> Vec<R>
+where
+    T: Sync,
+    R: Send,
+    F: Fn(&T) -> R + Sync,
+{
+    let workers = workers.clamp(1, items.len().max(1));
+    if workers == 1 {
+        return items.iter().map(&func).collect();
+    }
+    let next = std::sync::atomic::AtomicUsize::new(0);
+    let mut results: Vec<(usize, R)> = std::thread::scope(|scope| {
+        let handles: Vec<_> = (0..workers)
+            .map(|_| {
+                scope.spawn(|| {
+                    let mut done = Vec::new();
+                    loop {
+                        let id = next.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+                        let Some(item) = items.get(id) else {
+                            return done;
+                        };
+                        done.push((id, func(item)));
+                    }
+                })
+            })
+            .collect();
+        handles
+            .into_iter()
+            .flat_map(|h| h.join().unwrap_or_else(|e| std::panic::resume_unwind(e)))
+            .collect()
+    });
+    results.sort_by_key(|(id, _)| *id);
+    results.into_iter().map(|(_, res)| res).collect()
+}
+
+/// Returns the number of threads the work of a process should be spread on
+pub fn get_parallelism() -> usize {
+    std::thread::available_parallelism()
+        .map(|n| n.get())
+        .unwrap_or(1)
+}
+
+/// A file written atomically: the contents go into a temporary file next to it that replaces
+/// it on [`AtomicFile::commit`], so a crash never leaves a truncated file behind. The temporary
+/// file is removed if it's dropped before.
+pub struct AtomicFile {
+    file: Option<std::fs::File>,
+    path: std::path::PathBuf,
+    tmp_path: std::path::PathBuf,
+}
+
+impl AtomicFile {
+    pub fn create(path: &std::path::Path) -> std::io::Result<Self> {
+        let name = path.file_name().unwrap_or_default().to_string_lossy();
+        let tmp_path = path.with_file_name(format!(".{}.tmp", name));
+        let file = std::fs::File::create(&tmp_path)?;
+        Ok(Self {
+            file: Some(file),
+            path: path.to_path_buf(),
+            tmp_path,
+        })
+    }
+    /// Flushes the contents to the disk and replaces the file with them, the permissions of
+    /// the replaced file are kept
+    pub fn commit(mut self) -> std::io::Result<()> {
+        let Some(file) = &self.file else {
+            return Ok(());
+        };
+        file.sync_all()?;
+        if let Ok(meta) = std::fs::metadata(&self.path) {
+            file.set_permissions(meta.permissions())?;
+        }
+        // closed before the rename, a failed one still removes the temporary file on drop
+        self.file = None;
+        let res = std::fs::rename(&self.tmp_path, &self.path);
+        if res.is_err() {
+            let _ = std::fs::remove_file(&self.tmp_path);
+        }
+        res
+    }
+}
+
+impl std::io::Write for AtomicFile {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        match &mut self.file {
+            Some(file) => file.write(buf),
+            None => Err(std::io::Error::other("the file is already committed")),
+        }
+    }
+    fn flush(&mut self) -> std::io::Result<()> {
+        match &mut self.file {
+            Some(file) => file.flush(),
+            None => Ok(()),
+        }
+    }
+}
+
+impl Drop for AtomicFile {
+    fn drop(&mut self) {
+        if self.file.take().is_some() {
+            let _ = std::fs::remove_file(&self.tmp_path);
+        }
+    }
+}
+
+/// Writes the contents into the file atomically, see [`AtomicFile`]
+pub fn write_atomic(path: &std::path::Path, contents: impl AsRef<[u8]>) -> std::io::Result<()> {
+    use std::io::Write;
+
+    let mut file = AtomicFile::create(path)?;
+    file.write_all(contents.as_ref())?;
+    file.commit()
+}
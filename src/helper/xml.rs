@@ -0,0 +1,125 @@
+//! Minimal reading and writing of XML, enough for the exchange formats (TMX, XLIFF). Namespaces,
+//! DTDs and comments aren't interpreted.
+
+/// An element found in a document
+pub struct Element<'a> {
+    pub attributes: &'a str,
+    pub inner: &'a str,
+    /// the document after the element
+    pub rest: &'a str,
+}
+
+/// Finds the next element named _name_, `None` if there is none. Elements of the same name
+/// can't be nested.
+pub fn next_element<'a>(text: &'a str, name: &str) -> Result<Option<Element<'a>>, String> {
+    let open = format!("<{}", name);
+    let mut from = 0;
+    let start = loop {
+        let Some(id) = text[from..].find(&open).map(|id| from + id) else {
+            return Ok(None);
+        };
+        let after = &text[id + open.len()..];
+        // `<tu` is also the beginning of `<tuv`
+        if after.starts_with(|c: char| c.is_whitespace() || c == '>' || c == '/') {
+            break id + open.len();
+        }
+        from = id + open.len();
+    };
+    let tag_end = text[start..]
+        .find('>')
+        .map(|id| start + id)
+        .ok_or_else(|| format!("unclosed <{}> tag", name))?;
+    if text[..tag_end].ends_with('/') {
+        return Ok(Some(Element {
+            attributes: &text[start..tag_end - 1],
+            inner: "",
+            rest: &text[tag_end + 1..],
+        }));
+    }
+    let close = format!("</{}>", name);
+    let inner_end = text[tag_end..]
+        .find(&close)
+        .map(|id| tag_end + id)
+        .ok_or_else(|| format!("unclosed <{}> element", name))?;
+    Ok(Some(Element {
+        attributes: &text[start..tag_end],
+        inner: &text[tag_end + 1..inner_end],
+        rest: &text[inner_end + close.len()..],
+    }))
+}
+
+/// Returns the unescaped value of the attribute, `None` if it isn't set
+pub fn get_attribute(attributes: &str, name: &str) -> Option<String> {
+    let mut rest = attributes;
+    while let Some(id) = rest.find(name) {
+        let before_ok = rest[..id].ends_with(char::is_whitespace);
+        let value = rest[id + name.len()..].trim_start().strip_prefix('=');
+        if let Some(value) = value.filter(|_| before_ok) {
+            let value = value.trim_start();
+            let quote = value.chars().next()?;
+            if quote != '"' && quote != '\'' {
+                return None;
+            }
+            let end = value[1..].find(quote)?;
+            return Some(unescape(&value[1..end + 1]));
+        }
+        rest = &rest[id + name.len()..];
+    }
+    None
+}
+
+/// Replaces the XML entities and character references by their characters
+pub fn unescape(text: &str) -> String {
+    let mut res = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(id) = rest.find('&') {
+        res.push_str(&rest[..id]);
+        rest = &rest[id..];
+        let decoded = rest.find(';').and_then(|end| {
+            let entity = &rest[1..end];
+            let c = match entity {
+                "amp" => Some('&'),
+                "lt" => Some('<'),
+                "gt" => Some('>'),
+                "quot" => Some('"'),
+                "apos" => Some('\''),
+                _ => entity
+                    .strip_prefix("#x")
+                    .map(|hex| u32::from_str_radix(hex, 16))
+                    .or_else(|| entity.strip_prefix('#').map(|dec| dec.parse()))
+                    .and_then(|code| code.ok())
+                    .and_then(char::from_u32),
+            };
+            c.map(|c| (c, end))
+        });
+        match decoded {
+            Some((c, end)) => {
+                res.push(c);
+                rest = &rest[end + 1..];
+            }
+            None => {
+                res.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    res.push_str(rest);
+    res
+}
+
+/// Escapes the text for XML, the control characters XML doesn't allow are left out
+pub fn escape(text: &str) -> String {
+    let mut res = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => res.push_str("&amp;"),
+            '<' => res.push_str("&lt;"),
+            '>' => res.push_str("&gt;"),
+            '"' => res.push_str("&quot;"),
+            '\t' | '\n' | '\r' => res.push(c),
+            c if c.is_control() && (c as u32) < 0x20 => {}
+            c => res.push(c),
+        }
+    }
+    res
+}
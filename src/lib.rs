@@ -1,23 +1,208 @@
+pub mod bundle;
+pub mod change_report;
+pub mod confirm;
+pub mod detect;
+pub mod doctor;
 pub mod errors;
+pub mod estimate;
+pub mod events;
+pub mod exchange;
+pub mod format;
+pub mod fs;
+pub mod glossary;
 pub mod helper;
 pub mod lib_config;
+pub mod lock;
+pub mod merge;
+pub mod metrics;
+pub mod naming;
+pub mod progress;
 pub mod project;
 pub mod project_config;
+pub mod qa;
+pub mod sandbox;
+pub mod server;
+pub mod shared_store;
+pub mod shutdown;
+pub mod state;
+pub mod status;
+pub mod testing;
+pub mod translation_memory;
 pub mod translator;
-#[cfg(feature = "cli_support")]
-use clap::ValueEnum;
+pub mod watch;
+pub mod watermark;
+pub mod workspace;
+use std::sync::Arc;
 
-#[cfg_attr(feature = "cli_support", derive(ValueEnum))]
-#[derive(Debug, Clone, Copy, serde::Serialize, serde::Deserialize, PartialEq, Eq)]
+use errors::language_errors::LanguageError;
+
+/// Languages with their data built into the crate (e.g. the stop words of the glossary
+/// extraction), the other ones are [`Language::Custom`]
+pub const BUILT_IN_LANGUAGES: [Language; 15] = [
+    Language::French,
+    Language::English,
+    Language::German,
+    Language::Spanish,
+    Language::Ukrainian,
+    Language::Italian,
+    Language::Portuguese,
+    Language::Dutch,
+    Language::Polish,
+    Language::Japanese,
+    Language::Chinese,
+    Language::Korean,
+    Language::Arabic,
+    Language::Russian,
+    Language::Turkish,
+];
+
+/// Primary subtags of the languages written from right to left
+const RTL_CODES: [&str; 10] = ["ar", "arc", "dv", "fa", "he", "ps", "sd", "ug", "ur", "yi"];
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize, PartialEq, Eq, Hash)]
+#[serde(try_from = "LanguageRepr")]
 pub enum Language {
     French,
     English,
     German,
     Spanish,
     Ukrainian,
+    Italian,
+    Portuguese,
+    Dutch,
+    Polish,
+    Japanese,
+    Chinese,
+    Korean,
+    Arabic,
+    Russian,
+    Turkish,
+    /// any other language, see [`Language::custom`]
+    Custom(Arc<CustomLanguage>),
+}
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize, PartialEq, Eq, Hash)]
+/// A language that isn't built into the crate
+pub struct CustomLanguage {
+    /// BCP-47 code of the language (e.g. `ja`, `pt-BR`)
+    code: String,
+    /// name of the language in English, used in the prompt
+    name: String,
+    /// suffix of the name of its target directory (e.g. `_ja`)
+    suffix: String,
+}
+
+/// How a language is stored, the custom ones are validated when they're read
+#[derive(serde::Deserialize)]
+enum LanguageRepr {
+    French,
+    English,
+    German,
+    Spanish,
+    Ukrainian,
+    Italian,
+    Portuguese,
+    Dutch,
+    Polish,
+    Japanese,
+    Chinese,
+    Korean,
+    Arabic,
+    Russian,
+    Turkish,
+    Custom(CustomLanguage),
+}
+
+impl TryFrom<LanguageRepr> for Language {
+    type Error = LanguageError;
+
+    fn try_from(value: LanguageRepr) -> Result<Self, Self::Error> {
+        let lang = match value {
+            LanguageRepr::French => Language::French,
+            LanguageRepr::English => Language::English,
+            LanguageRepr::German => Language::German,
+            LanguageRepr::Spanish => Language::Spanish,
+            LanguageRepr::Ukrainian => Language::Ukrainian,
+            LanguageRepr::Italian => Language::Italian,
+            LanguageRepr::Portuguese => Language::Portuguese,
+            LanguageRepr::Dutch => Language::Dutch,
+            LanguageRepr::Polish => Language::Polish,
+            LanguageRepr::Japanese => Language::Japanese,
+            LanguageRepr::Chinese => Language::Chinese,
+            LanguageRepr::Korean => Language::Korean,
+            LanguageRepr::Arabic => Language::Arabic,
+            LanguageRepr::Russian => Language::Russian,
+            LanguageRepr::Turkish => Language::Turkish,
+            LanguageRepr::Custom(custom) => {
+                return Language::custom(&custom.code, &custom.name, Some(&custom.suffix))
+            }
+        };
+        Ok(lang)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+/// What a UI needs to show a language, see [`Language::info`]
+pub struct LanguageInfo {
+    pub language: Language,
+    /// ISO 639-1 code, the BCP-47 code of a custom language
+    pub code: String,
+    /// ISO 639-3 code, `None` if it isn't known
+    pub iso639_3: Option<String>,
+    /// name in English
+    pub name: String,
+    /// name in the language itself
+    pub native_name: String,
+    /// if true, the language is written from right to left
+    pub rtl: bool,
+}
+
+impl CustomLanguage {
+    pub fn get_code(&self) -> &str {
+        &self.code
+    }
+    pub fn get_name(&self) -> &str {
+        &self.name
+    }
+    pub fn get_suffix(&self) -> &str {
+        &self.suffix
+    }
 }
 
 impl Language {
+    /// Returns the language of the BCP-47 _code_ that isn't built into the crate, _name_ is its
+    /// name in English and _suffix_ the suffix of its target directory (`_<code>` if `None`).
+    /// The code of a built-in language (e.g. `fr`) can't be used, a region of it can (`fr-CA`).
+    pub fn custom(code: &str, name: &str, suffix: Option<&str>) -> Result<Language, LanguageError> {
+        if !is_language_code(code) {
+            return Err(LanguageError::InvalidCode(code.to_string()));
+        }
+        if BUILT_IN_LANGUAGES
+            .iter()
+            .any(|l| l.get_code().eq_ignore_ascii_case(code))
+        {
+            return Err(LanguageError::BuiltInCode(code.to_string()));
+        }
+        if name.trim().is_empty() {
+            return Err(LanguageError::EmptyName);
+        }
+        let suffix = match suffix {
+            Some(suffix) => suffix.to_string(),
+            None => format!("_{}", code.to_ascii_lowercase()),
+        };
+        if !is_dir_suffix(&suffix) {
+            return Err(LanguageError::InvalidSuffix(suffix));
+        }
+        Ok(Language::Custom(Arc::new(CustomLanguage {
+            code: code.to_string(),
+            name: name.trim().to_string(),
+            suffix,
+        })))
+    }
+    /// Returns the languages built into the crate, see [`BUILT_IN_LANGUAGES`]
+    pub fn all() -> Vec<Language> {
+        BUILT_IN_LANGUAGES.to_vec()
+    }
     pub fn get_dir_suffix(&self) -> &str {
         match self {
             Language::French => "_fr",
@@ -25,18 +210,202 @@ impl Language {
             Language::German => "_de",
             Language::Spanish => "_sp",
             Language::Ukrainian => "_ua",
+            Language::Italian => "_it",
+            Language::Portuguese => "_pt",
+            Language::Dutch => "_nl",
+            Language::Polish => "_pl",
+            Language::Japanese => "_ja",
+            Language::Chinese => "_zh",
+            Language::Korean => "_ko",
+            Language::Arabic => "_ar",
+            Language::Russian => "_ru",
+            Language::Turkish => "_tr",
+            Language::Custom(custom) => &custom.suffix,
         }
     }
-}
-
-impl From<Language> for &str {
-    fn from(value: Language) -> Self {
-        match value {
+    /// returns the ISO 639-1 code of the language, the BCP-47 code of a custom one
+    pub fn get_code(&self) -> &str {
+        match self {
+            Language::French => "fr",
+            Language::English => "en",
+            Language::German => "de",
+            Language::Spanish => "es",
+            Language::Ukrainian => "uk",
+            Language::Italian => "it",
+            Language::Portuguese => "pt",
+            Language::Dutch => "nl",
+            Language::Polish => "pl",
+            Language::Japanese => "ja",
+            Language::Chinese => "zh",
+            Language::Korean => "ko",
+            Language::Arabic => "ar",
+            Language::Russian => "ru",
+            Language::Turkish => "tr",
+            Language::Custom(custom) => &custom.code,
+        }
+    }
+    /// returns the name of the language in itself (e.g. `français`), the name of a custom one
+    pub fn get_native_name(&self) -> &str {
+        match self {
+            Language::French => "français",
+            Language::English => "English",
+            Language::German => "Deutsch",
+            Language::Spanish => "español",
+            Language::Ukrainian => "українська",
+            Language::Italian => "italiano",
+            Language::Portuguese => "português",
+            Language::Dutch => "Nederlands",
+            Language::Polish => "polski",
+            Language::Japanese => "日本語",
+            Language::Chinese => "中文",
+            Language::Korean => "한국어",
+            Language::Arabic => "العربية",
+            Language::Russian => "русский",
+            Language::Turkish => "Türkçe",
+            Language::Custom(custom) => &custom.name,
+        }
+    }
+    /// returns the name of the language in English
+    pub fn get_name(&self) -> &str {
+        match self {
             Language::French => "French",
             Language::English => "English",
             Language::German => "German",
             Language::Spanish => "Spanish",
             Language::Ukrainian => "Ukrainian",
+            Language::Italian => "Italian",
+            Language::Portuguese => "Portuguese",
+            Language::Dutch => "Dutch",
+            Language::Polish => "Polish",
+            Language::Japanese => "Japanese",
+            Language::Chinese => "Chinese",
+            Language::Korean => "Korean",
+            Language::Arabic => "Arabic",
+            Language::Russian => "Russian",
+            Language::Turkish => "Turkish",
+            Language::Custom(custom) => &custom.name,
+        }
+    }
+    /// returns the ISO 639-3 code of the language, for a custom one its primary subtag if it has
+    /// three letters
+    pub fn get_iso639_3(&self) -> Option<&str> {
+        let code = match self {
+            Language::French => "fra",
+            Language::English => "eng",
+            Language::German => "deu",
+            Language::Spanish => "spa",
+            Language::Ukrainian => "ukr",
+            Language::Italian => "ita",
+            Language::Portuguese => "por",
+            Language::Dutch => "nld",
+            Language::Polish => "pol",
+            Language::Japanese => "jpn",
+            Language::Chinese => "zho",
+            Language::Korean => "kor",
+            Language::Arabic => "ara",
+            Language::Russian => "rus",
+            Language::Turkish => "tur",
+            Language::Custom(custom) => custom.code.split('-').next().filter(|p| p.len() == 3)?,
+        };
+        Some(code)
+    }
+    /// returns true if the language is written from right to left
+    pub fn is_rtl(&self) -> bool {
+        let code = self.get_code();
+        let primary = code.split(['-', '_']).next().unwrap_or(code);
+        RTL_CODES.iter().any(|c| c.eq_ignore_ascii_case(primary))
+    }
+    /// Returns the names, the codes and the direction of the language
+    pub fn info(&self) -> LanguageInfo {
+        LanguageInfo {
+            language: self.clone(),
+            code: self.get_code().to_string(),
+            iso639_3: self.get_iso639_3().map(str::to_string),
+            name: self.get_name().to_string(),
+            native_name: self.get_native_name().to_string(),
+            rtl: self.is_rtl(),
+        }
+    }
+    /// returns the built-in language of an ISO 639-1 code, a region is ignored (e.g. `fr-CA`).
+    /// The custom languages are found by the project, see
+    /// [`project_config::ProjectConfig::get_lang_by_code`].
+    pub fn from_code(code: &str) -> Option<Language> {
+        let primary = code.split(['-', '_']).next()?.to_ascii_lowercase();
+        BUILT_IN_LANGUAGES
+            .into_iter()
+            .find(|l| l.get_code() == primary)
+    }
+    /// returns true if _code_ is the code of the language, a region is ignored for a built-in
+    /// one
+    pub fn has_code(&self, code: &str) -> bool {
+        match self {
+            Language::Custom(custom) => custom.code.eq_ignore_ascii_case(&code.replace('_', "-")),
+            _ => Language::from_code(code).as_ref() == Some(self),
+        }
+    }
+}
+
+impl std::str::FromStr for Language {
+    type Err = LanguageError;
+
+    /// Parses the English or the native name of a built-in language (e.g. `French`, `français`)
+    /// or its code, ignoring the case. Another BCP-47 code is a custom language, named
+    /// `<code>:<name>` (e.g. `haw:Hawaiian`) or after its code. The [`Display`] of a language is
+    /// parsed back into it, but for the suffix of a custom language.
+    ///
+    /// [`Display`]: std::fmt::Display
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (code, name) = match s.split_once(':') {
+            Some((code, name)) => (code.trim(), Some(name.trim())),
+            None => (s.trim(), None),
+        };
+        let lowercase = code.to_lowercase();
+        let known = BUILT_IN_LANGUAGES.into_iter().find(|l| match name {
+            None => {
+                l.get_name().to_lowercase() == lowercase
+                    || l.get_native_name().to_lowercase() == lowercase
+            }
+            Some(_) => l.get_code().eq_ignore_ascii_case(code),
+        });
+        if let Some(lang) = known {
+            return Ok(lang);
+        }
+        match name {
+            Some(name) => Language::custom(code, name, None),
+            None => match Language::from_code(code) {
+                Some(lang) => Ok(lang),
+                None if is_language_code(code) => Language::custom(code, code, None),
+                None => Err(LanguageError::UnknownLanguage(s.trim().to_string())),
+            },
+        }
+    }
+}
+
+/// returns true if _code_ is shaped like a BCP-47 language tag (e.g. `ja`, `pt-BR`, `zh-Hant`)
+fn is_language_code(code: &str) -> bool {
+    let mut subtags = code.split('-');
+    let primary = subtags.next().unwrap_or_default();
+    (2..=3).contains(&primary.len())
+        && primary.chars().all(|c| c.is_ascii_alphabetic())
+        && subtags
+            .all(|t| (1..=8).contains(&t.len()) && t.chars().all(|c| c.is_ascii_alphanumeric()))
+}
+
+/// returns true if _suffix_ can end the name of a directory on every platform
+fn is_dir_suffix(suffix: &str) -> bool {
+    !suffix.is_empty()
+        && !suffix.ends_with('.')
+        && suffix
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.'))
+}
+
+/// The name of a built-in language, `<code>:<name>` for a custom one so it's parsed back
+impl std::fmt::Display for Language {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Language::Custom(custom) => write!(f, "{}:{}", custom.code, custom.name),
+            _ => f.write_str(self.get_name()),
         }
     }
 }
@@ -0,0 +1,45 @@
+//! The configuration of the library shared by all the projects of the user. It's read from
+//! `$TRANSLATE_DIR_CONFIG` if set, otherwise from `translate-dir/config.json` in the user's
+//! config directory (`$XDG_CONFIG_HOME` or `~/.config`).
+use std::path::PathBuf;
+
+#[derive(Debug, Clone, Default, serde::Serialize, serde::Deserialize)]
+pub struct LibConfig {
+    /// prompt used by the projects that don't set their own one
+    #[serde(default)]
+    prompt_file: Option<PathBuf>,
+    /// directory of the store shared by the projects of a workspace, see
+    /// [`crate::shared_store`]
+    #[serde(default)]
+    shared_store: Option<PathBuf>,
+}
+
+impl LibConfig {
+    pub fn get_prompt_file(&self) -> Option<PathBuf> {
+        self.prompt_file.clone()
+    }
+    pub fn get_shared_store(&self) -> Option<PathBuf> {
+        self.shared_store.clone()
+    }
+}
+
+/// Returns the path to the config file of the library, `None` if there's no config directory
+pub fn get_lib_config_path() -> Option<PathBuf> {
+    if let Some(path) = std::env::var_os("TRANSLATE_DIR_CONFIG") {
+        return Some(PathBuf::from(path));
+    }
+    let config_dir = match std::env::var_os("XDG_CONFIG_HOME") {
+        Some(dir) if !dir.is_empty() => PathBuf::from(dir),
+        _ => PathBuf::from(std::env::var_os("HOME")?).join(".config"),
+    };
+    Some(config_dir.join("translate-dir").join("config.json"))
+}
+
+/// Loads the config of the library, returns the default one if there's no config file or it
+/// can't be parsed
+pub fn load_lib_config() -> LibConfig {
+    get_lib_config_path()
+        .and_then(|path| std::fs::read_to_string(path).ok())
+        .and_then(|contents| serde_json::from_str(&contents).ok())
+        .unwrap_or_default()
+}
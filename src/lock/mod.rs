@@ -0,0 +1,88 @@
+//! Advisory lock of a project: the operations that change the config or the target directories
+//! (a sync, a translation, a setting) hold it while they run, so two processes can't run them on
+//! the same project at once. The lock is reentrant: an operation made of others holds it once
+//! for all of them.
+use std::{
+    fs::{File, TryLockError},
+    path::Path,
+    sync::{Arc, Mutex},
+};
+
+use crate::errors::lock_errors::LockError;
+
+/// Name of the lock file, in the project root next to the config
+pub const LOCK_FILE_NAME: &str = "trans_conf.lock";
+
+#[derive(Debug)]
+/// The lock file of a project, opened when the project is loaded
+pub struct ProjectLock {
+    state: Arc<LockState>,
+}
+
+#[derive(Debug)]
+struct LockState {
+    file: File,
+    /// number of the guards of this instance that are alive
+    depth: Mutex<usize>,
+}
+
+/// Held lock of a project, released when the last guard is dropped
+#[derive(Debug)]
+pub struct LockGuard {
+    state: Arc<LockState>,
+    outermost: bool,
+}
+
+impl ProjectLock {
+    /// Opens the lock file of the project, it is created if missing
+    pub fn open(project_root: &Path) -> std::io::Result<Self> {
+        let file = File::options()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(project_root.join(LOCK_FILE_NAME))?;
+        Ok(Self {
+            state: Arc::new(LockState {
+                file,
+                depth: Mutex::new(0),
+            }),
+        })
+    }
+    /// Takes the lock without waiting, fails with `Locked` if another process (or another
+    /// loaded instance of the project) holds it. A lock already held by this instance is taken
+    /// again.
+    pub fn acquire(&self) -> Result<LockGuard, LockError> {
+        let mut depth = self.state.depth.lock().unwrap_or_else(|e| e.into_inner());
+        if *depth == 0 {
+            match self.state.file.try_lock() {
+                Ok(()) => {}
+                Err(TryLockError::WouldBlock) => return Err(LockError::Locked),
+                Err(TryLockError::Error(e)) => return Err(LockError::IoError(e)),
+            }
+        }
+        *depth += 1;
+        Ok(LockGuard {
+            state: Arc::clone(&self.state),
+            outermost: *depth == 1,
+        })
+    }
+}
+
+impl LockGuard {
+    /// returns true if no other guard of this instance was alive when this one was taken, the
+    /// state on the disk may have been changed by another process since
+    pub fn is_outermost(&self) -> bool {
+        self.outermost
+    }
+}
+
+impl Drop for LockGuard {
+    fn drop(&mut self) {
+        let mut depth = self.state.depth.lock().unwrap_or_else(|e| e.into_inner());
+        *depth -= 1;
+        if *depth == 0 {
+            let _ = self.state.file.unlock();
+        }
+    }
+}
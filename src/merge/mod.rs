@@ -0,0 +1,330 @@
+//! Three-way merge of a translation edited by hand with a changed source. Every translated file
+//! keeps its base in the state directory: the segments (chunks) of the source with the
+//! translation that was written for them. A merge compares the base sources with the new ones,
+//! translates only the segments that changed and keeps the text of the edited target for the
+//! others.
+use std::path::{Path, PathBuf};
+
+use crate::{
+    format::{handler_for_path, protect, reinsert_part},
+    helper::{content_hash, write_atomic},
+    state::{get_state_dir, read_json, write_json},
+    translator::{chunks::plan_chunks, translate_chunk, PreviousChunk, TranslationContext},
+    Language,
+};
+
+/// Name of the directory with the bases of the translated files, in the state directory
+pub const MERGE_DIR: &str = "merge";
+
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+/// A segment of a translated file, with the protected regions in place
+pub struct BaseSegment {
+    pub source: String,
+    /// the text written to the target file for the segment
+    pub translation: String,
+}
+
+#[derive(Debug, Clone, Default, serde::Serialize, serde::Deserialize)]
+/// The segments of a file as it was translated, a later merge starts from them
+pub struct MergeBase {
+    /// path of the source file relative to the project root
+    pub file: String,
+    pub segments: Vec<BaseSegment>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+/// Outcome of a merge
+pub struct MergeReport {
+    /// segments whose source didn't change, their text in the target is kept
+    pub kept: usize,
+    /// segments of the new source that were translated
+    pub retranslated: usize,
+    /// segments of the old source that were removed with their translation
+    pub removed: usize,
+    /// text edited by hand that was replaced because its source changed
+    pub discarded: Vec<String>,
+}
+
+/// A part of the edited target: the text of the base segments at _indices_, or text added by
+/// hand between the segments if _indices_ is empty
+struct Unit {
+    indices: Vec<usize>,
+    text: String,
+}
+
+enum Op {
+    Keep(usize, usize),
+    Delete(usize),
+    Insert(usize),
+}
+
+/// Returns the base of the translation of the source _file_ into _lang_, `None` if the file was
+/// never translated with the project
+pub fn load_base(project_root: &Path, file: &Path, lang: Language) -> Option<MergeBase> {
+    let key = get_file_key(project_root, file)?;
+    read_json(&get_base_path(project_root, &key, lang))
+}
+
+/// Records the base of the file translated in the context, the _chunks_ are the translated
+/// segments with the placeholders of the _protected_ regions. Does nothing without a project.
+pub(crate) fn record_base(
+    ctx: &TranslationContext,
+    lang: Language,
+    chunks: &[PreviousChunk],
+    protected: &[String],
+) -> std::io::Result<()> {
+    let (Some(root), Some(file)) = (&ctx.project_root, &ctx.file) else {
+        return Ok(());
+    };
+    let Some(key) = get_file_key(root, file) else {
+        return Ok(());
+    };
+    let mut segments = Vec::new();
+    for chunk in chunks {
+        segments.push(BaseSegment {
+            source: reinsert_part(&chunk.source, protected).map_err(invalid_data)?,
+            translation: reinsert_part(&chunk.translation, protected).map_err(invalid_data)?,
+        });
+    }
+    save_base(
+        root,
+        &MergeBase {
+            file: key,
+            segments,
+        },
+        lang,
+    )
+}
+
+/// Merges the changed source at _from_path_ into its translation at _to_path_ edited by hand:
+/// the changed segments are translated and spliced into the target, the text of the other ones
+/// is kept as it is. The edits of a segment whose source changed are lost, they are listed in
+/// the report. Fails with `NotFound` if the file has no base.
+pub fn merge_translation(
+    from_path: &Path,
+    to_path: &Path,
+    tgt_lang: &Language,
+    ctx: &TranslationContext,
+) -> std::io::Result<MergeReport> {
+    let ctx = &ctx.for_file(from_path);
+    let not_found = || std::io::Error::new(std::io::ErrorKind::NotFound, "no merge base");
+    let root = ctx.project_root.as_deref().ok_or_else(not_found)?;
+    let base = load_base(root, from_path, tgt_lang.clone()).ok_or_else(not_found)?;
+    let target = std::fs::read_to_string(to_path)?;
+    let contents = std::fs::read_to_string(from_path)?;
+    let (text, protected) = protect(handler_for_path(from_path).as_ref(), &contents);
+    let chunks = plan_chunks(&text, ctx)?;
+    let mut sources = Vec::new();
+    for chunk in &chunks {
+        sources.push(reinsert_part(chunk, &protected).map_err(invalid_data)?);
+    }
+
+    let units = split_target(&base.segments, &target);
+    let old: Vec<&str> = base.segments.iter().map(|s| s.source.as_str()).collect();
+    let new: Vec<&str> = sources.iter().map(|s| s.as_str()).collect();
+    let ops = diff(&old, &new);
+
+    let mut unit_of = vec![0; old.len()];
+    for (id, unit) in units.iter().enumerate() {
+        for i in &unit.indices {
+            unit_of[*i] = id;
+        }
+    }
+    let mut kept = vec![false; old.len()];
+    for op in &ops {
+        if let Op::Keep(i, _) = op {
+            kept[*i] = true;
+        }
+    }
+    let intact: Vec<bool> = units
+        .iter()
+        .map(|u| u.indices.iter().all(|i| kept[*i]))
+        .collect();
+
+    let mut report = MergeReport::default();
+    for (unit, _) in units.iter().zip(&intact).filter(|(_, intact)| !**intact) {
+        let machine: String = unit
+            .indices
+            .iter()
+            .map(|i| base.segments[*i].translation.as_str())
+            .collect();
+        if unit.text != machine {
+            report.discarded.push(unit.text.clone());
+        }
+    }
+
+    // the text of every new segment, the text added by hand goes with the segment before it
+    let mut translations = vec![String::new(); new.len()];
+    let mut leading = String::new();
+    let mut last: Option<usize> = None;
+    let mut push = |j: Option<usize>, text: &str, last: &mut Option<usize>| match j.or(*last) {
+        Some(j) => {
+            translations[j].push_str(text);
+            *last = Some(j);
+        }
+        None => leading.push_str(text),
+    };
+    let mut next_unit = 0;
+    let mut previous: Option<PreviousChunk> = None;
+    for op in ops {
+        if let Op::Keep(i, _) | Op::Delete(i) = op {
+            while next_unit < unit_of[i] {
+                if units[next_unit].indices.is_empty() {
+                    push(None, &units[next_unit].text, &mut last);
+                }
+                next_unit += 1;
+            }
+        }
+        let (j, text) = match op {
+            Op::Keep(i, j) => {
+                report.kept += 1;
+                let unit = &units[unit_of[i]];
+                let text = match intact[unit_of[i]] {
+                    true if unit.indices[0] == i => unit.text.clone(),
+                    true => String::new(),
+                    false => base.segments[i].translation.clone(),
+                };
+                (j, text)
+            }
+            Op::Delete(_) => {
+                report.removed += 1;
+                continue;
+            }
+            Op::Insert(j) => {
+                report.retranslated += 1;
+                let translation = translate_chunk(&chunks[j], tgt_lang, ctx, previous.as_ref())?;
+                (
+                    j,
+                    reinsert_part(&translation, &protected).map_err(invalid_data)?,
+                )
+            }
+        };
+        push(Some(j), &text, &mut last);
+        previous = Some(PreviousChunk {
+            source: chunks[j].clone(),
+            translation: text,
+        });
+    }
+    for unit in units[next_unit..].iter().filter(|u| u.indices.is_empty()) {
+        push(None, &unit.text, &mut last);
+    }
+    match translations.first_mut() {
+        Some(first) => first.insert_str(0, &leading),
+        None => translations.push(leading),
+    }
+
+    write_atomic(to_path, translations.concat())?;
+    let segments = sources
+        .into_iter()
+        .zip(translations)
+        .map(|(source, translation)| BaseSegment {
+            source,
+            translation,
+        })
+        .collect();
+    let key = get_file_key(root, from_path).ok_or_else(not_found)?;
+    save_base(
+        root,
+        &MergeBase {
+            file: key,
+            segments,
+        },
+        tgt_lang.clone(),
+    )?;
+    Ok(report)
+}
+
+/// Splits the edited target into the parts of the base segments. The translation of every
+/// segment is looked for in the order of the segments, the segments that aren't found were
+/// edited and share the text between the found ones.
+fn split_target(segments: &[BaseSegment], target: &str) -> Vec<Unit> {
+    let mut units = Vec::new();
+    let mut pending = Vec::new();
+    let mut cursor = 0;
+    for (i, segment) in segments.iter().enumerate() {
+        let Some(offset) = target[cursor..].find(&segment.translation) else {
+            pending.push(i);
+            continue;
+        };
+        let start = cursor + offset;
+        let gap = &target[cursor..start];
+        if !pending.is_empty() || !gap.is_empty() {
+            units.push(Unit {
+                indices: std::mem::take(&mut pending),
+                text: gap.to_string(),
+            });
+        }
+        units.push(Unit {
+            indices: vec![i],
+            text: segment.translation.clone(),
+        });
+        cursor = start + segment.translation.len();
+    }
+    let rest = &target[cursor..];
+    if !pending.is_empty() || !rest.is_empty() {
+        units.push(Unit {
+            indices: pending,
+            text: rest.to_string(),
+        });
+    }
+    units
+}
+
+/// Returns the operations turning _old_ into _new_ with the most segments kept
+fn diff(old: &[&str], new: &[&str]) -> Vec<Op> {
+    let (n, m) = (old.len(), new.len());
+    let mut lcs = vec![vec![0usize; m + 1]; n + 1];
+    for i in (0..n).rev() {
+        for j in (0..m).rev() {
+            lcs[i][j] = if old[i] == new[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+    let mut ops = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    while i < n || j < m {
+        if i < n && j < m && old[i] == new[j] {
+            ops.push(Op::Keep(i, j));
+            i += 1;
+            j += 1;
+        } else if i < n && (j == m || lcs[i + 1][j] >= lcs[i][j + 1]) {
+            ops.push(Op::Delete(i));
+            i += 1;
+        } else {
+            ops.push(Op::Insert(j));
+            j += 1;
+        }
+    }
+    ops
+}
+
+pub(crate) fn save_base(
+    project_root: &Path,
+    base: &MergeBase,
+    lang: Language,
+) -> std::io::Result<()> {
+    write_json(&get_base_path(project_root, &base.file, lang), base)
+}
+
+/// Returns the path of the file relative to the project root with `/` separators
+pub(crate) fn get_file_key(project_root: &Path, file: &Path) -> Option<String> {
+    let rel = file.strip_prefix(project_root).ok()?;
+    Some(rel.to_string_lossy().replace('\\', "/"))
+}
+
+fn get_base_path(project_root: &Path, key: &str, lang: Language) -> PathBuf {
+    get_state_dir(project_root)
+        .join(MERGE_DIR)
+        .join(lang.get_code())
+        .join(format!("{}.json", content_hash(key)))
+}
+
+fn invalid_data<E>(e: E) -> std::io::Error
+where
+    E: Into<Box<dyn std::error::Error + Send + Sync>>,
+{
+    std::io::Error::new(std::io::ErrorKind::InvalidData, e)
+}
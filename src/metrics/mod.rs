@@ -0,0 +1,122 @@
+//! In-process metrics of the library: counts and timings of what a run did, kept in memory only
+//! and never sent anywhere. Embedders read them with [`snapshot`] (or
+//! [`crate::project::Project::metrics`]) to show them in their own dashboards.
+use std::{
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+/// Metrics collected since the start of the process or the last [`reset`]
+pub struct MetricsSnapshot {
+    /// requests sent to the model provider
+    pub api_calls: u64,
+    /// requests that failed
+    pub api_errors: u64,
+    /// time spent waiting for the answers, in milliseconds
+    pub api_time_ms: u64,
+    /// longest time of a single request, in milliseconds
+    pub api_max_time_ms: u64,
+    /// time spent waiting for the schedule and the rate limit, in milliseconds
+    pub throttled_time_ms: u64,
+    /// batch jobs submitted
+    pub batch_jobs: u64,
+    /// files written by the translator
+    pub files_translated: u64,
+    /// untranslatable files copied to the target directories
+    pub files_copied: u64,
+    /// files and directories removed from the target directories
+    pub deletions: u64,
+    /// chunks served from the cache of the project
+    pub cache_hits: u64,
+    /// chunks looked up in the cache of the project and not found
+    pub cache_misses: u64,
+    /// chunks translated from the translation memory of the project
+    pub memory_hits: u64,
+    /// chunks looked up in the translation memory and not found
+    pub memory_misses: u64,
+}
+
+static METRICS: Mutex<MetricsSnapshot> = Mutex::new(MetricsSnapshot {
+    api_calls: 0,
+    api_errors: 0,
+    api_time_ms: 0,
+    api_max_time_ms: 0,
+    throttled_time_ms: 0,
+    batch_jobs: 0,
+    files_translated: 0,
+    files_copied: 0,
+    deletions: 0,
+    cache_hits: 0,
+    cache_misses: 0,
+    memory_hits: 0,
+    memory_misses: 0,
+});
+
+fn update<F: FnOnce(&mut MetricsSnapshot)>(func: F) {
+    let mut metrics = METRICS.lock().unwrap_or_else(|e| e.into_inner());
+    func(&mut metrics);
+}
+
+/// Returns the current values of the metrics
+pub fn snapshot() -> MetricsSnapshot {
+    *METRICS.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Sets all the metrics to zero, e.g. before starting a new run
+pub fn reset() {
+    update(|m| *m = MetricsSnapshot::default());
+}
+
+/// Records a request to the provider that started at _start_
+pub(crate) fn record_api_call(start: Instant, success: bool) {
+    let elapsed = start.elapsed().as_millis() as u64;
+    update(|m| {
+        m.api_calls += 1;
+        if !success {
+            m.api_errors += 1;
+        }
+        m.api_time_ms += elapsed;
+        m.api_max_time_ms = m.api_max_time_ms.max(elapsed);
+    });
+}
+
+pub(crate) fn record_throttling(waited: Duration) {
+    update(|m| m.throttled_time_ms += waited.as_millis() as u64);
+}
+
+pub(crate) fn record_batch_job() {
+    update(|m| m.batch_jobs += 1);
+}
+
+pub(crate) fn record_file_translated() {
+    update(|m| m.files_translated += 1);
+}
+
+pub(crate) fn record_copy() {
+    update(|m| m.files_copied += 1);
+}
+
+pub(crate) fn record_deletion() {
+    update(|m| m.deletions += 1);
+}
+
+pub(crate) fn record_cache_lookup(hit: bool) {
+    update(|m| {
+        if hit {
+            m.cache_hits += 1;
+        } else {
+            m.cache_misses += 1;
+        }
+    });
+}
+
+pub(crate) fn record_memory_lookup(hit: bool) {
+    update(|m| {
+        if hit {
+            m.memory_hits += 1;
+        } else {
+            m.memory_misses += 1;
+        }
+    });
+}
@@ -0,0 +1,47 @@
+//! Naming of the translated files. By default a translated file has the name of its source file,
+//! a naming rule can add a language suffix (`intro.md` → `intro.fr.md`) and map extensions
+//! (`.rst` → `.md`).
+use std::collections::BTreeMap;
+
+use crate::Language;
+
+/// Placeholder of the suffix replaced by the code of the target language
+pub const LANG_PLACEHOLDER: &str = "{lang}";
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+/// How the name of a translated file is derived from the name of its source file. Only the
+/// translatable files are renamed, the copied ones keep their names.
+pub struct NamingRule {
+    /// inserted before the extension, `{lang}` is replaced by the code of the target language,
+    /// e.g. `.{lang}` turns `intro.md` into `intro.fr.md`
+    #[serde(default)]
+    pub suffix: Option<String>,
+    /// extensions (without the dot) replaced in the translated files, e.g. `rst` → `md`
+    #[serde(default)]
+    pub extensions: BTreeMap<String, String>,
+}
+
+impl NamingRule {
+    /// returns true if the rule keeps every name as it is
+    pub fn is_identity(&self) -> bool {
+        self.suffix.as_deref().unwrap_or("").is_empty() && self.extensions.is_empty()
+    }
+
+    /// Returns the name of the translation into _lang_ of the source file named _file_name_
+    pub fn apply(&self, file_name: &str, lang: &Language) -> String {
+        let (stem, ext) = match file_name.rfind('.') {
+            // a leading dot is a hidden file, not an extension
+            Some(pos) if pos > 0 => (&file_name[..pos], Some(&file_name[pos + 1..])),
+            _ => (file_name, None),
+        };
+        let mut res = stem.to_string();
+        if let Some(suffix) = &self.suffix {
+            res.push_str(&suffix.replace(LANG_PLACEHOLDER, lang.get_code()));
+        }
+        if let Some(ext) = ext {
+            res.push('.');
+            res.push_str(self.extensions.get(ext).map(|e| e.as_str()).unwrap_or(ext));
+        }
+        res
+    }
+}
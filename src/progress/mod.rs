@@ -0,0 +1,64 @@
+//! Progress of the long operations of a [`crate::project::Project`] (the analysis of the source
+//! directory, the sync, the translation). An embedding application sets a [`ProgressHandler`] on
+//! the project to drive a progress bar, it's called as the operations advance.
+use std::path::Path;
+
+use crate::Language;
+
+#[derive(Debug, Clone, Copy)]
+/// A step of an operation of the project
+pub enum ProgressEvent<'a> {
+    /// the source directory was analyzed, _files_ were found in it
+    SourceScanned { dir: &'a Path, files: usize },
+    /// the file of the sync was copied into the target directory of _lang_ or left as it was,
+    /// _done_ of the _total_ files of all the languages are synced
+    FileSynced {
+        path: &'a Path,
+        lang: &'a Language,
+        done: usize,
+        total: usize,
+    },
+    /// the translation of the source file into _lang_ starts, it's the _index_-th of the
+    /// _total_ files of the run
+    FileStarted {
+        file: &'a Path,
+        lang: &'a Language,
+        index: usize,
+        total: usize,
+    },
+    /// the _chunk_-th of the _chunks_ of the source file was translated
+    ChunkCompleted {
+        file: &'a Path,
+        lang: &'a Language,
+        chunk: usize,
+        chunks: usize,
+    },
+    /// the translation of the source file was written to _path_
+    FileWritten {
+        file: &'a Path,
+        path: &'a Path,
+        lang: &'a Language,
+        index: usize,
+        total: usize,
+    },
+}
+
+/// Called with every step of the operations of the project
+pub trait ProgressHandler: Send + Sync {
+    fn on_progress(&self, event: &ProgressEvent);
+}
+
+impl<F> ProgressHandler for F
+where
+    F: Fn(&ProgressEvent) + Send + Sync,
+{
+    fn on_progress(&self, event: &ProgressEvent) {
+        self(event)
+    }
+}
+
+impl std::fmt::Debug for dyn ProgressHandler {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("ProgressHandler")
+    }
+}
@@ -1,20 +1,101 @@
 use crate::{
+    bundle::{Bundle, BundleOptions},
+    change_report::{ChangeKind, ChangeReport, SyncReport},
+    confirm::{ConfirmHandler, DestructiveOp},
+    detect::{detect_language, sample_dir},
+    doctor::{CheckStatus, DoctorReport},
+    errors::bundle_errors::BundleError,
+    errors::exchange_errors::ExchangeError,
+    errors::glossary_errors::GlossaryError,
+    errors::lock_errors::LockError,
+    errors::project_config_errors::{LoadConfigError, WriteConfigError},
     errors::project_errors::{
-        AddLanguageError, AddTranslatableFileError, CopyFileDirError, GetTranslatableFilesError,
-        InitProjectError, LoadProjectError, RemoveLangaugeError, SetSourceDirError, SyncFilesError,
-        TranslateFileError, UpdateSourceDirConfig,
+        AddLanguageError, AddTranslatableFileError, BackupError, CopyFileDirError, EstimateError,
+        GetTranslatableFilesError, InitProjectError, LangSettingsError, LoadProjectError,
+        QaCheckError, RemoveLangaugeError, SetPromptFileError, SetReviewStateError,
+        SetSourceDirError, SetTranslatorOptionsError, SyncFilesError, TranslateFileError,
+        UpdateSourceDirConfig,
     },
-    helper,
-    project_config::{write_conf, Directory},
+    errors::sandbox_errors::SandboxError,
+    errors::status_errors::StatusError,
+    errors::translation_memory_errors::TranslationMemoryError,
+    errors::translator_errors::BatchError,
+    errors::watch_errors::WatchError,
+    errors::ErrorInfo,
+    estimate::{
+        cost::{get_model_pricing, CostEstimate, ModelPricing},
+        estimate_file, estimate_tokens,
+        plan::{PlannedAction, PlannedFile, TranslatePlan},
+        stats::{count_file, FileStats, LangStats, ProjectStats, TextCounts},
+        BudgetThresholds, TranslationEstimate,
+    },
+    events::{self, EventForwarder, ProjectEvent, EVENT_CAPACITY},
+    exchange::{
+        po::{parse_po, write_po, PoEntry, PO_DIR},
+        xliff::{parse_xliff, write_xliff, XliffFile, XliffSegment},
+        Workflow,
+    },
+    format::{handler_for_path, protect, reinsert},
+    fs::{FileSystem, RealFs},
+    glossary::{
+        extract::{extract_terms, ExtractOptions, GlossaryDraft},
+        Glossary, GlossaryEntry,
+    },
+    helper::{self, bytes_hash, content_hash},
+    lock::{LockGuard, ProjectLock},
+    merge::{load_base, merge_translation, MergeReport},
+    metrics::{self, MetricsSnapshot},
+    naming::NamingRule,
+    progress::{ProgressEvent, ProgressHandler},
+    project_config::{
+        is_excluded, write_conf, AssetMode, Directory, FileMetadata, ReviewState, SymlinkPolicy,
+    },
+    qa::{
+        glossary::check_glossary_compliance,
+        invariants::check_invariants,
+        markdown::check_markdown_integrity,
+        readability::{check_readability, ReadabilityThresholds},
+        QaReport,
+    },
+    sandbox::{canonicalize_existing, confine_path, confine_user_path},
+    shared_store::SharedStore,
+    shutdown::{self, interrupted_error, CancellationToken},
+    state::{
+        backups, get_state_dir, hashes::WrittenHashes, journal::RunJournal, trash,
+        usage::ProjectUsage,
+    },
+    status::{
+        get_all_files, get_lang_status, get_translation_state, html::render_html, write_status_csv,
+        FileStatus, ProjectReport, ProjectStatus, TranslationState, TranslationStatus,
+    },
+    translation_memory::{
+        tmx::{parse_tmx, write_tmx},
+        MemoryEntry, TranslationMemory,
+    },
+    translator::{
+        budget::is_budget_exceeded,
+        cache::clear_cache,
+        check_credentials,
+        chunks::plan_chunks,
+        prompt::{load_prompt_file, resolve_prompt, ExamplePair, PROJECT_PROMPT_FILE},
+        translate_segments_keeping, PreviousChunk, Provider, TranslationContext, TranslatorOptions,
+    },
+    watch::{SourceWatcher, WatchEvent, WatchOptions},
+    watermark::{apply_watermark, TranslationMetadata, WatermarkMode},
     Language,
 };
 use std::{
-    collections::HashSet,
+    collections::{HashMap, HashSet, VecDeque},
     path::{Path, PathBuf},
-    thread,
-    time::Duration,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
 };
 
+use futures_util::Stream;
+use tokio::sync::broadcast;
+
 use crate::project_config::ProjectConfig;
 
 #[derive(Debug)]
@@ -24,6 +105,26 @@ pub struct Project {
     path_to_root: PathBuf,
     /// Config of the project
     config: ProjectConfig,
+    /// Template of the prompt the files are translated with
+    prompt: String,
+    /// Templates of the prompts overridden for some target languages
+    lang_prompts: HashMap<Language, String>,
+    /// Store shared by the projects of the workspace, `None` if the library config sets none
+    shared_store: Option<SharedStore>,
+    /// Glossary of the shared store, empty if the project doesn't use the store
+    shared_glossary: Glossary,
+    /// Consulted before the deletions, `None` if they always proceed
+    confirm: Option<Arc<dyn ConfirmHandler>>,
+    /// Told about the progress of the long operations
+    progress: Option<Arc<dyn ProgressHandler>>,
+    /// Broadcast of the events of the operations to the subscribers
+    events: broadcast::Sender<ProjectEvent>,
+    /// Stops the sync and the translations once cancelled, `None` if only a shutdown does
+    cancel: Option<CancellationToken>,
+    /// Held by the operations that change the config or the target directories
+    lock: ProjectLock,
+    /// File system the sync and the trash work on, the disk unless it's replaced
+    fs: Arc<dyn FileSystem>,
 }
 
 /// Initialize project for translation
@@ -33,9 +134,73 @@ pub fn init(name: &str, path: PathBuf) -> Result<(), InitProjectError> {
     }
     let path = std::fs::canonicalize(path).map_err(|_| InitProjectError::InvalidPath)?;
 
-    let conf = crate::project_config::init(name, path)?;
+    crate::project_config::init(name, path)
+}
+
+/// Imports the project of the bundle into the _dest_ directory (created if it doesn't exist) and
+/// loads it. The paths of the bundle must stay inside of _dest_ and can't overwrite the files
+/// already in it.
+pub fn import_bundle(bundle_path: &Path, dest: PathBuf) -> Result<Project, BundleError> {
+    let bundle = Bundle::read(bundle_path)?;
+    if dest.exists() && !dest.is_dir() {
+        return Err(BundleError::InvalidDestination);
+    }
+    let dest = std::path::absolute(dest)
+        .and_then(|dest| canonicalize_existing(&dest))
+        .map_err(BundleError::IoError)?;
+    let conf_path = dest.join("trans_conf.json");
+    if conf_path.exists() {
+        return Err(BundleError::AlreadyAProject);
+    }
 
-    Ok(())
+    // the whole bundle is checked before _dest_ is created and the first file is written: the
+    // config and the paths of the entries and of the directories, with the symlinks already in
+    // _dest_ resolved
+    let entries = bundle.decode_entries()?;
+    let conf_data = entries
+        .iter()
+        .find(|(p, _)| p == Path::new("trans_conf.json"))
+        .map(|(_, data)| data)
+        .ok_or_else(|| BundleError::InvalidBundle("no project config".to_string()))?;
+    let mut conf: ProjectConfig = serde_json::from_slice(conf_data).map_err(|_| {
+        BundleError::LoadProjectError(LoadProjectError::LoadConfigError(
+            LoadConfigError::IncorrectConfigFileFormat,
+        ))
+    })?;
+    conf.rebase(Path::new(""), &dest);
+    let mut files = Vec::new();
+    for (rel, data) in entries {
+        let path = confine_path(&dest, &rel).map_err(BundleError::PathOutsideProject)?;
+        if std::fs::symlink_metadata(&path).is_ok() {
+            return Err(BundleError::WouldOverwrite(path));
+        }
+        files.push((path, data));
+    }
+    // a bundle without the content still needs the directories of the project
+    let dirs = conf
+        .get_src_trees()
+        .into_iter()
+        .map(|(d, _)| d.get_path())
+        .chain(
+            conf.get_lang_dirs_as_ref()
+                .iter()
+                .map(|d| d.get_dir_as_ref().get_path()),
+        )
+        .map(|dir| confine_path(&dest, &dir).map_err(BundleError::PathOutsideProject))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    std::fs::create_dir_all(&dest).map_err(BundleError::IoError)?;
+    for (path, data) in files {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent).map_err(BundleError::IoError)?;
+        }
+        std::fs::write(path, data).map_err(BundleError::IoError)?;
+    }
+    for dir in dirs {
+        std::fs::create_dir_all(dir).map_err(BundleError::IoError)?;
+    }
+    write_conf(conf_path, &conf).map_err(BundleError::ConfigWritingError)?;
+    load(dest).map_err(BundleError::LoadProjectError)
 }
 
 /// Load project from the given path (even if the path is a child of the project directory)
@@ -55,10 +220,39 @@ pub fn load(path: PathBuf) -> Result<Project, LoadProjectError> {
 
     let conf = crate::project_config::load_config_from_file(conf_file_path)
         .map_err(LoadProjectError::LoadConfigError)?;
+    let prompt = resolve_prompt(&root, conf.get_prompt_file().as_deref())
+        .map_err(LoadProjectError::PromptError)?;
+    let mut lang_prompts = HashMap::new();
+    for lang_dir in conf.get_lang_dirs_as_ref() {
+        if let Some(file) = lang_dir.get_prompt_file() {
+            let lang_prompt =
+                load_prompt_file(&root, &file).map_err(LoadProjectError::PromptError)?;
+            lang_prompts.insert(lang_dir.get_lang(), lang_prompt);
+        }
+    }
+
+    let lock = ProjectLock::open(&root).map_err(LoadProjectError::LockFileError)?;
+    let shared_store = SharedStore::open();
+    let shared_glossary = match &shared_store {
+        Some(store) if conf.get_use_shared_store() => store
+            .load_glossary()
+            .map_err(LoadProjectError::SharedStoreError)?,
+        _ => Glossary::new(),
+    };
 
     Ok(Project {
         path_to_root: root,
         config: conf,
+        prompt,
+        lang_prompts,
+        shared_store,
+        shared_glossary,
+        confirm: None,
+        progress: None,
+        events: broadcast::channel(EVENT_CAPACITY).0,
+        cancel: None,
+        lock,
+        fs: Arc::new(RealFs),
     })
 }
 
@@ -78,6 +272,26 @@ impl Project {
     fn get_config_file_path(&self) -> PathBuf {
         self.get_root_path().join("trans_conf.json")
     }
+    /// Takes the lock of the project, see [`ProjectLock`]. When it wasn't held yet the config
+    /// is read again, another process may have changed it since the project was loaded.
+    fn acquire_lock(&mut self) -> Result<LockGuard, LockError> {
+        let guard = self.lock.acquire()?;
+        if guard.is_outermost() {
+            self.config = crate::project_config::load_config_from_file(self.get_config_file_path())
+                .map_err(LockError::ConfigError)?;
+        }
+        Ok(guard)
+    }
+    /// Same as [`Project::acquire_lock`] for the operations that only change the config
+    fn lock_config(&mut self) -> Result<LockGuard, WriteConfigError> {
+        self.acquire_lock().map_err(WriteConfigError::Locked)
+    }
+    /// Checks that the path given by a user (relative to the current directory) is inside of the
+    /// project and returns its canonical form, `None` if it doesn't exist
+    fn confine_existing_path(&self, path: &Path) -> Result<Option<PathBuf>, SandboxError> {
+        let path = confine_user_path(&self.get_root_path(), path)?;
+        Ok(path.exists().then_some(path))
+    }
 
     /// returns source language in an option or None if the source directory with a language isn't set
     fn get_src_lang(&self) -> Option<Language> {
@@ -99,13 +313,41 @@ impl Project {
             .map(|e| e.get_lang())
             .collect()
     }
+    /// Detects the language of the directory (e.g. before it's set as the source directory) from
+    /// a sample of its text files, `None` if it's unclear. The detection is local, see
+    /// [`detect_language`].
+    pub fn detect_source_language(
+        &self,
+        dir_name: &str,
+    ) -> Result<Option<Language>, SetSourceDirError> {
+        let full_dir_path = confine_path(&self.get_root_path(), Path::new(dir_name))
+            .map_err(SetSourceDirError::PathOutsideProject)?;
+        if !full_dir_path.exists() {
+            return Err(SetSourceDirError::DirectoryDoesNotExist);
+        }
+        if !full_dir_path.is_dir() {
+            return Err(SetSourceDirError::NotDirectory);
+        }
+        let sample = sample_dir(
+            &full_dir_path,
+            self.config.get_exclude_as_ref(),
+            self.config.get_symlink_policy(),
+        )
+        .map_err(SetSourceDirError::AnalyzeDirError)?;
+        Ok(detect_language(&sample).map(|d| d.language))
+    }
+
     /// Set source directory that the contents will be translated of
     pub fn set_source_dir(
         &mut self,
         dir_name: &str,
         lang: Language,
     ) -> Result<(), SetSourceDirError> {
-        let full_dir_path = self.get_root_path().join(dir_name);
+        let _lock = self
+            .lock_config()
+            .map_err(SetSourceDirError::ConfigWritingError)?;
+        let full_dir_path = confine_path(&self.get_root_path(), Path::new(dir_name))
+            .map_err(SetSourceDirError::PathOutsideProject)?;
         if !full_dir_path.exists() {
             return Err(SetSourceDirError::DirectoryDoesNotExist);
         }
@@ -116,68 +358,223 @@ impl Project {
         let src_lang_op = self.get_src_lang();
 
         // verifying this lang isn't in the project
-        if let Some(src_lang) = src_lang_op {
-            if src_lang == lang {
-                return Err(SetSourceDirError::LangAlreadyInTheProj);
-            }
+        if src_lang_op.as_ref() == Some(&lang) {
+            return Err(SetSourceDirError::LangAlreadyInTheProj);
         }
         for lang_dir in self.config.get_lang_dirs_as_ref() {
             let t_lang = lang_dir.get_lang();
-            if t_lang == lang {
+            if t_lang.get_code().eq_ignore_ascii_case(lang.get_code()) {
                 return Err(SetSourceDirError::LangAlreadyInTheProj);
             }
+            if lang_dir.get_pivot().as_ref() == Some(&lang) {
+                return Err(SetSourceDirError::PivotLanguage);
+            }
+        }
+        // the extra source directories can't overlap with it nor mix with its mirror
+        for dir in self.config.get_extra_src_dirs_as_ref() {
+            let path = dir.get_path();
+            if full_dir_path.starts_with(&path) || path.starts_with(&full_dir_path) {
+                return Err(SetSourceDirError::AlreadyInProject);
+            }
+            let name = dir.get_dir_name();
+            if full_dir_path.join(&name).exists() {
+                return Err(SetSourceDirError::NameConflict(name));
+            }
         }
 
         //set as src dir
-        let _ = self
+        self.config
+            .set_src_dir(full_dir_path.clone(), lang)
+            .map_err(SetSourceDirError::AnalyzeDirError)?;
+        if let Some(src_dir) = self.config.get_src_dir_as_ref() {
+            self.report_progress(ProgressEvent::SourceScanned {
+                dir: &full_dir_path,
+                files: get_all_files(src_dir.get_dir_as_ref()).len(),
+            });
+        }
+
+        write_conf(self.get_config_file_path(), &self.get_config())
+            .map_err(SetSourceDirError::ConfigWritingError)
+    }
+
+    /// Adds another directory in the source language (e.g. `guides` next to `docs`), its
+    /// structure is mirrored in the directory of the same name inside every target directory
+    pub fn add_source_dir(&mut self, dir_name: &str) -> Result<(), SetSourceDirError> {
+        let _lock = self
+            .lock_config()
+            .map_err(SetSourceDirError::ConfigWritingError)?;
+        let full_dir_path = confine_path(&self.get_root_path(), Path::new(dir_name))
+            .map_err(SetSourceDirError::PathOutsideProject)?;
+        if !full_dir_path.exists() {
+            return Err(SetSourceDirError::DirectoryDoesNotExist);
+        }
+        if !full_dir_path.is_dir() {
+            return Err(SetSourceDirError::NotDirectory);
+        }
+        let src_dir = self
             .config
-            .set_src_dir(full_dir_path, lang)
-            .map_err(SetSourceDirError::AnalyzeDirError);
+            .get_src_dir_as_ref()
+            .as_ref()
+            .ok_or(SetSourceDirError::NoSourceLang)?
+            .get_dir_as_ref();
 
-        let _ = write_conf(self.get_config_file_path(), &self.get_config());
-        Ok(())
+        // the directory can't overlap with a directory of the project
+        let project_dirs = std::iter::once(src_dir)
+            .chain(self.config.get_extra_src_dirs_as_ref())
+            .chain(
+                self.config
+                    .get_lang_dirs_as_ref()
+                    .iter()
+                    .map(|d| d.get_dir_as_ref()),
+            );
+        for dir in project_dirs {
+            let path = dir.get_path();
+            if full_dir_path.starts_with(&path) || path.starts_with(&full_dir_path) {
+                return Err(SetSourceDirError::AlreadyInProject);
+            }
+        }
+
+        // the mirror of the directory would mix with the one of the main source directory
+        let name = full_dir_path
+            .file_name()
+            .map(|n| n.to_string_lossy().into_owned())
+            .ok_or(SetSourceDirError::IncorrectPath)?;
+        let taken = src_dir
+            .get_dirs_as_ref()
+            .iter()
+            .map(|d| d.get_dir_name())
+            .chain(src_dir.get_files_as_ref().iter().map(|f| f.get_name()))
+            .chain(
+                self.config
+                    .get_extra_src_dirs_as_ref()
+                    .iter()
+                    .map(|d| d.get_dir_name()),
+            )
+            .any(|n| n == name);
+        if taken {
+            return Err(SetSourceDirError::NameConflict(name));
+        }
+
+        self.config
+            .add_extra_src_dir(full_dir_path.clone())
+            .map_err(SetSourceDirError::AnalyzeDirError)?;
+        if let Some(dir) = self.config.get_extra_src_dirs_as_ref().last() {
+            self.report_progress(ProgressEvent::SourceScanned {
+                dir: &full_dir_path,
+                files: get_all_files(dir).len(),
+            });
+        }
+        write_conf(self.get_config_file_path(), &self.config)
+            .map_err(SetSourceDirError::ConfigWritingError)
+    }
+
+    /// Removes an extra source directory from the project, its files stay where they are. The
+    /// next sync removes its mirror from the target directories.
+    pub fn remove_source_dir(&mut self, dir_name: &str) -> Result<(), SetSourceDirError> {
+        let _lock = self
+            .lock_config()
+            .map_err(SetSourceDirError::ConfigWritingError)?;
+        let full_dir_path = confine_path(&self.get_root_path(), Path::new(dir_name))
+            .map_err(SetSourceDirError::PathOutsideProject)?;
+        if !self.config.remove_extra_src_dir(&full_dir_path) {
+            return Err(SetSourceDirError::NotSourceDir);
+        }
+        write_conf(self.get_config_file_path(), &self.config)
+            .map_err(SetSourceDirError::ConfigWritingError)
     }
 
-    /// adds a language that the source directory will be translated into
+    /// adds a language that the source directory will be translated into, its directory is
+    /// `<project name><language suffix>` in the project root
     pub fn add_lang(&mut self, lang: Language) -> Result<(), AddLanguageError> {
-        // verifying we can create a directory for the lang
         let mut dir_name = self.get_config().get_name().clone();
         dir_name.push_str(lang.get_dir_suffix());
+        self.add_lang_with_dir(lang, &dir_name)
+    }
+
+    /// Adds a language like [`Project::add_lang_with_dir`], in the directory of
+    /// [`Project::add_lang`] if _dir_name_ is `None`, and syncs the untranslatable files into its
+    /// directory in the same call. The directories of the other languages aren't touched and
+    /// the project stays locked between the two steps. If the sync fails, the language stays in
+    /// the project and the next sync completes its directory.
+    pub fn add_lang_and_sync(
+        &mut self,
+        lang: Language,
+        dir_name: Option<&str>,
+    ) -> Result<SyncReport, AddLanguageError> {
+        let _lock = self.acquire_lock().map_err(AddLanguageError::Locked)?;
+        match dir_name {
+            Some(dir_name) => self.add_lang_with_dir(lang.clone(), dir_name)?,
+            None => self.add_lang(lang.clone())?,
+        }
+        self.sync_files_of(Some(lang), false)
+            .map_err(AddLanguageError::SyncError)
+    }
 
-        let new_path = self.get_root_path().join(dir_name);
+    /// Adds a language that the source directory will be translated into, in the directory at
+    /// _dir_name_ relative to the project root (e.g. `content/fr` to match the layout of a
+    /// site). The directory is created, an existing one must be empty.
+    pub fn add_lang_with_dir(
+        &mut self,
+        lang: Language,
+        dir_name: &str,
+    ) -> Result<(), AddLanguageError> {
+        let _lock = self.acquire_lock().map_err(AddLanguageError::Locked)?;
+        // verifying we can create a directory for the lang
+        let new_path = confine_path(&self.get_root_path(), Path::new(dir_name))
+            .map_err(AddLanguageError::PathOutsideProject)?;
 
-        if new_path.exists() {
+        let is_empty_dir = std::fs::read_dir(&new_path).is_ok_and(|mut e| e.next().is_none());
+        if new_path.exists() && !is_empty_dir {
             return Err(AddLanguageError::LangDirExists);
         }
+        let project_dirs = self
+            .config
+            .get_src_trees()
+            .into_iter()
+            .map(|(d, _)| d.get_path())
+            .chain(
+                self.config
+                    .get_lang_dirs_as_ref()
+                    .iter()
+                    .map(|d| d.get_dir_as_ref().get_path()),
+            );
+        for path in project_dirs {
+            if new_path.starts_with(&path) || path.starts_with(&new_path) {
+                return Err(AddLanguageError::DirOverlaps);
+            }
+        }
 
         // verifying there's a source language
         let conf = self.get_config();
         let src_lang = self.get_src_lang().ok_or(AddLanguageError::NoSourceLang)?;
 
-        // verifying this lang isn't in the project
-        if src_lang == lang {
+        // verifying this lang isn't in the project, two custom languages can't share a code
+        let same_code = |l: &Language| l.get_code().eq_ignore_ascii_case(lang.get_code());
+        if same_code(&src_lang) {
             return Err(AddLanguageError::LangAlreadyInTheProj);
         }
         for lang_dir in conf.get_lang_dirs_as_ref() {
-            let t_lang = lang_dir.get_lang();
-            if t_lang == lang {
+            if same_code(&lang_dir.get_lang()) {
                 return Err(AddLanguageError::LangAlreadyInTheProj);
             }
         }
 
-        std::fs::create_dir(&new_path).map_err(AddLanguageError::IoError)?;
+        std::fs::create_dir_all(&new_path).map_err(AddLanguageError::IoError)?;
 
         self.config
             .add_lang(new_path, lang)
             .map_err(AddLanguageError::IoError)?;
 
-        let _ = write_conf(self.get_config_file_path(), &self.get_config());
-
-        Ok(())
+        write_conf(self.get_config_file_path(), &self.get_config())
+            .map_err(AddLanguageError::ConfigWritingError)
     }
 
     /// removes the given language from the target languages and removes it's directory
     pub fn remove_lang(&mut self, lang: Language) -> Result<(), RemoveLangaugeError> {
+        let _span = self
+            .operation_span("remove_lang", Some(lang.clone()))
+            .entered();
+        let _lock = self.acquire_lock().map_err(RemoveLangaugeError::Locked)?;
         let tgt_lang_path = match self.config.get_tgt_dir_path_by_lang(&lang).as_ref() {
             None => return Err(RemoveLangaugeError::TargetLanguageNotInProject),
             Some(r) => r.to_path_buf(),
@@ -186,17 +583,71 @@ impl Project {
         if !tgt_lang_path.exists() || !tgt_lang_path.is_dir() {
             return Err(RemoveLangaugeError::LangDirDoesNotExist);
         }
+        let op = DestructiveOp::RemoveLang {
+            lang: &lang,
+            dir: &tgt_lang_path,
+        };
+        if !self.is_confirmed(&op) {
+            return Err(RemoveLangaugeError::Declined);
+        }
 
-        self.config.remove_lang(lang);
+        self.config.remove_lang(lang.clone());
+        self.lang_prompts.remove(&lang);
 
         let _ = write_conf(self.get_config_file_path(), &self.get_config());
-        std::fs::remove_dir_all(&tgt_lang_path).map_err(RemoveLangaugeError::IoError)?;
+        if self.config.get_use_trash() {
+            let root = self.get_root_path();
+            let entry = trash::new_entry(&root);
+            trash::move_into(self.fs.as_ref(), &root, &entry, &tgt_lang_path)
+                .map_err(RemoveLangaugeError::IoError)?;
+        } else {
+            self.fs
+                .remove_dir_all(&tgt_lang_path)
+                .map_err(RemoveLangaugeError::IoError)?;
+        }
         Ok(())
     }
 
-    /// Syncing untranslatable files from the source directory to the target directories
-    pub fn sync_files(&mut self) -> Result<(), SyncFilesError> {
-        let src_lang = self.get_src_lang().ok_or(SyncFilesError::NoSourceLang)?;
+    /// Syncing untranslatable files from the source directory to the target directories. Fails
+    /// with `TargetModified` if a copy in a target directory was edited since it was written,
+    /// unless _force_ is set. Returns what was copied, skipped and removed, and the files that
+    /// couldn't be copied. If the sync of a language fails, the changes of all of them are
+    /// rolled back.
+    pub fn sync_files(&mut self, force: bool) -> Result<SyncReport, SyncFilesError> {
+        self.sync_files_of(None, force)
+    }
+
+    /// Syncs the untranslatable files like [`Project::sync_files`], into the directory of the
+    /// _only_ language if it's set, into all of them otherwise
+    fn sync_files_of(
+        &mut self,
+        only: Option<Language>,
+        force: bool,
+    ) -> Result<SyncReport, SyncFilesError> {
+        let _span = self.operation_span("sync", only.clone()).entered();
+        let _lock = self.acquire_lock().map_err(SyncFilesError::Locked)?;
+        let mut report = self.start_run("sync", only.clone());
+        let mut synced = SyncReport::default();
+        let res = self.sync_files_helper(&mut report, &mut synced, only, force);
+        tracing::info!(
+            copied = synced.copied.len(),
+            skipped = synced.skipped.len(),
+            removed = synced.removed.len(),
+            errors = synced.errors.len(),
+            "files synced"
+        );
+        self.write_change_report(report, res, SyncFilesError::ReportWritingError)?;
+        Ok(synced)
+    }
+
+    fn sync_files_helper(
+        &mut self,
+        report: &mut ChangeReport,
+        synced: &mut SyncReport,
+        only: Option<Language>,
+        force: bool,
+    ) -> Result<(), SyncFilesError> {
+        self.get_src_lang().ok_or(SyncFilesError::NoSourceLang)?;
 
         self.update_project_structure()
             .map_err(SyncFilesError::UpdateStructureError)?;
@@ -207,37 +658,226 @@ impl Project {
             return Err(SyncFilesError::NoTransLangs);
         }
 
-        let lang_dirs_names: Vec<String> = lang_dirs
+        let root = self.get_root_path();
+        let fs = self.fs.as_ref();
+        // the target directories relative to the root, e.g. `content/fr`
+        let lang_dirs_names: Vec<(String, Language)> = lang_dirs
             .iter()
-            .map(|e| e.get_dir_as_ref().get_dir_name())
+            .filter(|e| only.clone().is_none_or(|lang| lang == e.get_lang()))
+            .map(|e| {
+                let path = e.get_dir_as_ref().get_path();
+                let relative = path.strip_prefix(&root).unwrap_or(&path);
+                (relative.to_string_lossy().into_owned(), e.get_lang())
+            })
+            .collect();
+        // every source directory with the path of its mirror inside the target directories
+        let sources: Vec<(Directory, PathBuf)> = conf
+            .get_src_trees()
+            .into_iter()
+            .map(|(dir, mount)| (dir.clone(), mount))
+            .collect();
+        // the paths the sync leaves alone in every target directory, the mirrors of the extra
+        // source directories are synced on their own
+        let kept: HashMap<Language, Vec<String>> = lang_dirs
+            .iter()
+            .map(|e| {
+                let mut patterns = conf.get_exclude_as_ref().clone();
+                patterns.extend(e.get_protected_as_ref().iter().cloned());
+                patterns.extend(
+                    sources
+                        .iter()
+                        .skip(1)
+                        .map(|(_, mount)| format!("{}/**", mount.display())),
+                );
+                (e.get_lang(), patterns)
+            })
             .collect();
-
-        let src_dir = conf.get_src_dir_as_ref();
-        let src_dir_name = if let Some(l_dir) = src_dir {
-            l_dir.get_dir_as_ref().get_dir_name()
-        } else {
-            panic!("impossible case")
-        };
-
-        let lang_src_dir = src_dir.clone().unwrap();
-        let src_dir = lang_src_dir.get_dir_as_ref();
 
         // copy files
-        for d_name in lang_dirs_names {
-            remove_files_not_in_source_dir(
-                &src_dir.get_path(),
-                &self.get_root_path().join(&d_name),
-                src_dir,
-            )
-            .map_err(SyncFilesError::RemoveUntrackedError)?;
-            copy_untranslatable_files(&self.get_root_path(), &src_dir_name, &d_name, src_dir)
-                .map_err(SyncFilesError::CopyError)?;
+        if !force {
+            let mut targets = Vec::new();
+            for (src_dir, mount) in &sources {
+                let src_path = src_dir.get_path();
+                for (file, translatable) in get_all_files(src_dir) {
+                    if translatable {
+                        continue;
+                    }
+                    let relative = file.strip_prefix(&src_path).unwrap_or(&file);
+                    for (d_name, _) in &lang_dirs_names {
+                        targets.push(root.join(d_name).join(mount).join(relative));
+                    }
+                }
+            }
+            let modified = WrittenHashes::load(&root).find_modified(&root, &targets);
+            if !modified.is_empty() {
+                return Err(SyncFilesError::TargetModified(modified));
+            }
+        }
+        if self.confirm.is_some() {
+            let mut planned = Vec::new();
+            for (d_name, lang) in &lang_dirs_names {
+                for (src_dir, mount) in &sources {
+                    let tgt_dir = root.join(d_name).join(mount);
+                    // the mirror of a new source directory is created by the copy
+                    if !fs.metadata(&tgt_dir).is_ok_and(|m| m.is_dir) {
+                        continue;
+                    }
+                    planned.extend(
+                        find_files_not_in_source_dir_in(
+                            fs,
+                            &src_dir.get_path(),
+                            &tgt_dir,
+                            src_dir,
+                            conf,
+                            lang,
+                            &kept[lang],
+                        )
+                        .map_err(SyncFilesError::RemoveUntrackedError)?,
+                    );
+                }
+            }
+            let op = DestructiveOp::SyncRemovals { paths: &planned };
+            if !planned.is_empty() && !self.is_confirmed(&op) {
+                return Err(SyncFilesError::RemovalDeclined(planned));
+            }
+        }
+        if self.is_cancelled() {
+            return Err(SyncFilesError::Interrupted);
+        }
+        // the removed and the replaced files of all the languages are staged in the same trash
+        // entry, the sync is rolled back from it if a language fails
+        let staging = trash::new_entry(&root);
+        // the languages are synced in parallel, each with its share of the workers
+        let workers = (helper::get_parallelism() / lang_dirs_names.len()).max(1);
+        let total = sources
+            .iter()
+            .map(|(src_dir, _)| get_all_files(src_dir).iter().filter(|(_, t)| !t).count())
+            .sum::<usize>()
+            * lang_dirs_names.len();
+        let done = AtomicUsize::new(0);
+        // the mirrors the sync creates, they're removed again if the sync is rolled back
+        let created = Mutex::new(Vec::new());
+        let results =
+            helper::run_parallel(&lang_dirs_names, lang_dirs_names.len(), |(d_name, lang)| {
+                let mut removed = Vec::new();
+                let mut copied = SyncReport::default();
+                for (src_dir, mount) in &sources {
+                    let src_path = src_dir.get_path();
+                    let tgt_name = Path::new(d_name).join(mount);
+                    let tgt_dir = root.join(&tgt_name);
+                    if !fs.metadata(&tgt_dir).is_ok_and(|m| m.is_dir) {
+                        // the first sync of an extra source directory creates its mirror
+                        fs.create_dir_all(&tgt_dir)
+                            .map_err(|e| SyncFilesError::CopyError(CopyFileDirError::IoError(e)))?;
+                        created
+                            .lock()
+                            .unwrap_or_else(|e| e.into_inner())
+                            .push(tgt_dir.clone());
+                    } else {
+                        removed.extend(
+                            RemoveContext::new(fs, &tgt_dir, conf, lang, &kept[lang])
+                                .removing(Some((&root, &staging)))
+                                .run(&src_path, src_dir)
+                                .map_err(SyncFilesError::RemoveUntrackedError)?,
+                        );
+                    }
+                    let on_file = |path: &Path| {
+                        self.report_progress(ProgressEvent::FileSynced {
+                            path,
+                            lang,
+                            done: done.fetch_add(1, Ordering::Relaxed) + 1,
+                            total,
+                        })
+                    };
+                    let options = CopyOptions {
+                        workers,
+                        staging: Some((&root, &staging)),
+                        on_file: Some(&on_file),
+                        cancel: self.cancel.as_ref(),
+                        fs,
+                    };
+                    let src_name = src_path.strip_prefix(&root).unwrap_or(&src_path);
+                    copied.extend(
+                        copy_untranslatable_files(
+                            &root,
+                            &src_name.to_string_lossy(),
+                            &tgt_name.to_string_lossy(),
+                            src_dir,
+                            conf,
+                            options,
+                        )
+                        .map_err(SyncFilesError::CopyError)?,
+                    );
+                }
+                Ok((removed, copied))
+            });
+        let copied: Vec<PathBuf> = results
+            .iter()
+            .flatten()
+            .flat_map(|(_, copied)| copied.copied.iter().cloned())
+            .collect();
+        // a file that couldn't be copied fails the sync like a language that couldn't be synced
+        let failed: Vec<(PathBuf, String)> = results
+            .iter()
+            .flatten()
+            .flat_map(|(_, copied)| copied.errors.iter().cloned())
+            .collect();
+        if results.iter().any(|res| res.is_err()) || !failed.is_empty() {
+            let created = created.into_inner().unwrap_or_else(|e| e.into_inner());
+            rollback_sync(fs, &root, &staging, &copied, &created)
+                .map_err(SyncFilesError::RollbackError)?;
+            let error = results.into_iter().find_map(|res| res.err());
+            return Err(error.unwrap_or(SyncFilesError::FilesNotCopied(failed)));
         }
+        // every language synced, the written hashes of all of them are recorded together below
+        let results: Vec<(Vec<PathBuf>, SyncReport)> = results.into_iter().flatten().collect();
+        if conf.get_use_trash() {
+            // only the removed files go to the trash, not the previous versions of the copies
+            trash::forget(fs, &root, &staging, &copied)
+                .map_err(SyncFilesError::RemoveUntrackedError)?;
+        } else {
+            // the staged files are deleted for good once the sync succeeded
+            discard(fs, &staging, None)
+                .or_else(ignore_not_found)
+                .map_err(SyncFilesError::RemoveUntrackedError)?;
+        }
+        trash::prune(fs, &root);
+        let mut written = Vec::new();
+        let mut linked = Vec::new();
+        for ((_, lang), (removed, copied)) in lang_dirs_names.into_iter().zip(results) {
+            for path in &removed {
+                report.push(&root, path, lang.clone(), ChangeKind::Removed);
+            }
+            synced.removed.extend(removed);
+            for path in &copied.copied {
+                report.push(&root, path, lang.clone(), ChangeKind::Copied);
+                // the contents of a link (or a hard link) follow the source, they aren't edited
+                // in the target, a link to a directory has no contents to hash anyway
+                let meta = fs.symlink_metadata(path);
+                let is_link = meta.as_ref().is_ok_and(|m| m.is_symlink);
+                if is_link || conf.get_asset_mode() != AssetMode::Copy {
+                    linked.push(path.clone());
+                } else if meta.is_ok_and(|m| m.is_file) {
+                    written.push(path.clone());
+                }
+            }
+            if conf.get_asset_mode() != AssetMode::Copy {
+                linked.extend(copied.skipped.iter().cloned());
+            }
+            synced.extend(copied);
+        }
+        self.record_written_files(&written)
+            .and_then(|_| self.forget_written_files(&linked))
+            .map_err(|e| SyncFilesError::CopyError(CopyFileDirError::IoError(e)))?;
         self.config
             .analyze_lang_dirs()
             .map_err(SyncFilesError::BuildingConfigError)?;
         write_conf(self.get_config_file_path(), &self.config)
             .map_err(SyncFilesError::ConfigWritingError)?;
+        // what was synced before the cancellation is kept, the rest is synced by the next run
+        if self.is_cancelled() {
+            return Err(SyncFilesError::Interrupted);
+        }
         Ok(())
     }
 
@@ -246,7 +886,13 @@ impl Project {
         &mut self,
         path: PathBuf,
     ) -> Result<(), AddTranslatableFileError> {
-        let path = std::fs::canonicalize(path).map_err(|_| AddTranslatableFileError::NoFile)?;
+        let _lock = self
+            .lock_config()
+            .map_err(AddTranslatableFileError::ConfigWritingError)?;
+        let path = self
+            .confine_existing_path(&path)
+            .map_err(AddTranslatableFileError::PathOutsideProject)?
+            .ok_or(AddTranslatableFileError::NoFile)?;
         self.config.make_translatable_file(path)?;
         write_conf(self.get_config_file_path(), &self.config)
             .map_err(AddTranslatableFileError::ConfigWritingError)?;
@@ -258,34 +904,74 @@ impl Project {
         &mut self,
         path: PathBuf,
     ) -> Result<(), AddTranslatableFileError> {
-        let path = std::fs::canonicalize(path).map_err(|_| AddTranslatableFileError::NoFile)?;
+        let _lock = self
+            .lock_config()
+            .map_err(AddTranslatableFileError::ConfigWritingError)?;
+        let path = self
+            .confine_existing_path(&path)
+            .map_err(AddTranslatableFileError::PathOutsideProject)?
+            .ok_or(AddTranslatableFileError::NoFile)?;
         self.config.make_untranslatable_file(path)?;
         write_conf(self.get_config_file_path(), &self.config)
             .map_err(AddTranslatableFileError::ConfigWritingError)?;
         Ok(())
     }
 
+    /// Sets the review state of the translation of the source file at _path_ into _lang_, `None`
+    /// removes it
+    pub fn set_review_state(
+        &mut self,
+        path: PathBuf,
+        lang: Language,
+        state: Option<ReviewState>,
+    ) -> Result<(), SetReviewStateError> {
+        let _lock = self
+            .lock_config()
+            .map_err(SetReviewStateError::ConfigWritingError)?;
+        let path = self
+            .confine_existing_path(&path)
+            .map_err(SetReviewStateError::PathOutsideProject)?
+            .ok_or(SetReviewStateError::NoFile)?;
+        self.config.set_review_state(&path, lang, state)?;
+        write_conf(self.get_config_file_path(), &self.config)
+            .map_err(SetReviewStateError::ConfigWritingError)
+    }
+
+    /// Returns the review state of the translation of the source file at _path_ into _lang_,
+    /// `None` if it has none
+    pub fn get_review_state(&self, path: &Path, lang: Language) -> Option<ReviewState> {
+        let path = self.confine_existing_path(path).ok()??;
+        self.config.get_src_file(&path)?.get_review_state(lang)
+    }
+
     /// Returns a list of files of the source directory that are translatable
     pub fn get_translatable_files(&self) -> Result<Vec<PathBuf>, GetTranslatableFilesError> {
-        let src_lang = match self.get_src_lang() {
-            None => {
-                return Err(GetTranslatableFilesError::NoSourceLang);
-            }
-            Some(s) => s,
-        };
+        if self.get_src_lang().is_none() {
+            return Err(GetTranslatableFilesError::NoSourceLang);
+        }
         self.config.get_translatable_files()
     }
 
-    /// Makes the file by given path untranslatable (for the source directory)
-    pub fn translate_file(&self, path: PathBuf, lang: Language) -> Result<(), TranslateFileError> {
-        let path = std::fs::canonicalize(path).map_err(|_| TranslateFileError::FileNotExist)?;
+    /// Translates the file by given path into _lang_. Fails with `TargetModified` if its
+    /// translation was edited since it was written, unless _force_ is set.
+    pub fn translate_file(
+        &mut self,
+        path: PathBuf,
+        lang: Language,
+        force: bool,
+    ) -> Result<(), TranslateFileError> {
+        let _span = self
+            .operation_span("translate", Some(lang.clone()))
+            .entered();
+        let _lock = self.acquire_lock().map_err(TranslateFileError::Locked)?;
+        let path = self
+            .confine_existing_path(&path)
+            .map_err(TranslateFileError::PathOutsideProject)?
+            .ok_or(TranslateFileError::FileNotExist)?;
 
-        let src_lang = match self.get_src_lang() {
-            None => {
-                return Err(TranslateFileError::NoSourceLang);
-            }
-            Some(s) => s,
-        };
+        if self.get_src_lang().is_none() {
+            return Err(TranslateFileError::NoSourceLang);
+        }
         let tgt_langs = self.get_tgt_langs();
         if !tgt_langs.contains(&lang) {
             return Err(TranslateFileError::TargetLanguageNotInProject);
@@ -299,95 +985,2413 @@ impl Project {
             return Err(TranslateFileError::UntranslatableFile);
         }
 
+        let root = self.get_root_path();
+        let gettext = self.config.get_workflow() == Workflow::Gettext;
+        let modified = self
+            .config
+            .get_tgt_file_path(&path, &lang)
+            .filter(|tgt_path| !force && WrittenHashes::load(&root).is_modified(&root, tgt_path));
+        if let Some(tgt_path) = modified.filter(|_| !gettext) {
+            return Err(TranslateFileError::TargetModified(vec![tgt_path]));
+        }
+
         // get new path in tgt_dir
-        translate_file_helper(&path, &self.config, &lang)
+        let ctx = self.get_translation_context(&lang);
+        let mut report = self.start_run("translate", Some(lang.clone()));
+        self.report_progress(ProgressEvent::FileStarted {
+            file: &path,
+            lang: &lang,
+            index: 0,
+            total: 1,
+        });
+        // the gettext workflow writes the translation to the PO file, compiled after the review
+        if gettext {
+            let res = self
+                .translate_to_po(lang.clone(), &ctx, std::slice::from_ref(&path))
+                .map(|po_path| report.push(&root, &po_path, lang.clone(), ChangeKind::Translated));
+            let res = res.and_then(|_| self.record_translations(&[path], lang));
+            return self.write_change_report(report, res, TranslateFileError::IoError);
+        }
+        let res = translate_file_helper(&path, &self.config, &lang, &ctx).and_then(|new_path| {
+            self.record_written_files(std::slice::from_ref(&new_path))
+                .map_err(TranslateFileError::IoError)?;
+            report.push(&root, &new_path, lang.clone(), ChangeKind::Translated);
+            self.report_progress(ProgressEvent::FileWritten {
+                file: &path,
+                path: &new_path,
+                lang: &lang,
+                index: 0,
+                total: 1,
+            });
+            Ok(())
+        });
+        let res = res.and_then(|_| self.record_translations(&[path], lang));
+        self.write_change_report(report, res, TranslateFileError::IoError)
     }
 
-    /// Translates all translatable files
-    pub fn translate_all(&self, lang: Language) -> Result<(), TranslateFileError> {
+    /// Merges the changed source file by given path into its translation into _lang_ edited by
+    /// hand: only the changed segments are translated, the edits of the others are kept, see
+    /// [`merge_translation`]
+    pub fn merge_file(
+        &mut self,
+        path: PathBuf,
+        lang: Language,
+    ) -> Result<MergeReport, TranslateFileError> {
+        let _span = self.operation_span("merge", Some(lang.clone())).entered();
+        let _lock = self.acquire_lock().map_err(TranslateFileError::Locked)?;
+        let path = self
+            .confine_existing_path(&path)
+            .map_err(TranslateFileError::PathOutsideProject)?
+            .ok_or(TranslateFileError::FileNotExist)?;
+        if !self.get_tgt_langs().contains(&lang) {
+            return Err(TranslateFileError::TargetLanguageNotInProject);
+        }
         let trans_files = self
             .get_translatable_files()
             .map_err(TranslateFileError::TranslatableFilesError)?;
-        for file in &trans_files {
-            translate_file_helper(file, &self.config, &lang)?;
+        if !trans_files.contains(&path) {
+            return Err(TranslateFileError::UntranslatableFile);
         }
-        Ok(())
+        let root = self.get_root_path();
+        let tgt_path = self
+            .config
+            .get_tgt_file_path(&path, &lang)
+            .filter(|p| p.is_file() && load_base(&root, &path, lang.clone()).is_some())
+            .ok_or(TranslateFileError::NoMergeBase)?;
+
+        let ctx = self.get_translation_context(&lang);
+        let mut report = self.start_run("merge", Some(lang.clone()));
+        let mut merged = MergeReport::default();
+        let res = merge_translation(&path, &tgt_path, &lang, &ctx)
+            .map_err(translation_error)
+            .and_then(|res| {
+                merged = res;
+                self.record_written_files(std::slice::from_ref(&tgt_path))
+                    .map_err(TranslateFileError::IoError)?;
+                report.push(&root, &tgt_path, lang.clone(), ChangeKind::Translated);
+                Ok(())
+            });
+        let res = res.and_then(|_| self.record_translations(&[path], lang));
+        self.write_change_report(report, res, TranslateFileError::IoError)?;
+        Ok(merged)
     }
 
-    /// Updates source directory structure (if for example it has been changed since the initialization of the project)
-    pub fn update_project_structure(&mut self) -> Result<(), UpdateSourceDirConfig> {
-        self.config.update_source_dir_config()
+    /// Translates all translatable files, as one batch job if the batch mode is enabled in the
+    /// translator options
+    pub fn translate_all(&mut self, lang: Language) -> Result<(), TranslateFileError> {
+        let _span = self
+            .operation_span("translate", Some(lang.clone()))
+            .entered();
+        let _lock = self.acquire_lock().map_err(TranslateFileError::Locked)?;
+        let mut report = self.start_run("translate", Some(lang.clone()));
+        let mut translated = Vec::new();
+        let res = self
+            .get_translatable_files()
+            .map_err(TranslateFileError::TranslatableFilesError)
+            .and_then(|files| {
+                self.translate_all_helper(lang.clone(), files, &mut report, &mut translated)
+            });
+        // the files translated before a failure are recorded too
+        let recorded = self.record_translations(&translated, lang);
+        self.write_change_report(report, res.and(recorded), TranslateFileError::IoError)
     }
-}
 
-/// Helper function to translate a file to a _lang_ language.
-fn translate_file_helper(
-    path: &PathBuf,
-    conf: &ProjectConfig,
-    lang: &Language,
-) -> Result<(), TranslateFileError> {
-    if !path.exists() || !path.is_file() {
-        return Err(TranslateFileError::FileNotExist);
+    /// Returns what [`Project::translate_all`] would do with every source file: which
+    /// translations are created, which are overwritten (and among them the ones edited by hand)
+    /// and which files are skipped. Nothing is sent to the model and nothing is written.
+    pub fn plan_translate(&self, lang: Language) -> Result<TranslatePlan, TranslateFileError> {
+        if !self.get_tgt_langs().contains(&lang) {
+            return Err(TranslateFileError::TargetLanguageNotInProject);
+        }
+        let trans_files = self
+            .get_translatable_files()
+            .map_err(TranslateFileError::TranslatableFilesError)?;
+        let untrans_files = self
+            .config
+            .get_untranslatable_files()
+            .map_err(TranslateFileError::TranslatableFilesError)?;
+        let root = self.get_root_path();
+        let hashes = WrittenHashes::load(&root);
+        // the gettext workflow writes all the translations to one PO file
+        let po_path = Some(self.get_po_path(Some(lang.clone())))
+            .filter(|_| self.config.get_workflow() == Workflow::Gettext);
+        let mut files = Vec::new();
+        for source in trans_files {
+            let target = match &po_path {
+                Some(po_path) => po_path.clone(),
+                None => self
+                    .config
+                    .get_tgt_file_path(&source, &lang)
+                    .ok_or(TranslateFileError::TargetLanguageNotInProject)?,
+            };
+            let action = if !target.exists() {
+                PlannedAction::Create
+            } else if hashes.is_modified(&root, &target) {
+                PlannedAction::OverwriteEdited
+            } else {
+                PlannedAction::Overwrite
+            };
+            files.push(PlannedFile {
+                source,
+                target: Some(target),
+                action,
+            });
+        }
+        for source in untrans_files {
+            files.push(PlannedFile {
+                source,
+                target: None,
+                action: PlannedAction::Skip,
+            });
+        }
+        Ok(TranslatePlan { lang, files })
     }
 
-    let src_dir_path = conf.get_src_dir_path().unwrap();
-    let tgt_lang_path = conf.get_tgt_dir_path_by_lang(lang).unwrap();
-    let relative_path = path
-        .strip_prefix(src_dir_path)
-        .map_err(|_| TranslateFileError::FileNotExist)?;
-    let new_path = tgt_lang_path.join(relative_path);
-    crate::translator::translate_file_to_file(path, new_path, lang)
-        .map_err(TranslateFileError::IoError)?;
-    thread::sleep(Duration::from_secs(8));
-    Ok(())
-}
+    /// Returns the translatable files whose source changed since they were last translated into
+    /// _lang_, or that were never translated into it
+    pub fn stale_files(&self, lang: Language) -> Result<Vec<PathBuf>, TranslateFileError> {
+        if !self.get_tgt_langs().contains(&lang) {
+            return Err(TranslateFileError::TargetLanguageNotInProject);
+        }
+        let trans_files = self
+            .get_translatable_files()
+            .map_err(TranslateFileError::TranslatableFilesError)?;
+        let mut res = Vec::new();
+        for file in trans_files {
+            let recorded = self
+                .config
+                .get_src_file(&file)
+                .and_then(|f| f.get_metadata(lang.clone()))
+                .map(|m| m.source_hash.clone());
+            let contents = std::fs::read(&file).map_err(TranslateFileError::IoError)?;
+            if recorded != Some(bytes_hash(&contents)) {
+                res.push(file);
+            }
+        }
+        Ok(res)
+    }
 
-pub fn copy_untranslatable_files(
-    root_path: &Path,
-    from_name: &str,
-    to_name: &str,
-    from_structure: &Directory,
-) -> Result<(), CopyFileDirError> {
-    let from_dir = root_path.join(from_name);
-    let to_dir = root_path.join(to_name);
-    copy_untranslatable_files_rec(&from_dir, &to_dir, from_structure)
-}
+    /// Translates only the stale files into _lang_, see [`Project::stale_files`]. Returns the
+    /// source files that were translated.
+    pub fn retranslate_stale(
+        &mut self,
+        lang: Language,
+    ) -> Result<Vec<PathBuf>, TranslateFileError> {
+        let _span = self
+            .operation_span("translate", Some(lang.clone()))
+            .entered();
+        let _lock = self.acquire_lock().map_err(TranslateFileError::Locked)?;
+        let stale = self.stale_files(lang.clone())?;
+        if stale.is_empty() {
+            return Ok(stale);
+        }
+        let mut report = self.start_run("translate", Some(lang.clone()));
+        let mut translated = Vec::new();
+        let res = self.translate_all_helper(lang.clone(), stale, &mut report, &mut translated);
+        let recorded = self.record_translations(&translated, lang);
+        self.write_change_report(report, res.and(recorded), TranslateFileError::IoError)?;
+        Ok(translated)
+    }
 
-fn copy_untranslatable_files_rec(
-    from_dir: &Path,
-    to_dir: &Path,
-    dir: &Directory,
-) -> Result<(), CopyFileDirError> {
-    for file in dir.get_files_as_ref() {
-        if file.is_translatable() {
-            continue;
+    /// Translates only the translatable files that have no translation into _lang_ yet, the
+    /// existing translations are left untouched. Returns the source files that were translated.
+    pub fn translate_missing(
+        &mut self,
+        lang: Language,
+    ) -> Result<Vec<PathBuf>, TranslateFileError> {
+        let _span = self
+            .operation_span("translate", Some(lang.clone()))
+            .entered();
+        let _lock = self.acquire_lock().map_err(TranslateFileError::Locked)?;
+        if !self.get_tgt_langs().contains(&lang) {
+            return Err(TranslateFileError::TargetLanguageNotInProject);
         }
-        let full_path = file.get_path();
-        let relative_path = full_path
-            .strip_prefix(from_dir)
-            .map_err(CopyFileDirError::StripPathError)?
-            .to_path_buf();
+        // the target files of the gettext workflow are only written by `compile_po`, a file is
+        // translated once it has entries in the PO file
+        let in_po = if self.config.get_workflow() == Workflow::Gettext {
+            Some(self.get_po_files(&lang)?)
+        } else {
+            None
+        };
+        let missing: Vec<PathBuf> = self
+            .get_translatable_files()
+            .map_err(TranslateFileError::TranslatableFilesError)?
+            .into_iter()
+            .filter(|file| match &in_po {
+                Some(in_po) => !in_po.contains(&get_relative_name(&self.config, file)),
+                None => self
+                    .config
+                    .get_tgt_file_path(file, &lang)
+                    .is_some_and(|tgt_path| !tgt_path.exists()),
+            })
+            .collect();
+        if missing.is_empty() {
+            return Ok(missing);
+        }
+        let mut report = self.start_run("translate", Some(lang.clone()));
+        let mut translated = Vec::new();
+        let res = self.translate_all_helper(lang.clone(), missing, &mut report, &mut translated);
+        let recorded = self.record_translations(&translated, lang);
+        self.write_change_report(report, res.and(recorded), TranslateFileError::IoError)?;
+        Ok(translated)
+    }
 
-        let new_path = to_dir.join(relative_path);
-        let _ = std::fs::copy(full_path, new_path);
+    /// Returns the tokens used by the requests of the project since its usage was last reset,
+    /// in total and per model
+    pub fn usage(&self) -> ProjectUsage {
+        ProjectUsage::load(&self.get_root_path())
     }
-    for sub_dir in dir.get_dirs_as_ref() {
-        let full_path = sub_dir.get_path();
-        let relative_path = full_path
-            .strip_prefix(from_dir)
-            .map_err(CopyFileDirError::StripPathError)?
-            .to_path_buf();
 
-        let new_path = to_dir.join(relative_path);
-        if !&new_path.exists() {
-            std::fs::create_dir(new_path).map_err(CopyFileDirError::IoError)?;
+    /// Sets the handler consulted before the target directory of a language or the untracked
+    /// files of a sync are deleted, `None` to let them proceed. See [`ConfirmHandler`].
+    pub fn set_confirm_handler(&mut self, handler: Option<Arc<dyn ConfirmHandler>>) {
+        self.confirm = handler;
+    }
+
+    /// Returns true if the destructive operation may proceed
+    fn is_confirmed(&self, op: &DestructiveOp) -> bool {
+        self.confirm
+            .as_ref()
+            .is_none_or(|handler| handler.confirm(op))
+    }
+
+    /// Sets the handler told about the progress of the analysis of the source directory, the
+    /// sync and the translations, `None` to remove it. See [`ProgressHandler`].
+    pub fn set_progress_handler(&mut self, handler: Option<Arc<dyn ProgressHandler>>) {
+        self.progress = handler;
+    }
+
+    fn report_progress(&self, event: ProgressEvent) {
+        if let Some(progress) = &self.progress {
+            progress.on_progress(&event);
         }
-        copy_untranslatable_files_rec(from_dir, to_dir, sub_dir)?;
+        events::send(&self.events, (&event).into());
     }
-    Ok(())
-}
 
-/// Verifies and removes all the files and directories in the target directory that are not in the source directory.
+    /// Returns a receiver of the events of the operations of the project run from now on, e.g.
+    /// for an async frontend that runs them in a blocking task. See [`ProjectEvent`].
+    pub fn subscribe(&self) -> broadcast::Receiver<ProjectEvent> {
+        self.events.subscribe()
+    }
+
+    /// Returns the events of the operations of the project run from now on as a stream, the
+    /// ones a slow consumer missed are skipped
+    pub fn event_stream(&self) -> impl Stream<Item = ProjectEvent> + use<> {
+        events::into_stream(self.subscribe())
+    }
+
+    /// Sets the token that stops the running sync or translation once it's cancelled, e.g. from
+    /// another thread, `None` to remove it. The files already synced or translated are kept and
+    /// the files left by a cancelled translation are written to its journal, see
+    /// [`Project::translate_all_resume`].
+    pub fn set_cancellation_token(&mut self, token: Option<CancellationToken>) {
+        self.cancel = token;
+    }
+
+    /// Sets the file system the target directories are synced on and the trash is kept in, e.g.
+    /// a [`crate::fs::MemoryFs`] in tests. The project is on the disk by default.
+    pub fn set_file_system(&mut self, fs: Arc<dyn FileSystem>) {
+        self.fs = fs;
+    }
+
+    /// returns true if a shutdown was requested or the token of the project was cancelled
+    fn is_cancelled(&self) -> bool {
+        shutdown::is_cancelled(self.cancel.as_ref())
+    }
+
+    /// Starts the report of a sync or translation run and tells the subscribers about it
+    fn start_run(&self, operation: &str, lang: Option<Language>) -> ChangeReport {
+        events::send(
+            &self.events,
+            ProjectEvent::OperationStarted {
+                operation: operation.to_string(),
+                lang,
+            },
+        );
+        ChangeReport::new(operation)
+    }
+
+    /// Sets if the target directories of the removed languages and the untracked files removed
+    /// by the sync are moved into the trash of the project instead of being deleted
+    pub fn set_use_trash(&mut self, enabled: bool) -> Result<(), WriteConfigError> {
+        let _lock = self.lock_config()?;
+        self.config.set_use_trash(enabled);
+        write_conf(self.get_config_file_path(), &self.config)
+    }
+
+    /// Returns the names of the entries of the trash, one per removal, the oldest first
+    pub fn list_trash(&self) -> std::io::Result<Vec<String>> {
+        trash::list(self.fs.as_ref(), &self.get_root_path())
+    }
+
+    /// Moves the files of the trash entry _name_ back where they were removed from, returns
+    /// their paths. The ones that exist again are left in the trash.
+    pub fn restore_trash(&self, name: &str) -> std::io::Result<Vec<PathBuf>> {
+        let _lock = self.lock.acquire().map_err(std::io::Error::other)?;
+        trash::restore(self.fs.as_ref(), &self.get_root_path(), name)
+    }
+
+    /// Deletes the entries of the trash for good
+    pub fn empty_trash(&self) -> std::io::Result<()> {
+        let _lock = self.lock.acquire().map_err(std::io::Error::other)?;
+        trash::empty(self.fs.as_ref(), &self.get_root_path())
+    }
+
+    /// Returns the names of the saved previous versions of the translated file at _path_, the
+    /// oldest first, see [`TranslatorOptions::backups`]
+    pub fn list_backups(&self, path: &Path) -> Result<Vec<String>, BackupError> {
+        let root = self.get_root_path();
+        let path = confine_user_path(&root, path).map_err(BackupError::PathOutsideProject)?;
+        backups::list(&root, &path).map_err(BackupError::IoError)
+    }
+
+    /// Writes the saved version _name_ of the translated file at _path_ back to it
+    pub fn restore_backup(&self, path: &Path, name: &str) -> Result<(), BackupError> {
+        let _lock = self.lock.acquire().map_err(BackupError::Locked)?;
+        let root = self.get_root_path();
+        let path = confine_user_path(&root, path).map_err(BackupError::PathOutsideProject)?;
+        if !backups::list(&root, &path)
+            .map_err(BackupError::IoError)?
+            .iter()
+            .any(|n| n == name)
+        {
+            return Err(BackupError::NoBackup);
+        }
+        backups::restore(&root, &path, name).map_err(BackupError::IoError)
+    }
+
+    /// Forgets the usage recorded for the project
+    pub fn reset_usage(&self) -> std::io::Result<()> {
+        let _lock = self.lock.acquire().map_err(std::io::Error::other)?;
+        ProjectUsage::reset(&self.get_root_path())
+    }
+
+    /// Translates the files left by the last run into _lang_ that didn't finish (its budget
+    /// was exhausted, it was cancelled or the process crashed), the files it translated aren't
+    /// translated again. Returns the source files that were translated, empty if there is no
+    /// such run.
+    pub fn translate_all_resume(
+        &mut self,
+        lang: Language,
+    ) -> Result<Vec<PathBuf>, TranslateFileError> {
+        let _span = self.operation_span("resume", Some(lang.clone())).entered();
+        let _lock = self.acquire_lock().map_err(TranslateFileError::Locked)?;
+        if !self.get_tgt_langs().contains(&lang) {
+            return Err(TranslateFileError::TargetLanguageNotInProject);
+        }
+        let root = self.get_root_path();
+        let Some(journal) = RunJournal::load(&root, lang.clone()) else {
+            return Ok(Vec::new());
+        };
+        let trans_files = self
+            .get_translatable_files()
+            .map_err(TranslateFileError::TranslatableFilesError)?;
+        // the files removed from the project since are left out
+        let remaining: Vec<PathBuf> = journal
+            .get_files(&root)
+            .into_iter()
+            .filter(|f| trans_files.contains(f))
+            .collect();
+        let mut report = self.start_run("translate", Some(lang.clone()));
+        let mut translated = Vec::new();
+        let res = self.translate_all_helper(lang.clone(), remaining, &mut report, &mut translated);
+        let recorded = self.record_translations(&translated, lang);
+        self.write_change_report(report, res.and(recorded), TranslateFileError::IoError)?;
+        Ok(translated)
+    }
+
+    /// Translates the _trans_files_ within the budget of the run, the source files that were
+    /// translated are added to _translated_. The files are written to the journal of the run
+    /// when it starts and removed from it once translated, so the files left when the budget is
+    /// exhausted, the run is cancelled or the process crashes are resumed by
+    /// [`Project::translate_all_resume`].
+    fn translate_all_helper(
+        &self,
+        lang: Language,
+        trans_files: Vec<PathBuf>,
+        report: &mut ChangeReport,
+        translated: &mut Vec<PathBuf>,
+    ) -> Result<(), TranslateFileError> {
+        let root = self.get_root_path();
+        // the context is shared by the files of the run and so is its usage
+        let ctx = self.get_translation_context(&lang);
+        // the files left by an earlier run stay in the journal with the ones of this run
+        let mut journal = RunJournal::load(&root, lang.clone())
+            .unwrap_or_else(|| RunJournal::new(&root, lang.clone(), &[]));
+        journal.add_files(&root, &trans_files);
+        journal.save(&root).map_err(TranslateFileError::IoError)?;
+        let res = self.translate_files_helper(
+            lang.clone(),
+            &ctx,
+            &trans_files,
+            report,
+            translated,
+            &mut journal,
+        );
+        if journal.is_empty() {
+            RunJournal::remove(&root, lang)
+        } else {
+            journal.save(&root)
+        }
+        .map_err(TranslateFileError::IoError)?;
+        let exceeded = match &res {
+            Err(TranslateFileError::IoError(e)) => is_budget_exceeded(e),
+            Err(TranslateFileError::BatchError(BatchError::IoError(e))) => is_budget_exceeded(e),
+            _ => false,
+        };
+        if !exceeded {
+            return res;
+        }
+        Err(TranslateFileError::BudgetExceeded {
+            usage: ctx.usage.get(),
+            translated: translated.len(),
+            remaining: trans_files.len() - translated.len(),
+        })
+    }
+
+    /// Translates the _trans_files_, the source files that were translated are added to
+    /// _translated_ and removed from the _journal_
+    fn translate_files_helper(
+        &self,
+        lang: Language,
+        ctx: &TranslationContext,
+        trans_files: &[PathBuf],
+        report: &mut ChangeReport,
+        translated: &mut Vec<PathBuf>,
+        journal: &mut RunJournal,
+    ) -> Result<(), TranslateFileError> {
+        let root = self.get_root_path();
+        if self.config.get_workflow() == Workflow::Gettext {
+            let po_path = self.translate_to_po(lang.clone(), ctx, trans_files)?;
+            report.push(&root, &po_path, lang, ChangeKind::Translated);
+            translated.extend_from_slice(trans_files);
+            journal.mark_translated(&root, trans_files);
+            return Ok(());
+        }
+        if ctx.options.batch {
+            let mut files = Vec::new();
+            for file in trans_files {
+                let tgt_path = self
+                    .config
+                    .get_tgt_file_path(file, &lang)
+                    .ok_or(TranslateFileError::TargetLanguageNotInProject)?;
+                files.push((file.clone(), tgt_path));
+            }
+            crate::translator::translate_files_batch(&files, &lang, ctx).map_err(|e| match e {
+                BatchError::IoError(io)
+                    if matches!(
+                        io.kind(),
+                        std::io::ErrorKind::Interrupted | std::io::ErrorKind::TimedOut
+                    ) =>
+                {
+                    translation_error(io)
+                }
+                e => TranslateFileError::BatchError(e),
+            })?;
+            for (index, (src_path, tgt_path)) in files.iter().enumerate() {
+                apply_watermark_if_enabled(tgt_path, &self.config, &lang)?;
+                report.push(&root, tgt_path, lang.clone(), ChangeKind::Translated);
+                self.report_progress(ProgressEvent::FileWritten {
+                    file: src_path,
+                    path: tgt_path,
+                    lang: &lang,
+                    index,
+                    total: files.len(),
+                });
+            }
+            let written: Vec<PathBuf> = files
+                .into_iter()
+                .map(|(src_path, tgt_path)| {
+                    translated.push(src_path);
+                    tgt_path
+                })
+                .collect();
+            journal.mark_translated(&root, translated);
+            return self
+                .record_written_files(&written)
+                .map_err(TranslateFileError::IoError);
+        }
+        let total = trans_files.len();
+        for (index, file) in trans_files.iter().enumerate() {
+            if self.is_cancelled() {
+                return Err(TranslateFileError::Interrupted);
+            }
+            self.report_progress(ProgressEvent::FileStarted {
+                file,
+                lang: &lang,
+                index,
+                total,
+            });
+            let new_path = translate_file_helper(file, &self.config, &lang, ctx)?;
+            self.record_written_files(std::slice::from_ref(&new_path))
+                .map_err(TranslateFileError::IoError)?;
+            report.push(&root, &new_path, lang.clone(), ChangeKind::Translated);
+            translated.push(file.clone());
+            journal.mark_translated(&root, std::slice::from_ref(file));
+            journal.save(&root).map_err(TranslateFileError::IoError)?;
+            self.report_progress(ProgressEvent::FileWritten {
+                file,
+                path: &new_path,
+                lang: &lang,
+                index,
+                total,
+            });
+        }
+        Ok(())
+    }
+
+    /// Records in the config how the source _files_ were just translated into _lang_: the hash
+    /// of their contents, the time, the model and the version of the prompt
+    fn record_translations(
+        &mut self,
+        files: &[PathBuf],
+        lang: Language,
+    ) -> Result<(), TranslateFileError> {
+        if files.is_empty() {
+            return Ok(());
+        }
+        let ctx = self.get_translation_context(&lang);
+        let translated_at = chrono::Utc::now().to_rfc3339();
+        for file in files {
+            let contents = std::fs::read(file).map_err(TranslateFileError::IoError)?;
+            let meta = FileMetadata {
+                lang: lang.clone(),
+                source_hash: bytes_hash(&contents),
+                translated_at: translated_at.clone(),
+                model: ctx.model.clone(),
+                prompt_version: content_hash(&ctx.prompt),
+            };
+            self.config.set_file_metadata(file, meta);
+        }
+        write_conf(self.get_config_file_path(), &self.config)
+            .map_err(TranslateFileError::ConfigWritingError)
+    }
+
+    /// Returns how the source file by given path was last translated into every language it
+    /// was translated into, empty if it never was
+    pub fn file_metadata(&self, path: &Path) -> Vec<FileMetadata> {
+        let file = match self.confine_existing_path(path) {
+            Ok(Some(p)) => p,
+            _ => return Vec::new(),
+        };
+        self.config
+            .get_src_file(&file)
+            .map(|f| f.get_metadata_as_ref().clone())
+            .unwrap_or_default()
+    }
+
+    /// Forgets the hashes of the target files at _paths_, see [`WrittenHashes::forget`]
+    fn forget_written_files(&self, paths: &[PathBuf]) -> std::io::Result<()> {
+        if paths.is_empty() {
+            return Ok(());
+        }
+        let root = self.get_root_path();
+        let mut hashes = WrittenHashes::load(&root);
+        for path in paths {
+            hashes.forget(&root, path);
+        }
+        hashes.save(&root)
+    }
+
+    /// Records the hashes of the target files just written, so a later edit by hand is
+    /// detected before they are overwritten
+    fn record_written_files(&self, paths: &[PathBuf]) -> std::io::Result<()> {
+        if paths.is_empty() {
+            return Ok(());
+        }
+        let root = self.get_root_path();
+        let mut hashes = WrittenHashes::load(&root);
+        for path in paths {
+            hashes.record(&root, path)?;
+        }
+        hashes.save(&root)
+    }
+
+    /// Returns the names of the files with entries in the PO file of _lang_
+    fn get_po_files(&self, lang: &Language) -> Result<HashSet<String>, TranslateFileError> {
+        match std::fs::read_to_string(self.get_po_path(Some(lang.clone()))) {
+            Ok(text) => Ok(parse_po(&text)
+                .map_err(TranslateFileError::ExchangeError)?
+                .entries
+                .into_iter()
+                .map(|e| e.file)
+                .collect()),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(HashSet::new()),
+            Err(e) => Err(TranslateFileError::IoError(e)),
+        }
+    }
+
+    /// Translates the segments of the _files_ into the PO file of _lang_, the reviewed
+    /// translations already in it aren't translated again. The entries of the other
+    /// translatable files are kept. Returns the path of the PO file.
+    fn translate_to_po(
+        &self,
+        lang: Language,
+        ctx: &TranslationContext,
+        files: &[PathBuf],
+    ) -> Result<PathBuf, TranslateFileError> {
+        let po_path = self.get_po_path(Some(lang.clone()));
+        let existing: Vec<PoEntry> = match std::fs::read_to_string(&po_path) {
+            Ok(text) => {
+                parse_po(&text)
+                    .map_err(TranslateFileError::ExchangeError)?
+                    .entries
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Vec::new(),
+            Err(e) => return Err(TranslateFileError::IoError(e)),
+        };
+        self.get_src_lang()
+            .ok_or(TranslateFileError::NoSourceLang)?;
+        let mut entries = Vec::new();
+        for file in self
+            .get_translatable_files()
+            .map_err(TranslateFileError::TranslatableFilesError)?
+        {
+            let file_name = get_relative_name(&self.config, &file);
+            let of_file = existing.iter().filter(|e| e.file == file_name);
+            if !files.contains(&file) {
+                entries.extend(of_file.cloned());
+                continue;
+            }
+            if self.is_cancelled() {
+                return Err(TranslateFileError::Interrupted);
+            }
+            let reviewed: HashMap<usize, PreviousChunk> = of_file
+                .filter(|e| !e.fuzzy && !e.msgstr.is_empty())
+                .map(|e| {
+                    let chunk = PreviousChunk {
+                        source: e.msgid.clone(),
+                        translation: e.msgstr.clone(),
+                    };
+                    (e.index, chunk)
+                })
+                .collect();
+            let contents = std::fs::read_to_string(&file).map_err(TranslateFileError::IoError)?;
+            let (text, _) = protect(handler_for_path(&file).as_ref(), &contents);
+            let segments =
+                translate_segments_keeping(&text, &lang, &ctx.for_file(&file), &reviewed)
+                    .map_err(translation_error)?;
+            for (index, segment) in segments.into_iter().enumerate() {
+                let fuzzy = reviewed
+                    .get(&index)
+                    .is_none_or(|r| r.source != segment.source);
+                entries.push(PoEntry {
+                    file: file_name.clone(),
+                    index,
+                    msgid: segment.source,
+                    msgstr: segment.translation,
+                    fuzzy,
+                });
+            }
+        }
+        write_file_creating_dirs(
+            &po_path,
+            &write_po(&self.config.get_name(), Some(lang), &entries),
+        )
+        .map_err(TranslateFileError::IoError)?;
+        Ok(po_path)
+    }
+
+    /// Writes the report of the run if it's enabled in the config. An error of the run takes
+    /// precedence over an error of writing the report.
+    fn write_change_report<E: std::fmt::Display>(
+        &self,
+        mut report: ChangeReport,
+        res: Result<(), E>,
+        map_err: fn(std::io::Error) -> E,
+    ) -> Result<(), E> {
+        let changes = report.changes.len();
+        match &res {
+            Ok(()) => tracing::info!(changes, "{} finished", report.operation),
+            Err(e) => tracing::warn!(changes, error = %e, "{} failed", report.operation),
+        }
+        events::send(
+            &self.events,
+            ProjectEvent::OperationFinished {
+                operation: report.operation.clone(),
+                succeeded: res.is_ok(),
+                changes,
+            },
+        );
+        if !self.config.get_change_report() {
+            return res;
+        }
+        let written = report.finish(&self.get_root_path(), res.is_ok());
+        res?;
+        written.map_err(map_err)
+    }
+
+    /// Returns the span of an operation of the project, the events of the sync and of the
+    /// translation of every file and chunk are recorded in it
+    fn operation_span(&self, operation: &'static str, lang: Option<Language>) -> tracing::Span {
+        tracing::info_span!(
+            "project",
+            name = %self.config.get_name(),
+            operation,
+            lang = ?lang,
+        )
+    }
+
+    /// Sets whether every sync and translation run writes a report of its changes, see
+    /// [`crate::change_report`]
+    pub fn set_change_report(&mut self, enabled: bool) -> Result<(), WriteConfigError> {
+        let _lock = self.lock_config()?;
+        self.config.set_change_report(enabled);
+        write_conf(self.get_config_file_path(), &self.config)
+    }
+
+    /// Sets how the translated files are marked as machine translated, `None` disables marking
+    pub fn set_watermark(&mut self, mode: Option<WatermarkMode>) -> Result<(), WriteConfigError> {
+        let _lock = self.lock_config()?;
+        self.config.set_watermark(mode);
+        write_conf(self.get_config_file_path(), &self.config)
+    }
+
+    /// Sets the model the files of the project are translated with
+    pub fn set_model(&mut self, model: &str) -> Result<(), WriteConfigError> {
+        let _lock = self.lock_config()?;
+        self.config.set_model(model);
+        write_conf(self.get_config_file_path(), &self.config)
+    }
+
+    /// Sets the file with the prompt to translate with, relative to the project root. `None`
+    /// falls back to `prompt.txt` in the project root, the prompt of the library config or the
+    /// default one. The prompt is loaded and validated before it's set.
+    pub fn set_prompt_file(&mut self, path: Option<PathBuf>) -> Result<(), SetPromptFileError> {
+        let _lock = self
+            .lock_config()
+            .map_err(SetPromptFileError::ConfigWritingError)?;
+        self.prompt = resolve_prompt(&self.get_root_path(), path.as_deref())
+            .map_err(SetPromptFileError::PromptError)?;
+        self.config.set_prompt_file(path);
+        write_conf(self.get_config_file_path(), &self.config)
+            .map_err(SetPromptFileError::ConfigWritingError)
+    }
+
+    /// Returns the glossary the files are translated with: the entries of the project and the
+    /// entries of the shared store the project doesn't override
+    pub fn get_glossary(&self) -> Glossary {
+        self.shared_glossary
+            .overridden_by(self.config.get_glossary_as_ref())
+    }
+
+    /// Adds an entry to the glossary of the shared store, so every project of the workspace
+    /// uses it. Fails if it conflicts with an existing shared entry.
+    pub fn add_shared_glossary_entry(&mut self, entry: GlossaryEntry) -> Result<(), GlossaryError> {
+        let store = self
+            .shared_store
+            .as_ref()
+            .ok_or(GlossaryError::NoSharedStore)?;
+        // other projects may have changed the store since it was loaded
+        let mut glossary = store.load_glossary().map_err(GlossaryError::IoError)?;
+        glossary.add_entry(entry)?;
+        store
+            .save_glossary(&glossary)
+            .map_err(GlossaryError::IoError)?;
+        if self.config.get_use_shared_store() {
+            self.shared_glossary = glossary;
+        }
+        Ok(())
+    }
+
+    /// Removes the entry of the term with exactly this language scope from the glossary of the
+    /// shared store
+    pub fn remove_shared_glossary_entry(
+        &mut self,
+        term: &str,
+        source: Option<Language>,
+        target: Option<Language>,
+    ) -> Result<(), GlossaryError> {
+        let store = self
+            .shared_store
+            .as_ref()
+            .ok_or(GlossaryError::NoSharedStore)?;
+        let mut glossary = store.load_glossary().map_err(GlossaryError::IoError)?;
+        if !glossary.remove_entry(term, source, target) {
+            return Err(GlossaryError::NoSuchEntry);
+        }
+        store
+            .save_glossary(&glossary)
+            .map_err(GlossaryError::IoError)?;
+        if self.config.get_use_shared_store() {
+            self.shared_glossary = glossary;
+        }
+        Ok(())
+    }
+
+    /// Sets whether the project uses the glossary and the translation memory of the shared store
+    /// of the workspace
+    pub fn set_use_shared_store(&mut self, use_shared_store: bool) -> Result<(), GlossaryError> {
+        let _lock = self
+            .lock_config()
+            .map_err(GlossaryError::ConfigWritingError)?;
+        self.shared_glossary = match &self.shared_store {
+            Some(store) if use_shared_store => {
+                store.load_glossary().map_err(GlossaryError::IoError)?
+            }
+            _ => Glossary::new(),
+        };
+        self.config.set_use_shared_store(use_shared_store);
+        write_conf(self.get_config_file_path(), &self.config)
+            .map_err(GlossaryError::ConfigWritingError)
+    }
+
+    /// Adds an entry to the glossary of the project, fails if it conflicts with an existing one
+    pub fn add_glossary_entry(&mut self, entry: GlossaryEntry) -> Result<(), GlossaryError> {
+        let _lock = self
+            .lock_config()
+            .map_err(GlossaryError::ConfigWritingError)?;
+        self.config.get_glossary_as_mut().add_entry(entry)?;
+        write_conf(self.get_config_file_path(), &self.config)
+            .map_err(GlossaryError::ConfigWritingError)
+    }
+
+    /// Adds the approved translation of the _term_ of the source language into _lang_
+    pub fn add_glossary_term(
+        &mut self,
+        term: &str,
+        lang: Language,
+        translation: &str,
+    ) -> Result<(), GlossaryError> {
+        let src_lang = self.get_src_lang().ok_or(GlossaryError::NoSourceLang)?;
+        if !self.get_tgt_langs().contains(&lang) {
+            return Err(GlossaryError::TargetLanguageNotInProject);
+        }
+        let entry = GlossaryEntry::new(term, translation).with_pair(Some(src_lang), Some(lang));
+        self.add_glossary_entry(entry)
+    }
+
+    /// Removes the glossary entry of the term with exactly this language scope
+    pub fn remove_glossary_entry(
+        &mut self,
+        term: &str,
+        source: Option<Language>,
+        target: Option<Language>,
+    ) -> Result<(), GlossaryError> {
+        let _lock = self
+            .lock_config()
+            .map_err(GlossaryError::ConfigWritingError)?;
+        if !self
+            .config
+            .get_glossary_as_mut()
+            .remove_entry(term, source, target)
+        {
+            return Err(GlossaryError::NoSuchEntry);
+        }
+        write_conf(self.get_config_file_path(), &self.config)
+            .map_err(GlossaryError::ConfigWritingError)
+    }
+
+    /// Scans the translatable files for frequent terms and returns a draft glossary for the
+    /// translation into _lang_. If _suggest_ is true the model is asked for translations of the
+    /// terms. The draft isn't saved, see [`Project::commit_glossary_draft`].
+    pub fn draft_glossary(
+        &self,
+        lang: Language,
+        options: &ExtractOptions,
+        suggest: bool,
+    ) -> Result<GlossaryDraft, GlossaryError> {
+        let src_lang = self.get_src_lang().ok_or(GlossaryError::NoSourceLang)?;
+        if !self.get_tgt_langs().contains(&lang) {
+            return Err(GlossaryError::TargetLanguageNotInProject);
+        }
+        let trans_files = self
+            .get_translatable_files()
+            .map_err(GlossaryError::TranslatableFilesError)?;
+        let mut files = Vec::new();
+        for path in trans_files {
+            let contents = std::fs::read_to_string(&path).map_err(GlossaryError::IoError)?;
+            files.push((path, contents));
+        }
+
+        let candidates = extract_terms(&files, src_lang.clone(), options);
+        let mut draft = GlossaryDraft::new(
+            src_lang.clone(),
+            lang.clone(),
+            candidates,
+            &self.get_glossary(),
+        );
+        if suggest {
+            let terms: Vec<String> = draft.entries.iter().map(|e| e.term.clone()).collect();
+            let suggestions = crate::translator::suggest_term_translations(
+                &terms,
+                &src_lang,
+                &lang,
+                &self.get_translation_context(&lang),
+            )
+            .map_err(GlossaryError::IoError)?;
+            for entry in &mut draft.entries {
+                if let Some(translation) = suggestions.get(&entry.term) {
+                    entry.translation = translation.clone();
+                }
+            }
+        }
+        Ok(draft)
+    }
+
+    /// Adds the translated terms of the reviewed draft to the glossary. Nothing is added if one
+    /// of them conflicts with the glossary. Returns the number of entries added.
+    pub fn commit_glossary_draft(&mut self, draft: &GlossaryDraft) -> Result<usize, GlossaryError> {
+        let _lock = self
+            .lock_config()
+            .map_err(GlossaryError::ConfigWritingError)?;
+        let mut glossary = self.config.get_glossary_as_ref().clone();
+        let entries = draft.to_entries();
+        for entry in &entries {
+            glossary.add_entry(entry.clone())?;
+        }
+        self.config.set_glossary(glossary);
+        write_conf(self.get_config_file_path(), &self.config)
+            .map_err(GlossaryError::ConfigWritingError)?;
+        Ok(entries.len())
+    }
+
+    /// Exports the glossary entries that apply to the translation into _lang_ as tab separated
+    /// values
+    pub fn export_glossary(&self, lang: Language) -> Result<String, GlossaryError> {
+        let src_lang = self.get_src_lang().ok_or(GlossaryError::NoSourceLang)?;
+        if !self.get_tgt_langs().contains(&lang) {
+            return Err(GlossaryError::TargetLanguageNotInProject);
+        }
+        Ok(self.get_glossary().export_pair(src_lang, lang))
+    }
+
+    /// Returns the metrics collected in this process since the start or the last
+    /// [`crate::metrics::reset`]: API calls, copies, deletions and their timings
+    pub fn metrics(&self) -> MetricsSnapshot {
+        metrics::snapshot()
+    }
+
+    /// Removes the cached translations of the chunks, the next run asks the model again
+    pub fn clear_cache(&self) -> std::io::Result<()> {
+        clear_cache(&self.get_root_path())
+    }
+
+    /// Returns the translation memory of the project, the segments it translated so far, backed
+    /// by the memory of the shared store if the project uses it
+    pub fn get_translation_memory(&self) -> TranslationMemory {
+        let memory = TranslationMemory::open(&self.get_root_path());
+        match self.get_shared_memory_path() {
+            Some(shared) => memory.with_shared(shared),
+            None => memory,
+        }
+    }
+
+    /// Returns the directory of the translation memory of the shared store, `None` if the
+    /// project doesn't use the store
+    fn get_shared_memory_path(&self) -> Option<PathBuf> {
+        self.shared_store
+            .as_ref()
+            .filter(|_| self.config.get_use_shared_store())
+            .map(|store| store.get_memory_path())
+    }
+
+    /// Exports the segments of the translation memory translated into _lang_ as a TMX file at
+    /// _path_, returns the number of exported segments
+    pub fn export_tmx(&self, path: &Path, lang: Language) -> Result<usize, TranslationMemoryError> {
+        let src_lang = self
+            .get_src_lang()
+            .ok_or(TranslationMemoryError::NoSourceLang)?;
+        if !self.get_tgt_langs().contains(&lang) {
+            return Err(TranslationMemoryError::TargetLanguageNotInProject);
+        }
+        let mut entries = self
+            .get_translation_memory()
+            .entries(lang.clone())
+            .map_err(TranslationMemoryError::IoError)?;
+        // segments recorded before the source language changed belong to another pair
+        entries.retain(|e| e.source_lang.clone().is_none_or(|l| l == src_lang));
+        entries.sort_by(|a, b| a.source.cmp(&b.source));
+        helper::write_atomic(path, write_tmx(&entries, src_lang, lang))
+            .map_err(TranslationMemoryError::IoError)?;
+        Ok(entries.len())
+    }
+
+    /// Imports the units of the TMX file at _path_ into the translation memory: every unit with
+    /// a variant in the source language gives a segment for each target language of the project
+    /// it has a variant in. The segments the project already translated are kept. Returns the
+    /// number of imported segments.
+    pub fn import_tmx(&self, path: &Path) -> Result<usize, TranslationMemoryError> {
+        let _lock = self
+            .lock
+            .acquire()
+            .map_err(TranslationMemoryError::Locked)?;
+        let src_lang = self
+            .get_src_lang()
+            .ok_or(TranslationMemoryError::NoSourceLang)?;
+        let text = std::fs::read_to_string(path).map_err(TranslationMemoryError::IoError)?;
+        let units = parse_tmx(&text)?;
+        let memory = self.get_translation_memory();
+        let mut imported = 0;
+        for unit in units {
+            let Some(source) = unit.get_variant(&src_lang).filter(|s| !s.trim().is_empty()) else {
+                continue;
+            };
+            for lang in self.get_tgt_langs() {
+                let translation = match unit.get_variant(&lang) {
+                    Some(t) if !t.trim().is_empty() => t,
+                    _ => continue,
+                };
+                if memory.lookup(source, lang.clone()).is_some() {
+                    continue;
+                }
+                memory
+                    .insert(&MemoryEntry {
+                        source: source.to_string(),
+                        translation: translation.to_string(),
+                        source_lang: Some(src_lang.clone()),
+                        target_lang: lang,
+                        recorded_at: unit
+                            .created_at
+                            .clone()
+                            .unwrap_or_else(|| chrono::Utc::now().to_rfc3339()),
+                        context: None,
+                    })
+                    .map_err(TranslationMemoryError::IoError)?;
+                imported += 1;
+            }
+        }
+        Ok(imported)
+    }
+
+    /// Exports the translatable files as an XLIFF 2.1 document at _path_ for a review of their
+    /// translation into _lang_. Every file is split into the chunks sent to the model, the
+    /// machine translation of a chunk comes from the translation memory. Returns the number of
+    /// exported files.
+    pub fn export_xliff(&self, path: &Path, lang: Language) -> Result<usize, ExchangeError> {
+        let src_lang = self.get_src_lang().ok_or(ExchangeError::NoSourceLang)?;
+        if !self.get_tgt_langs().contains(&lang) {
+            return Err(ExchangeError::TargetLanguageNotInProject);
+        }
+        let files = self
+            .get_translatable_files()
+            .map_err(ExchangeError::TranslatableFilesError)?;
+        let ctx = self.get_translation_context(&lang);
+        let memory_context = ctx.get_memory_context(&lang);
+        let memory = self.get_translation_memory();
+        let mut exported = Vec::new();
+        for file in files {
+            let contents = std::fs::read_to_string(&file).map_err(ExchangeError::IoError)?;
+            let (text, protected) = protect(handler_for_path(&file).as_ref(), &contents);
+            let chunks =
+                plan_chunks(&text, &ctx.for_file(&file)).map_err(ExchangeError::IoError)?;
+            let segments = chunks
+                .into_iter()
+                .map(|chunk| XliffSegment {
+                    target: memory.recall(&chunk, lang.clone(), &memory_context),
+                    source: chunk,
+                })
+                .collect();
+            exported.push(XliffFile {
+                original: get_relative_name(&self.config, &file),
+                protected,
+                segments,
+            });
+        }
+        helper::write_atomic(path, write_xliff(&exported, src_lang, lang))
+            .map_err(ExchangeError::IoError)?;
+        Ok(exported.len())
+    }
+
+    /// Imports a reviewed XLIFF document: the translation of every file in it is written to the
+    /// target directory of its target language and the reviewed segments replace their machine
+    /// translation in the translation memory. Returns the paths of the written files.
+    pub fn import_xliff(&self, path: &Path) -> Result<Vec<PathBuf>, ExchangeError> {
+        let _lock = self.lock.acquire().map_err(ExchangeError::Locked)?;
+        let project_src_lang = self.get_src_lang().ok_or(ExchangeError::NoSourceLang)?;
+        let text = std::fs::read_to_string(path).map_err(ExchangeError::IoError)?;
+        let doc = parse_xliff(&text)?;
+        let lang = doc
+            .tgt_lang
+            .as_deref()
+            .and_then(|code| self.config.get_lang_by_code(code))
+            .filter(|l| self.get_tgt_langs().contains(l))
+            .ok_or(ExchangeError::TargetLanguageNotInProject)?;
+        // a document without a source language is of the source of the project
+        let other_src_lang = doc
+            .src_lang
+            .as_deref()
+            .filter(|code| self.config.get_lang_by_code(code).as_ref() != Some(&project_src_lang));
+        if let Some(code) = other_src_lang {
+            return Err(ExchangeError::SourceLanguageMismatch(code.to_string()));
+        }
+        let translatable: HashSet<PathBuf> = self
+            .get_translatable_files()
+            .map_err(ExchangeError::TranslatableFilesError)?
+            .into_iter()
+            .collect();
+        // the whole document is checked before the first file is written
+        let mut planned = Vec::new();
+        for file in &doc.files {
+            let src_file = resolve_relative_name(&self.config, &file.original)?;
+            if !translatable.contains(&src_file) {
+                return Err(ExchangeError::FileNotInSource(file.original.clone()));
+            }
+            let mut translated = String::new();
+            for (id, segment) in file.segments.iter().enumerate() {
+                let target = segment.target.as_ref().ok_or_else(|| {
+                    ExchangeError::MissingTarget(format!("{}#u{}", file.original, id + 1))
+                })?;
+                translated.push_str(target);
+            }
+            let contents =
+                reinsert(&translated, &file.protected).map_err(ExchangeError::ReinsertError)?;
+            let tgt_file = self
+                .config
+                .get_tgt_file_path(&src_file, &lang)
+                .ok_or_else(|| ExchangeError::FileNotInSource(file.original.clone()))?;
+            planned.push((tgt_file, contents, file));
+        }
+        let memory = self.get_translation_memory();
+        let mut written = Vec::new();
+        for (tgt_file, contents, file) in planned {
+            write_file_creating_dirs(&tgt_file, &contents).map_err(ExchangeError::IoError)?;
+            for segment in &file.segments {
+                if let (Some(target), false) = (&segment.target, segment.source.trim().is_empty()) {
+                    memory
+                        .record(
+                            &segment.source,
+                            target,
+                            Some(project_src_lang.clone()),
+                            lang.clone(),
+                            None,
+                        )
+                        .map_err(ExchangeError::IoError)?;
+                }
+            }
+            written.push(tgt_file);
+        }
+        self.record_written_files(&written)
+            .map_err(ExchangeError::IoError)?;
+        Ok(written)
+    }
+
+    /// Returns the translation status of every file of the source directory in every target
+    /// language
+    pub fn get_files_status(&self) -> Result<Vec<FileStatus>, StatusError> {
+        self.get_src_lang().ok_or(StatusError::NoSourceLang)?;
+        let mut res = Vec::new();
+        // the paths of the files are relative to the target directories
+        for (src_dir, mount) in self.config.get_src_trees() {
+            let src_path = src_dir.get_path();
+            for (file, translatable) in get_all_files(src_dir) {
+                let relative = mount.join(file.strip_prefix(&src_path).unwrap_or(&file));
+                let source_hash = bytes_hash(&std::fs::read(&file).map_err(StatusError::IoError)?);
+                let src_file = self.config.get_src_file(&file);
+                let mut langs = Vec::new();
+                for lang_dir in self.config.get_lang_dirs_as_ref() {
+                    let lang = lang_dir.get_lang();
+                    // the untranslatable files are copied under their own name
+                    let tgt_file = if translatable {
+                        self.config.get_tgt_file_path(&file, &lang)
+                    } else {
+                        Some(lang_dir.get_dir_as_ref().get_path().join(&relative))
+                    };
+                    let Some(tgt_file) = tgt_file else {
+                        continue;
+                    };
+                    // a translation is of the source it was recorded from, a copy is of the
+                    // source with the same contents
+                    let written_from = if translatable {
+                        src_file
+                            .and_then(|f| f.get_metadata(lang.clone()))
+                            .map(|m| m.source_hash.clone())
+                    } else {
+                        std::fs::read(&tgt_file).ok().map(|c| bytes_hash(&c))
+                    };
+                    langs.push(get_lang_status(
+                        &tgt_file,
+                        lang,
+                        &source_hash,
+                        written_from.as_deref(),
+                    ));
+                }
+                res.push(FileStatus {
+                    path: relative,
+                    translatable,
+                    langs,
+                });
+            }
+        }
+        res.sort_by(|a, b| a.path.cmp(&b.path));
+        Ok(res)
+    }
+
+    /// Returns the state of the translation of every translatable file into every target
+    /// language, see [`crate::status::TranslationState`]
+    pub fn status(&self) -> Result<ProjectStatus, StatusError> {
+        self.get_src_lang().ok_or(StatusError::NoSourceLang)?;
+        let trans_files = self
+            .get_translatable_files()
+            .map_err(StatusError::TranslatableFilesError)?;
+        let mut status = ProjectStatus::default();
+        for path in trans_files {
+            let Some(file) = self.config.get_src_file(&path) else {
+                continue;
+            };
+            let source = std::fs::read(&path).map_err(StatusError::IoError)?;
+            let relative = self
+                .config
+                .get_src_relative_path(&path)
+                .unwrap_or(path.clone());
+            for lang in self.get_tgt_langs() {
+                let Some(tgt_file) = self.config.get_tgt_file_path(&path, &lang) else {
+                    continue;
+                };
+                status.entries.push(TranslationStatus {
+                    path: relative.clone(),
+                    lang: lang.clone(),
+                    state: get_translation_state(file, &source, &tgt_file, lang),
+                });
+            }
+        }
+        status.entries.sort_by(|a, b| a.path.cmp(&b.path));
+        Ok(status)
+    }
+
+    /// Returns the state of the project (its languages, the status of every file and the counts
+    /// of the files per state) in a form that can be serialized, e.g. to JSON for a CI pipeline
+    pub fn report(&self) -> Result<ProjectReport, StatusError> {
+        Ok(ProjectReport::new(
+            &self.config.get_name(),
+            self.get_src_lang(),
+            self.get_tgt_langs(),
+            self.get_files_status()?,
+        ))
+    }
+
+    /// Writes the report of the project as a standalone HTML page at _path_, see
+    /// [`crate::status::html`]
+    pub fn render_report_html(&self, path: &Path) -> Result<(), StatusError> {
+        helper::write_atomic(path, render_html(&self.report()?)).map_err(StatusError::IoError)
+    }
+
+    /// Writes the translation status of every file of the source directory as CSV at _path_,
+    /// returns the number of files
+    pub fn export_status_csv(&self, path: &Path) -> Result<usize, StatusError> {
+        let statuses = self.get_files_status()?;
+        helper::write_atomic(path, write_status_csv(&statuses, &self.get_tgt_langs()))
+            .map_err(StatusError::IoError)?;
+        Ok(statuses.len())
+    }
+
+    /// Sets how the sync puts the untranslatable files into the target directories: copied or
+    /// linked to the source files, see [`AssetMode`]. The existing targets are replaced by the
+    /// next sync.
+    pub fn set_asset_mode(&mut self, mode: AssetMode) -> Result<(), WriteConfigError> {
+        let _lock = self.lock_config()?;
+        self.config.set_asset_mode(mode);
+        write_conf(self.get_config_file_path(), &self.config)
+    }
+
+    /// Sets if the sync mirrors the permissions and the access and modification times of the
+    /// untranslatable files and their directories onto the copies, e.g. for build systems
+    /// relying on them
+    pub fn set_preserve_metadata(&mut self, preserve: bool) -> Result<(), WriteConfigError> {
+        let _lock = self.lock_config()?;
+        self.config.set_preserve_metadata(preserve);
+        write_conf(self.get_config_file_path(), &self.config)
+    }
+
+    /// Sets what the analysis, the sync and the removal of the untracked files do with the
+    /// symbolic links of the language directories, see [`SymlinkPolicy`]
+    pub fn set_symlink_policy(&mut self, policy: SymlinkPolicy) -> Result<(), WriteConfigError> {
+        let _lock = self.lock_config()?;
+        self.config.set_symlink_policy(policy);
+        write_conf(self.get_config_file_path(), &self.config)
+    }
+
+    /// Sets what the translation runs produce: the translated files or PO files, see
+    /// [`Workflow`]
+    pub fn set_workflow(&mut self, workflow: Workflow) -> Result<(), WriteConfigError> {
+        let _lock = self.lock_config()?;
+        self.config.set_workflow(workflow);
+        write_conf(self.get_config_file_path(), &self.config)
+    }
+
+    /// Returns the path of the PO file of _lang_, of the template if _lang_ is `None`
+    pub fn get_po_path(&self, lang: Option<Language>) -> PathBuf {
+        let dir = self.get_root_path().join(PO_DIR);
+        match lang {
+            Some(lang) => dir.join(format!("{}.po", lang.get_code())),
+            None => dir.join(format!("{}.pot", self.config.get_name())),
+        }
+    }
+
+    /// Extracts the segments of all the translatable files into the PO template of the project,
+    /// returns its path
+    pub fn extract_pot(&self) -> Result<PathBuf, ExchangeError> {
+        self.get_src_lang().ok_or(ExchangeError::NoSourceLang)?;
+        let files = self
+            .get_translatable_files()
+            .map_err(ExchangeError::TranslatableFilesError)?;
+        let mut ctx = self.config.get_translation_context("");
+        ctx.project_root = Some(self.get_root_path());
+        let mut entries = Vec::new();
+        for file in files {
+            let contents = std::fs::read_to_string(&file).map_err(ExchangeError::IoError)?;
+            let (text, _) = protect(handler_for_path(&file).as_ref(), &contents);
+            let chunks =
+                plan_chunks(&text, &ctx.for_file(&file)).map_err(ExchangeError::IoError)?;
+            let file_name = get_relative_name(&self.config, &file);
+            entries.extend(
+                chunks
+                    .into_iter()
+                    .enumerate()
+                    .map(|(index, msgid)| PoEntry {
+                        file: file_name.clone(),
+                        index,
+                        msgid,
+                        msgstr: String::new(),
+                        fuzzy: false,
+                    }),
+            );
+        }
+        let path = self.get_po_path(None);
+        write_file_creating_dirs(&path, &write_po(&self.config.get_name(), None, &entries))
+            .map_err(ExchangeError::IoError)?;
+        Ok(path)
+    }
+
+    /// Compiles the reviewed PO file of _lang_ into the target directory: every file is put
+    /// together from the translations of its segments. The translations replace the machine
+    /// translation in the translation memory. Returns the paths of the written files.
+    pub fn compile_po(&self, lang: Language) -> Result<Vec<PathBuf>, ExchangeError> {
+        let _lock = self.lock.acquire().map_err(ExchangeError::Locked)?;
+        if !self.get_tgt_langs().contains(&lang) {
+            return Err(ExchangeError::TargetLanguageNotInProject);
+        }
+        let src_lang = self.get_src_lang().ok_or(ExchangeError::NoSourceLang)?;
+        let text = std::fs::read_to_string(self.get_po_path(Some(lang.clone())))
+            .map_err(ExchangeError::IoError)?;
+        let mut files: Vec<(String, Vec<PoEntry>)> = Vec::new();
+        for entry in parse_po(&text)?.entries {
+            match files.iter_mut().find(|(name, _)| *name == entry.file) {
+                Some((_, entries)) => entries.push(entry),
+                None => files.push((entry.file.clone(), vec![entry])),
+            }
+        }
+        let translatable: HashSet<PathBuf> = self
+            .get_translatable_files()
+            .map_err(ExchangeError::TranslatableFilesError)?
+            .into_iter()
+            .collect();
+        // every file is checked before the first one is written
+        let mut compiled = Vec::new();
+        for (name, mut entries) in files {
+            let src_file = resolve_relative_name(&self.config, &name)?;
+            if !translatable.contains(&src_file) {
+                return Err(ExchangeError::FileNotInSource(name));
+            }
+            entries.sort_by_key(|e| e.index);
+            let contents = std::fs::read_to_string(&src_file).map_err(ExchangeError::IoError)?;
+            let (text, protected) = protect(handler_for_path(&src_file).as_ref(), &contents);
+            if entries.iter().map(|e| e.msgid.as_str()).collect::<String>() != text {
+                return Err(ExchangeError::SourceChanged(name));
+            }
+            if let Some(entry) = entries.iter().find(|e| e.msgstr.is_empty()) {
+                return Err(ExchangeError::MissingTarget(entry.get_context()));
+            }
+            let translated: String = entries.iter().map(|e| e.msgstr.as_str()).collect();
+            let contents =
+                reinsert(&translated, &protected).map_err(ExchangeError::ReinsertError)?;
+            let tgt_file = self
+                .config
+                .get_tgt_file_path(&src_file, &lang)
+                .ok_or_else(|| ExchangeError::FileNotInSource(name.clone()))?;
+            compiled.push((tgt_file, contents, entries));
+        }
+        let memory = self.get_translation_memory();
+        let mut written = Vec::new();
+        for (tgt_file, contents, entries) in compiled {
+            write_file_creating_dirs(&tgt_file, &contents).map_err(ExchangeError::IoError)?;
+            for entry in entries
+                .iter()
+                .filter(|e| !e.fuzzy && !e.msgid.trim().is_empty())
+            {
+                memory
+                    .record(
+                        &entry.msgid,
+                        &entry.msgstr,
+                        Some(src_lang.clone()),
+                        lang.clone(),
+                        None,
+                    )
+                    .map_err(ExchangeError::IoError)?;
+            }
+            written.push(tgt_file);
+        }
+        self.record_written_files(&written)
+            .map_err(ExchangeError::IoError)?;
+        Ok(written)
+    }
+
+    /// Sets the file with the prompt used when _lang_ is the target language, relative to the
+    /// project root. `None` removes the override. The prompt is loaded and validated before it's
+    /// set.
+    pub fn set_lang_prompt_file(
+        &mut self,
+        lang: Language,
+        path: Option<PathBuf>,
+    ) -> Result<(), SetPromptFileError> {
+        let _lock = self
+            .lock_config()
+            .map_err(SetPromptFileError::ConfigWritingError)?;
+        if !self.get_tgt_langs().contains(&lang) {
+            return Err(SetPromptFileError::TargetLanguageNotInProject);
+        }
+        match &path {
+            Some(file) => {
+                let lang_prompt = load_prompt_file(&self.get_root_path(), file)
+                    .map_err(SetPromptFileError::PromptError)?;
+                self.lang_prompts.insert(lang.clone(), lang_prompt);
+            }
+            None => {
+                self.lang_prompts.remove(&lang);
+            }
+        }
+        self.config.set_lang_prompt_file(lang, path);
+        write_conf(self.get_config_file_path(), &self.config)
+            .map_err(SetPromptFileError::ConfigWritingError)
+    }
+
+    /// Returns the template of the prompt the files are translated into _lang_ with
+    pub fn get_prompt_as_ref(&self, lang: &Language) -> &str {
+        self.lang_prompts.get(lang).unwrap_or(&self.prompt)
+    }
+
+    /// Returns the context the files of the project are translated into _lang_ with
+    fn get_translation_context(&self, lang: &Language) -> TranslationContext {
+        let mut ctx = self
+            .config
+            .get_translation_context(self.get_prompt_as_ref(lang));
+        ctx.project_root = Some(self.get_root_path());
+        ctx.shared_memory = self.get_shared_memory_path();
+        ctx.glossary = self.get_glossary();
+        ctx.progress = Some(Arc::new(EventForwarder {
+            handler: self.progress.clone(),
+            sender: self.events.clone(),
+        }));
+        ctx.cancel = self.cancel.clone();
+        if let Some(lang_dir) = self
+            .config
+            .get_lang_dirs_as_ref()
+            .iter()
+            .find(|d| d.get_lang() == *lang)
+        {
+            ctx.examples = lang_dir.get_examples_as_ref().clone();
+            ctx.pivot = lang_dir.get_pivot();
+            ctx.pivot_prompt = Some(self.prompt.clone());
+        }
+        ctx
+    }
+
+    /// Sets the options of the translator (e.g. streaming or the schedule of requests)
+    pub fn set_translator_options(
+        &mut self,
+        options: TranslatorOptions,
+    ) -> Result<(), SetTranslatorOptionsError> {
+        let _lock = self
+            .lock_config()
+            .map_err(SetTranslatorOptionsError::ConfigWritingError)?;
+        if !options.schedule.is_valid() {
+            return Err(SetTranslatorOptionsError::InvalidSchedule);
+        }
+        self.config.set_translator_options(options);
+        write_conf(self.get_config_file_path(), &self.config)
+            .map_err(SetTranslatorOptionsError::ConfigWritingError)
+    }
+
+    /// Sets the glob patterns of the paths that are never analyzed or mirrored into the target
+    /// directories (e.g. `**/node_modules/**` or `*.log`), they're applied by the next sync
+    pub fn set_exclude_patterns(&mut self, patterns: Vec<String>) -> Result<(), WriteConfigError> {
+        let _lock = self.lock_config()?;
+        self.config.set_exclude(patterns);
+        write_conf(self.get_config_file_path(), &self.config)
+    }
+
+    /// Sets the limits above which files are flagged by [`Project::estimate_translation`]
+    pub fn set_budget_thresholds(
+        &mut self,
+        budget: BudgetThresholds,
+    ) -> Result<(), WriteConfigError> {
+        let _lock = self.lock_config()?;
+        self.config.set_budget(budget);
+        write_conf(self.get_config_file_path(), &self.config)
+    }
+
+    /// Estimates the requests and tokens needed to translate the translatable files into _lang_
+    /// and flags the files exceeding the budget thresholds of the project, so they can be split
+    /// or excluded before the run
+    pub fn estimate_translation(
+        &self,
+        lang: Language,
+    ) -> Result<TranslationEstimate, EstimateError> {
+        if self.get_src_lang().is_none() {
+            return Err(EstimateError::NoSourceLang);
+        }
+        if !self.get_tgt_langs().contains(&lang) {
+            return Err(EstimateError::TargetLanguageNotInProject);
+        }
+        let trans_files = self
+            .get_translatable_files()
+            .map_err(EstimateError::TranslatableFilesError)?;
+        let chunking = &self.config.get_translator_options_as_ref().chunking;
+        let mut estimate = TranslationEstimate::default();
+        for file in trans_files {
+            let contents = std::fs::read_to_string(&file).map_err(EstimateError::IoError)?;
+            estimate.files.push(estimate_file(
+                &file,
+                &contents,
+                self.config.get_budget_as_ref(),
+                chunking,
+            ));
+        }
+        Ok(estimate)
+    }
+
+    /// Sets the rates of a model used by [`Project::estimate_cost`], e.g. for a model that has no
+    /// built-in rates or whose prices changed
+    pub fn set_model_pricing(&mut self, pricing: ModelPricing) -> Result<(), WriteConfigError> {
+        let _lock = self.lock_config()?;
+        self.config.set_pricing(pricing);
+        write_conf(self.get_config_file_path(), &self.config)
+    }
+
+    /// Estimates the requests, the tokens and the price of translating all the translatable files
+    /// into _lang_ with the model of the project, e.g. to ask for a confirmation before
+    /// [`Project::translate_all`]
+    pub fn estimate_cost(&self, lang: Language) -> Result<CostEstimate, EstimateError> {
+        let estimate = self.estimate_translation(lang.clone())?;
+        let ctx = self.get_translation_context(&lang);
+        let prompt_tokens = estimate_tokens(&ctx.get_rendered_prompt(&lang));
+        let pricing = get_model_pricing(&ctx.model, self.config.get_pricing_as_ref());
+        Ok(CostEstimate::new(
+            &estimate,
+            &ctx.model,
+            prompt_tokens,
+            pricing,
+        ))
+    }
+
+    /// Counts the words and the characters of the translatable files, per file, per directory
+    /// and in total, and for every target language how much of it isn't translated up to date
+    pub fn stats(&self) -> Result<ProjectStats, StatusError> {
+        self.get_src_lang().ok_or(StatusError::NoSourceLang)?;
+        let trans_files = self
+            .get_translatable_files()
+            .map_err(StatusError::TranslatableFilesError)?;
+        let mut files = Vec::new();
+        for file in trans_files {
+            let contents = std::fs::read_to_string(&file).map_err(StatusError::IoError)?;
+            files.push(FileStats {
+                file: self
+                    .config
+                    .get_src_relative_path(&file)
+                    .unwrap_or(file.clone()),
+                counts: count_file(&file, &contents),
+            });
+        }
+        files.sort_by(|a, b| a.file.cmp(&b.file));
+        let mut stats = ProjectStats::new(files);
+        let status = self.status()?;
+        for lang in self.get_tgt_langs() {
+            let mut remaining = TextCounts::default();
+            for entry in status.entries.iter().filter(|e| e.lang == lang) {
+                let up_to_date = matches!(
+                    entry.state,
+                    TranslationState::UpToDate | TranslationState::Approved
+                );
+                if let Some(counts) = stats.get_file_counts(&entry.path).filter(|_| !up_to_date) {
+                    remaining.add(&counts);
+                }
+            }
+            stats.langs.push(LangStats {
+                lang,
+                total: stats.total,
+                remaining,
+            });
+        }
+        Ok(stats)
+    }
+
+    /// Adds an example translation into _lang_ that is included in the prompt to steer the tone
+    /// and the formatting of the translation
+    pub fn add_example(
+        &mut self,
+        lang: Language,
+        source: &str,
+        translation: &str,
+    ) -> Result<(), LangSettingsError> {
+        let mut examples = self
+            .config
+            .get_lang_dirs_as_ref()
+            .iter()
+            .find(|d| d.get_lang() == lang)
+            .ok_or(LangSettingsError::TargetLanguageNotInProject)?
+            .get_examples_as_ref()
+            .clone();
+        examples.push(ExamplePair {
+            source: source.to_string(),
+            translation: translation.to_string(),
+        });
+        self.set_examples(lang, examples)
+    }
+
+    /// Replaces the example translations into _lang_, an empty list removes them
+    pub fn set_examples(
+        &mut self,
+        lang: Language,
+        examples: Vec<ExamplePair>,
+    ) -> Result<(), LangSettingsError> {
+        let _lock = self
+            .lock_config()
+            .map_err(LangSettingsError::ConfigWritingError)?;
+        if !self.config.set_examples(lang, examples) {
+            return Err(LangSettingsError::TargetLanguageNotInProject);
+        }
+        write_conf(self.get_config_file_path(), &self.config)
+            .map_err(LangSettingsError::ConfigWritingError)
+    }
+
+    /// Sets the glob patterns of the paths in the target directory of _lang_ that only this
+    /// language has (e.g. `fr-only-assets/`), the sync never removes them
+    pub fn set_protected_paths(
+        &mut self,
+        lang: Language,
+        patterns: Vec<String>,
+    ) -> Result<(), LangSettingsError> {
+        let _lock = self
+            .lock_config()
+            .map_err(LangSettingsError::ConfigWritingError)?;
+        if !self.config.set_protected_paths(lang, patterns) {
+            return Err(LangSettingsError::TargetLanguageNotInProject);
+        }
+        write_conf(self.get_config_file_path(), &self.config)
+            .map_err(LangSettingsError::ConfigWritingError)
+    }
+
+    /// Sets the language the files are translated into first and from it into the target
+    /// language _lang_, e.g. English for a pair with few resources like Ukrainian to Spanish.
+    /// `None` translates them directly.
+    pub fn set_pivot_language(
+        &mut self,
+        lang: Language,
+        pivot: Option<Language>,
+    ) -> Result<(), LangSettingsError> {
+        let _lock = self
+            .lock_config()
+            .map_err(LangSettingsError::ConfigWritingError)?;
+        if pivot
+            .clone()
+            .is_some_and(|p| p == lang || Some(p) == self.get_src_lang())
+        {
+            return Err(LangSettingsError::InvalidPivot);
+        }
+        if !self.config.set_pivot(lang, pivot) {
+            return Err(LangSettingsError::TargetLanguageNotInProject);
+        }
+        write_conf(self.get_config_file_path(), &self.config)
+            .map_err(LangSettingsError::ConfigWritingError)
+    }
+
+    /// Sets the thresholds of the readability check for the target language, `None` disables the
+    /// check for that language
+    pub fn set_readability_thresholds(
+        &mut self,
+        lang: Language,
+        thresholds: Option<ReadabilityThresholds>,
+    ) -> Result<(), LangSettingsError> {
+        let _lock = self
+            .lock_config()
+            .map_err(LangSettingsError::ConfigWritingError)?;
+        if !self.config.set_readability_thresholds(lang, thresholds) {
+            return Err(LangSettingsError::TargetLanguageNotInProject);
+        }
+        write_conf(self.get_config_file_path(), &self.config)
+            .map_err(LangSettingsError::ConfigWritingError)
+    }
+
+    /// Runs the readability check on every translated file of the _lang_ language. Returns an
+    /// empty report if the check is disabled for that language.
+    pub fn check_readability(&self, lang: Language) -> Result<QaReport, QaCheckError> {
+        let lang_dir = self
+            .config
+            .get_lang_dirs_as_ref()
+            .iter()
+            .find(|d| d.get_lang() == lang)
+            .ok_or(QaCheckError::TargetLanguageNotInProject)?;
+
+        let mut report = QaReport::new();
+        let thresholds = match lang_dir.get_readability_as_ref() {
+            Some(t) => t,
+            None => return Ok(report),
+        };
+
+        let trans_files = self
+            .get_translatable_files()
+            .map_err(QaCheckError::TranslatableFilesError)?;
+        for file in trans_files {
+            let tgt_path = match self.config.get_tgt_file_path(&file, &lang) {
+                Some(p) if p.is_file() => p,
+                _ => continue,
+            };
+            let text = std::fs::read_to_string(&tgt_path).map_err(QaCheckError::IoError)?;
+            report.extend(check_readability(
+                &tgt_path,
+                &text,
+                lang.clone(),
+                thresholds,
+            ));
+        }
+        Ok(report)
+    }
+
+    /// Compares every translated file of the _lang_ language with its source and reports the
+    /// placeholders, the numbers and the URLs the translation doesn't keep, and for Markdown
+    /// the structure (headings, code fences, link definitions) it doesn't keep
+    pub fn check_translations(&self, lang: Language) -> Result<QaReport, QaCheckError> {
+        if !self.get_tgt_langs().contains(&lang) {
+            return Err(QaCheckError::TargetLanguageNotInProject);
+        }
+        let trans_files = self
+            .get_translatable_files()
+            .map_err(QaCheckError::TranslatableFilesError)?;
+        let mut report = QaReport::new();
+        for file in trans_files {
+            let tgt_path = match self.config.get_tgt_file_path(&file, &lang) {
+                Some(p) if p.is_file() => p,
+                _ => continue,
+            };
+            let source = std::fs::read_to_string(&file).map_err(QaCheckError::IoError)?;
+            let text = std::fs::read_to_string(&tgt_path).map_err(QaCheckError::IoError)?;
+            report.extend(check_invariants(&tgt_path, &source, &text, lang.clone()));
+            if handler_for_path(&file).name() == "markdown" {
+                report.extend(check_markdown_integrity(
+                    &tgt_path,
+                    &source,
+                    &text,
+                    lang.clone(),
+                ));
+            }
+        }
+        Ok(report)
+    }
+
+    /// Checks that every translated file of the _lang_ language uses the approved translations
+    /// of the glossary terms its source contains, see [`check_glossary_compliance`]
+    pub fn check_glossary(&self, lang: Language) -> Result<QaReport, QaCheckError> {
+        if !self.get_tgt_langs().contains(&lang) {
+            return Err(QaCheckError::TargetLanguageNotInProject);
+        }
+        let src_lang = self.get_src_lang().ok_or(QaCheckError::NoSourceLang)?;
+        let glossary = self.get_glossary();
+        let entries = glossary.entries_for_pair(src_lang, lang.clone());
+        let mut report = QaReport::new();
+        if entries.is_empty() {
+            return Ok(report);
+        }
+        let trans_files = self
+            .get_translatable_files()
+            .map_err(QaCheckError::TranslatableFilesError)?;
+        for file in trans_files {
+            let tgt_path = match self.config.get_tgt_file_path(&file, &lang) {
+                Some(p) if p.is_file() => p,
+                _ => continue,
+            };
+            let source = std::fs::read_to_string(&file).map_err(QaCheckError::IoError)?;
+            let text = std::fs::read_to_string(&tgt_path).map_err(QaCheckError::IoError)?;
+            report.extend(check_glossary_compliance(
+                &tgt_path,
+                &source,
+                &text,
+                lang.clone(),
+                &entries,
+            ));
+        }
+        Ok(report)
+    }
+
+    /// Exports the project into a bundle at _path_ that [`import_bundle`] restores on another
+    /// machine: the config with paths relative to the root, the prompts, the state (e.g. the
+    /// chunk boundaries) and, if enabled in _options_, the source and the translated files.
+    /// Returns the number of files in the bundle.
+    pub fn export_bundle(
+        &self,
+        path: &Path,
+        options: &BundleOptions,
+    ) -> Result<usize, BundleError> {
+        let root = self.get_root_path();
+        let mut bundle = Bundle::new();
+
+        let mut conf = self.config.clone();
+        conf.rebase(&root, Path::new(""));
+        let serialized =
+            serde_json::to_string(&conf).map_err(|e| BundleError::IoError(e.into()))?;
+        bundle.add(Path::new("trans_conf.json"), serialized.as_bytes());
+
+        let prompt_files = self
+            .config
+            .get_prompt_file()
+            .into_iter()
+            .chain(
+                self.config
+                    .get_lang_dirs_as_ref()
+                    .iter()
+                    .filter_map(|d| d.get_prompt_file()),
+            )
+            .chain(std::iter::once(PathBuf::from(PROJECT_PROMPT_FILE)));
+        let canonical_root = std::fs::canonicalize(&root).map_err(BundleError::IoError)?;
+        for file in prompt_files {
+            // a prompt outside of the project (through `..` or a symlink) isn't a part of it
+            let Ok(file) = confine_path(&root, &file) else {
+                continue;
+            };
+            if file.is_file() {
+                bundle
+                    .add_file(&canonical_root, &file)
+                    .map_err(BundleError::IoError)?;
+            }
+        }
+
+        let state_dir = get_state_dir(&root);
+        if state_dir.is_dir() {
+            bundle
+                .add_dir(&root, &state_dir)
+                .map_err(BundleError::IoError)?;
+        }
+
+        if options.include_content {
+            let dirs = self
+                .config
+                .get_src_trees()
+                .into_iter()
+                .map(|(d, _)| d.get_path())
+                .chain(
+                    self.config
+                        .get_lang_dirs_as_ref()
+                        .iter()
+                        .map(|d| d.get_dir_as_ref().get_path()),
+                )
+                .collect::<Vec<_>>();
+            for dir in dirs.into_iter().filter(|d| d.is_dir()) {
+                bundle.add_dir(&root, &dir).map_err(BundleError::IoError)?;
+            }
+        }
+
+        bundle.write(path).map_err(BundleError::IoError)?;
+        Ok(bundle.entries.len())
+    }
+
+    /// Sets how the translated files are named. The translations already made keep their old
+    /// names until the next sync removes them as orphans.
+    pub fn set_naming_rule(&mut self, naming: NamingRule) -> Result<(), WriteConfigError> {
+        let _lock = self.lock_config()?;
+        self.config.set_naming(naming);
+        write_conf(self.get_config_file_path(), &self.config)
+    }
+
+    /// Checks the setup of the project and the credentials of the provider, so a broken setup is
+    /// reported before a long run. Every problem is reported in the result instead of stopping at
+    /// the first one.
+    pub fn doctor(&self) -> DoctorReport {
+        let mut report = DoctorReport::new();
+
+        match check_credentials(Provider::default()) {
+            Ok(r) => report.push(
+                "credentials",
+                CheckStatus::Ok,
+                format!(
+                    "{} answered in {} ms",
+                    r.provider.get_name(),
+                    r.latency.as_millis()
+                ),
+            ),
+            Err(e) => report.push(
+                "credentials",
+                if e.category().is_retryable() {
+                    CheckStatus::Warning
+                } else {
+                    CheckStatus::Failed
+                },
+                e.to_string(),
+            ),
+        }
+
+        match self.config.get_src_dir_path() {
+            None => report.push(
+                "source_dir",
+                CheckStatus::Failed,
+                "no source directory is set".to_string(),
+            ),
+            Some(p) if !p.is_dir() => report.push(
+                "source_dir",
+                CheckStatus::Failed,
+                format!("the source directory {} doesn't exist", p.display()),
+            ),
+            Some(p) => report.push("source_dir", CheckStatus::Ok, format!("{}", p.display())),
+        }
+
+        let tgt_langs = self.get_tgt_langs();
+        if tgt_langs.is_empty() {
+            report.push(
+                "target_langs",
+                CheckStatus::Failed,
+                "no target language is added".to_string(),
+            );
+        }
+        for lang in tgt_langs {
+            match self.config.get_tgt_dir_path_by_lang(&lang) {
+                Some(p) if p.is_dir() => {
+                    report.push("target_langs", CheckStatus::Ok, lang.to_string())
+                }
+                _ => report.push(
+                    "target_langs",
+                    CheckStatus::Warning,
+                    format!("the directory of {} is missing, sync the files", lang),
+                ),
+            }
+        }
+
+        match self.get_translatable_files() {
+            Ok(files) if files.is_empty() => report.push(
+                "translatable_files",
+                CheckStatus::Warning,
+                "no file is marked as translatable".to_string(),
+            ),
+            Ok(files) => report.push(
+                "translatable_files",
+                CheckStatus::Ok,
+                format!("{} files", files.len()),
+            ),
+            // reported by the source directory check
+            Err(_) => {}
+        }
+
+        let conflicts = self.config.get_glossary_as_ref().validate();
+        if conflicts.is_empty() {
+            report.push("glossary", CheckStatus::Ok, String::new());
+        }
+        for conflict in conflicts {
+            report.push(
+                "glossary",
+                CheckStatus::Warning,
+                format!(
+                    "'{}' is translated as both '{}' and '{}'",
+                    conflict.first.get_term(),
+                    conflict.first.get_translation(),
+                    conflict.second.get_translation()
+                ),
+            );
+        }
+
+        report
+    }
+
+    /// Watches the source directory until a shutdown is requested: the untranslatable files are
+    /// synced when they change and the changed translatable files are queued to be translated
+    /// into every target language, one file between two batches of changes. _on_event_ is
+    /// called with what was done, a failed sync or translation doesn't stop the watch.
+    pub fn watch<F>(&mut self, options: &WatchOptions, mut on_event: F) -> Result<(), WatchError>
+    where
+        F: FnMut(&WatchEvent),
+    {
+        self.get_src_lang().ok_or(WatchError::NoSourceLang)?;
+        let src_paths: Vec<PathBuf> = self
+            .config
+            .get_src_trees()
+            .into_iter()
+            .map(|(dir, _)| dir.get_path())
+            .collect();
+        let watcher = SourceWatcher::new(&src_paths).map_err(WatchError::WatcherError)?;
+        let mut queue: VecDeque<PathBuf> = VecDeque::new();
+        while !self.is_cancelled() {
+            let changed = watcher.next_batch(options.debounce, queue.is_empty());
+            let translatable = self.get_translatable_files().unwrap_or_default();
+            let mut needs_sync = false;
+            for path in changed {
+                if !translatable.contains(&path) {
+                    needs_sync = true;
+                } else if path.is_file() && !queue.contains(&path) {
+                    on_event(&WatchEvent::Changed(path.clone()));
+                    if options.translate {
+                        queue.push_back(path);
+                    }
+                }
+            }
+            if needs_sync {
+                on_event(&match self.sync_files(options.force) {
+                    Ok(report) => WatchEvent::Synced(report),
+                    Err(e) => WatchEvent::Failed {
+                        file: None,
+                        error: e.to_string(),
+                    },
+                });
+            }
+            let Some(file) = queue.pop_front() else {
+                continue;
+            };
+            for lang in self.get_tgt_langs() {
+                on_event(
+                    &match self.translate_file(file.clone(), lang.clone(), options.force) {
+                        Ok(()) => WatchEvent::Translated {
+                            file: file.clone(),
+                            lang,
+                        },
+                        Err(e) => WatchEvent::Failed {
+                            file: Some(file.clone()),
+                            error: e.to_string(),
+                        },
+                    },
+                );
+            }
+        }
+        Ok(())
+    }
+
+    /// Updates source directory structure (if for example it has been changed since the initialization of the project)
+    pub fn update_project_structure(&mut self) -> Result<(), UpdateSourceDirConfig> {
+        self.config.update_source_dir_config()
+    }
+}
+
+/// Returns the error of the translator as the error of the translation of a file, a shutdown or
+/// a cancellation is `Interrupted` and a request or a file over its time limit is `Timeout`
+fn translation_error(e: std::io::Error) -> TranslateFileError {
+    match e.kind() {
+        std::io::ErrorKind::Interrupted => TranslateFileError::Interrupted,
+        std::io::ErrorKind::TimedOut => TranslateFileError::Timeout(e),
+        _ => TranslateFileError::IoError(e),
+    }
+}
+
+/// Helper function to translate a file to a _lang_ language.
+fn translate_file_helper(
+    path: &PathBuf,
+    conf: &ProjectConfig,
+    lang: &Language,
+    ctx: &TranslationContext,
+) -> Result<PathBuf, TranslateFileError> {
+    if !path.exists() || !path.is_file() {
+        return Err(TranslateFileError::FileNotExist);
+    }
+
+    let new_path = conf
+        .get_tgt_file_path(path, lang)
+        .ok_or(TranslateFileError::FileNotExist)?;
+    crate::translator::translate_file_to_file(path, &new_path, lang, ctx)
+        .map_err(translation_error)?;
+    apply_watermark_if_enabled(&new_path, conf, lang)?;
+    Ok(new_path)
+}
+
+/// Returns the path of the source file relative to the target directories with `/` separators,
+/// as it's written in the exchange formats
+fn get_relative_name(conf: &ProjectConfig, file: &Path) -> String {
+    conf.get_src_relative_path(file)
+        .unwrap_or(file.to_path_buf())
+        .to_string_lossy()
+        .replace('\\', "/")
+}
+
+/// Returns the source file of the name written in the exchange formats, the name can't leave
+/// the source directory it belongs to
+fn resolve_relative_name(conf: &ProjectConfig, name: &str) -> Result<PathBuf, ExchangeError> {
+    let (dir, rest) = conf
+        .split_src_relative_path(Path::new(name))
+        .ok_or(ExchangeError::NoSourceLang)?;
+    confine_path(&dir, &rest).map_err(ExchangeError::PathOutsideProject)
+}
+
+fn write_file_creating_dirs(path: &Path, contents: &str) -> std::io::Result<()> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    helper::write_atomic(path, contents)
+}
+
+/// Marks the translated file as machine translated if it's enabled in the config
+fn apply_watermark_if_enabled(
+    path: &Path,
+    conf: &ProjectConfig,
+    lang: &Language,
+) -> Result<(), TranslateFileError> {
+    if let (Some(mode), Some(src_dir)) = (conf.get_watermark(), conf.get_src_dir_as_ref()) {
+        let meta = TranslationMetadata::new(&conf.get_model(), src_dir.get_lang(), lang.clone());
+        apply_watermark(path, mode, &meta).map_err(TranslateFileError::IoError)?;
+    }
+    Ok(())
+}
+
+#[derive(Clone, Copy)]
+/// How [`copy_untranslatable_files`] runs
+pub struct CopyOptions<'a> {
+    /// number of files copied in parallel, at least one
+    pub workers: usize,
+    /// the project root and the trash entry the replaced files are moved into, `None` to
+    /// delete them
+    pub staging: Option<(&'a Path, &'a Path)>,
+    /// called with the target path of every file once it's copied or skipped
+    pub on_file: Option<&'a (dyn Fn(&Path) + Sync)>,
+    /// the files not copied yet are left out once it's cancelled
+    pub cancel: Option<&'a CancellationToken>,
+    /// the file system the files are copied on, the disk by default
+    pub fs: &'a dyn FileSystem,
+}
+
+impl Default for CopyOptions<'_> {
+    fn default() -> Self {
+        Self {
+            workers: 0,
+            staging: None,
+            on_file: None,
+            cancel: None,
+            fs: &RealFs,
+        }
+    }
+}
+
+/// Copies the untranslatable files of the _from_name_ directory into the _to_name_ one as the
+/// asset mode, the metadata and the symlink settings of the project say, returns the paths of
+/// the copies
+pub fn copy_untranslatable_files(
+    root_path: &Path,
+    from_name: &str,
+    to_name: &str,
+    from_structure: &Directory,
+    conf: &ProjectConfig,
+    options: CopyOptions,
+) -> Result<SyncReport, CopyFileDirError> {
+    let fs = options.fs;
+    let staging = options.staging;
+    let mode = conf.get_asset_mode();
+    let preserve_metadata = conf.get_preserve_metadata();
+    let links = conf.get_symlink_policy();
+    let from_dir = root_path.join(from_name);
+    let to_dir = root_path.join(to_name);
+    // the directories are created first, the files are then copied in parallel
+    let mut jobs = Vec::new();
+    let mut dirs = Vec::new();
+    plan_untranslatable_files(fs, &from_dir, &to_dir, from_structure, &mut jobs, &mut dirs)?;
+    let results = helper::run_parallel(&jobs, options.workers.max(1), |(source, target)| {
+        if shutdown::is_cancelled(options.cancel) {
+            return Err(interrupted_error());
+        }
+        let is_link = fs.symlink_metadata(source).is_ok_and(|m| m.is_symlink);
+        let res = if links == SymlinkPolicy::CopyLink && is_link {
+            copy_link(fs, source, target, staging)
+        } else {
+            sync_asset(fs, source, target, mode, preserve_metadata, staging)
+        };
+        if let Some(on_file) = options.on_file {
+            on_file(target);
+        }
+        res
+    });
+    let mut report = SyncReport::default();
+    for ((_, new_path), res) in jobs.into_iter().zip(results) {
+        match res {
+            Ok(false) => report.skipped.push(new_path),
+            Ok(true) => {
+                metrics::record_copy();
+                report.copied.push(new_path);
+            }
+            // a cancelled copy isn't an error, the file is copied by the next sync
+            Err(e) if e.kind() == std::io::ErrorKind::Interrupted => {}
+            Err(e) => report.errors.push((new_path, e.to_string())),
+        }
+    }
+    // the files written into a directory change its times, the deepest ones go first
+    for (source, target) in dirs.iter().rev().filter(|_| preserve_metadata) {
+        if let Err(e) = mirror_metadata(fs, source, target) {
+            report.errors.push((target.clone(), e.to_string()));
+        }
+    }
+    Ok(report)
+}
+
+/// Creates the directories of the target and collects the (source, target) pairs of the
+/// untranslatable files into _jobs_ and the ones of the subdirectories into _dirs_
+fn plan_untranslatable_files(
+    fs: &dyn FileSystem,
+    from_dir: &Path,
+    to_dir: &Path,
+    dir: &Directory,
+    jobs: &mut Vec<(PathBuf, PathBuf)>,
+    dirs: &mut Vec<(PathBuf, PathBuf)>,
+) -> Result<(), CopyFileDirError> {
+    for file in dir.get_files_as_ref() {
+        if file.is_translatable() {
+            continue;
+        }
+        let full_path = file.get_path();
+        let relative_path = full_path
+            .strip_prefix(from_dir)
+            .map_err(CopyFileDirError::StripPathError)?
+            .to_path_buf();
+
+        let new_path = to_dir.join(relative_path);
+        jobs.push((full_path, new_path));
+    }
+    for sub_dir in dir.get_dirs_as_ref() {
+        let full_path = sub_dir.get_path();
+        let relative_path = full_path
+            .strip_prefix(from_dir)
+            .map_err(CopyFileDirError::StripPathError)?
+            .to_path_buf();
+
+        let new_path = to_dir.join(relative_path);
+        // the copies must not be written through a link left by another symlink policy
+        if fs.symlink_metadata(&new_path).is_ok_and(|m| m.is_symlink) {
+            fs.remove_file(&new_path)
+                .map_err(CopyFileDirError::IoError)?;
+        }
+        if !fs.exists(&new_path) {
+            fs.create_dir_all(&new_path)
+                .map_err(CopyFileDirError::IoError)?;
+        }
+        dirs.push((full_path, new_path));
+        plan_untranslatable_files(fs, from_dir, to_dir, sub_dir, jobs, dirs)?;
+    }
+    Ok(())
+}
+
+/// Puts the untranslatable _source_ file at _target_ as the _mode_ says, a target of another
+/// mode is replaced. Returns false if the target was already up to date.
+fn sync_asset(
+    fs: &dyn FileSystem,
+    source: &Path,
+    target: &Path,
+    mode: AssetMode,
+    preserve_metadata: bool,
+    staging: Option<(&Path, &Path)>,
+) -> std::io::Result<bool> {
+    let link = fs.symlink_metadata(target).ok();
+    let is_symlink = link.as_ref().is_some_and(|m| m.is_symlink);
+    match mode {
+        AssetMode::Copy => {
+            // writing through a link would overwrite the source itself
+            let linked = is_symlink || fs.is_same_file(source, target);
+            if !linked && is_copy_up_to_date(fs, source, target) {
+                if preserve_metadata {
+                    fs.copy_permissions(source, target)?;
+                }
+                return Ok(false);
+            }
+            if link.is_some() {
+                discard(fs, target, staging)?;
+            }
+            copy_with_mtime(fs, source, target)?;
+            if preserve_metadata {
+                mirror_metadata(fs, source, target)?;
+            }
+        }
+        AssetMode::Symlink => {
+            let link_target = get_relative_link(target, source);
+            if is_symlink && fs.read_link(target).ok() == Some(link_target.clone()) {
+                return Ok(false);
+            }
+            if link.is_some() {
+                discard(fs, target, staging)?;
+            }
+            fs.symlink(&link_target, target)?;
+        }
+        AssetMode::Hardlink => {
+            if !is_symlink && fs.is_same_file(source, target) {
+                return Ok(false);
+            }
+            if link.is_some() {
+                discard(fs, target, staging)?;
+            }
+            fs.hard_link(source, target)?;
+        }
+    }
+    Ok(true)
+}
+
+/// Makes _target_ a symbolic link pointing to the same path as the _source_ one, returns false if
+/// it already was
+fn copy_link(
+    fs: &dyn FileSystem,
+    source: &Path,
+    target: &Path,
+    staging: Option<(&Path, &Path)>,
+) -> std::io::Result<bool> {
+    let link_target = fs.read_link(source)?;
+    if fs.read_link(target).ok() == Some(link_target.clone()) {
+        return Ok(false);
+    }
+    if fs.exists(target) {
+        discard(fs, target, staging)?;
+    }
+    fs.symlink(&link_target, target)?;
+    Ok(true)
+}
+
+/// Returns the path of _source_ relative to the directory of the _link_, so the links stay
+/// valid when the project is moved
+fn get_relative_link(link: &Path, source: &Path) -> PathBuf {
+    let link_dir: Vec<_> = link.parent().unwrap_or(link).components().collect();
+    let source_parts: Vec<_> = source.components().collect();
+    let common = link_dir
+        .iter()
+        .zip(&source_parts)
+        .take_while(|(a, b)| a == b)
+        .count();
+    let mut res = PathBuf::new();
+    for _ in common..link_dir.len() {
+        res.push("..");
+    }
+    for part in &source_parts[common..] {
+        res.push(part);
+    }
+    res
+}
+
+/// Returns true if the copy at _target_ is up to date with the _source_ file. A copy with the
+/// size and the modification time of the source is, without reading them; otherwise the
+/// contents are compared and the time of an identical copy is aligned for the next sync.
+fn is_copy_up_to_date(fs: &dyn FileSystem, source: &Path, target: &Path) -> bool {
+    let (Ok(src_meta), Ok(tgt_meta)) = (fs.metadata(source), fs.metadata(target)) else {
+        return false;
+    };
+    if src_meta.len != tgt_meta.len {
+        return false;
+    }
+    if src_meta.modified.is_some() && src_meta.modified == tgt_meta.modified {
+        return true;
+    }
+    let same = matches!(
+        (fs.read(source), fs.read(target)),
+        (Ok(a), Ok(b)) if a == b
+    );
+    if let Some(time) = src_meta.modified.filter(|_| same) {
+        // an older copy stays up to date anyway, the time only spares reading it next time
+        let _ = fs.set_times(target, None, time);
+    }
+    same
+}
+
+/// Copies the file and gives the copy the modification time of the source, so the next sync
+/// recognizes it without reading it
+fn copy_with_mtime(fs: &dyn FileSystem, source: &Path, target: &Path) -> std::io::Result<u64> {
+    let size = fs.copy(source, target)?;
+    if let Some(modified) = fs.metadata(source)?.modified {
+        fs.set_times(target, None, modified)?;
+    }
+    Ok(size)
+}
+
+/// Gives _target_ the permissions and the access and modification times of _source_
+fn mirror_metadata(fs: &dyn FileSystem, source: &Path, target: &Path) -> std::io::Result<()> {
+    let meta = fs.metadata(source)?;
+    if let Some(modified) = meta.modified {
+        fs.set_times(target, meta.accessed, modified)?;
+    }
+    fs.copy_permissions(source, target)
+}
+
+/// Verifies and removes all the files and directories in the target directory that are not in the source directory.
 ///
 /// - `from_dir_path`: The actual disk path of the current source directory being considered
 ///   (e.g., initially /path/to/project/src_en, then /path/to/project/src_en/subdir1, etc.).
@@ -395,17 +3399,181 @@ fn copy_untranslatable_files_rec(
 ///   (e.g., initially /path/to/project/target_fr, then /path/to/project/target_fr/subdir1, etc.).
 /// - `source_dir_model`: The DirectoryModel representing the structure within `from_dir_path`.
 ///   Names within this model are relative to the current `from_dir_path`.
+/// - `conf`: the config of the project, its naming rule and its symlink policy are applied.
+/// - `lang`: the language the translated files in `to_dir_path` are named for, a translation
+///   is only kept under its mapped name.
+/// - `exclude`: the glob patterns of the excluded and the protected paths, they are left
+///   untouched.
+/// - `trash`: the project root and the trash entry the removed paths are moved into, `None` to
+///   delete them.
+///
+/// Returns the paths of the removed files and directories.
 pub fn remove_files_not_in_source_dir(
     from_dir_path: &Path, // Path to the corresponding directory in the source structure
     to_dir_path: &Path,   // Path to the target directory to clean up
     source_dir_model: &Directory,
+    conf: &ProjectConfig,
+    lang: &Language,
+    exclude: &[String],
+    trash: Option<(&Path, &Path)>,
+) -> std::io::Result<Vec<PathBuf>> {
+    RemoveContext::new(&RealFs, to_dir_path, conf, lang, exclude)
+        .removing(trash)
+        .run(from_dir_path, source_dir_model)
+}
+
+/// Returns the paths [`remove_files_not_in_source_dir`] would remove, nothing is removed
+pub fn find_files_not_in_source_dir(
+    from_dir_path: &Path,
+    to_dir_path: &Path,
+    source_dir_model: &Directory,
+    conf: &ProjectConfig,
+    lang: &Language,
+    exclude: &[String],
+) -> std::io::Result<Vec<PathBuf>> {
+    find_files_not_in_source_dir_in(
+        &RealFs,
+        from_dir_path,
+        to_dir_path,
+        source_dir_model,
+        conf,
+        lang,
+        exclude,
+    )
+}
+
+/// Same as [`find_files_not_in_source_dir`] on the given file system
+pub fn find_files_not_in_source_dir_in(
+    fs: &dyn FileSystem,
+    from_dir_path: &Path,
+    to_dir_path: &Path,
+    source_dir_model: &Directory,
+    conf: &ProjectConfig,
+    lang: &Language,
+    exclude: &[String],
+) -> std::io::Result<Vec<PathBuf>> {
+    RemoveContext::new(fs, to_dir_path, conf, lang, exclude).run(from_dir_path, source_dir_model)
+}
+
+/// What stays the same in the whole walk of [`remove_files_not_in_source_dir`]
+struct RemoveContext<'a> {
+    fs: &'a dyn FileSystem,
+    to_root: &'a Path,
+    naming: &'a NamingRule,
+    lang: &'a Language,
+    exclude: &'a [String],
+    links: SymlinkPolicy,
+    trash: Option<(&'a Path, &'a Path)>,
+    /// if true, the paths are only collected
+    dry_run: bool,
+}
+
+impl<'a> RemoveContext<'a> {
+    /// The walk of the target directory _to_root_, a dry run unless it's
+    /// [`RemoveContext::removing`]
+    fn new(
+        fs: &'a dyn FileSystem,
+        to_root: &'a Path,
+        conf: &'a ProjectConfig,
+        lang: &'a Language,
+        exclude: &'a [String],
+    ) -> Self {
+        Self {
+            fs,
+            to_root,
+            naming: conf.get_naming_as_ref(),
+            lang,
+            exclude,
+            links: conf.get_symlink_policy(),
+            trash: None,
+            dry_run: true,
+        }
+    }
+    /// Removes the paths, they're moved into the _trash_ entry (project root, entry) if set
+    fn removing(self, trash: Option<(&'a Path, &'a Path)>) -> Self {
+        Self {
+            trash,
+            dry_run: false,
+            ..self
+        }
+    }
+    /// Returns the paths of the target directory that aren't in the _source_dir_model_ of
+    /// _from_dir_path_, they're removed unless it's a dry run
+    fn run(
+        &self,
+        from_dir_path: &Path,
+        source_dir_model: &Directory,
+    ) -> std::io::Result<Vec<PathBuf>> {
+        remove_untracked_rec(self, from_dir_path, self.to_root, source_dir_model)
+    }
+    fn remove(&self, path: &Path) -> std::io::Result<()> {
+        if !self.dry_run {
+            discard(self.fs, path, self.trash)?;
+            metrics::record_deletion();
+        }
+        Ok(())
+    }
+}
+
+/// Removes the file, the link or the directory at _path_, it's moved into the _trash_ entry
+/// (project root, entry) instead if set
+fn discard(fs: &dyn FileSystem, path: &Path, trash: Option<(&Path, &Path)>) -> std::io::Result<()> {
+    match trash {
+        Some((root, entry)) => trash::move_into(fs, root, entry, path).map(|_| ()),
+        None if fs.symlink_metadata(path)?.is_dir => fs.remove_dir_all(path),
+        None => fs.remove_file(path),
+    }
+}
+
+fn ignore_not_found(e: std::io::Error) -> std::io::Result<()> {
+    match e.kind() {
+        std::io::ErrorKind::NotFound => Ok(()),
+        _ => Err(e),
+    }
+}
+
+/// Undoes a failed sync: removes the _copied_ files and puts the removed and the replaced ones
+/// back from the _staging_ trash entry. The _created_ mirrors of the source directories are
+/// removed, the other directories the sync created stay.
+fn rollback_sync(
+    fs: &dyn FileSystem,
+    root: &Path,
+    staging: &Path,
+    copied: &[PathBuf],
+    created: &[PathBuf],
 ) -> std::io::Result<()> {
-    // Collect names from the source model for efficient lookup.
+    for path in copied.iter().chain(created) {
+        discard(fs, path, None).or_else(ignore_not_found)?;
+    }
+    let Some(name) = staging.file_name() else {
+        return Ok(());
+    };
+    if fs.exists(staging) {
+        trash::restore(fs, root, &name.to_string_lossy())?;
+    }
+    trash::prune(fs, root);
+    Ok(())
+}
+
+fn remove_untracked_rec(
+    ctx: &RemoveContext,
+    from_dir_path: &Path,
+    to_dir_path: &Path,
+    source_dir_model: &Directory,
+) -> std::io::Result<Vec<PathBuf>> {
+    let mut removed = Vec::new();
+    // Collect the names the files of the source model have in the target directory.
     // These names are expected to be simple file/directory names, not paths.
     let model_file_names: HashSet<String> = source_dir_model
         .get_files_as_ref()
         .iter()
-        .map(|f| f.get_name())
+        .map(|f| {
+            if f.is_translatable() {
+                ctx.naming.apply(&f.get_name(), ctx.lang)
+            } else {
+                f.get_name()
+            }
+        })
         .collect();
 
     let model_dir_names: HashSet<String> = source_dir_model
@@ -415,24 +3583,27 @@ pub fn remove_files_not_in_source_dir(
         .collect();
 
     // Iterate over entries in the target directory on disk.
-    for entry_result in std::fs::read_dir(to_dir_path)? {
-        let entry = entry_result?;
-        let entry_path = entry.path();
-        let entry_name_os = entry.file_name(); // This is an OsString
+    for entry_path in ctx.fs.read_dir(to_dir_path)? {
+        let entry_name_os = entry_path.file_name().unwrap_or_default().to_os_string();
 
         let entry_name_cow = entry_name_os.to_string_lossy();
         let entry_name_str = entry_name_cow.as_ref();
 
-        let symlink_meta = std::fs::symlink_metadata(&entry_path)?;
+        let symlink_meta = ctx.fs.symlink_metadata(&entry_path)?;
+        let relative = entry_path.strip_prefix(ctx.to_root).unwrap_or(&entry_path);
+        if is_excluded(ctx.exclude, relative, symlink_meta.is_dir) {
+            continue;
+        }
 
-        if symlink_meta.is_dir() {
+        if symlink_meta.is_dir {
             // Is an actual directory (not a symlink to one)
             if !model_dir_names.contains(entry_name_str) {
                 // Directory exists in target but not in source model: remove it.
-                if !symlink_meta.is_symlink() {
-                    std::fs::remove_dir_all(&entry_path)?;
+                if !symlink_meta.is_symlink {
+                    ctx.remove(&entry_path)?;
+                    removed.push(entry_path);
                 }
-            } else if !symlink_meta.is_symlink() {
+            } else if !symlink_meta.is_symlink {
                 // Directory exists in both target and source model: recurse.
                 // Find the corresponding Directory for this subdirectory.
                 if let Some(sub_dir_model) = source_dir_model
@@ -441,28 +3612,131 @@ pub fn remove_files_not_in_source_dir(
                     .find(|dm| dm.get_dir_name() == entry_name_str)
                 {
                     let next_from_dir_path = from_dir_path.join(&entry_name_os);
-                    remove_files_not_in_source_dir(
+                    removed.extend(remove_untracked_rec(
+                        ctx,
                         &next_from_dir_path,
                         &entry_path,
                         sub_dir_model,
-                    )?;
+                    )?);
                 } else {
                     // This case should ideally not be reached if model_dir_names.contains was true
                     // and get_dir_name() is consistent. Could indicate an issue or duplicate names.
                     return Err(std::io::Error::new(
                         std::io::ErrorKind::NotFound,
-                        format!("Logic error: DirectoryModel for '{}' not found despite being in name set.", entry_name_str)
+                        format!(
+                            "Logic error: DirectoryModel for '{}' not found despite being in name set.",
+                            entry_name_str
+                        ),
                     ));
                 }
             }
-        } else if symlink_meta.is_file() {
+        } else if symlink_meta.is_file {
             // Is an actual file (not a symlink to one)
-            if !model_file_names.contains(entry_name_str) {
+            // Sidecar metadata of a translated file isn't in the source but belongs to it.
+            let is_sidecar_of_source_file = entry_name_str
+                .strip_suffix(".meta.json")
+                .is_some_and(|n| model_file_names.contains(n));
+            if !model_file_names.contains(entry_name_str) && !is_sidecar_of_source_file {
                 // File exists in target but not in source model: remove it.
-                std::fs::remove_file(&entry_path)?;
+                ctx.remove(&entry_path)?;
+                removed.push(entry_path);
+            }
+        } else if symlink_meta.is_symlink
+            && !model_file_names.contains(entry_name_str)
+            && !model_dir_names.contains(entry_name_str)
+        {
+            // Only the link itself is removed, never what it points to. When the links are
+            // skipped, only the ones to the source made by the sync are, a dangling one too.
+            let links_to_source = match ctx.fs.canonicalize(&entry_path) {
+                Ok(resolved) => ctx
+                    .fs
+                    .canonicalize(from_dir_path)
+                    .is_ok_and(|from_dir| resolved.starts_with(from_dir)),
+                Err(_) => true,
+            };
+            if links_to_source || ctx.links != SymlinkPolicy::Skip {
+                ctx.remove(&entry_path)?;
+                removed.push(entry_path);
             }
         }
     }
 
-    Ok(())
+    Ok(removed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{fs::MemoryFs, project_config::build_tree_in};
+
+    /// A file system with the file `/p/src/a.txt` and the empty target directory `/p/fr`
+    fn memory_project() -> MemoryFs {
+        let fs = MemoryFs::new();
+        fs.create_dir_all(Path::new("/p/src")).unwrap();
+        fs.create_dir_all(Path::new("/p/fr")).unwrap();
+        fs.write(Path::new("/p/src/a.txt"), b"source").unwrap();
+        fs
+    }
+
+    #[test]
+    fn syncs_an_asset_in_every_mode() {
+        let (source, target) = (Path::new("/p/src/a.txt"), Path::new("/p/fr/a.txt"));
+        for mode in [AssetMode::Copy, AssetMode::Symlink, AssetMode::Hardlink] {
+            let fs = memory_project();
+            assert!(sync_asset(&fs, source, target, mode, false, None).unwrap());
+            assert!(!sync_asset(&fs, source, target, mode, false, None).unwrap());
+            assert_eq!(fs.read(target).unwrap(), b"source");
+        }
+    }
+
+    #[test]
+    fn replaces_an_asset_of_another_mode() {
+        let fs = memory_project();
+        let (source, target) = (Path::new("/p/src/a.txt"), Path::new("/p/fr/a.txt"));
+        sync_asset(&fs, source, target, AssetMode::Symlink, false, None).unwrap();
+        assert_eq!(fs.read_link(target).unwrap(), Path::new("../src/a.txt"));
+
+        sync_asset(&fs, source, target, AssetMode::Hardlink, false, None).unwrap();
+        assert!(fs.is_same_file(source, target));
+        fs.write(source, b"edited").unwrap();
+        assert_eq!(fs.read(target).unwrap(), b"edited");
+
+        // the copy must not be written through the hard link into the source
+        sync_asset(&fs, source, target, AssetMode::Copy, false, None).unwrap();
+        assert!(!fs.is_same_file(source, target));
+        fs.write(target, b"copy").unwrap();
+        assert_eq!(fs.read(source).unwrap(), b"edited");
+    }
+
+    #[test]
+    fn copies_a_link() {
+        let fs = memory_project();
+        fs.symlink(Path::new("a.txt"), Path::new("/p/src/b.txt"))
+            .unwrap();
+        let (source, target) = (Path::new("/p/src/b.txt"), Path::new("/p/fr/b.txt"));
+        assert!(copy_link(&fs, source, target, None).unwrap());
+        assert!(!copy_link(&fs, source, target, None).unwrap());
+        assert_eq!(fs.read_link(target).unwrap(), Path::new("a.txt"));
+    }
+
+    #[test]
+    fn removes_the_untracked_files_into_the_trash() {
+        let fs = memory_project();
+        fs.write(Path::new("/p/fr/a.txt"), b"source").unwrap();
+        fs.write(Path::new("/p/fr/old.txt"), b"old").unwrap();
+        let model = build_tree_in(&fs, Path::new("/p/src"), &[], SymlinkPolicy::Skip).unwrap();
+        let conf = ProjectConfig::new("p");
+        let (root, entry) = (Path::new("/p"), Path::new("/p/.trash/1"));
+
+        let removed = RemoveContext::new(&fs, Path::new("/p/fr"), &conf, &Language::French, &[])
+            .removing(Some((root, entry)))
+            .run(Path::new("/p/src"), &model)
+            .unwrap();
+        assert_eq!(removed, vec![PathBuf::from("/p/fr/old.txt")]);
+        assert!(fs.exists(Path::new("/p/fr/a.txt")));
+        assert_eq!(
+            fs.read(Path::new("/p/.trash/1/fr/old.txt")).unwrap(),
+            b"old"
+        );
+    }
 }
@@ -1,16 +1,31 @@
 use crate::errors::project_config_errors::{LoadConfigError, WriteConfigError};
 use crate::errors::project_errors::{
-    AddTranslatableFileError, GetTranslatableFilesError, InitProjectError, UpdateSourceDirConfig,
+    AddTranslatableFileError, GetTranslatableFilesError, InitProjectError, SetReviewStateError,
+    UpdateSourceDirConfig,
 };
+use crate::estimate::{
+    cost::{get_model_pricing, ModelPricing},
+    BudgetThresholds,
+};
+use crate::exchange::Workflow;
+use crate::fs::{FileSystem, RealFs};
+use crate::glossary::Glossary;
+use crate::helper::{glob_match, write_atomic};
+use crate::naming::NamingRule;
+use crate::qa::readability::ReadabilityThresholds;
+use crate::translation_memory::RunIndexes;
+use crate::translator::budget::UsageMeter;
+use crate::translator::prompt::ExamplePair;
+use crate::translator::{TranslationContext, TranslatorOptions, DEFAULT_MODEL};
+use crate::watermark::WatermarkMode;
 use crate::Language;
 use queues::*;
 use serde;
 use std::collections::HashMap;
 use std::{
-    io::{Read, Write},
+    io::Read,
     path::{Path, PathBuf},
 };
-use thiserror::Error;
 
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 /// A struct representing a particular project's config, this config contains the root directory
@@ -22,6 +37,100 @@ pub struct ProjectConfig {
     lang_dirs: Vec<LangDir>,
     /// the master directory that the files are copied and translated from
     src_dir: Option<LangDir>,
+    /// other directories in the source language, each one is mirrored in the directory of its
+    /// name inside every target directory
+    #[serde(default)]
+    extra_src_dirs: Vec<Directory>,
+    /// how translated files are marked as machine translated, `None` if they aren't
+    #[serde(default)]
+    watermark: Option<WatermarkMode>,
+    /// options of the translator
+    #[serde(default)]
+    translator: TranslatorOptions,
+    /// model the files are translated with
+    #[serde(default = "default_model")]
+    model: String,
+    /// file with the prompt to translate with, relative to the project root, `None` to use the
+    /// prompt of the library config or the default one
+    #[serde(default)]
+    prompt_file: Option<PathBuf>,
+    /// approved translations of terms
+    #[serde(default)]
+    glossary: Glossary,
+    /// limits above which files are flagged when a run is estimated
+    #[serde(default)]
+    budget: BudgetThresholds,
+    /// rates of the models added to or overriding the built-in ones
+    #[serde(default)]
+    pricing: Vec<ModelPricing>,
+    /// how the translated files are named
+    #[serde(default)]
+    naming: NamingRule,
+    /// if true, the glossary and the translation memory of the shared store of the workspace are
+    /// used under the project's own ones
+    #[serde(default = "default_true")]
+    use_shared_store: bool,
+    /// if true, every sync and translation run writes a report of its changes
+    #[serde(default)]
+    change_report: bool,
+    /// what a translation run produces
+    #[serde(default)]
+    workflow: Workflow,
+    /// glob patterns of the paths, relative to the language directories, that are never
+    /// analyzed, copied into the target directories or removed from them, e.g.
+    /// `**/node_modules/**` or `*.log`
+    #[serde(default)]
+    exclude: Vec<String>,
+    /// how the untranslatable files are put into the target directories
+    #[serde(default)]
+    asset_mode: AssetMode,
+    /// if true, the copies of the untranslatable files and their directories get the
+    /// permissions and the access and modification times of the source
+    #[serde(default)]
+    preserve_metadata: bool,
+    /// what the analysis, the sync and the removal of the untracked files do with the symbolic
+    /// links
+    #[serde(default)]
+    symlinks: SymlinkPolicy,
+    /// if true, the removed target directories and untracked files are moved into the trash of
+    /// the project instead of being deleted
+    #[serde(default)]
+    use_trash: bool,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+/// How the sync puts the untranslatable files (images, videos...) into the target directories
+pub enum AssetMode {
+    /// every target directory gets its own copy
+    #[default]
+    Copy,
+    /// the targets are symbolic links to the source files
+    Symlink,
+    /// the targets are hard links to the source files, they must be on the same file system
+    Hardlink,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+/// What the project does with the symbolic links found in the language directories
+pub enum SymlinkPolicy {
+    /// the links are ignored: they aren't analyzed, copied or removed, except the ones made by
+    /// the sync in [`AssetMode::Symlink`]
+    #[default]
+    Skip,
+    /// the links are treated as the files and directories they point to, the targets get
+    /// copies of them, a link loop is cut where it closes
+    Follow,
+    /// the links are reproduced as they are in the target directories, pointing to the same
+    /// path
+    CopyLink,
+}
+
+fn default_model() -> String {
+    DEFAULT_MODEL.to_string()
+}
+
+fn default_true() -> bool {
+    true
 }
 
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
@@ -29,12 +138,35 @@ pub struct ProjectConfig {
 pub struct LangDir {
     dir: Directory,
     language: Language,
+    /// thresholds of the readability check, `None` if the check is disabled for this language
+    #[serde(default)]
+    readability: Option<ReadabilityThresholds>,
+    /// file with the prompt used when this language is the target, relative to the project root,
+    /// `None` to use the prompt of the project
+    #[serde(default)]
+    prompt_file: Option<PathBuf>,
+    /// example translations into this language included in the prompt
+    #[serde(default)]
+    examples: Vec<ExamplePair>,
+    /// glob patterns of the paths, relative to the directory, that only this language has and
+    /// the sync never removes, e.g. `fr-only-assets/`
+    #[serde(default)]
+    protected: Vec<String>,
+    /// language the files are translated into first and from it into this one, `None` to
+    /// translate them directly
+    #[serde(default)]
+    pivot: Option<Language>,
 }
 impl LangDir {
     pub(crate) fn new(dir: Directory, lang: Language) -> Self {
         Self {
             dir,
             language: lang,
+            readability: None,
+            prompt_file: None,
+            examples: Vec::new(),
+            protected: Vec::new(),
+            pivot: None,
         }
     }
     pub fn get_lang(&self) -> Language {
@@ -46,6 +178,21 @@ impl LangDir {
     pub(crate) fn set_dir(&mut self, dir: Directory) {
         self.dir = dir;
     }
+    pub fn get_readability_as_ref(&self) -> &Option<ReadabilityThresholds> {
+        &self.readability
+    }
+    pub fn get_prompt_file(&self) -> Option<PathBuf> {
+        self.prompt_file.clone()
+    }
+    pub fn get_examples_as_ref(&self) -> &Vec<ExamplePair> {
+        &self.examples
+    }
+    pub fn get_protected_as_ref(&self) -> &Vec<String> {
+        &self.protected
+    }
+    pub fn get_pivot(&self) -> Option<Language> {
+        self.pivot.clone()
+    }
 }
 
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
@@ -87,6 +234,24 @@ impl Directory {
     pub fn get_dirs_as_ref(&self) -> &Vec<Directory> {
         &self.dirs
     }
+    /// Moves every path of the directory from the _from_ prefix to the _to_ one
+    fn rebase(&mut self, from: &Path, to: &Path) {
+        self.path = rebase_path(&self.path, from, to);
+        for file in &mut self.files {
+            file.path = rebase_path(&file.path, from, to);
+        }
+        for dir in &mut self.dirs {
+            dir.rebase(from, to);
+        }
+    }
+}
+
+/// Replaces the _from_ prefix of the path by _to_, a path without the prefix is kept as is
+fn rebase_path(path: &Path, from: &Path, to: &Path) -> PathBuf {
+    match path.strip_prefix(from) {
+        Ok(rel) => to.join(rel),
+        Err(_) => path.to_path_buf(),
+    }
 }
 
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
@@ -98,6 +263,55 @@ pub struct File {
     path: PathBuf,
     /// if the file is translatable (false is not, true if it is)
     translatable: bool,
+    /// review state of the translation into every language that has one
+    #[serde(default)]
+    reviews: Vec<FileReview>,
+    /// how the file was last translated into every language it was translated into
+    #[serde(default)]
+    translations: Vec<FileMetadata>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+/// How a file was translated into a language
+pub struct FileMetadata {
+    pub lang: Language,
+    /// hash of the contents of the source file that was translated
+    pub source_hash: String,
+    /// date and time of the translation in RFC 3339 format
+    pub translated_at: String,
+    /// model that produced the translation
+    pub model: String,
+    /// hash of the prompt template the file was translated with
+    pub prompt_version: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+/// How far the human review of a translated file went
+pub enum ReviewState {
+    /// translated by the model, nobody has looked at it yet
+    MachineTranslated,
+    /// a reviewer went through the translation
+    Reviewed,
+    /// the translation is approved for publishing
+    Approved,
+}
+
+impl ReviewState {
+    pub fn get_name(&self) -> &'static str {
+        match self {
+            ReviewState::MachineTranslated => "machine-translated",
+            ReviewState::Reviewed => "reviewed",
+            ReviewState::Approved => "approved",
+        }
+    }
+}
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+/// The review state of the translation of a file into a language
+pub struct FileReview {
+    lang: Language,
+    state: ReviewState,
 }
 
 impl File {
@@ -110,14 +324,57 @@ impl File {
     pub fn is_translatable(&self) -> bool {
         self.translatable
     }
+    /// Returns the review state of the translation into _lang_, `None` if it has none
+    pub fn get_review_state(&self, lang: Language) -> Option<ReviewState> {
+        self.reviews
+            .iter()
+            .find(|r| r.lang == lang)
+            .map(|r| r.state)
+    }
+    pub fn get_metadata_as_ref(&self) -> &Vec<FileMetadata> {
+        &self.translations
+    }
+    /// Returns how the file was last translated into _lang_, `None` if it never was
+    pub fn get_metadata(&self, lang: Language) -> Option<&FileMetadata> {
+        self.translations.iter().find(|m| m.lang == lang)
+    }
+    /// Replaces the metadata of the translation into the language of _meta_
+    pub(crate) fn set_metadata(&mut self, meta: FileMetadata) {
+        self.translations.retain(|m| m.lang != meta.lang);
+        self.translations.push(meta);
+    }
+    /// Sets the review state of the translation into _lang_, `None` removes it
+    pub(crate) fn set_review_state(&mut self, lang: Language, state: Option<ReviewState>) {
+        self.reviews.retain(|r| r.lang != lang);
+        if let Some(state) = state {
+            self.reviews.push(FileReview { lang, state });
+        }
+    }
 }
 
 impl ProjectConfig {
-    fn new(proj_name: &str) -> Self {
+    pub(crate) fn new(proj_name: &str) -> Self {
         ProjectConfig {
             name: proj_name.to_string(),
             lang_dirs: Vec::new(),
             src_dir: None,
+            extra_src_dirs: Vec::new(),
+            watermark: None,
+            translator: TranslatorOptions::default(),
+            model: default_model(),
+            prompt_file: None,
+            glossary: Glossary::new(),
+            budget: BudgetThresholds::default(),
+            pricing: Vec::new(),
+            naming: NamingRule::default(),
+            use_shared_store: true,
+            change_report: false,
+            workflow: Workflow::Files,
+            exclude: Vec::new(),
+            asset_mode: AssetMode::Copy,
+            preserve_metadata: false,
+            symlinks: SymlinkPolicy::Skip,
+            use_trash: false,
         }
     }
     pub fn get_name(&self) -> String {
@@ -129,6 +386,186 @@ impl ProjectConfig {
     pub fn get_lang_dirs_as_ref(&self) -> &Vec<LangDir> {
         &self.lang_dirs
     }
+    /// Returns the language of the project with the BCP-47 _code_, a language with that exact
+    /// code (e.g. a custom `fr-CA`) comes before a built-in one matched by its primary subtag.
+    /// A built-in language that isn't in the project is returned too, see
+    /// [`Language::from_code`].
+    pub fn get_lang_by_code(&self, code: &str) -> Option<Language> {
+        let langs: Vec<Language> = self
+            .src_dir
+            .iter()
+            .chain(&self.lang_dirs)
+            .map(|d| d.get_lang())
+            .collect();
+        let normalized = code.replace('_', "-");
+        langs
+            .iter()
+            .find(|l| l.get_code().eq_ignore_ascii_case(&normalized))
+            .or_else(|| langs.iter().find(|l| l.has_code(code)))
+            .cloned()
+            .or_else(|| Language::from_code(code))
+    }
+    pub fn get_extra_src_dirs_as_ref(&self) -> &Vec<Directory> {
+        &self.extra_src_dirs
+    }
+    /// Returns every source directory with the path it's mirrored at inside the target
+    /// directories: empty for the main one, the name of the directory for the others
+    pub fn get_src_trees(&self) -> Vec<(&Directory, PathBuf)> {
+        let main = self.src_dir.iter().map(|d| (&d.dir, PathBuf::new()));
+        let extra = self
+            .extra_src_dirs
+            .iter()
+            .map(|d| (d, PathBuf::from(d.get_dir_name())));
+        main.chain(extra).collect()
+    }
+    /// Returns the path of the source file relative to the target directories, `None` if it's in
+    /// none of the source directories
+    pub fn get_src_relative_path(&self, src_file_path: &Path) -> Option<PathBuf> {
+        self.get_src_trees().into_iter().find_map(|(dir, mount)| {
+            let relative = src_file_path.strip_prefix(dir.get_path()).ok()?;
+            Some(mount.join(relative))
+        })
+    }
+    /// Splits a path relative to the target directories (see
+    /// [`ProjectConfig::get_src_relative_path`]) into the source directory it belongs to and the
+    /// path relative to it
+    pub fn split_src_relative_path(&self, relative: &Path) -> Option<(PathBuf, PathBuf)> {
+        let trees = self.get_src_trees();
+        // a file of an extra directory is resolved before the main directory is tried
+        let (main, extra) = trees.split_first()?;
+        extra
+            .iter()
+            .find_map(|(dir, mount)| {
+                let rest = relative.strip_prefix(mount).ok()?;
+                Some((dir.get_path(), rest.to_path_buf()))
+            })
+            .or_else(|| Some((main.0.get_path(), relative.to_path_buf())))
+    }
+    pub fn get_workflow(&self) -> Workflow {
+        self.workflow
+    }
+    pub(crate) fn set_workflow(&mut self, workflow: Workflow) {
+        self.workflow = workflow;
+    }
+    pub fn get_watermark(&self) -> Option<WatermarkMode> {
+        self.watermark
+    }
+    pub(crate) fn set_watermark(&mut self, mode: Option<WatermarkMode>) {
+        self.watermark = mode;
+    }
+    pub fn get_translator_options_as_ref(&self) -> &TranslatorOptions {
+        &self.translator
+    }
+    pub(crate) fn set_translator_options(&mut self, options: TranslatorOptions) {
+        self.translator = options;
+    }
+    pub fn get_model(&self) -> String {
+        self.model.clone()
+    }
+    pub(crate) fn set_model(&mut self, model: &str) {
+        self.model = model.to_string();
+    }
+    pub fn get_glossary_as_ref(&self) -> &Glossary {
+        &self.glossary
+    }
+    pub(crate) fn get_glossary_as_mut(&mut self) -> &mut Glossary {
+        &mut self.glossary
+    }
+    pub(crate) fn set_glossary(&mut self, glossary: Glossary) {
+        self.glossary = glossary;
+    }
+    pub fn get_prompt_file(&self) -> Option<PathBuf> {
+        self.prompt_file.clone()
+    }
+    pub fn get_budget_as_ref(&self) -> &BudgetThresholds {
+        &self.budget
+    }
+    pub(crate) fn set_budget(&mut self, budget: BudgetThresholds) {
+        self.budget = budget;
+    }
+    pub fn get_asset_mode(&self) -> AssetMode {
+        self.asset_mode
+    }
+    pub(crate) fn set_asset_mode(&mut self, mode: AssetMode) {
+        self.asset_mode = mode;
+    }
+    pub fn get_preserve_metadata(&self) -> bool {
+        self.preserve_metadata
+    }
+    pub(crate) fn set_preserve_metadata(&mut self, preserve: bool) {
+        self.preserve_metadata = preserve;
+    }
+    pub fn get_symlink_policy(&self) -> SymlinkPolicy {
+        self.symlinks
+    }
+    pub(crate) fn set_symlink_policy(&mut self, policy: SymlinkPolicy) {
+        self.symlinks = policy;
+    }
+    pub fn get_use_trash(&self) -> bool {
+        self.use_trash
+    }
+    pub(crate) fn set_use_trash(&mut self, enabled: bool) {
+        self.use_trash = enabled;
+    }
+    pub fn get_exclude_as_ref(&self) -> &Vec<String> {
+        &self.exclude
+    }
+    pub(crate) fn set_exclude(&mut self, patterns: Vec<String>) {
+        self.exclude = patterns;
+    }
+    pub fn get_pricing_as_ref(&self) -> &Vec<ModelPricing> {
+        &self.pricing
+    }
+    /// Sets the rates of a model, replacing the ones the project had for it
+    pub(crate) fn set_pricing(&mut self, pricing: ModelPricing) {
+        self.pricing.retain(|p| p.model != pricing.model);
+        self.pricing.push(pricing);
+    }
+    pub fn get_change_report(&self) -> bool {
+        self.change_report
+    }
+    pub(crate) fn set_change_report(&mut self, enabled: bool) {
+        self.change_report = enabled;
+    }
+    pub fn get_use_shared_store(&self) -> bool {
+        self.use_shared_store
+    }
+    pub(crate) fn set_use_shared_store(&mut self, use_shared_store: bool) {
+        self.use_shared_store = use_shared_store;
+    }
+    pub fn get_naming_as_ref(&self) -> &NamingRule {
+        &self.naming
+    }
+    pub(crate) fn set_naming(&mut self, naming: NamingRule) {
+        self.naming = naming;
+    }
+    pub(crate) fn set_prompt_file(&mut self, path: Option<PathBuf>) {
+        self.prompt_file = path;
+    }
+    /// Returns the context the translator needs to translate the files of the project with the
+    /// given prompt template
+    pub fn get_translation_context(&self, prompt: &str) -> TranslationContext {
+        TranslationContext {
+            model: self.get_model(),
+            options: self.translator.clone(),
+            prompt: prompt.to_string(),
+            source_language: self.src_dir.as_ref().map(|d| d.get_lang()),
+            glossary: self.glossary.clone(),
+            file: None,
+            project_root: None,
+            shared_memory: None,
+            examples: Vec::new(),
+            summary: None,
+            pricing: get_model_pricing(&self.get_model(), &self.pricing),
+            progress: None,
+            cancel: None,
+            deadline: None,
+            pivot: None,
+            pivot_prompt: None,
+            usage: UsageMeter::default(),
+            memory_indexes: RunIndexes::default(),
+        }
+    }
     pub fn get_src_dir_path(&self) -> Option<PathBuf> {
         self.src_dir
             .as_ref()
@@ -142,15 +579,110 @@ impl ProjectConfig {
         }
         None
     }
+    /// Returns the path of the translation into the _lang_ language of the given file of the
+    /// source directory, named by the naming rule of the project
+    pub fn get_tgt_file_path(&self, src_file_path: &Path, lang: &Language) -> Option<PathBuf> {
+        let tgt_dir_path = self.get_tgt_dir_path_by_lang(lang)?;
+        let relative_path = &self.get_src_relative_path(src_file_path)?;
+        let file_name = relative_path.file_name()?.to_string_lossy();
+        Some(
+            tgt_dir_path
+                .join(relative_path)
+                .with_file_name(self.naming.apply(&file_name, lang)),
+        )
+    }
+    /// Sets the paths of the target directory of _lang_ the sync never removes, returns false if
+    /// the language isn't in the project
+    pub(crate) fn set_protected_paths(&mut self, lang: Language, patterns: Vec<String>) -> bool {
+        match self.lang_dirs.iter_mut().find(|d| d.get_lang() == lang) {
+            Some(dir) => {
+                dir.protected = patterns;
+                true
+            }
+            None => false,
+        }
+    }
+    /// Sets the readability thresholds for the target language, returns false if there's no such
+    /// target language
+    pub(crate) fn set_readability_thresholds(
+        &mut self,
+        lang: Language,
+        thresholds: Option<ReadabilityThresholds>,
+    ) -> bool {
+        match self.lang_dirs.iter_mut().find(|d| d.get_lang() == lang) {
+            Some(dir) => {
+                dir.readability = thresholds;
+                true
+            }
+            None => false,
+        }
+    }
+    /// Sets the pivot language of the target language, returns false if there's no such target
+    /// language
+    pub(crate) fn set_pivot(&mut self, lang: Language, pivot: Option<Language>) -> bool {
+        match self.lang_dirs.iter_mut().find(|d| d.get_lang() == lang) {
+            Some(dir) => {
+                dir.pivot = pivot;
+                true
+            }
+            None => false,
+        }
+    }
+    /// Sets the example translations of the target language, returns false if there's no such
+    /// target language
+    pub(crate) fn set_examples(&mut self, lang: Language, examples: Vec<ExamplePair>) -> bool {
+        match self.lang_dirs.iter_mut().find(|d| d.get_lang() == lang) {
+            Some(dir) => {
+                dir.examples = examples;
+                true
+            }
+            None => false,
+        }
+    }
+    /// Sets the prompt override of the target language, returns false if there's no such target
+    /// language
+    pub(crate) fn set_lang_prompt_file(&mut self, lang: Language, path: Option<PathBuf>) -> bool {
+        match self.lang_dirs.iter_mut().find(|d| d.get_lang() == lang) {
+            Some(dir) => {
+                dir.prompt_file = path;
+                true
+            }
+            None => false,
+        }
+    }
+    /// Moves every path of the config from the _from_ prefix to the _to_ one, e.g. to make the
+    /// paths relative to the project root (with an empty _to_) before the config is moved to
+    /// another machine
+    pub(crate) fn rebase(&mut self, from: &Path, to: &Path) {
+        for lang_dir in self.src_dir.iter_mut().chain(self.lang_dirs.iter_mut()) {
+            lang_dir.dir.rebase(from, to);
+        }
+        for dir in &mut self.extra_src_dirs {
+            dir.rebase(from, to);
+        }
+    }
     pub(crate) fn set_src_dir(&mut self, dir_path: PathBuf, lang: Language) -> std::io::Result<()> {
-        let dir = build_tree(dir_path)?;
+        let dir = build_tree(dir_path, &self.exclude, self.symlinks)?;
         let lang_dir = LangDir::new(dir, lang);
 
         self.src_dir = Some(lang_dir);
         Ok(())
     }
+    /// Adds a directory in the source language to the ones the files are translated from
+    pub(crate) fn add_extra_src_dir(&mut self, dir_path: PathBuf) -> std::io::Result<()> {
+        let dir = build_tree(dir_path, &self.exclude, self.symlinks)?;
+        self.extra_src_dirs.push(dir);
+        Ok(())
+    }
+    /// Removes the extra source directory at _dir_path_, returns false if there's no such
+    /// directory
+    pub(crate) fn remove_extra_src_dir(&mut self, dir_path: &Path) -> bool {
+        let len = self.extra_src_dirs.len();
+        self.extra_src_dirs.retain(|d| d.path != dir_path);
+        self.extra_src_dirs.len() != len
+    }
     pub(crate) fn add_lang(&mut self, dir_path: PathBuf, lang: Language) -> std::io::Result<()> {
-        let dir = build_tree(dir_path)?;
+        let dir = build_tree(dir_path, &self.exclude, self.symlinks)?;
         let lang_dir = LangDir::new(dir, lang);
         self.lang_dirs.push(lang_dir);
         Ok(())
@@ -169,7 +701,7 @@ impl ProjectConfig {
     pub(crate) fn analyze_lang_dirs(&mut self) -> std::io::Result<()> {
         for dir in &mut self.lang_dirs {
             let path = dir.get_dir_as_ref().get_path();
-            let tree = build_tree(path)?;
+            let tree = build_tree(path, &self.exclude, self.symlinks)?;
             dir.set_dir(tree);
         }
         Ok(())
@@ -182,17 +714,10 @@ impl ProjectConfig {
         let mut func = |f: &mut File| {
             f.translatable = true;
         };
-        let src_dir = &mut match &mut self.src_dir {
-            Some(r) => r,
-            None => {
-                return Err(AddTranslatableFileError::NoSourceLang);
-            }
-        }
-        .dir;
-        let res = find_file_and_apply(src_dir, &path, &mut func);
-        match res {
-            true => Ok(()),
-            false => Err(AddTranslatableFileError::NoFile),
+        match self.find_src_file_and_apply(&path, &mut func) {
+            None => Err(AddTranslatableFileError::NoSourceLang),
+            Some(true) => Ok(()),
+            Some(false) => Err(AddTranslatableFileError::NoFile),
         }
     }
 
@@ -203,30 +728,91 @@ impl ProjectConfig {
         let mut func = |f: &mut File| {
             f.translatable = false;
         };
-        let src_dir = &mut match &mut self.src_dir {
-            Some(r) => r,
-            None => {
-                return Err(AddTranslatableFileError::NoSourceLang);
+        match self.find_src_file_and_apply(&path, &mut func) {
+            None => Err(AddTranslatableFileError::NoSourceLang),
+            Some(true) => Ok(()),
+            Some(false) => Err(AddTranslatableFileError::NoFile),
+        }
+    }
+    /// Sets the review state of the translation of the source file at _path_ into _lang_, `None`
+    /// removes it
+    pub fn set_review_state(
+        &mut self,
+        path: &Path,
+        lang: Language,
+        state: Option<ReviewState>,
+    ) -> Result<(), SetReviewStateError> {
+        if !self.lang_dirs.iter().any(|d| d.get_lang() == lang) {
+            return Err(SetReviewStateError::TargetLanguageNotInProject);
+        }
+        let mut func = |f: &mut File| f.set_review_state(lang.clone(), state);
+        match self.find_src_file_and_apply(path, &mut func) {
+            None => Err(SetReviewStateError::NoSourceLang),
+            Some(true) => Ok(()),
+            Some(false) => Err(SetReviewStateError::NoFile),
+        }
+    }
+    /// Records how the source file at _path_ was translated, returns false if there's no such
+    /// file
+    pub(crate) fn set_file_metadata(&mut self, path: &Path, meta: FileMetadata) -> bool {
+        let mut meta = Some(meta);
+        let mut func = |f: &mut File| {
+            if let Some(meta) = meta.take() {
+                f.set_metadata(meta);
             }
+        };
+        self.find_src_file_and_apply(path, &mut func) == Some(true)
+    }
+    /// Applies the function to the file at _path_ of the source directories, returns whether
+    /// the file was found or `None` if there's no source directory
+    fn find_src_file_and_apply<F>(&mut self, path: &Path, func: &mut F) -> Option<bool>
+    where
+        F: FnMut(&mut File),
+    {
+        let src_dir = &mut self.src_dir.as_mut()?.dir;
+        let found = std::iter::once(src_dir)
+            .chain(self.extra_src_dirs.iter_mut())
+            .any(|dir| find_file_and_apply(dir, path, func));
+        Some(found)
+    }
+    /// Returns the config of the source file at _path_, `None` if there's no such file
+    pub fn get_src_file(&self, path: &Path) -> Option<&File> {
+        let mut queue = Queue::<&Directory>::new();
+        for (dir, _) in self.get_src_trees() {
+            let _ = queue.add(dir);
         }
-        .dir;
-        let res = find_file_and_apply(src_dir, &path, &mut func);
-        match res {
-            true => Ok(()),
-            false => Err(AddTranslatableFileError::NoFile),
+        while let Ok(dir) = queue.remove() {
+            if let Some(file) = dir.files.iter().find(|f| f.path == path) {
+                return Some(file);
+            }
+            for sub_dir in &dir.dirs {
+                let _ = queue.add(sub_dir);
+            }
         }
+        None
     }
     pub fn get_translatable_files(&self) -> Result<Vec<PathBuf>, GetTranslatableFilesError> {
+        self.get_src_files(true)
+    }
+
+    /// Returns the files of the source directory that aren't translated, only copied
+    pub fn get_untranslatable_files(&self) -> Result<Vec<PathBuf>, GetTranslatableFilesError> {
+        self.get_src_files(false)
+    }
+
+    /// Returns the files of the source directory that are translatable or not
+    fn get_src_files(&self, translatable: bool) -> Result<Vec<PathBuf>, GetTranslatableFilesError> {
         let mut res = Vec::<PathBuf>::new();
         let mut queue = Queue::<&Directory>::new();
-        let src_dir = match &self.src_dir {
-            Some(d) => &d.dir,
-            None => return Err(GetTranslatableFilesError::NoSourceLang),
-        }; //verified that it exists upper
-        let _ = queue.add(src_dir);
+        if self.src_dir.is_none() {
+            return Err(GetTranslatableFilesError::NoSourceLang);
+        }
+        for (dir, _) in self.get_src_trees() {
+            let _ = queue.add(dir);
+        }
         while let Ok(dir) = queue.remove() {
             for file in &dir.files {
-                if file.is_translatable() {
+                if file.is_translatable() == translatable {
                     res.push(file.get_path());
                 }
             }
@@ -245,14 +831,21 @@ impl ProjectConfig {
             .ok_or(UpdateSourceDirConfig::NoSourceLang)?;
 
         let old_dir = src_dir_lang.get_dir_as_ref();
-        let new_dir =
-            build_tree(old_dir.get_path()).map_err(UpdateSourceDirConfig::AnalyzeDirError)?;
+        let new_dir = build_tree(old_dir.get_path(), &self.exclude, self.symlinks)
+            .map_err(UpdateSourceDirConfig::AnalyzeDirError)?;
 
         let res_dir = compare_and_submit_dir_structs(old_dir, &new_dir);
-        self.src_dir = Some(LangDir {
-            dir: res_dir,
-            language: src_dir_lang.get_lang(),
-        });
+        let mut lang_dir = src_dir_lang.clone();
+        lang_dir.set_dir(res_dir);
+        self.src_dir = Some(lang_dir);
+
+        let mut extra_dirs = Vec::new();
+        for old_dir in &self.extra_src_dirs {
+            let new_dir = build_tree(old_dir.get_path(), &self.exclude, self.symlinks)
+                .map_err(UpdateSourceDirConfig::AnalyzeDirError)?;
+            extra_dirs.push(compare_and_submit_dir_structs(old_dir, &new_dir));
+        }
+        self.extra_src_dirs = extra_dirs;
         Ok(())
     }
 }
@@ -277,32 +870,74 @@ where
     false
 }
 
-/// Build a `Directory` tree rooted at `root`.
-pub fn build_tree<P: AsRef<Path>>(root: P) -> std::io::Result<Directory> {
-    fn recurse(path: &Path) -> std::io::Result<Directory> {
-        let name = path
-            .file_name()
-            .map(|n| n.to_string_lossy().into_owned())
-            .unwrap_or_else(|| String::from("/"));
+/// Build a `Directory` tree rooted at `root`, the paths matching one of the _exclude_ patterns
+/// are left out, see [`is_excluded`]. The symbolic links are handled as the _links_ policy says,
+/// a kept link is a file of the tree.
+pub fn build_tree<P: AsRef<Path>>(
+    root: P,
+    exclude: &[String],
+    links: SymlinkPolicy,
+) -> std::io::Result<Directory> {
+    build_tree_in(&RealFs, root.as_ref(), exclude, links)
+}
 
+/// Same as [`build_tree`] on the given file system
+pub fn build_tree_in(
+    fs: &dyn FileSystem,
+    root: &Path,
+    exclude: &[String],
+    links: SymlinkPolicy,
+) -> std::io::Result<Directory> {
+    fn recurse(
+        fs: &dyn FileSystem,
+        root: &Path,
+        path: &Path,
+        exclude: &[String],
+        links: SymlinkPolicy,
+        ancestors: &mut Vec<PathBuf>,
+    ) -> std::io::Result<Directory> {
         let mut dir = Directory::new(path.to_path_buf());
 
-        for entry in std::fs::read_dir(path)? {
-            let entry = entry?;
-            let meta = entry.metadata()?;
+        for entry_path in fs.read_dir(path)? {
+            let mut meta = fs.symlink_metadata(&entry_path)?;
 
-            if meta.is_symlink() {
+            if meta.is_symlink {
+                match links {
+                    SymlinkPolicy::Skip => continue,
+                    SymlinkPolicy::CopyLink => {}
+                    // a dangling link has nothing to follow
+                    SymlinkPolicy::Follow => match fs.metadata(&entry_path) {
+                        Ok(resolved) => meta = resolved,
+                        Err(_) => continue,
+                    },
+                }
+            }
+            let relative = entry_path.strip_prefix(root).unwrap_or(&entry_path);
+            if is_excluded(exclude, relative, meta.is_dir) {
                 continue;
             }
 
-            if meta.is_dir() {
-                dir.dirs.push(recurse(&entry.path())?);
-            } else if meta.is_file() {
-                let file_name = entry.file_name().to_string_lossy().into_owned();
+            if meta.is_dir {
+                let real = fs.canonicalize(&entry_path)?;
+                if ancestors.contains(&real) {
+                    continue;
+                }
+                ancestors.push(real);
+                let sub_dir = recurse(fs, root, &entry_path, exclude, links, ancestors);
+                ancestors.pop();
+                dir.dirs.push(sub_dir?);
+            } else if meta.is_file || meta.is_symlink {
+                let file_name = entry_path
+                    .file_name()
+                    .unwrap_or_default()
+                    .to_string_lossy()
+                    .into_owned();
                 dir.files.push(File {
                     name: file_name.clone(),
-                    path: entry.path(),
+                    path: entry_path,
                     translatable: false,
+                    reviews: Vec::new(),
+                    translations: Vec::new(),
                 });
             }
         }
@@ -310,7 +945,28 @@ pub fn build_tree<P: AsRef<Path>>(root: P) -> std::io::Result<Directory> {
         Ok(dir)
     }
 
-    recurse(root.as_ref())
+    let mut ancestors = vec![fs.canonicalize(root)?];
+    recurse(fs, root, root, exclude, links, &mut ancestors)
+}
+
+/// Returns true if the path, relative to its language directory, matches one of the glob
+/// _patterns_. A pattern without a `/` matches the name of a file or a directory anywhere, a
+/// pattern ending with a `/` only matches directories.
+pub fn is_excluded(patterns: &[String], relative: &Path, is_dir: bool) -> bool {
+    let path = relative.to_string_lossy().replace('\\', "/");
+    let name = path.rsplit('/').next().unwrap_or_default();
+    patterns.iter().any(|pattern| {
+        let dir_only = pattern.ends_with('/');
+        let pattern = pattern.trim_end_matches('/').trim_start_matches('/');
+        if dir_only && !is_dir {
+            return false;
+        }
+        if pattern.contains('/') {
+            glob_match(pattern, &path)
+        } else {
+            glob_match(pattern, name)
+        }
+    })
 }
 
 /// Init project config with it's file
@@ -329,20 +985,11 @@ pub(crate) fn init(proj_name: &str, path: PathBuf) -> Result<(), InitProjectErro
     Ok(())
 }
 
+/// Writes the config atomically, a crash never leaves a truncated config behind
 pub(crate) fn write_conf(path: PathBuf, conf: &ProjectConfig) -> Result<(), WriteConfigError> {
-    let mut file = std::fs::OpenOptions::new()
-        .create(true)
-        .truncate(true)
-        .write(true)
-        .read(true)
-        .open(path)
-        .map_err(WriteConfigError::IoError)?;
-
     let serialized = serde_json::to_string(conf)
         .map_err(|e| WriteConfigError::SerialisationError(e.to_string()))?;
-    file.write_fmt(format_args!("{}", serialized))
-        .map_err(WriteConfigError::IoError)?;
-    Ok(())
+    write_atomic(&path, serialized).map_err(WriteConfigError::IoError)
 }
 
 pub(crate) fn load_config_from_file(path: PathBuf) -> Result<ProjectConfig, LoadConfigError> {
@@ -0,0 +1,71 @@
+//! Checks that the translations use the approved translations of the glossary terms: every time
+//! a term appears in the source its mandated translation is expected in the translated file.
+use std::path::Path;
+
+use crate::{glossary::GlossaryEntry, Language};
+
+use super::{QaFinding, QaFindingKind, Severity};
+
+/// Compares the use of the glossary _entries_ in the translated text of a file with the source
+/// and returns a finding per term whose approved translation is missing. The terms are matched
+/// as whole words ignoring the case, so inflected forms of the translation aren't recognized.
+pub fn check_glossary_compliance(
+    file: &Path,
+    source: &str,
+    translated: &str,
+    lang: Language,
+    entries: &[&GlossaryEntry],
+) -> Vec<QaFinding> {
+    let (source, translated) = (source.to_lowercase(), translated.to_lowercase());
+    let mut res = Vec::new();
+    for entry in entries {
+        let (term, translation) = (entry.get_term(), entry.get_translation());
+        let expected = count_occurrences(&source, &term.to_lowercase());
+        if expected == 0 {
+            continue;
+        }
+        let found = count_occurrences(&translated, &translation.to_lowercase());
+        if found >= expected {
+            continue;
+        }
+        let (severity, message) = if found == 0 {
+            (
+                Severity::Error,
+                format!(
+                    "\"{}\" is used {} time(s) in the source but its translation \"{}\" is never used",
+                    term, expected, translation
+                ),
+            )
+        } else {
+            (
+                Severity::Warning,
+                format!(
+                    "\"{}\" is used {} time(s) in the source but its translation \"{}\" only {} time(s)",
+                    term, expected, translation, found
+                ),
+            )
+        };
+        res.push(QaFinding {
+            file: file.to_path_buf(),
+            lang: lang.clone(),
+            severity,
+            kind: QaFindingKind::GlossaryViolation { term },
+            message,
+        });
+    }
+    res
+}
+
+/// Counts the occurrences of _word_ in _text_ that aren't a part of a longer word
+fn count_occurrences(text: &str, word: &str) -> usize {
+    if word.is_empty() {
+        return 0;
+    }
+    text.match_indices(word)
+        .filter(|(i, _)| {
+            let before = text[..*i].chars().next_back();
+            let after = text[i + word.len()..].chars().next();
+            !before.is_some_and(char::is_alphanumeric) && !after.is_some_and(char::is_alphanumeric)
+        })
+        .count()
+}
@@ -0,0 +1,231 @@
+//! Checks of what a translation must keep from its source unchanged: the placeholders of format
+//! strings (`{var}`, `%s`...), the numbers and the URLs.
+use std::{collections::HashMap, path::Path};
+
+use crate::Language;
+
+use super::{QaFinding, QaFindingKind, Severity};
+
+/// Compares the translated text of a file with its source and returns the findings
+pub fn check_invariants(
+    file: &Path,
+    source: &str,
+    translated: &str,
+    lang: Language,
+) -> Vec<QaFinding> {
+    let mut res = Vec::new();
+    let mut finding = |severity: Severity, kind: QaFindingKind, message: String| {
+        res.push(QaFinding {
+            file: file.to_path_buf(),
+            lang: lang.clone(),
+            severity,
+            kind,
+            message,
+        })
+    };
+
+    let (src_placeholders, tgt_placeholders) = (
+        extract_placeholders(source),
+        extract_placeholders(translated),
+    );
+    for (placeholder, missing) in count_difference(&src_placeholders, &tgt_placeholders) {
+        finding(
+            Severity::Error,
+            QaFindingKind::MissingPlaceholder,
+            format!("placeholder {} is missing {} time(s)", placeholder, missing),
+        );
+    }
+    for (placeholder, extra) in count_difference(&tgt_placeholders, &src_placeholders) {
+        finding(
+            Severity::Error,
+            QaFindingKind::UnexpectedPlaceholder,
+            format!(
+                "placeholder {} isn't in the source ({} time(s))",
+                placeholder, extra
+            ),
+        );
+    }
+
+    let (src_urls, tgt_urls) = (extract_urls(source), extract_urls(translated));
+    for (url, _) in count_difference(&src_urls, &tgt_urls) {
+        finding(
+            Severity::Error,
+            QaFindingKind::UrlMismatch,
+            format!("URL {} is missing or altered", url),
+        );
+    }
+
+    let src_numbers = extract_numbers(&remove_urls(source));
+    let tgt_numbers = extract_numbers(&remove_urls(translated));
+    let normalized = |numbers: &[(String, String)]| -> Vec<String> {
+        numbers.iter().map(|(n, _)| n.clone()).collect()
+    };
+    for (number, missing) in count_difference(&normalized(&src_numbers), &normalized(&tgt_numbers))
+    {
+        // reported as written in the source
+        let written = src_numbers
+            .iter()
+            .find(|(n, _)| *n == number)
+            .map(|(_, w)| w.as_str())
+            .unwrap_or(&number);
+        finding(
+            Severity::Warning,
+            QaFindingKind::NumberMismatch,
+            format!("number {} is missing {} time(s)", written, missing),
+        );
+    }
+    res
+}
+
+/// Returns the items of _a_ that appear fewer times in _b_, with the difference of the counts
+fn count_difference(a: &[String], b: &[String]) -> Vec<(String, usize)> {
+    let count = |items: &[String]| {
+        let mut counts: HashMap<String, usize> = HashMap::new();
+        for item in items {
+            *counts.entry(item.clone()).or_default() += 1;
+        }
+        counts
+    };
+    let (a_counts, b_counts) = (count(a), count(b));
+    let mut res: Vec<(String, usize)> = a_counts
+        .into_iter()
+        .filter_map(|(item, n)| {
+            let m = b_counts.get(&item).copied().unwrap_or(0);
+            (n > m).then(|| (item, n - m))
+        })
+        .collect();
+    res.sort();
+    res
+}
+
+/// Returns the placeholders of format strings: `{name}`, `{0}`, `{}`, `${name}`, `%s`, `%1$d`,
+/// `%(name)s`. A brace right after a word (e.g. a LaTeX argument) isn't a placeholder.
+fn extract_placeholders(text: &str) -> Vec<String> {
+    let chars: Vec<char> = text.chars().collect();
+    let mut res = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let end = match chars[i] {
+            '{' if i == 0
+                || !(chars[i - 1].is_alphanumeric() || matches!(chars[i - 1], '}' | ']')) =>
+            {
+                let mut j = i + 1;
+                while j < chars.len()
+                    && (chars[j].is_alphanumeric() || matches!(chars[j], '_' | '.' | ':' | '-'))
+                {
+                    j += 1;
+                }
+                (j < chars.len() && chars[j] == '}').then_some(j + 1)
+            }
+            '%' => printf_end(&chars, i + 1),
+            _ => None,
+        };
+        match end {
+            Some(end) => {
+                // `${name}` is a placeholder as a whole
+                let start = if i > 0 && chars[i - 1] == '$' && chars[i] == '{' {
+                    i - 1
+                } else {
+                    i
+                };
+                res.push(chars[start..end].iter().collect());
+                i = end;
+            }
+            None => i += 1,
+        }
+    }
+    res
+}
+
+/// Returns the end of a printf conversion whose `%` is right before _start_
+fn printf_end(chars: &[char], start: usize) -> Option<usize> {
+    let mut j = start;
+    if chars.get(j) == Some(&'(') {
+        j += 1;
+        while chars
+            .get(j)
+            .is_some_and(|c| c.is_alphanumeric() || *c == '_')
+        {
+            j += 1;
+        }
+        if chars.get(j) != Some(&')') {
+            return None;
+        }
+        j += 1;
+    }
+    while chars
+        .get(j)
+        .is_some_and(|c| c.is_ascii_digit() || matches!(c, '$' | '-' | '+' | '#' | '.'))
+    {
+        j += 1;
+    }
+    chars
+        .get(j)
+        .filter(|c| "sdifFeEgGxXoucp".contains(**c))
+        .map(|_| j + 1)
+}
+
+/// Returns the URLs of the text without the punctuation that ends the sentence around them
+fn extract_urls(text: &str) -> Vec<String> {
+    let mut res = Vec::new();
+    for scheme in ["https://", "http://"] {
+        let mut rest = text;
+        while let Some(id) = rest.find(scheme) {
+            let url = &rest[id..];
+            let end = url
+                .find(|c: char| {
+                    c.is_whitespace() || matches!(c, '<' | '>' | '"' | '\'' | ')' | ']')
+                })
+                .unwrap_or(url.len());
+            let url = url[..end].trim_end_matches(['.', ',', ';', ':', '!', '?']);
+            res.push(url.to_string());
+            rest = &rest[id + end.max(scheme.len())..];
+        }
+    }
+    res
+}
+
+fn remove_urls(text: &str) -> String {
+    let mut res = text.to_string();
+    for url in extract_urls(text) {
+        res = res.replace(&url, " ");
+    }
+    res
+}
+
+/// Returns the numbers of the text without their separators and as they are written, so
+/// `1,000.5` in the source and `1 000,5` in the translation are the same number
+fn extract_numbers(text: &str) -> Vec<(String, String)> {
+    let chars: Vec<char> = text.chars().collect();
+    let mut res = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        if !chars[i].is_ascii_digit() {
+            i += 1;
+            continue;
+        }
+        let start = i;
+        let mut number = String::new();
+        while i < chars.len() {
+            if chars[i].is_ascii_digit() {
+                number.push(chars[i]);
+            } else if !is_digit_separator(&chars, i) {
+                break;
+            }
+            i += 1;
+        }
+        res.push((number, chars[start..i].iter().collect()));
+    }
+    res
+}
+
+/// returns true if the character at _i_ separates the digits of a number: a decimal point or
+/// comma, or a space that groups thousands
+fn is_digit_separator(chars: &[char], i: usize) -> bool {
+    let digit_at = |j: usize| chars.get(j).is_some_and(|c| c.is_ascii_digit());
+    match chars[i] {
+        '.' | ',' => digit_at(i + 1),
+        ' ' | '\u{a0}' | '\u{202f}' => (1..=3).all(|k| digit_at(i + k)) && !digit_at(i + 4),
+        _ => false,
+    }
+}
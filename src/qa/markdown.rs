@@ -0,0 +1,139 @@
+//! Structural checks of translated Markdown: the translation must have the headings, the code
+//! fences and the link reference definitions of its source, a difference means the model
+//! corrupted the structure (e.g. merged two sections or dropped a fence).
+use std::path::Path;
+
+use crate::Language;
+
+use super::{QaFinding, QaFindingKind, Severity};
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+/// The structure of a Markdown text
+pub struct MarkdownStructure {
+    /// levels of the ATX headings in their order
+    pub headings: Vec<usize>,
+    /// number of lines that open or close a fenced code block
+    pub code_fences: usize,
+    /// labels of the link reference definitions (e.g. `[docs]: https://...`), lowercase
+    pub link_definitions: Vec<String>,
+}
+
+impl MarkdownStructure {
+    /// Reads the structure of the text, the headings and definitions in code blocks don't count
+    pub fn parse(text: &str) -> Self {
+        let mut res = Self::default();
+        let mut fence: Option<String> = None;
+        for line in text.lines() {
+            let trimmed = line.trim_start_matches(' ');
+            let indent = line.len() - trimmed.len();
+            if let Some(marker) = get_fence_marker(trimmed).filter(|_| indent <= 3) {
+                match &fence {
+                    Some(open) if marker.starts_with(open.as_str()) => {
+                        fence = None;
+                        res.code_fences += 1;
+                    }
+                    Some(_) => {}
+                    None => {
+                        fence = Some(marker);
+                        res.code_fences += 1;
+                    }
+                }
+                continue;
+            }
+            if fence.is_some() || indent > 3 {
+                continue;
+            }
+            if let Some(level) = get_heading_level(trimmed) {
+                res.headings.push(level);
+            } else if let Some(label) = get_link_definition(trimmed) {
+                res.link_definitions.push(label);
+            }
+        }
+        res
+    }
+}
+
+/// Compares the structure of the translated Markdown with the one of its source and returns
+/// the findings
+pub fn check_markdown_integrity(
+    file: &Path,
+    source: &str,
+    translated: &str,
+    lang: Language,
+) -> Vec<QaFinding> {
+    let src = MarkdownStructure::parse(source);
+    let tgt = MarkdownStructure::parse(translated);
+    let mut res = Vec::new();
+    let mut finding = |kind: QaFindingKind, message: String| {
+        res.push(QaFinding {
+            file: file.to_path_buf(),
+            lang: lang.clone(),
+            severity: Severity::Error,
+            kind,
+            message,
+        })
+    };
+    if src.headings != tgt.headings {
+        finding(
+            QaFindingKind::HeadingMismatch,
+            format!(
+                "{} heading(s) in the source, {} in the translation{}",
+                src.headings.len(),
+                tgt.headings.len(),
+                if src.headings.len() == tgt.headings.len() {
+                    " with different levels"
+                } else {
+                    ""
+                }
+            ),
+        );
+    }
+    if src.code_fences != tgt.code_fences {
+        finding(
+            QaFindingKind::CodeFenceMismatch,
+            format!(
+                "{} code fence(s) in the source, {} in the translation",
+                src.code_fences, tgt.code_fences
+            ),
+        );
+    }
+    if src.link_definitions.len() != tgt.link_definitions.len() {
+        finding(
+            QaFindingKind::LinkDefinitionMismatch,
+            format!(
+                "{} link definition(s) in the source, {} in the translation",
+                src.link_definitions.len(),
+                tgt.link_definitions.len()
+            ),
+        );
+    }
+    res
+}
+
+/// Returns the marker of a line that opens or closes a code fence (e.g. "```"), `None` if the
+/// line isn't a fence
+fn get_fence_marker(line: &str) -> Option<String> {
+    let c = line.chars().next().filter(|c| *c == '`' || *c == '~')?;
+    let len = line.chars().take_while(|x| *x == c).count();
+    (len >= 3).then(|| c.to_string().repeat(len))
+}
+
+fn get_heading_level(line: &str) -> Option<usize> {
+    let level = line.chars().take_while(|c| *c == '#').count();
+    let is_heading = (1..=6).contains(&level)
+        && line[level..]
+            .chars()
+            .next()
+            .is_none_or(|c| c == ' ' || c == '\t');
+    is_heading.then_some(level)
+}
+
+/// Returns the label of a link reference definition, `[label]: destination`
+fn get_link_definition(line: &str) -> Option<String> {
+    let rest = line.strip_prefix('[')?;
+    let end = rest.find("]:")?;
+    let label = &rest[..end];
+    let destination = rest[end + 2..].trim();
+    (!label.is_empty() && !label.starts_with('^') && !destination.is_empty())
+        .then(|| label.to_lowercase())
+}
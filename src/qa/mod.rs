@@ -0,0 +1,143 @@
+//! A module with quality checks that are run on the translated files. Every check produces
+//! findings that are collected into a [`QaReport`].
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+};
+
+use crate::{format::handler_for_path, glossary::GlossaryEntry, Language};
+
+pub mod glossary;
+pub mod invariants;
+pub mod markdown;
+pub mod readability;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+/// How serious a finding is
+pub enum Severity {
+    /// Something worth a look, the translation is still usable
+    Warning,
+    /// The translation is most likely broken
+    Error,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+/// The kind of the problem a finding reports
+pub enum QaFindingKind {
+    /// A single sentence is longer than the configured threshold
+    LongSentence,
+    /// The average sentence length of the file is above the configured threshold
+    HighAverageSentenceLength,
+    /// The text addresses the reader in a register other than the configured one
+    RegisterMismatch,
+    /// A placeholder of the source (e.g. `{name}` or `%s`) is missing in the translation
+    MissingPlaceholder,
+    /// The translation has a placeholder that isn't in the source
+    UnexpectedPlaceholder,
+    /// A number of the source is missing in the translation
+    NumberMismatch,
+    /// A URL of the source is missing or altered in the translation
+    UrlMismatch,
+    /// The Markdown translation doesn't have the headings of its source
+    HeadingMismatch,
+    /// The Markdown translation doesn't have the code fences of its source
+    CodeFenceMismatch,
+    /// The Markdown translation doesn't have the link definitions of its source
+    LinkDefinitionMismatch,
+    /// The translation doesn't use the approved translation of a glossary term of the source
+    GlossaryViolation { term: String },
+}
+
+#[derive(Debug, Clone, serde::Serialize)]
+/// A single problem found in a translated file
+pub struct QaFinding {
+    /// path to the translated file
+    pub file: PathBuf,
+    /// language of the translated file
+    pub lang: Language,
+    pub severity: Severity,
+    pub kind: QaFindingKind,
+    /// human readable description of the problem
+    pub message: String,
+}
+
+/// Runs the checks that compare a translated text with its source: the invariants, the glossary
+/// _entries_ and, if _file_ is a Markdown file, its structure. Returns only the errors.
+pub fn check_segment(
+    file: &Path,
+    source: &str,
+    translated: &str,
+    lang: Language,
+    entries: &[&GlossaryEntry],
+) -> Vec<QaFinding> {
+    let mut findings = invariants::check_invariants(file, source, translated, lang.clone());
+    findings.extend(glossary::check_glossary_compliance(
+        file,
+        source,
+        translated,
+        lang.clone(),
+        entries,
+    ));
+    if handler_for_path(file).name() == "markdown" {
+        findings.extend(markdown::check_markdown_integrity(
+            file, source, translated, lang,
+        ));
+    }
+    findings.retain(|f| f.severity == Severity::Error);
+    findings
+}
+
+#[derive(Debug, Clone, Default, serde::Serialize)]
+/// A collection of findings produced by the QA checks
+pub struct QaReport {
+    findings: Vec<QaFinding>,
+}
+
+impl QaReport {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    pub fn push(&mut self, finding: QaFinding) {
+        self.findings.push(finding);
+    }
+    pub fn extend(&mut self, findings: impl IntoIterator<Item = QaFinding>) {
+        self.findings.extend(findings);
+    }
+    pub fn get_findings_as_ref(&self) -> &Vec<QaFinding> {
+        &self.findings
+    }
+    /// returns only the findings with the `Warning` severity
+    pub fn warnings(&self) -> impl Iterator<Item = &QaFinding> {
+        self.findings
+            .iter()
+            .filter(|f| f.severity == Severity::Warning)
+    }
+    /// returns only the findings with the `Error` severity
+    pub fn errors(&self) -> impl Iterator<Item = &QaFinding> {
+        self.findings
+            .iter()
+            .filter(|f| f.severity == Severity::Error)
+    }
+    /// returns the findings grouped by the file they were found in
+    pub fn by_file(&self) -> BTreeMap<&Path, Vec<&QaFinding>> {
+        let mut res: BTreeMap<&Path, Vec<&QaFinding>> = BTreeMap::new();
+        for finding in &self.findings {
+            res.entry(finding.file.as_path()).or_default().push(finding);
+        }
+        res
+    }
+    /// returns the glossary violations grouped by the term they are about
+    pub fn by_term(&self) -> BTreeMap<&str, Vec<&QaFinding>> {
+        let mut res: BTreeMap<&str, Vec<&QaFinding>> = BTreeMap::new();
+        for finding in &self.findings {
+            if let QaFindingKind::GlossaryViolation { term } = &finding.kind {
+                res.entry(term.as_str()).or_default().push(finding);
+            }
+        }
+        res
+    }
+    /// returns true if no check found anything
+    pub fn is_empty(&self) -> bool {
+        self.findings.is_empty()
+    }
+}
@@ -0,0 +1,230 @@
+//! Readability and register checks for translated texts, meant for content intended for
+//! learners where long sentences and a wrong form of address hurt more than usual.
+use std::path::Path;
+
+use crate::Language;
+
+use super::{QaFinding, QaFindingKind, Severity};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+/// The form of address the translation is expected to use
+pub enum Register {
+    /// e.g. "vous" in French or "Sie" in German
+    Formal,
+    /// e.g. "tu" in French or "du" in German
+    Informal,
+}
+
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+/// Thresholds of the readability check for one target language
+pub struct ReadabilityThresholds {
+    /// maximum number of words in a single sentence
+    pub max_sentence_words: usize,
+    /// maximum average number of words per sentence in a file
+    pub max_avg_sentence_words: f64,
+    /// expected register of the text, `None` disables the register check
+    pub register: Option<Register>,
+}
+
+impl Default for ReadabilityThresholds {
+    fn default() -> Self {
+        Self {
+            max_sentence_words: 35,
+            max_avg_sentence_words: 20.0,
+            register: None,
+        }
+    }
+}
+
+/// Checks the translated text of a file against the thresholds and returns the findings
+pub fn check_readability(
+    file: &Path,
+    text: &str,
+    lang: Language,
+    thresholds: &ReadabilityThresholds,
+) -> Vec<QaFinding> {
+    let mut res = Vec::new();
+    let finding = |kind: QaFindingKind, message: String| QaFinding {
+        file: file.to_path_buf(),
+        lang: lang.clone(),
+        severity: Severity::Warning,
+        kind,
+        message,
+    };
+
+    let sentences = split_sentences(&strip_code_blocks(text));
+    let lengths: Vec<usize> = sentences
+        .iter()
+        .map(|s| s.split_whitespace().count())
+        .filter(|n| *n > 0)
+        .collect();
+
+    for (sentence, len) in sentences.iter().zip(lengths.iter()) {
+        if *len > thresholds.max_sentence_words {
+            res.push(finding(
+                QaFindingKind::LongSentence,
+                format!(
+                    "sentence of {} words (max {}): \"{}\"",
+                    len,
+                    thresholds.max_sentence_words,
+                    shorten(sentence, 60)
+                ),
+            ));
+        }
+    }
+
+    if !lengths.is_empty() {
+        let avg = lengths.iter().sum::<usize>() as f64 / lengths.len() as f64;
+        if avg > thresholds.max_avg_sentence_words {
+            res.push(finding(
+                QaFindingKind::HighAverageSentenceLength,
+                format!(
+                    "average sentence length is {:.1} words (max {:.1})",
+                    avg, thresholds.max_avg_sentence_words
+                ),
+            ));
+        }
+    }
+
+    if let Some(register) = thresholds.register {
+        let (formal, informal) = count_address_forms(text, lang.clone());
+        let (wrong, wrong_name) = match register {
+            Register::Formal => (informal, "informal"),
+            Register::Informal => (formal, "formal"),
+        };
+        if wrong > 0 {
+            res.push(finding(
+                QaFindingKind::RegisterMismatch,
+                format!(
+                    "{} {} form(s) of address found, expected {:?} register",
+                    wrong, wrong_name, register
+                ),
+            ));
+        }
+    }
+
+    res
+}
+
+/// Removes fenced code blocks from the text, they shouldn't count as sentences
+fn strip_code_blocks(text: &str) -> String {
+    let mut res = String::new();
+    let mut in_block = false;
+    for line in text.lines() {
+        if line.trim_start().starts_with("```") {
+            in_block = !in_block;
+            continue;
+        }
+        if !in_block {
+            res.push_str(line);
+            res.push('\n');
+        }
+    }
+    res
+}
+
+/// Splits the text into sentences on terminal punctuation and on empty lines
+fn split_sentences(text: &str) -> Vec<String> {
+    let mut res = Vec::new();
+    let mut current = String::new();
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        current.push(c);
+        let end_of_paragraph = c == '\n' && chars.peek() == Some(&'\n');
+        let end_of_sentence =
+            matches!(c, '.' | '!' | '?' | '…') && chars.peek().is_none_or(|n| n.is_whitespace());
+        if end_of_paragraph || end_of_sentence {
+            if !current.trim().is_empty() {
+                res.push(current.trim().to_string());
+            }
+            current = String::new();
+        }
+    }
+    if !current.trim().is_empty() {
+        res.push(current.trim().to_string());
+    }
+    res
+}
+
+/// Counts (formal, informal) forms of address in the text. Languages without a T–V distinction
+/// and the custom ones always return zeros.
+fn count_address_forms(text: &str, lang: Language) -> (usize, usize) {
+    let (formal, informal): (&[&str], &[&str]) = match lang {
+        Language::French => (
+            &["vous", "votre", "vos"],
+            &["tu", "toi", "ton", "ta", "tes"],
+        ),
+        Language::German => (
+            &["Sie", "Ihnen", "Ihr", "Ihre", "Ihren", "Ihrem", "Ihrer"],
+            &[
+                "du", "dich", "dir", "dein", "deine", "deinen", "deinem", "deiner",
+            ],
+        ),
+        Language::Spanish => (&["usted", "ustedes"], &["tú", "tu", "tus", "ti", "contigo"]),
+        Language::Ukrainian => (
+            &["ви", "вас", "вам", "ваш", "ваша", "ваше", "ваші"],
+            &["ти", "тебе", "тобі", "твій", "твоя", "твоє", "твої"],
+        ),
+        Language::Russian => (
+            &["вы", "вас", "вам", "ваш", "ваша", "ваше", "ваши"],
+            &["ты", "тебя", "тебе", "твой", "твоя", "твоё", "твои"],
+        ),
+        Language::Dutch => (&["u", "uw"], &["jij", "jou", "jouw", "je"]),
+        Language::Polish => (
+            &["pan", "pani", "państwo", "pana", "panu", "panią"],
+            &[
+                "ty", "ciebie", "cię", "tobie", "ci", "twój", "twoja", "twoje",
+            ],
+        ),
+        Language::Turkish => (
+            &["siz", "sizin", "size", "sizi", "sizde"],
+            &["sen", "senin", "sana", "seni", "sende"],
+        ),
+        // the address of the other ones isn't told apart by a few words (e.g. the Italian Lei is
+        // also "she", Japanese and Korean mark it with the verb forms)
+        Language::English
+        | Language::Italian
+        | Language::Portuguese
+        | Language::Japanese
+        | Language::Chinese
+        | Language::Korean
+        | Language::Arabic
+        | Language::Custom(_) => (&[], &[]),
+    };
+    // the German formal pronouns are only told apart from "sie" (she, they) and "ihr" by their
+    // capital letter, the informal ones are capitalized too at the start of a sentence or in a
+    // letter
+    let formal_case_sensitive = lang == Language::German;
+
+    let mut res = (0, 0);
+    for word in text.split(|c: char| !c.is_alphabetic()) {
+        if word.is_empty() {
+            continue;
+        }
+        let lowercase = word.to_lowercase();
+        let matches = |list: &[&str], case_sensitive: bool| {
+            list.iter().any(|w| {
+                if case_sensitive {
+                    *w == word
+                } else {
+                    w.to_lowercase() == lowercase
+                }
+            })
+        };
+        if matches(formal, formal_case_sensitive) {
+            res.0 += 1;
+        } else if matches(informal, false) {
+            res.1 += 1;
+        }
+    }
+    res
+}
+
+fn shorten(text: &str, max_chars: usize) -> String {
+    if text.chars().count() <= max_chars {
+        return text.to_string();
+    }
+    let mut res: String = text.chars().take(max_chars).collect();
+    res.push('…');
+    res
+}
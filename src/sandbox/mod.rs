@@ -0,0 +1,77 @@
+//! Confinement of file operations to the project root. Paths given to the [`crate::project::Project`]
+//! API may come from untrusted users, they are checked here before anything is read or written:
+//! `..` leaving the root, absolute paths outside of the root and symlinks pointing out of the tree
+//! are rejected.
+use std::path::{Component, Path, PathBuf};
+
+use crate::errors::sandbox_errors::SandboxError;
+
+/// Checks that _path_ stays inside of _root_ and returns it in the canonical form (the part of
+/// the path that doesn't exist yet is kept as is). A relative path is relative to _root_, which
+/// doesn't have to exist yet.
+pub fn confine_path(root: &Path, path: &Path) -> Result<PathBuf, SandboxError> {
+    let canonical_root = canonicalize_existing(root).map_err(SandboxError::IoError)?;
+    let full_path = if path.is_absolute() {
+        path.to_path_buf()
+    } else {
+        root.join(path)
+    };
+
+    let normalized = normalize(&full_path);
+    if !normalized.starts_with(root) && !normalized.starts_with(&canonical_root) {
+        let has_parent = path.components().any(|c| c == Component::ParentDir);
+        return Err(if has_parent {
+            SandboxError::ParentTraversal
+        } else {
+            SandboxError::OutsideRoot
+        });
+    }
+
+    let resolved = canonicalize_existing(&normalized).map_err(SandboxError::IoError)?;
+    if !resolved.starts_with(&canonical_root) {
+        return Err(SandboxError::SymlinkEscape);
+    }
+    Ok(resolved)
+}
+
+/// Same as [`confine_path`] but a relative path is relative to the current directory, as paths
+/// typed by a user on the command line are
+pub fn confine_user_path(root: &Path, path: &Path) -> Result<PathBuf, SandboxError> {
+    let path = std::path::absolute(path).map_err(SandboxError::IoError)?;
+    confine_path(root, &path)
+}
+
+/// Resolves `.` and `..` without touching the file system
+fn normalize(path: &Path) -> PathBuf {
+    let mut res = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                res.pop();
+            }
+            other => res.push(other),
+        }
+    }
+    res
+}
+
+/// Canonicalizes the longest existing prefix of the path and appends the rest to it
+pub(crate) fn canonicalize_existing(path: &Path) -> std::io::Result<PathBuf> {
+    let mut existing = path;
+    let mut rest = Vec::new();
+    while std::fs::symlink_metadata(existing).is_err() {
+        match (existing.parent(), existing.file_name()) {
+            (Some(parent), Some(name)) => {
+                rest.push(name);
+                existing = parent;
+            }
+            _ => break,
+        }
+    }
+    let mut res = std::fs::canonicalize(existing)?;
+    for name in rest.iter().rev() {
+        res.push(name);
+    }
+    Ok(res)
+}
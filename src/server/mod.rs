@@ -0,0 +1,286 @@
+//! Server mode: a loaded project is driven over a local socket with JSON-RPC 2.0, one request
+//! and one response per line, so editors and GUIs don't reload the project for every command.
+//!
+//! The methods are `status`, `sync` (`force`), `translate` (`lang` code, optional `file` and
+//! `force`), `mark_translatable` (`path`, `translatable`) and `shutdown`. The paths are relative
+//! to the project root. A failed operation answers with the error code `-32000`, the stable code
+//! and the category of the error are in its data, see [`crate::errors::ErrorInfo`].
+use std::{
+    io::{BufRead, Write},
+    path::{Path, PathBuf},
+};
+
+use serde_json::{json, Value};
+
+use crate::{errors::ErrorInfo, project::Project, shutdown::is_shutdown_requested};
+
+/// Interval at which a waiting server checks if it has to stop
+const STOP_POLL: std::time::Duration = std::time::Duration::from_millis(200);
+
+const PARSE_ERROR: i64 = -32700;
+const INVALID_REQUEST: i64 = -32600;
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+/// error of the operation itself
+const OPERATION_ERROR: i64 = -32000;
+
+/// A request without an `id` member is a notification, it gets no response. A `null` id still
+/// gets one.
+#[derive(Debug, serde::Deserialize)]
+struct Request {
+    method: String,
+    #[serde(default)]
+    params: Value,
+}
+
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct RpcError {
+    pub code: i64,
+    pub message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub data: Option<Value>,
+}
+
+impl RpcError {
+    fn new(code: i64, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            message: message.into(),
+            data: None,
+        }
+    }
+    fn from_operation<E: ErrorInfo + std::fmt::Display>(e: E) -> Self {
+        Self {
+            code: OPERATION_ERROR,
+            message: e.to_string(),
+            data: Some(json!({ "code": e.code(), "category": e.category() })),
+        }
+    }
+}
+
+#[derive(Debug, Default, serde::Deserialize)]
+struct SyncParams {
+    #[serde(default)]
+    force: bool,
+}
+
+#[derive(Debug, serde::Deserialize)]
+struct TranslateParams {
+    lang: String,
+    /// the file to translate, all the translatable files if `None`
+    #[serde(default)]
+    file: Option<PathBuf>,
+    #[serde(default)]
+    force: bool,
+}
+
+#[derive(Debug, serde::Deserialize)]
+struct MarkParams {
+    path: PathBuf,
+    #[serde(default = "default_true")]
+    translatable: bool,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+/// Serves the requests on a loaded project
+pub struct Server {
+    project: Project,
+    /// set by the `shutdown` method
+    stopping: bool,
+}
+
+impl Server {
+    pub fn new(project: Project) -> Self {
+        Self {
+            project,
+            stopping: false,
+        }
+    }
+
+    pub fn get_project_as_ref(&self) -> &Project {
+        &self.project
+    }
+
+    /// returns true once the server was asked to stop, by the `shutdown` method or a shutdown
+    /// request of the process
+    pub fn is_stopping(&self) -> bool {
+        self.stopping || is_shutdown_requested()
+    }
+
+    /// Handles a request line, returns the response line, `None` for a notification
+    pub fn handle_line(&mut self, line: &str) -> Option<String> {
+        let (id, res) = match serde_json::from_str::<Value>(line) {
+            Err(e) => (Value::Null, Err(RpcError::new(PARSE_ERROR, e.to_string()))),
+            Ok(value) => {
+                let id = value.get("id").cloned();
+                match serde_json::from_value::<Request>(value) {
+                    Err(e) => (
+                        id.unwrap_or(Value::Null),
+                        Err(RpcError::new(INVALID_REQUEST, e.to_string())),
+                    ),
+                    Ok(request) => {
+                        let res = self.call(&request.method, request.params);
+                        (id?, res)
+                    }
+                }
+            }
+        };
+        let response = match res {
+            Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+            Err(error) => json!({ "jsonrpc": "2.0", "id": id, "error": error }),
+        };
+        Some(response.to_string())
+    }
+
+    /// Calls the method with its params, returns its result
+    pub fn call(&mut self, method: &str, params: Value) -> Result<Value, RpcError> {
+        match method {
+            "status" => {
+                let status = self.project.status().map_err(RpcError::from_operation)?;
+                Ok(json!(status))
+            }
+            "sync" => {
+                let params: SyncParams = parse_params(params)?;
+                let report = self
+                    .project
+                    .sync_files(params.force)
+                    .map_err(RpcError::from_operation)?;
+                Ok(json!(report))
+            }
+            "translate" => {
+                let params: TranslateParams = parse_params(params)?;
+                let lang = self
+                    .project
+                    .get_config_as_ref()
+                    .get_lang_by_code(&params.lang)
+                    .ok_or_else(|| {
+                        RpcError::new(
+                            INVALID_PARAMS,
+                            format!("unknown language '{}'", params.lang),
+                        )
+                    })?;
+                match params.file {
+                    Some(file) => {
+                        let path = self.get_path(&file);
+                        self.project.translate_file(path, lang, params.force)
+                    }
+                    None => self.project.translate_all(lang),
+                }
+                .map_err(RpcError::from_operation)?;
+                Ok(Value::Null)
+            }
+            "mark_translatable" => {
+                let params: MarkParams = parse_params(params)?;
+                let path = self.get_path(&params.path);
+                if params.translatable {
+                    self.project.make_translatable_file(path)
+                } else {
+                    self.project.make_untranslatable_file(path)
+                }
+                .map_err(RpcError::from_operation)?;
+                Ok(Value::Null)
+            }
+            "shutdown" => {
+                self.stopping = true;
+                Ok(Value::Null)
+            }
+            _ => Err(RpcError::new(
+                METHOD_NOT_FOUND,
+                format!("unknown method '{}'", method),
+            )),
+        }
+    }
+
+    /// Serves the request lines of _reader_ until its end or until the server is stopping,
+    /// the responses are written to _writer_. A reader with a read timeout lets a stop be
+    /// noticed while waiting.
+    pub fn serve_connection<R: BufRead, W: Write>(
+        &mut self,
+        mut reader: R,
+        mut writer: W,
+    ) -> std::io::Result<()> {
+        let mut line = String::new();
+        while !self.is_stopping() {
+            match reader.read_line(&mut line) {
+                Ok(0) => return Ok(()),
+                Ok(_) => {
+                    let request = line.trim();
+                    let response = if request.is_empty() {
+                        None
+                    } else {
+                        self.handle_line(request)
+                    };
+                    if let Some(response) = response {
+                        writeln!(writer, "{}", response)?;
+                        writer.flush()?;
+                    }
+                    line.clear();
+                }
+                // a partial line stays in the buffer until the rest arrives
+                Err(e) if is_timeout(&e) => {}
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(())
+    }
+
+    /// Listens on the Unix socket at _socket_path_ and serves its connections one after
+    /// another until the server is stopping. A stale socket file is replaced, any other file at
+    /// the path is an error. The socket file is removed when the server stops.
+    #[cfg(unix)]
+    pub fn serve_unix(&mut self, socket_path: &Path) -> std::io::Result<()> {
+        use std::os::unix::{fs::FileTypeExt, net::UnixListener};
+
+        match std::fs::symlink_metadata(socket_path) {
+            Ok(meta) if meta.file_type().is_socket() => std::fs::remove_file(socket_path)?,
+            Ok(_) => {
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::AlreadyExists,
+                    format!("{} isn't a socket", socket_path.display()),
+                ))
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+            Err(e) => return Err(e),
+        }
+        let listener = UnixListener::bind(socket_path)?;
+        listener.set_nonblocking(true)?;
+        while !self.is_stopping() {
+            match listener.accept() {
+                Ok((stream, _)) => {
+                    stream.set_nonblocking(false)?;
+                    stream.set_read_timeout(Some(STOP_POLL))?;
+                    let reader = std::io::BufReader::new(stream.try_clone()?);
+                    // a client that goes away doesn't stop the server
+                    let _ = self.serve_connection(reader, stream);
+                }
+                Err(e) if is_timeout(&e) => std::thread::sleep(STOP_POLL),
+                Err(e) => return Err(e),
+            }
+        }
+        std::fs::remove_file(socket_path)
+    }
+
+    /// Returns the path relative to the project root as an absolute one
+    fn get_path(&self, path: &Path) -> PathBuf {
+        self.project.get_root_path().join(path)
+    }
+}
+
+fn parse_params<T: serde::de::DeserializeOwned>(params: Value) -> Result<T, RpcError> {
+    // a method without params may be called without them
+    let params = match params {
+        Value::Null => json!({}),
+        params => params,
+    };
+    serde_json::from_value(params).map_err(|e| RpcError::new(INVALID_PARAMS, e.to_string()))
+}
+
+fn is_timeout(e: &std::io::Error) -> bool {
+    matches!(
+        e.kind(),
+        std::io::ErrorKind::WouldBlock | std::io::ErrorKind::TimedOut
+    )
+}
@@ -0,0 +1,60 @@
+//! A store shared by all the projects of a workspace (e.g. every translated repository of an
+//! organization), so their terminology stays consistent. It's a directory set by `shared_store`
+//! in the library config with a glossary and a translation memory, a project's own glossary
+//! entries and translated segments override the shared ones.
+use std::path::{Path, PathBuf};
+
+use crate::{
+    glossary::Glossary,
+    lib_config::{get_lib_config_path, load_lib_config},
+    state::write_json,
+    translation_memory::MEMORY_DIR,
+};
+
+/// Name of the file with the shared glossary, in the store directory
+pub const GLOSSARY_FILE: &str = "glossary.json";
+
+#[derive(Debug, Clone)]
+pub struct SharedStore {
+    root: PathBuf,
+}
+
+impl SharedStore {
+    pub fn new(root: PathBuf) -> Self {
+        Self { root }
+    }
+    /// Opens the store set in the library config, `None` if there's none. A relative path is
+    /// relative to the directory of the library config file.
+    pub fn open() -> Option<Self> {
+        let path = load_lib_config().get_shared_store()?;
+        if path.is_absolute() {
+            return Some(Self::new(path));
+        }
+        let config_dir = get_lib_config_path()?.parent()?.to_path_buf();
+        Some(Self::new(config_dir.join(path)))
+    }
+    pub fn get_root_path(&self) -> &Path {
+        &self.root
+    }
+    pub fn get_glossary_path(&self) -> PathBuf {
+        self.root.join(GLOSSARY_FILE)
+    }
+    /// Returns the directory of the shared translation memory
+    pub fn get_memory_path(&self) -> PathBuf {
+        self.root.join(MEMORY_DIR)
+    }
+    /// Loads the shared glossary, an empty one if the store has no glossary yet
+    pub fn load_glossary(&self) -> std::io::Result<Glossary> {
+        let contents = match std::fs::read_to_string(self.get_glossary_path()) {
+            Ok(c) => c,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Glossary::new()),
+            Err(e) => return Err(e),
+        };
+        serde_json::from_str(&contents)
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))
+    }
+    /// Saves the shared glossary, the file is replaced atomically as other projects may read it
+    pub fn save_glossary(&self, glossary: &Glossary) -> std::io::Result<()> {
+        write_json(&self.get_glossary_path(), glossary)
+    }
+}
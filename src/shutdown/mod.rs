@@ -0,0 +1,110 @@
+//! Graceful shutdown of long operations. Once a shutdown is requested (by SIGINT/SIGTERM or by
+//! [`request_shutdown`]), the translation finishes the chunk in flight, flushes what it has
+//! written and stops with an `Interrupted` error, so the run can be resumed later. A single
+//! project is stopped the same way by cancelling its [`CancellationToken`].
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Once,
+    },
+    time::{Duration, Instant},
+};
+
+pub use tokio_util::sync::CancellationToken;
+
+static SHUTDOWN_REQUESTED: AtomicBool = AtomicBool::new(false);
+static INSTALL_HANDLERS: Once = Once::new();
+
+/// Interval at which a waiting operation checks if it has to stop
+const STOP_POLL: Duration = Duration::from_millis(200);
+
+/// Asks the running operations to stop at the next safe point
+pub fn request_shutdown() {
+    SHUTDOWN_REQUESTED.store(true, Ordering::SeqCst);
+}
+
+/// returns true if a shutdown was requested
+pub fn is_shutdown_requested() -> bool {
+    SHUTDOWN_REQUESTED.load(Ordering::SeqCst)
+}
+
+/// returns true if a shutdown was requested or the _token_ was cancelled
+pub fn is_cancelled(token: Option<&CancellationToken>) -> bool {
+    is_shutdown_requested() || token.is_some_and(|t| t.is_cancelled())
+}
+
+/// Clears a previous request, e.g. before starting a new run in the same process
+pub fn reset() {
+    SHUTDOWN_REQUESTED.store(false, Ordering::SeqCst);
+}
+
+/// Returns the error the operations stop with after a shutdown request
+pub fn interrupted_error() -> std::io::Error {
+    std::io::Error::new(std::io::ErrorKind::Interrupted, "shutdown requested")
+}
+
+/// Sleeps for the _duration_ in short steps, stops with [`interrupted_error`] as soon as a
+/// shutdown is requested or the _token_ is cancelled
+pub fn sleep_cancellable(
+    duration: Duration,
+    token: Option<&CancellationToken>,
+) -> std::io::Result<()> {
+    let end = Instant::now() + duration;
+    loop {
+        let now = Instant::now();
+        if now >= end {
+            return Ok(());
+        }
+        if is_cancelled(token) {
+            return Err(interrupted_error());
+        }
+        std::thread::sleep((end - now).min(STOP_POLL));
+    }
+}
+
+/// Installs handlers of SIGINT and SIGTERM (Ctrl-C on Windows) that request a shutdown. A second
+/// signal received while shutting down exits the process immediately. Calling this function
+/// more than once has no effect.
+pub fn install_signal_handlers() {
+    INSTALL_HANDLERS.call_once(|| {
+        std::thread::spawn(|| {
+            let rt = match tokio::runtime::Builder::new_current_thread()
+                .enable_io()
+                .build()
+            {
+                Ok(rt) => rt,
+                Err(_) => return,
+            };
+            rt.block_on(async {
+                loop {
+                    wait_for_signal().await;
+                    if is_shutdown_requested() {
+                        std::process::exit(130);
+                    }
+                    request_shutdown();
+                }
+            });
+        });
+    });
+}
+
+#[cfg(unix)]
+async fn wait_for_signal() {
+    use tokio::signal::unix::{signal, SignalKind};
+    match signal(SignalKind::terminate()) {
+        Ok(mut sigterm) => {
+            tokio::select! {
+                _ = tokio::signal::ctrl_c() => {}
+                _ = sigterm.recv() => {}
+            }
+        }
+        Err(_) => {
+            let _ = tokio::signal::ctrl_c().await;
+        }
+    }
+}
+
+#[cfg(not(unix))]
+async fn wait_for_signal() {
+    let _ = tokio::signal::ctrl_c().await;
+}
@@ -0,0 +1,67 @@
+//! Backups of the translated files, the previous version of a target file is saved before a
+//! translation overwrites it so a bad run doesn't destroy the prior work. Every file has its own
+//! directory of backups named after their time, only the newest ones are kept.
+use std::path::{Path, PathBuf};
+
+use super::get_state_dir;
+
+/// Name of the directory with the backups, in the state directory
+const BACKUPS_DIR: &str = "backups";
+
+/// Saves the current contents of the file at _path_ as its newest backup and removes the
+/// oldest ones so at most _keep_ remain. Returns the path of the backup, `None` if the file
+/// doesn't exist or _keep_ is 0.
+pub fn backup(project_root: &Path, path: &Path, keep: usize) -> std::io::Result<Option<PathBuf>> {
+    if keep == 0 || !path.is_file() {
+        return Ok(None);
+    }
+    let dir = get_file_backups_dir(project_root, path);
+    std::fs::create_dir_all(&dir)?;
+    let stamp = chrono::Utc::now().format("%Y%m%dT%H%M%S%.3fZ");
+    let backup_path = dir.join(stamp.to_string());
+    std::fs::copy(path, &backup_path)?;
+
+    let names = list(project_root, path)?;
+    for name in names.iter().take(names.len().saturating_sub(keep)) {
+        std::fs::remove_file(dir.join(name))?;
+    }
+    Ok(Some(backup_path))
+}
+
+/// Returns the names of the backups of the file at _path_, the oldest first
+pub fn list(project_root: &Path, path: &Path) -> std::io::Result<Vec<String>> {
+    let mut names = Vec::new();
+    match std::fs::read_dir(get_file_backups_dir(project_root, path)) {
+        Ok(dir) => {
+            for entry in dir {
+                names.push(entry?.file_name().to_string_lossy().into_owned());
+            }
+        }
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+        Err(e) => return Err(e),
+    }
+    names.sort();
+    Ok(names)
+}
+
+/// Writes the backup _name_ of the file at _path_ back to it, the backup is kept
+pub fn restore(project_root: &Path, path: &Path, name: &str) -> std::io::Result<()> {
+    let backup_path = get_file_backups_dir(project_root, path).join(name);
+    if !backup_path.is_file() {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::NotFound,
+            format!("no backup '{}' of {}", name, path.display()),
+        ));
+    }
+    std::fs::copy(backup_path, path)?;
+    Ok(())
+}
+
+fn get_file_backups_dir(project_root: &Path, path: &Path) -> PathBuf {
+    // a file outside of the project is kept under its name
+    let relative = match path.strip_prefix(project_root) {
+        Ok(relative) => relative.to_path_buf(),
+        Err(_) => PathBuf::from(path.file_name().unwrap_or_default()),
+    };
+    get_state_dir(project_root).join(BACKUPS_DIR).join(relative)
+}
@@ -0,0 +1,73 @@
+//! Checkpoints of the translation of the files split into several chunks: the translated chunks
+//! are saved as they arrive, so a run that crashes in the middle of a file continues it from the
+//! first chunk left instead of translating the file from its start.
+use std::path::{Path, PathBuf};
+
+use crate::{helper::content_hash, Language};
+
+use super::{get_state_dir, read_json, write_json};
+
+/// Name of the directory with the checkpoints, in the state directory
+const CHECKPOINTS_DIR: &str = "checkpoints";
+
+#[derive(Debug, Clone, Default, serde::Serialize, serde::Deserialize)]
+/// The chunks of a file translated so far
+pub struct Checkpoint {
+    /// hash of the text the chunks come from, a changed file is translated from its start
+    text_hash: String,
+    /// model that translated the chunks
+    model: String,
+    /// summary of the document sent with the chunks, if any
+    pub summary: Option<String>,
+    /// source and translation of the translated chunks, in order
+    chunks: Vec<(String, String)>,
+}
+
+impl Checkpoint {
+    /// Loads the checkpoint of the translation of _text_ of the file at _path_ into _lang_ by
+    /// _model_, an empty one if there is none or it was made for another text or model
+    pub fn load(project_root: &Path, path: &Path, lang: Language, text: &str, model: &str) -> Self {
+        let text_hash = content_hash(text);
+        read_json(&get_checkpoint_path(project_root, path, lang))
+            .filter(|c: &Checkpoint| c.text_hash == text_hash && c.model == model)
+            .unwrap_or(Checkpoint {
+                text_hash,
+                model: model.to_string(),
+                summary: None,
+                chunks: Vec::new(),
+            })
+    }
+    pub fn save(&self, project_root: &Path, path: &Path, lang: Language) -> std::io::Result<()> {
+        write_json(&get_checkpoint_path(project_root, path, lang), self)
+    }
+    /// Removes the checkpoint of the file, does nothing if there is none
+    pub fn remove(project_root: &Path, path: &Path, lang: Language) -> std::io::Result<()> {
+        match std::fs::remove_file(get_checkpoint_path(project_root, path, lang)) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e),
+            _ => Ok(()),
+        }
+    }
+    /// Returns the translation of the _index_-th chunk if it was translated from the same
+    /// _source_, the chunks after a different one are dropped
+    pub fn get(&mut self, index: usize, source: &str) -> Option<String> {
+        match self.chunks.get(index) {
+            Some((s, translation)) if s == source => Some(translation.clone()),
+            _ => {
+                self.chunks.truncate(index);
+                None
+            }
+        }
+    }
+    /// Records the translation of the next chunk
+    pub fn push(&mut self, source: &str, translation: &str) {
+        self.chunks
+            .push((source.to_string(), translation.to_string()));
+    }
+}
+
+fn get_checkpoint_path(project_root: &Path, path: &Path, lang: Language) -> PathBuf {
+    let relative = path.strip_prefix(project_root).unwrap_or(path);
+    let mut name = relative.as_os_str().to_owned();
+    name.push(format!(".{}.json", lang.get_code()));
+    get_state_dir(project_root).join(CHECKPOINTS_DIR).join(name)
+}
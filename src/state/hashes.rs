@@ -0,0 +1,74 @@
+//! Hashes of the target files written by the project. A target whose contents no longer match
+//! the recorded hash was edited by hand since, it isn't overwritten unless forced.
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+};
+
+use crate::helper::bytes_hash;
+
+use super::{get_state_dir, read_json, write_json};
+
+/// Name of the file with the hashes, in the state directory
+const HASHES_FILE: &str = "written.json";
+
+#[derive(Debug, Clone, Default, serde::Serialize, serde::Deserialize)]
+pub struct WrittenHashes {
+    /// hash of every written file by its path relative to the project root
+    files: BTreeMap<PathBuf, String>,
+}
+
+impl WrittenHashes {
+    /// Loads the hashes of the project at _project_root_, empty if none were recorded
+    pub fn load(project_root: &Path) -> Self {
+        read_json(&get_hashes_path(project_root)).unwrap_or_default()
+    }
+    pub fn save(&self, project_root: &Path) -> std::io::Result<()> {
+        write_json(&get_hashes_path(project_root), self)
+    }
+    /// Records the hash of the current contents of the file at _path_
+    pub fn record(&mut self, project_root: &Path, path: &Path) -> std::io::Result<()> {
+        let hash = bytes_hash(&std::fs::read(path)?);
+        self.files
+            .insert(get_relative_path(project_root, path), hash);
+        Ok(())
+    }
+    /// Forgets the hash of the file at _path_, e.g. once it's a link whose contents follow the
+    /// source
+    pub fn forget(&mut self, project_root: &Path, path: &Path) {
+        self.files.remove(&get_relative_path(project_root, path));
+    }
+    /// Returns true if the file at _path_ was written by the project and its contents changed
+    /// since, a file that is missing or was never recorded isn't modified, nor a symbolic link
+    /// since its contents are the ones of its target
+    pub fn is_modified(&self, project_root: &Path, path: &Path) -> bool {
+        let Some(recorded) = self.files.get(&get_relative_path(project_root, path)) else {
+            return false;
+        };
+        if std::fs::symlink_metadata(path).is_ok_and(|m| m.is_symlink()) {
+            return false;
+        }
+        match std::fs::read(path) {
+            Ok(contents) => bytes_hash(&contents) != *recorded,
+            Err(_) => false,
+        }
+    }
+    /// Returns the files of _paths_ that were modified since the project wrote them
+    pub fn find_modified(&self, project_root: &Path, paths: &[PathBuf]) -> Vec<PathBuf> {
+        paths
+            .iter()
+            .filter(|p| self.is_modified(project_root, p))
+            .cloned()
+            .collect()
+    }
+}
+
+fn get_hashes_path(project_root: &Path) -> PathBuf {
+    get_state_dir(project_root).join(HASHES_FILE)
+}
+
+fn get_relative_path(project_root: &Path, path: &Path) -> PathBuf {
+    path.strip_prefix(project_root)
+        .unwrap_or(path)
+        .to_path_buf()
+}
@@ -0,0 +1,72 @@
+//! Journal of a translation run that stopped before translating all its files (e.g. because its
+//! budget was exhausted or the process crashed), the run is resumed from it with the files left
+//! to translate. It's written when the run starts and updated after every translated file.
+use std::path::{Path, PathBuf};
+
+use crate::Language;
+
+use super::{get_state_dir, read_json, write_json};
+
+/// Name of the directory with the journals, in the state directory
+const JOURNAL_DIR: &str = "journal";
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct RunJournal {
+    pub lang: Language,
+    /// source files left to translate, relative to the project root
+    files: Vec<PathBuf>,
+}
+
+impl RunJournal {
+    /// Creates the journal of the _files_ left to translate into _lang_
+    pub fn new(project_root: &Path, lang: Language, files: &[PathBuf]) -> Self {
+        Self {
+            lang,
+            files: files
+                .iter()
+                .map(|f| f.strip_prefix(project_root).unwrap_or(f).to_path_buf())
+                .collect(),
+        }
+    }
+    /// Loads the journal of the last unfinished run into _lang_, `None` if there is none
+    pub fn load(project_root: &Path, lang: Language) -> Option<Self> {
+        read_json(&get_journal_path(project_root, lang))
+    }
+    pub fn save(&self, project_root: &Path) -> std::io::Result<()> {
+        write_json(&get_journal_path(project_root, self.lang.clone()), self)
+    }
+    /// Removes the journal of _lang_, does nothing if there is none
+    pub fn remove(project_root: &Path, lang: Language) -> std::io::Result<()> {
+        match std::fs::remove_file(get_journal_path(project_root, lang)) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e),
+            _ => Ok(()),
+        }
+    }
+    /// Returns the absolute paths of the files left to translate
+    pub fn get_files(&self, project_root: &Path) -> Vec<PathBuf> {
+        self.files.iter().map(|f| project_root.join(f)).collect()
+    }
+    /// Adds the _files_ to translate that the journal doesn't have yet
+    pub fn add_files(&mut self, project_root: &Path, files: &[PathBuf]) {
+        for file in files {
+            let relative = file.strip_prefix(project_root).unwrap_or(file);
+            if !self.files.iter().any(|f| f == relative) {
+                self.files.push(relative.to_path_buf());
+            }
+        }
+    }
+    /// Removes the _translated_ files from the journal
+    pub fn mark_translated(&mut self, project_root: &Path, translated: &[PathBuf]) {
+        self.files
+            .retain(|f| !translated.contains(&project_root.join(f)));
+    }
+    pub fn is_empty(&self) -> bool {
+        self.files.is_empty()
+    }
+}
+
+fn get_journal_path(project_root: &Path, lang: Language) -> PathBuf {
+    get_state_dir(project_root)
+        .join(JOURNAL_DIR)
+        .join(format!("{}.json", lang.get_code()))
+}
@@ -0,0 +1,36 @@
+//! Internal state of a project that isn't a part of its config (e.g. the chunk boundaries of the
+//! translated files), kept in the [`STATE_DIR`] directory of the project root.
+use std::path::{Path, PathBuf};
+
+use crate::helper::write_atomic;
+
+pub mod backups;
+pub mod checkpoints;
+pub mod hashes;
+pub mod journal;
+pub mod trash;
+pub mod usage;
+
+/// Name of the directory with the internal state, in the project root
+pub const STATE_DIR: &str = ".translate-dir";
+
+/// Returns the path to the state directory of the project
+pub fn get_state_dir(project_root: &Path) -> PathBuf {
+    project_root.join(STATE_DIR)
+}
+
+/// Reads a JSON state file, `None` if it doesn't exist or can't be parsed
+pub fn read_json<T: serde::de::DeserializeOwned>(path: &Path) -> Option<T> {
+    let contents = std::fs::read_to_string(path).ok()?;
+    serde_json::from_str(&contents).ok()
+}
+
+/// Writes a JSON state file, the file is replaced atomically so a crash never leaves a half
+/// written file behind
+pub fn write_json<T: serde::Serialize>(path: &Path, value: &T) -> std::io::Result<()> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let contents = serde_json::to_string_pretty(value).map_err(std::io::Error::other)?;
+    write_atomic(path, contents)
+}
@@ -0,0 +1,201 @@
+//! Trash of the project: the files and directories it removes (a target directory of a removed
+//! language, the untracked files of a sync) are moved there when it is enabled, every removal
+//! into an entry named after its time, so an accidental one can be undone.
+use std::path::{Path, PathBuf};
+
+use super::get_state_dir;
+use crate::fs::FileSystem;
+
+/// Name of the directory with the trash entries, in the state directory
+const TRASH_DIR: &str = "trash";
+
+/// Returns the directory of a new trash entry named after the current time, it is created by
+/// the first move into it
+pub fn new_entry(project_root: &Path) -> PathBuf {
+    let stamp = chrono::Utc::now().format("%Y%m%dT%H%M%S%.3fZ");
+    get_trash_dir(project_root).join(stamp.to_string())
+}
+
+/// Moves the file, directory or link at _path_ into the trash _entry_ at the same path relative
+/// to the project root, returns its new path. The trash must be on the same file system and the
+/// path inside the project.
+pub fn move_into(
+    fs: &dyn FileSystem,
+    project_root: &Path,
+    entry: &Path,
+    path: &Path,
+) -> std::io::Result<PathBuf> {
+    let relative = path.strip_prefix(project_root).map_err(|_| {
+        std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            format!("{} is outside of the project", path.display()),
+        )
+    })?;
+    let new_path = entry.join(relative);
+    if let Some(parent) = new_path.parent() {
+        fs.create_dir_all(parent)?;
+    }
+    move_path(fs, path, &new_path)?;
+    Ok(new_path)
+}
+
+/// Deletes the versions of the _paths_ of the project moved into the trash _entry_, e.g. the
+/// replaced copies that only had to be kept until the sync succeeded. The entry is removed if
+/// nothing else is left in it.
+pub fn forget(
+    fs: &dyn FileSystem,
+    project_root: &Path,
+    entry: &Path,
+    paths: &[PathBuf],
+) -> std::io::Result<()> {
+    for path in paths {
+        let Ok(relative) = path.strip_prefix(project_root) else {
+            continue;
+        };
+        let staged = entry.join(relative);
+        match fs.symlink_metadata(&staged) {
+            Ok(meta) if meta.is_dir => fs.remove_dir_all(&staged)?,
+            Ok(_) => fs.remove_file(&staged)?,
+            Err(_) => {}
+        }
+    }
+    if fs.exists(entry) {
+        remove_empty_dirs(fs, entry)?;
+    }
+    Ok(())
+}
+
+/// Removes the empty directories under _dir_ and _dir_ itself if it's left empty
+fn remove_empty_dirs(fs: &dyn FileSystem, dir: &Path) -> std::io::Result<()> {
+    for path in fs.read_dir(dir)? {
+        if fs.symlink_metadata(&path)?.is_dir {
+            remove_empty_dirs(fs, &path)?;
+        }
+    }
+    remove_if_empty(fs, dir)
+}
+
+/// Renames _from_ to _to_, across file systems (e.g. a target directory on another mount) it's
+/// copied and removed instead
+fn move_path(fs: &dyn FileSystem, from: &Path, to: &Path) -> std::io::Result<()> {
+    match fs.rename(from, to) {
+        Err(e) if e.kind() == std::io::ErrorKind::CrossesDevices => {
+            copy_tree(fs, from, to)?;
+            if fs.symlink_metadata(from)?.is_dir {
+                fs.remove_dir_all(from)
+            } else {
+                fs.remove_file(from)
+            }
+        }
+        res => res,
+    }
+}
+
+/// Copies the file, the link or the directory with everything in it, the files keep their
+/// modification time
+fn copy_tree(fs: &dyn FileSystem, from: &Path, to: &Path) -> std::io::Result<()> {
+    let meta = fs.symlink_metadata(from)?;
+    if meta.is_symlink {
+        return fs.symlink(&fs.read_link(from)?, to);
+    }
+    if !meta.is_dir {
+        fs.copy(from, to)?;
+        return match meta.modified {
+            Some(modified) => fs.set_times(to, meta.accessed, modified),
+            None => Ok(()),
+        };
+    }
+    fs.create_dir_all(to)?;
+    for path in fs.read_dir(from)? {
+        copy_tree(fs, &path, &to.join(path.file_name().unwrap_or_default()))?;
+    }
+    Ok(())
+}
+
+/// Returns the names of the trash entries, the oldest first
+pub fn list(fs: &dyn FileSystem, project_root: &Path) -> std::io::Result<Vec<String>> {
+    let mut entries = Vec::new();
+    match fs.read_dir(&get_trash_dir(project_root)) {
+        Ok(paths) => {
+            for path in paths {
+                let name = path.file_name().unwrap_or_default();
+                entries.push(name.to_string_lossy().into_owned());
+            }
+        }
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+        Err(e) => return Err(e),
+    }
+    entries.sort();
+    Ok(entries)
+}
+
+/// Moves the files and directories of the trash entry _name_ back to where they were removed
+/// from, returns their paths. A path that exists again is left in the entry, the entry is
+/// removed once it is empty. The name must be one of [`list`].
+pub fn restore(
+    fs: &dyn FileSystem,
+    project_root: &Path,
+    name: &str,
+) -> std::io::Result<Vec<PathBuf>> {
+    let entry = get_trash_dir(project_root).join(name);
+    let is_dir = fs.metadata(&entry).is_ok_and(|m| m.is_dir);
+    if !list(fs, project_root)?.iter().any(|n| n == name) || !is_dir {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::NotFound,
+            format!("no trash entry '{}'", name),
+        ));
+    }
+    let mut restored = Vec::new();
+    restore_rec(fs, &entry, &entry, project_root, &mut restored)?;
+    remove_if_empty(fs, &entry)?;
+    Ok(restored)
+}
+
+fn restore_rec(
+    fs: &dyn FileSystem,
+    entry: &Path,
+    dir: &Path,
+    project_root: &Path,
+    restored: &mut Vec<PathBuf>,
+) -> std::io::Result<()> {
+    for path in fs.read_dir(dir)? {
+        let original = project_root.join(path.strip_prefix(entry).unwrap_or(&path));
+        let meta = fs.symlink_metadata(&path)?;
+        if !fs.exists(&original) {
+            if let Some(parent) = original.parent() {
+                fs.create_dir_all(parent)?;
+            }
+            move_path(fs, &path, &original)?;
+            restored.push(original);
+        } else if meta.is_dir && fs.metadata(&original).is_ok_and(|m| m.is_dir) {
+            // the directory was made again, what it had is merged back into it
+            restore_rec(fs, entry, &path, project_root, restored)?;
+            remove_if_empty(fs, &path)?;
+        }
+    }
+    Ok(())
+}
+
+fn remove_if_empty(fs: &dyn FileSystem, dir: &Path) -> std::io::Result<()> {
+    if fs.read_dir(dir)?.is_empty() {
+        fs.remove_dir_all(dir)?;
+    }
+    Ok(())
+}
+
+/// Removes the trash directory if it has no entries left
+pub fn prune(fs: &dyn FileSystem, project_root: &Path) {
+    let _ = remove_if_empty(fs, &get_trash_dir(project_root));
+}
+
+/// Deletes the trash entries for good
+pub fn empty(fs: &dyn FileSystem, project_root: &Path) -> std::io::Result<()> {
+    match fs.remove_dir_all(&get_trash_dir(project_root)) {
+        Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e),
+        _ => Ok(()),
+    }
+}
+
+fn get_trash_dir(project_root: &Path) -> PathBuf {
+    get_state_dir(project_root).join(TRASH_DIR)
+}
@@ -0,0 +1,58 @@
+//! Tokens used by the requests of a project, accumulated across runs. The counts are the ones
+//! reported by the provider, estimated when it reports none.
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+};
+
+use crate::translator::budget::RunUsage;
+
+use super::{get_state_dir, read_json, write_json};
+
+/// Name of the file with the usage, in the state directory
+const USAGE_FILE: &str = "usage.json";
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct ProjectUsage {
+    pub total: RunUsage,
+    /// usage of every model the project was translated with
+    pub models: BTreeMap<String, RunUsage>,
+}
+
+impl ProjectUsage {
+    /// Loads the usage of the project at _project_root_, empty if nothing was recorded
+    pub fn load(project_root: &Path) -> Self {
+        read_json(&get_usage_path(project_root)).unwrap_or_default()
+    }
+    pub fn save(&self, project_root: &Path) -> std::io::Result<()> {
+        write_json(&get_usage_path(project_root), self)
+    }
+    pub fn add(&mut self, model: &str, usage: &RunUsage) {
+        self.total.add(usage);
+        self.models.entry(model.to_string()).or_default().add(usage);
+    }
+    /// Adds the _usage_ of a request to _model_ to the usage recorded for the project. Fails
+    /// without writing if the recorded usage can't be read, it isn't replaced by the request's.
+    pub fn record(project_root: &Path, model: &str, usage: &RunUsage) -> std::io::Result<()> {
+        let path = get_usage_path(project_root);
+        let mut recorded: Self = match std::fs::read_to_string(&path) {
+            Ok(contents) => serde_json::from_str(&contents)
+                .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Self::default(),
+            Err(e) => return Err(e),
+        };
+        recorded.add(model, usage);
+        recorded.save(project_root)
+    }
+    /// Removes the recorded usage of the project
+    pub fn reset(project_root: &Path) -> std::io::Result<()> {
+        match std::fs::remove_file(get_usage_path(project_root)) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e),
+            _ => Ok(()),
+        }
+    }
+}
+
+fn get_usage_path(project_root: &Path) -> PathBuf {
+    get_state_dir(project_root).join(USAGE_FILE)
+}
@@ -0,0 +1,87 @@
+//! A standalone HTML page of a [`ProjectReport`], for people who don't read JSON: a completion
+//! bar per target language and a table of the files per language.
+use crate::helper::xml::escape;
+
+use super::ProjectReport;
+
+const STYLE: &str = "body{font-family:sans-serif;margin:2em;color:#222}\
+table{border-collapse:collapse;margin-bottom:2em}\
+td,th{border:1px solid #ccc;padding:4px 8px;text-align:left}\
+.bar{width:400px;height:18px;background:#eee;display:flex;margin-bottom:4px}\
+.translated{background:#4caf50}.stale{background:#ff9800}.missing{background:#e0e0e0}\
+td.translated,td.stale{color:#fff}";
+
+/// Renders the report as a standalone HTML page
+pub fn render_html(report: &ProjectReport) -> String {
+    let name = escape(&report.name);
+    let mut res = format!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{} translation \
+         status</title>\n<style>{}</style>\n</head>\n<body>\n<h1>{}</h1>\n<p>{} files, {} \
+         translatable. Generated at {}.</p>\n",
+        name,
+        STYLE,
+        name,
+        report.files_count,
+        report.translatable_count,
+        escape(&report.generated_at)
+    );
+
+    res.push_str("<h2>Progress</h2>\n");
+    for counts in &report.counts {
+        let total = (counts.translated + counts.stale + counts.missing).max(1);
+        let percent = |n: usize| n as f64 * 100.0 / total as f64;
+        let lang_name: &str = counts.lang.get_name();
+        res.push_str(&format!(
+            "<h3>{}: {:.0}% up to date</h3>\n<div class=\"bar\">",
+            lang_name,
+            percent(counts.translated)
+        ));
+        for (class, n) in [
+            ("translated", counts.translated),
+            ("stale", counts.stale),
+            ("missing", counts.missing),
+        ] {
+            if n > 0 {
+                res.push_str(&format!(
+                    "<div class=\"{}\" style=\"width:{:.2}%\" title=\"{} {}\"></div>",
+                    class,
+                    percent(n),
+                    n,
+                    class
+                ));
+            }
+        }
+        res.push_str(&format!(
+            "</div>\n<p>{} translated, {} stale, {} missing</p>\n",
+            counts.translated, counts.stale, counts.missing
+        ));
+    }
+
+    res.push_str("<h2>Files</h2>\n<table>\n<tr><th>File</th><th>Translatable</th>");
+    for lang in &report.target_langs {
+        let lang_name: &str = lang.get_name();
+        res.push_str(&format!("<th>{}</th>", lang_name));
+    }
+    res.push_str("</tr>\n");
+    for file in &report.files {
+        res.push_str(&format!(
+            "<tr><td>{}</td><td>{}</td>",
+            escape(&file.path.to_string_lossy()),
+            if file.translatable { "yes" } else { "no" }
+        ));
+        for lang in &report.target_langs {
+            match file.langs.iter().find(|l| l.lang == *lang) {
+                Some(status) => res.push_str(&format!(
+                    "<td class=\"{}\" title=\"{}\">{}</td>",
+                    status.state.get_name(),
+                    escape(status.translated_at.as_deref().unwrap_or_default()),
+                    status.state.get_name()
+                )),
+                None => res.push_str("<td></td>"),
+            }
+        }
+        res.push_str("</tr>\n");
+    }
+    res.push_str("</table>\n</body>\n</html>\n");
+    res
+}
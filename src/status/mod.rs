@@ -0,0 +1,305 @@
+//! Translation status of the files of a project: for every file of the source directory and
+//! every target language, whether its translation (or copy) is up to date, stale or missing. A
+//! translation is stale when the source changed since it was written, i.e. the hash of the source
+//! differs from the one recorded when it was translated, and a copy when its contents differ
+//! from the source.
+//!
+//! [`ProjectStatus`] is the finer view of the translatable files: it compares the source with the
+//! hash recorded when the file was translated and takes the review state into account.
+use std::{
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+
+use crate::{
+    helper::bytes_hash,
+    project_config::{Directory, File, ReviewState},
+    Language,
+};
+
+pub mod html;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FileState {
+    /// the target file was written from the current source
+    Translated,
+    /// the source changed since the target file was written, or the project has no record of
+    /// writing it
+    Stale,
+    /// there's no target file
+    Missing,
+}
+
+impl FileState {
+    pub fn get_name(&self) -> &'static str {
+        match self {
+            FileState::Translated => "translated",
+            FileState::Stale => "stale",
+            FileState::Missing => "missing",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+/// State of the translation of a translatable file, like the states of `git status`
+pub enum TranslationState {
+    /// there's no target file
+    Missing,
+    /// the source changed since the file was translated
+    Stale,
+    /// the translation is of the current source
+    UpToDate,
+    /// the translation is of the current source and it's approved by a reviewer
+    Approved,
+    /// the target file exists but the project has no record of translating it
+    Untracked,
+}
+
+impl TranslationState {
+    pub fn get_name(&self) -> &'static str {
+        match self {
+            TranslationState::Missing => "missing",
+            TranslationState::Stale => "stale",
+            TranslationState::UpToDate => "up-to-date",
+            TranslationState::Approved => "approved",
+            TranslationState::Untracked => "untracked",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+/// State of the translation of a file into a target language
+pub struct TranslationStatus {
+    /// path of the file relative to the source directory
+    pub path: PathBuf,
+    pub lang: Language,
+    pub state: TranslationState,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+/// State of the translations of all the translatable files, one entry per file and target
+/// language
+pub struct ProjectStatus {
+    pub entries: Vec<TranslationStatus>,
+}
+
+impl ProjectStatus {
+    /// Returns the entries in the _state_
+    pub fn get_entries_in(
+        &self,
+        state: TranslationState,
+    ) -> impl Iterator<Item = &TranslationStatus> {
+        self.entries.iter().filter(move |e| e.state == state)
+    }
+    /// returns true if every translation is up to date or approved
+    pub fn is_clean(&self) -> bool {
+        self.entries.iter().all(|e| {
+            matches!(
+                e.state,
+                TranslationState::UpToDate | TranslationState::Approved
+            )
+        })
+    }
+}
+
+/// Returns the state of the translation of the source _file_ into _lang_, _source_ is the
+/// current contents of the source file and _tgt_file_ the path of its translation
+pub fn get_translation_state(
+    file: &File,
+    source: &[u8],
+    tgt_file: &Path,
+    lang: Language,
+) -> TranslationState {
+    if !tgt_file.is_file() {
+        return TranslationState::Missing;
+    }
+    match file.get_metadata(lang.clone()) {
+        None => TranslationState::Untracked,
+        Some(meta) if meta.source_hash != bytes_hash(source) => TranslationState::Stale,
+        Some(_) if file.get_review_state(lang) == Some(ReviewState::Approved) => {
+            TranslationState::Approved
+        }
+        Some(_) => TranslationState::UpToDate,
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+/// State of a file in a target language
+pub struct LangStatus {
+    pub lang: Language,
+    pub state: FileState,
+    /// when the target file was last written, RFC 3339, `None` if it's missing
+    pub translated_at: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct FileStatus {
+    /// path of the file relative to the target directories, the files of an extra source
+    /// directory are under its name
+    pub path: PathBuf,
+    pub translatable: bool,
+    /// one status per target language, in the order of the languages of the project
+    pub langs: Vec<LangStatus>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+/// Number of files in every state for a target language
+pub struct LangCounts {
+    pub lang: Language,
+    pub translated: usize,
+    pub stale: usize,
+    pub missing: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+/// State of a whole project, for CI pipelines and dashboards
+pub struct ProjectReport {
+    pub name: String,
+    /// `None` if the project has no source directory yet
+    pub source_lang: Option<Language>,
+    pub target_langs: Vec<Language>,
+    /// when the report was made, RFC 3339
+    pub generated_at: String,
+    /// number of files of the source directory
+    pub files_count: usize,
+    /// number of translatable files of the source directory
+    pub translatable_count: usize,
+    /// counts of the files in every state, per target language
+    pub counts: Vec<LangCounts>,
+    pub files: Vec<FileStatus>,
+}
+
+impl ProjectReport {
+    pub fn new(
+        name: &str,
+        source_lang: Option<Language>,
+        target_langs: Vec<Language>,
+        files: Vec<FileStatus>,
+    ) -> Self {
+        let counts = target_langs
+            .iter()
+            .map(|lang| {
+                let mut counts = LangCounts {
+                    lang: lang.clone(),
+                    translated: 0,
+                    stale: 0,
+                    missing: 0,
+                };
+                for status in files
+                    .iter()
+                    .flat_map(|f| &f.langs)
+                    .filter(|l| l.lang == *lang)
+                {
+                    match status.state {
+                        FileState::Translated => counts.translated += 1,
+                        FileState::Stale => counts.stale += 1,
+                        FileState::Missing => counts.missing += 1,
+                    }
+                }
+                counts
+            })
+            .collect();
+        Self {
+            name: name.to_string(),
+            source_lang,
+            target_langs,
+            generated_at: chrono::Utc::now().to_rfc3339(),
+            files_count: files.len(),
+            translatable_count: files.iter().filter(|f| f.translatable).count(),
+            counts,
+            files,
+        }
+    }
+}
+
+/// Returns the state of _tgt_file_, the target of a source file whose contents hash to
+/// _source_hash_. _written_from_ is the hash of the source the target was written from, `None`
+/// if the project has no record of it. A target is stale on the same condition as in
+/// [`get_translation_state`].
+pub fn get_lang_status(
+    tgt_file: &Path,
+    lang: Language,
+    source_hash: &str,
+    written_from: Option<&str>,
+) -> LangStatus {
+    let Ok(tgt_modified) = std::fs::metadata(tgt_file).and_then(|m| m.modified()) else {
+        return LangStatus {
+            lang,
+            state: FileState::Missing,
+            translated_at: None,
+        };
+    };
+    let state = if written_from == Some(source_hash) {
+        FileState::Translated
+    } else {
+        FileState::Stale
+    };
+    LangStatus {
+        lang,
+        state,
+        translated_at: Some(format_time(tgt_modified)),
+    }
+}
+
+/// Returns every file of the directory and of its subdirectories, with its translatable flag
+pub fn get_all_files(dir: &Directory) -> Vec<(PathBuf, bool)> {
+    let mut res: Vec<(PathBuf, bool)> = dir
+        .get_files_as_ref()
+        .iter()
+        .map(|f| (f.get_path(), f.is_translatable()))
+        .collect();
+    for sub_dir in dir.get_dirs_as_ref() {
+        res.extend(get_all_files(sub_dir));
+    }
+    res
+}
+
+/// Writes the statuses as CSV: the file, its translatable flag, then the state and the time of
+/// the last translation for every one of the _langs_
+pub fn write_status_csv(statuses: &[FileStatus], langs: &[Language]) -> String {
+    let mut header = vec!["file".to_string(), "translatable".to_string()];
+    for lang in langs {
+        header.push(format!("{}_state", lang.get_code()));
+        header.push(format!("{}_translated_at", lang.get_code()));
+    }
+    let mut res = csv_line(&header);
+    for status in statuses {
+        let mut fields = vec![
+            status.path.to_string_lossy().replace('\\', "/"),
+            status.translatable.to_string(),
+        ];
+        for lang in langs {
+            match status.langs.iter().find(|l| l.lang == *lang) {
+                Some(l) => {
+                    fields.push(l.state.get_name().to_string());
+                    fields.push(l.translated_at.clone().unwrap_or_default());
+                }
+                None => fields.extend([String::new(), String::new()]),
+            }
+        }
+        res.push_str(&csv_line(&fields));
+    }
+    res
+}
+
+/// Joins the fields into a CSV line, the fields with a separator, a quote or a line break are
+/// quoted
+fn csv_line(fields: &[String]) -> String {
+    let quoted: Vec<String> = fields
+        .iter()
+        .map(|f| {
+            if f.contains([',', '"', '\n', '\r']) {
+                format!("\"{}\"", f.replace('"', "\"\""))
+            } else {
+                f.clone()
+            }
+        })
+        .collect();
+    format!("{}\r\n", quoted.join(","))
+}
+
+fn format_time(time: SystemTime) -> String {
+    chrono::DateTime::<chrono::Utc>::from(time).to_rfc3339()
+}
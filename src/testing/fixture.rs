@@ -0,0 +1,297 @@
+//! Temporary projects for the tests of the users of the library: a [`ProjectBuilder`] creates a
+//! project with its source directory, files and languages in a directory of its own, which is
+//! removed when the [`TestProject`] is dropped.
+use std::{
+    ops::{Deref, DerefMut},
+    path::{Component, Path, PathBuf},
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+use crate::{
+    errors::fixture_errors::FixtureError,
+    merge::{get_file_key, save_base, BaseSegment, MergeBase},
+    project::{init, load, Project},
+    Language,
+};
+
+/// Number of the fixtures created by this process, part of their directory names
+static FIXTURE_COUNT: AtomicUsize = AtomicUsize::new(0);
+
+#[derive(Debug, Clone)]
+/// Builder of a [`TestProject`]
+pub struct ProjectBuilder {
+    name: String,
+    src_dir: String,
+    src_lang: Language,
+    /// path relative to the source directory, contents and whether the file is translatable
+    files: Vec<(PathBuf, Vec<u8>, bool)>,
+    /// language with its directory relative to the root, `None` for the default one
+    langs: Vec<(Language, Option<String>)>,
+    /// language, path relative to its directory and contents
+    target_files: Vec<(Language, PathBuf, Vec<u8>)>,
+    /// language, path of the source file relative to the source directory and its translation
+    translations: Vec<(Language, PathBuf, String)>,
+    sync: bool,
+}
+
+impl ProjectBuilder {
+    /// A project named _name_ with an English source directory `src` and no files
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            src_dir: "src".to_string(),
+            src_lang: Language::English,
+            files: Vec::new(),
+            langs: Vec::new(),
+            target_files: Vec::new(),
+            translations: Vec::new(),
+            sync: false,
+        }
+    }
+
+    /// sets the name and the language of the source directory
+    pub fn source(mut self, dir_name: &str, lang: Language) -> Self {
+        self.src_dir = dir_name.to_string();
+        self.src_lang = lang;
+        self
+    }
+
+    /// adds an untranslatable file at _path_ relative to the source directory
+    pub fn file(mut self, path: impl AsRef<Path>, contents: impl AsRef<[u8]>) -> Self {
+        self.files.push((
+            path.as_ref().to_path_buf(),
+            contents.as_ref().to_vec(),
+            false,
+        ));
+        self
+    }
+
+    /// adds a translatable file at _path_ relative to the source directory
+    pub fn translatable_file(mut self, path: impl AsRef<Path>, contents: impl AsRef<[u8]>) -> Self {
+        self.files.push((
+            path.as_ref().to_path_buf(),
+            contents.as_ref().to_vec(),
+            true,
+        ));
+        self
+    }
+
+    /// adds a target language
+    pub fn lang(mut self, lang: Language) -> Self {
+        self.langs.push((lang, None));
+        self
+    }
+
+    /// adds a target language in the directory at _dir_ relative to the project root, see
+    /// [`Project::add_lang_with_dir`]
+    pub fn lang_dir(mut self, lang: Language, dir: &str) -> Self {
+        self.langs.push((lang, Some(dir.to_string())));
+        self
+    }
+
+    /// adds a file at _path_ relative to the directory of the target language _lang_, e.g. an
+    /// existing translation
+    pub fn target_file(
+        mut self,
+        lang: Language,
+        path: impl AsRef<Path>,
+        contents: impl AsRef<[u8]>,
+    ) -> Self {
+        self.target_files.push((
+            lang,
+            path.as_ref().to_path_buf(),
+            contents.as_ref().to_vec(),
+        ));
+        self
+    }
+
+    /// adds the translation into _lang_ of the translatable file at _path_ relative to the
+    /// source directory, as if the whole file was translated into _translation_ in one segment:
+    /// the target file is written with its merge base, see [`Project::merge_file`]
+    pub fn translation(
+        mut self,
+        lang: Language,
+        path: impl AsRef<Path>,
+        translation: &str,
+    ) -> Self {
+        self.translations
+            .push((lang, path.as_ref().to_path_buf(), translation.to_string()));
+        self
+    }
+
+    /// syncs the target directories once the project is built
+    pub fn synced(mut self) -> Self {
+        self.sync = true;
+        self
+    }
+
+    /// Creates the project in a new directory of the temporary directory of the system
+    pub fn build(self) -> Result<TestProject, FixtureError> {
+        let paths = self
+            .files
+            .iter()
+            .map(|(path, _, _)| path)
+            .chain(self.target_files.iter().map(|(_, path, _)| path))
+            .chain(self.translations.iter().map(|(_, path, _)| path));
+        for path in paths {
+            check_relative(path)?;
+        }
+        check_relative(Path::new(&self.src_dir))?;
+
+        let root = create_fixture_dir(&self.name).map_err(FixtureError::IoError)?;
+        match self.build_in(&root) {
+            Ok(project) => Ok(TestProject {
+                project,
+                root,
+                keep: false,
+            }),
+            Err(e) => {
+                let _ = std::fs::remove_dir_all(&root);
+                Err(e)
+            }
+        }
+    }
+
+    fn build_in(&self, root: &Path) -> Result<Project, FixtureError> {
+        let src_path = root.join(&self.src_dir);
+        std::fs::create_dir_all(&src_path).map_err(FixtureError::IoError)?;
+        for (path, contents, _) in &self.files {
+            write_file(&src_path.join(path), contents)?;
+        }
+
+        init(&self.name, root.to_path_buf()).map_err(FixtureError::InitProjectError)?;
+        let mut project = load(root.to_path_buf()).map_err(FixtureError::LoadProjectError)?;
+        project
+            .set_source_dir(&self.src_dir, self.src_lang.clone())
+            .map_err(FixtureError::SetSourceDirError)?;
+        for (path, _, translatable) in &self.files {
+            if *translatable {
+                project
+                    .make_translatable_file(src_path.join(path))
+                    .map_err(FixtureError::AddTranslatableFileError)?;
+            }
+        }
+        for (lang, dir) in &self.langs {
+            match dir {
+                Some(dir) => project.add_lang_with_dir(lang.clone(), dir),
+                None => project.add_lang(lang.clone()),
+            }
+            .map_err(FixtureError::AddLanguageError)?;
+        }
+        let conf = project.get_config_as_ref();
+        for (lang, path, contents) in &self.target_files {
+            let dir = conf
+                .get_tgt_dir_path_by_lang(lang)
+                .ok_or(FixtureError::LangNotInProject(lang.clone()))?;
+            write_file(&dir.join(path), contents)?;
+        }
+        for (lang, path, translation) in &self.translations {
+            let src_file = src_path.join(path);
+            let tgt_file = conf
+                .get_tgt_file_path(&src_file, lang)
+                .ok_or(FixtureError::LangNotInProject(lang.clone()))?;
+            write_file(&tgt_file, translation.as_bytes())?;
+            let source = std::fs::read_to_string(&src_file).map_err(FixtureError::IoError)?;
+            let base = MergeBase {
+                file: get_file_key(root, &src_file).unwrap_or_default(),
+                segments: vec![BaseSegment {
+                    source,
+                    translation: translation.clone(),
+                }],
+            };
+            save_base(root, &base, lang.clone()).map_err(FixtureError::IoError)?;
+        }
+        if self.sync {
+            project
+                .sync_files(false)
+                .map_err(FixtureError::SyncFilesError)?;
+        }
+
+        Ok(project)
+    }
+}
+
+#[derive(Debug)]
+/// A project in a temporary directory, the directory is removed when it is dropped. It derefs
+/// to the [`Project`].
+pub struct TestProject {
+    project: Project,
+    root: PathBuf,
+    keep: bool,
+}
+
+impl TestProject {
+    /// returns the root directory of the project
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// returns the path of _relative_ in the project
+    pub fn path(&self, relative: impl AsRef<Path>) -> PathBuf {
+        self.root.join(relative)
+    }
+
+    /// reads the file at _relative_ in the project
+    pub fn read(&self, relative: impl AsRef<Path>) -> std::io::Result<String> {
+        std::fs::read_to_string(self.path(relative))
+    }
+
+    /// Keeps the directory on the disk and returns its path, e.g. to look into a failed test
+    pub fn keep(mut self) -> PathBuf {
+        self.keep = true;
+        self.root.clone()
+    }
+}
+
+impl Deref for TestProject {
+    type Target = Project;
+    fn deref(&self) -> &Project {
+        &self.project
+    }
+}
+
+impl DerefMut for TestProject {
+    fn deref_mut(&mut self) -> &mut Project {
+        &mut self.project
+    }
+}
+
+impl Drop for TestProject {
+    fn drop(&mut self) {
+        if !self.keep {
+            let _ = std::fs::remove_dir_all(&self.root);
+        }
+    }
+}
+
+fn create_fixture_dir(name: &str) -> std::io::Result<PathBuf> {
+    let count = FIXTURE_COUNT.fetch_add(1, Ordering::Relaxed);
+    let stamp = chrono::Utc::now().timestamp_nanos_opt().unwrap_or_default();
+    let dir = std::env::temp_dir().join(format!(
+        "translate-dir-{}-{}-{}-{}",
+        name,
+        std::process::id(),
+        count,
+        stamp
+    ));
+    std::fs::create_dir_all(&dir)?;
+    std::fs::canonicalize(dir)
+}
+
+fn write_file(path: &Path, contents: &[u8]) -> Result<(), FixtureError> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).map_err(FixtureError::IoError)?;
+    }
+    std::fs::write(path, contents).map_err(FixtureError::IoError)
+}
+
+fn check_relative(path: &Path) -> Result<(), FixtureError> {
+    if path
+        .components()
+        .all(|c| matches!(c, Component::Normal(_) | Component::CurDir))
+    {
+        Ok(())
+    } else {
+        Err(FixtureError::InvalidPath(path.display().to_string()))
+    }
+}
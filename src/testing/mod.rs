@@ -0,0 +1,134 @@
+//! Utilities for users of the library to validate their setup before trusting a full run and
+//! to test their code against a project.
+use std::path::{Path, PathBuf};
+
+use crate::{
+    errors::format_errors::RoundTripError,
+    format::{protect, reinsert, FormatHandler, Segment},
+};
+
+pub mod fixture;
+
+#[derive(Debug, Clone)]
+/// A protected region that didn't survive the round trip
+pub struct RoundTripMismatch {
+    /// index of the protected region in the original file
+    pub region: usize,
+    /// the region as it is in the original file
+    pub expected: String,
+    /// the region found at the same position in the result, `None` if there's none
+    pub found: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+/// Result of a round trip of one sample file
+pub struct RoundTripReport {
+    pub file: PathBuf,
+    /// name of the format handler
+    pub format: String,
+    /// number of protected regions in the original file
+    pub protected_regions: usize,
+    pub mismatches: Vec<RoundTripMismatch>,
+}
+
+impl RoundTripReport {
+    /// returns true if every protected region was preserved byte-for-byte
+    pub fn is_ok(&self) -> bool {
+        self.mismatches.is_empty()
+    }
+}
+
+/// Verifies that the file survives the extract → translate → reinsert round trip: the text with
+/// placeholders is passed to _translate_, the protected regions are put back and the result is
+/// extracted again to compare its protected regions with the original ones byte-for-byte.
+pub fn check_round_trip<F>(
+    handler: &dyn FormatHandler,
+    sample: &Path,
+    mut translate: F,
+) -> Result<RoundTripReport, RoundTripError>
+where
+    F: FnMut(&str) -> String,
+{
+    let contents = std::fs::read_to_string(sample).map_err(RoundTripError::IoError)?;
+
+    let segments = handler.extract(&contents);
+    let concatenated: String = segments.iter().map(|s| s.get_text_as_ref()).collect();
+    if concatenated != contents {
+        return Err(RoundTripError::LossyExtraction);
+    }
+    let expected = protected_regions(&segments);
+
+    let (text, protected) = protect(handler, &contents);
+    let translated = translate(&text);
+    let result = reinsert(&translated, &protected).map_err(RoundTripError::ReinsertError)?;
+    let found = protected_regions(&handler.extract(&result));
+
+    let mismatches = expected
+        .iter()
+        .enumerate()
+        .filter(|(id, region)| found.get(*id) != Some(region))
+        .map(|(id, region)| RoundTripMismatch {
+            region: id,
+            expected: region.clone(),
+            found: found.get(id).cloned(),
+        })
+        .collect();
+
+    Ok(RoundTripReport {
+        file: sample.to_path_buf(),
+        format: handler.name().to_string(),
+        protected_regions: expected.len(),
+        mismatches,
+    })
+}
+
+/// Same as [`check_round_trip`] but uses [`pseudo_translate`] instead of a model, so it can be
+/// run without any API access.
+pub fn check_round_trip_offline(
+    handler: &dyn FormatHandler,
+    sample: &Path,
+) -> Result<RoundTripReport, RoundTripError> {
+    check_round_trip(handler, sample, pseudo_translate)
+}
+
+/// A fake translation that changes every letter outside of the placeholders, good enough to
+/// detect handlers that let protected text through to the model.
+pub fn pseudo_translate(text: &str) -> String {
+    let mut res = String::new();
+    let mut rest = text;
+    while let Some(start) = rest.find("[[") {
+        res.push_str(&swap_case(&rest[..start]));
+        match rest[start..].find("]]") {
+            Some(end) => {
+                res.push_str(&rest[start..start + end + 2]);
+                rest = &rest[start + end + 2..];
+            }
+            None => {
+                res.push_str(&swap_case(&rest[start..]));
+                rest = "";
+            }
+        }
+    }
+    res.push_str(&swap_case(rest));
+    res
+}
+
+fn swap_case(text: &str) -> String {
+    text.chars()
+        .map(|c| {
+            if c.is_uppercase() {
+                c.to_lowercase().next().unwrap_or(c)
+            } else {
+                c.to_uppercase().next().unwrap_or(c)
+            }
+        })
+        .collect()
+}
+
+fn protected_regions(segments: &[Segment]) -> Vec<String> {
+    segments
+        .iter()
+        .filter(|s| s.is_protected())
+        .map(|s| s.get_text_as_ref().to_string())
+        .collect()
+}
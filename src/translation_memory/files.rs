@@ -0,0 +1,53 @@
+//! Storage of the translation memory in JSON files, one per segment under a directory per
+//! target language named after the hash of its source
+use std::path::{Path, PathBuf};
+
+use crate::{
+    helper::content_hash,
+    state::{read_json, write_json},
+    Language,
+};
+
+use super::MemoryEntry;
+
+/// Returns the entry of the segment in the memory at _root_, `None` if there is none or it can't
+/// be read
+pub(super) fn read_entry(root: &Path, source: &str, target_lang: &Language) -> Option<MemoryEntry> {
+    let entry: MemoryEntry = read_json(&get_entry_path(root, source, target_lang))?;
+    // two segments may share a hash
+    (entry.source == source).then_some(entry)
+}
+
+/// Writes the entry to the memory at _root_, replacing the one of the same source
+pub(super) fn write_entry(root: &Path, entry: &MemoryEntry) -> std::io::Result<()> {
+    write_json(
+        &get_entry_path(root, &entry.source, &entry.target_lang),
+        entry,
+    )
+}
+
+/// Reads the entries translated into _target_lang_ in the memory at _root_, the unreadable ones
+/// are skipped
+pub(super) fn read_entries(
+    root: &Path,
+    target_lang: &Language,
+) -> std::io::Result<Vec<MemoryEntry>> {
+    let read_dir = match std::fs::read_dir(root.join(target_lang.get_code())) {
+        Ok(r) => r,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e),
+    };
+    let mut entries = Vec::new();
+    for dir_entry in read_dir {
+        let path = dir_entry?.path();
+        if path.extension().is_some_and(|ext| ext == "json") {
+            entries.extend(read_json::<MemoryEntry>(&path));
+        }
+    }
+    Ok(entries)
+}
+
+fn get_entry_path(root: &Path, source: &str, target_lang: &Language) -> PathBuf {
+    root.join(target_lang.get_code())
+        .join(format!("{}.json", content_hash(source)))
+}
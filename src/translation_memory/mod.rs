@@ -0,0 +1,357 @@
+//! Translation memory of a project: every translated segment (a chunk sent to the model) is
+//! recorded with its source, so an identical segment anywhere in the project is translated from
+//! the memory instead of asking the model again. Unlike the response cache of the translator it
+//! doesn't depend on the context of the segment (e.g. the previous chunk), only on its source,
+//! the languages and the prompt and the glossary it was translated with.
+//!
+//! The memory lives in the [`MEMORY_DIR`] directory of the state directory, one file per segment
+//! under a directory per target language, so concurrent runs never rewrite each other's entries.
+//! With the `sqlite_memory` feature the segments are kept in a SQLite database in that
+//! directory instead. A project using the shared store of its workspace (see [`crate::shared_store`]) also reads
+//! the memory of the store and records its segments there, its own entries override the shared
+//! ones.
+use std::{
+    collections::{hash_map::Entry, BTreeMap, HashMap, HashSet},
+    ops::Range,
+    path::{Path, PathBuf},
+    sync::{Arc, RwLock},
+};
+
+#[cfg(not(feature = "sqlite_memory"))]
+mod files;
+#[cfg(feature = "sqlite_memory")]
+mod sqlite;
+pub mod tmx;
+
+#[cfg(not(feature = "sqlite_memory"))]
+use files as storage;
+#[cfg(feature = "sqlite_memory")]
+use sqlite as storage;
+
+use crate::{state::get_state_dir, translator::chunks::ends_sentence, Language};
+
+/// Name of the directory with the translation memory, in the state directory
+pub const MEMORY_DIR: &str = "memory";
+
+/// Minimal similarity of a near match by default, see [`similarity`]
+pub const DEFAULT_FUZZY_THRESHOLD: f64 = 0.9;
+
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+/// A translated segment
+pub struct MemoryEntry {
+    pub source: String,
+    pub translation: String,
+    /// language of the source, `None` if unknown
+    pub source_lang: Option<Language>,
+    pub target_lang: Language,
+    /// when the segment was translated, RFC 3339
+    pub recorded_at: String,
+    /// hash of the prompt and the glossary the model translated the segment with, `None` for a
+    /// translation made or reviewed by a person (e.g. imported from a TMX file). The segment is
+    /// translated again once they change, see [`TranslationMemory::recall`].
+    #[serde(default)]
+    pub context: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+/// A recorded segment similar to the one being translated
+pub struct FuzzyMatch {
+    pub entry: MemoryEntry,
+    /// similarity of the sources, between 0 and 1
+    pub similarity: f64,
+}
+
+#[derive(Debug, Clone)]
+pub struct TranslationMemory {
+    root: PathBuf,
+    /// directory of the shared memory, `None` if the project doesn't use one
+    shared: Option<PathBuf>,
+}
+
+impl TranslationMemory {
+    /// Opens the translation memory of the project at _project_root_
+    pub fn open(project_root: &Path) -> Self {
+        Self {
+            root: get_state_dir(project_root).join(MEMORY_DIR),
+            shared: None,
+        }
+    }
+    /// Returns the same memory backed by the shared one in the directory _shared_root_
+    pub fn with_shared(self, shared_root: PathBuf) -> Self {
+        Self {
+            shared: Some(shared_root),
+            ..self
+        }
+    }
+    pub fn get_root_path(&self) -> &Path {
+        &self.root
+    }
+    pub fn get_shared_root_path(&self) -> Option<&Path> {
+        self.shared.as_deref()
+    }
+    /// Returns the recorded translation of the segment into _target_lang_, `None` if the segment
+    /// was never translated into it. The project's entry comes before the shared one.
+    pub fn lookup(&self, source: &str, target_lang: Language) -> Option<MemoryEntry> {
+        self.get_roots()
+            .find_map(|root| storage::read_entry(root, source, &target_lang))
+    }
+    /// Returns the translation of the text: the recorded one of the whole text, else the
+    /// translations of its sentences put together. The translator records whole chunks while a
+    /// memory imported from a TMX file usually holds sentences. `None` unless every sentence was
+    /// translated into _target_lang_ by a person or in the current _context_ (see
+    /// [`MemoryEntry::context`]).
+    pub fn recall(&self, text: &str, target_lang: Language, context: &str) -> Option<String> {
+        let lookup = |source: &str| {
+            self.get_roots().find_map(|root| {
+                let entry = storage::read_entry(root, source, &target_lang)?;
+                let is_current = entry.context.as_deref().is_none_or(|c| c == context);
+                is_current.then_some(entry)
+            })
+        };
+        if let Some(entry) = lookup(text) {
+            return Some(entry.translation);
+        }
+        let sentences = get_sentence_ranges(text);
+        // a single sentence is the whole text, it wasn't found
+        if sentences.len() < 2 {
+            return None;
+        }
+        let mut res = String::new();
+        let mut last = 0;
+        for range in sentences {
+            res.push_str(&text[last..range.start]);
+            let entry = lookup(&text[range.clone()])?;
+            res.push_str(&entry.translation);
+            last = range.end;
+        }
+        res.push_str(&text[last..]);
+        Some(res)
+    }
+    /// Records the translation of the segment, replacing the previous one, and returns the
+    /// recorded entry. _context_ is the one of a machine translation, see
+    /// [`MemoryEntry::context`].
+    pub fn record(
+        &self,
+        source: &str,
+        translation: &str,
+        source_lang: Option<Language>,
+        target_lang: Language,
+        context: Option<String>,
+    ) -> std::io::Result<MemoryEntry> {
+        let entry = MemoryEntry {
+            source: source.to_string(),
+            translation: translation.to_string(),
+            source_lang,
+            target_lang,
+            recorded_at: chrono::Utc::now().to_rfc3339(),
+            context,
+        };
+        self.insert(&entry)?;
+        Ok(entry)
+    }
+    /// Adds the entry as it is, replacing the previous translation of its source in the memory
+    /// of the project and in the shared one
+    pub fn insert(&self, entry: &MemoryEntry) -> std::io::Result<()> {
+        for root in self.get_roots() {
+            storage::write_entry(root, entry)?;
+        }
+        Ok(())
+    }
+    /// Returns all the segments translated into _target_lang_, the unreadable entries are skipped.
+    /// A shared segment the project translated itself is returned once, with its translation.
+    pub fn entries(&self, target_lang: Language) -> std::io::Result<Vec<MemoryEntry>> {
+        let mut entries = storage::read_entries(&self.root, &target_lang)?;
+        if let Some(shared) = &self.shared {
+            let own: HashSet<String> = entries.iter().map(|e| e.source.clone()).collect();
+            let shared_entries = storage::read_entries(shared, &target_lang)?;
+            entries.extend(
+                shared_entries
+                    .into_iter()
+                    .filter(|e| !own.contains(&e.source)),
+            );
+        }
+        Ok(entries)
+    }
+    /// Returns the index of the near matches of the segments translated into _target_lang_
+    pub fn index(&self, target_lang: Language) -> std::io::Result<MemoryIndex> {
+        Ok(MemoryIndex::new(self.entries(target_lang)?))
+    }
+    /// Returns at most _limit_ segments translated into _target_lang_ whose source is at least
+    /// _threshold_ similar to _source_ but not identical, the most similar first. The whole
+    /// memory is read, see [`MemoryIndex`] to look for the matches of many segments.
+    pub fn fuzzy_matches(
+        &self,
+        source: &str,
+        target_lang: Language,
+        threshold: f64,
+        limit: usize,
+    ) -> std::io::Result<Vec<FuzzyMatch>> {
+        Ok(self
+            .index(target_lang)?
+            .fuzzy_matches(source, threshold, limit))
+    }
+    /// Removes every segment recorded in the memory of the project, the shared memory is kept for
+    /// the other projects
+    pub fn clear(&self) -> std::io::Result<()> {
+        match std::fs::remove_dir_all(&self.root) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e),
+            _ => Ok(()),
+        }
+    }
+    /// Returns the directory of the memory of the project, then the shared one
+    fn get_roots(&self) -> impl Iterator<Item = &Path> {
+        std::iter::once(self.root.as_path()).chain(self.shared.as_deref())
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+/// The segments translated into a language grouped by the number of words of their source, the
+/// near matches of a segment are only looked for among the sources of a similar length
+pub struct MemoryIndex {
+    by_length: BTreeMap<usize, Vec<MemoryEntry>>,
+}
+
+impl MemoryIndex {
+    /// Indexes the entries, their sources must be unique
+    pub fn new(entries: Vec<MemoryEntry>) -> Self {
+        let mut by_length: BTreeMap<usize, Vec<MemoryEntry>> = BTreeMap::new();
+        for entry in entries {
+            let length = entry.source.split_whitespace().count();
+            by_length.entry(length).or_default().push(entry);
+        }
+        Self { by_length }
+    }
+    /// Adds the entry, replacing the one of the same source
+    pub fn insert(&mut self, entry: MemoryEntry) {
+        let entries = self
+            .by_length
+            .entry(entry.source.split_whitespace().count())
+            .or_default();
+        match entries.iter_mut().find(|e| e.source == entry.source) {
+            Some(e) => *e = entry,
+            None => entries.push(entry),
+        }
+    }
+    /// Returns at most _limit_ entries whose source is at least _threshold_ similar to _source_
+    /// but not identical, the most similar first
+    pub fn fuzzy_matches(&self, source: &str, threshold: f64, limit: usize) -> Vec<FuzzyMatch> {
+        let words: Vec<&str> = source.split_whitespace().collect();
+        // the difference of the lengths alone rules the shorter and the longer sources out
+        let min_length = (threshold * words.len() as f64).ceil() as usize;
+        let mut matches = Vec::new();
+        for (&length, entries) in self.by_length.range(min_length..) {
+            if length > words.len() && (words.len() as f64) < threshold * length as f64 {
+                break;
+            }
+            for entry in entries.iter().filter(|e| e.source != source) {
+                let entry_words: Vec<&str> = entry.source.split_whitespace().collect();
+                let similarity = words_similarity(&words, &entry_words);
+                if similarity >= threshold {
+                    matches.push(FuzzyMatch {
+                        entry: entry.clone(),
+                        similarity,
+                    });
+                }
+            }
+        }
+        matches.sort_by(|a, b| b.similarity.total_cmp(&a.similarity));
+        matches.truncate(limit);
+        matches
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+/// The indexes of the translation memory read by a run, shared by the clones of its
+/// [`crate::translator::TranslationContext`] so the memory of a target language is read once
+/// per run. A new one starts empty.
+pub struct RunIndexes(Arc<RwLock<HashMap<Language, MemoryIndex>>>);
+
+impl RunIndexes {
+    /// Same as [`TranslationMemory::fuzzy_matches`], the memory is read the first time the
+    /// _target_lang_ is looked up
+    pub(crate) fn fuzzy_matches(
+        &self,
+        memory: &TranslationMemory,
+        source: &str,
+        target_lang: Language,
+        threshold: f64,
+        limit: usize,
+    ) -> std::io::Result<Vec<FuzzyMatch>> {
+        if let Some(index) = self
+            .0
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .get(&target_lang)
+        {
+            return Ok(index.fuzzy_matches(source, threshold, limit));
+        }
+        let mut indexes = self.0.write().unwrap_or_else(|e| e.into_inner());
+        let index = match indexes.entry(target_lang.clone()) {
+            Entry::Occupied(e) => e.into_mut(),
+            Entry::Vacant(e) => e.insert(memory.index(target_lang)?),
+        };
+        Ok(index.fuzzy_matches(source, threshold, limit))
+    }
+    /// Adds the entry recorded during the run to the index of its target language if it's read
+    pub(crate) fn insert(&self, entry: MemoryEntry) {
+        let mut indexes = self.0.write().unwrap_or_else(|e| e.into_inner());
+        if let Some(index) = indexes.get_mut(&entry.target_lang) {
+            index.insert(entry);
+        }
+    }
+}
+
+/// Returns the byte ranges of the sentences of the text, without the whitespace around them. A
+/// sentence ends at the end of its line at the latest.
+fn get_sentence_ranges(text: &str) -> Vec<Range<usize>> {
+    let mut res = Vec::new();
+    let mut start = None;
+    let mut end = 0;
+    for (i, c) in text.char_indices() {
+        if !c.is_whitespace() {
+            start.get_or_insert(i);
+            end = i + c.len_utf8();
+            continue;
+        }
+        if let Some(s) = start.filter(|&s| c == '\n' || ends_sentence(&text[s..end])) {
+            res.push(s..end);
+            start = None;
+        }
+    }
+    if let Some(s) = start {
+        res.push(s..end);
+    }
+    res
+}
+
+/// Returns the similarity of two texts between 0 and 1: one minus the edit distance of their
+/// words divided by the number of words of the longer one, the whitespace is ignored
+pub fn similarity(a: &str, b: &str) -> f64 {
+    words_similarity(
+        &a.split_whitespace().collect::<Vec<_>>(),
+        &b.split_whitespace().collect::<Vec<_>>(),
+    )
+}
+
+fn words_similarity(a: &[&str], b: &[&str]) -> f64 {
+    let longest = a.len().max(b.len());
+    if longest == 0 {
+        return 1.0;
+    }
+    1.0 - edit_distance(a, b) as f64 / longest as f64
+}
+
+/// Levenshtein distance of the word sequences
+fn edit_distance(a: &[&str], b: &[&str]) -> usize {
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    let mut current = vec![0; b.len() + 1];
+    for (i, word_a) in a.iter().enumerate() {
+        current[0] = i + 1;
+        for (j, word_b) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(word_a != word_b);
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        std::mem::swap(&mut previous, &mut current);
+    }
+    previous[b.len()]
+}
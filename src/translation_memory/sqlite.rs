@@ -0,0 +1,94 @@
+//! Storage of the translation memory in the SQLite database [`DATABASE_NAME`] of its directory,
+//! enabled by the `sqlite_memory` feature. The segments recorded in JSON files without the
+//! feature aren't read.
+use std::{path::Path, time::Duration};
+
+use rusqlite::{params, Connection, OptionalExtension};
+
+use crate::Language;
+
+use super::MemoryEntry;
+
+/// Name of the database file in the directory of the memory
+const DATABASE_NAME: &str = "memory.sqlite";
+
+/// How long a write waits for the one of a concurrent run
+const BUSY_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Returns the entry of the segment in the memory at _root_, `None` if there is none or it can't
+/// be read
+pub(super) fn read_entry(root: &Path, source: &str, target_lang: &Language) -> Option<MemoryEntry> {
+    let conn = open_existing(root).ok()??;
+    let entry: String = conn
+        .query_row(
+            "SELECT entry FROM segments WHERE target_lang = ?1 AND source = ?2",
+            params![target_lang.get_code(), source],
+            |row| row.get(0),
+        )
+        .optional()
+        .ok()??;
+    serde_json::from_str(&entry).ok()
+}
+
+/// Writes the entry to the memory at _root_, replacing the one of the same source
+pub(super) fn write_entry(root: &Path, entry: &MemoryEntry) -> std::io::Result<()> {
+    let conn = open(root)?;
+    let serialized = serde_json::to_string(entry).map_err(std::io::Error::other)?;
+    conn.execute(
+        "INSERT OR REPLACE INTO segments (target_lang, source, entry) VALUES (?1, ?2, ?3)",
+        params![entry.target_lang.get_code(), entry.source, serialized],
+    )
+    .map_err(std::io::Error::other)?;
+    Ok(())
+}
+
+/// Reads the entries translated into _target_lang_ in the memory at _root_, the unreadable ones
+/// are skipped
+pub(super) fn read_entries(
+    root: &Path,
+    target_lang: &Language,
+) -> std::io::Result<Vec<MemoryEntry>> {
+    let Some(conn) = open_existing(root)? else {
+        return Ok(Vec::new());
+    };
+    let mut statement = conn
+        .prepare("SELECT entry FROM segments WHERE target_lang = ?1")
+        .map_err(std::io::Error::other)?;
+    let rows = statement
+        .query_map(params![target_lang.get_code()], |row| {
+            row.get::<_, String>(0)
+        })
+        .map_err(std::io::Error::other)?;
+    let mut entries = Vec::new();
+    for row in rows {
+        let entry = row.map_err(std::io::Error::other)?;
+        entries.extend(serde_json::from_str::<MemoryEntry>(&entry).ok());
+    }
+    Ok(entries)
+}
+
+/// Opens the database of the memory at _root_, creating it if it doesn't exist
+fn open(root: &Path) -> std::io::Result<Connection> {
+    std::fs::create_dir_all(root)?;
+    let conn = Connection::open(root.join(DATABASE_NAME)).map_err(std::io::Error::other)?;
+    conn.busy_timeout(BUSY_TIMEOUT)
+        .map_err(std::io::Error::other)?;
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS segments (
+            target_lang TEXT NOT NULL,
+            source TEXT NOT NULL,
+            entry TEXT NOT NULL,
+            PRIMARY KEY (target_lang, source)
+        )",
+    )
+    .map_err(std::io::Error::other)?;
+    Ok(conn)
+}
+
+/// Opens the database of the memory at _root_, `None` if nothing was recorded there yet
+fn open_existing(root: &Path) -> std::io::Result<Option<Connection>> {
+    if !root.join(DATABASE_NAME).is_file() {
+        return Ok(None);
+    }
+    open(root).map(Some)
+}
@@ -0,0 +1,153 @@
+//! TMX 1.4 files, the exchange format of translation memories understood by the CAT tools
+//! (OmegaT, Trados...), so the segments translated by a project can be reused in them.
+use crate::{
+    errors::translation_memory_errors::TranslationMemoryError,
+    helper::xml::{escape, get_attribute, next_element, unescape},
+    Language,
+};
+
+use super::MemoryEntry;
+
+/// Name of the tool written in the header of the exported files
+const CREATION_TOOL: &str = "translate-dir-lib";
+
+/// Writes the segments as a TMX document with _src_lang_ as the source language
+pub fn write_tmx(entries: &[MemoryEntry], src_lang: Language, tgt_lang: Language) -> String {
+    let mut res =
+        String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<tmx version=\"1.4\">\n");
+    res.push_str(&format!(
+        "  <header creationtool=\"{}\" creationtoolversion=\"{}\" segtype=\"block\" \
+         o-tmf=\"{}\" adminlang=\"en\" srclang=\"{}\" datatype=\"plaintext\"/>\n",
+        CREATION_TOOL,
+        env!("CARGO_PKG_VERSION"),
+        CREATION_TOOL,
+        src_lang.get_code()
+    ));
+    res.push_str("  <body>\n");
+    for entry in entries {
+        match format_date(&entry.recorded_at) {
+            Some(date) => res.push_str(&format!("    <tu creationdate=\"{}\">\n", date)),
+            None => res.push_str("    <tu>\n"),
+        }
+        for (lang, text) in [
+            (src_lang.clone(), &entry.source),
+            (tgt_lang.clone(), &entry.translation),
+        ] {
+            res.push_str(&format!(
+                "      <tuv xml:lang=\"{}\"><seg>{}</seg></tuv>\n",
+                lang.get_code(),
+                escape(text)
+            ));
+        }
+        res.push_str("    </tu>\n");
+    }
+    res.push_str("  </body>\n</tmx>\n");
+    res
+}
+
+/// Converts an RFC 3339 date to the basic format of TMX, e.g. `20240131T120000Z`
+fn format_date(date: &str) -> Option<String> {
+    let date = chrono::DateTime::parse_from_rfc3339(date).ok()?;
+    Some(
+        date.with_timezone(&chrono::Utc)
+            .format("%Y%m%dT%H%M%SZ")
+            .to_string(),
+    )
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// A translation unit of a TMX file: the same segment in several languages
+pub struct TmxUnit {
+    /// the language code (e.g. `fr-FR`) and the text of every variant
+    pub variants: Vec<(String, String)>,
+    /// when the unit was created, RFC 3339
+    pub created_at: Option<String>,
+}
+
+impl TmxUnit {
+    /// Returns the text of the variant in _lang_, `None` if the unit has none
+    pub fn get_variant(&self, lang: &Language) -> Option<&str> {
+        self.variants
+            .iter()
+            .find(|(code, _)| lang.has_code(code))
+            .map(|(_, text)| text.as_str())
+    }
+}
+
+/// Reads the translation units of a TMX document. The inline codes of the segments (`<bpt>`,
+/// `<ph>`...) are left out, only their text is kept.
+pub fn parse_tmx(text: &str) -> Result<Vec<TmxUnit>, TranslationMemoryError> {
+    let start = text
+        .find("<tmx")
+        .ok_or_else(|| TranslationMemoryError::InvalidTmx("no <tmx> element".into()))?;
+    let mut units = Vec::new();
+    let mut rest = &text[start..];
+    while let Some(unit) = next_element(rest, "tu").map_err(TranslationMemoryError::InvalidTmx)? {
+        let mut variants = Vec::new();
+        let mut tuvs = unit.inner;
+        while let Some(tuv) =
+            next_element(tuvs, "tuv").map_err(TranslationMemoryError::InvalidTmx)?
+        {
+            // TMX 1.1 used `lang` instead of `xml:lang`
+            let lang = get_attribute(tuv.attributes, "xml:lang")
+                .or_else(|| get_attribute(tuv.attributes, "lang"));
+            if let (Some(lang), Some(seg)) = (
+                lang,
+                next_element(tuv.inner, "seg").map_err(TranslationMemoryError::InvalidTmx)?,
+            ) {
+                variants.push((lang, get_segment_text(seg.inner)));
+            }
+            tuvs = tuv.rest;
+        }
+        units.push(TmxUnit {
+            variants,
+            created_at: get_attribute(unit.attributes, "creationdate").and_then(|d| parse_date(&d)),
+        });
+        rest = unit.rest;
+    }
+    Ok(units)
+}
+
+/// Inline elements that hold the native codes of the original format, not text
+const CODE_ELEMENTS: [&str; 5] = ["bpt", "ept", "it", "ph", "ut"];
+
+/// Returns the text of a segment without its inline markup
+fn get_segment_text(seg: &str) -> String {
+    let mut res = String::new();
+    let mut rest = seg;
+    while let Some(id) = rest.find('<') {
+        res.push_str(&unescape(&rest[..id]));
+        rest = &rest[id..];
+        if let Some(cdata) = rest.strip_prefix("<![CDATA[") {
+            let end = cdata.find("]]>").unwrap_or(cdata.len());
+            res.push_str(&cdata[..end]);
+            rest = cdata.get(end + 3..).unwrap_or("");
+            continue;
+        }
+        let Some(tag_end) = rest.find('>') else {
+            rest = "";
+            break;
+        };
+        let tag = &rest[1..tag_end];
+        let name = tag
+            .split(|c: char| c.is_whitespace() || c == '/')
+            .next()
+            .unwrap_or_default();
+        rest = &rest[tag_end + 1..];
+        if !tag.ends_with('/') && CODE_ELEMENTS.contains(&name) {
+            let close = format!("</{}>", name);
+            rest = rest
+                .find(&close)
+                .map(|id| &rest[id + close.len()..])
+                .unwrap_or("");
+        }
+    }
+    res.push_str(&unescape(rest));
+    res
+}
+
+/// Converts a date of the basic format of TMX (e.g. `20240131T120000Z`) to RFC 3339
+fn parse_date(date: &str) -> Option<String> {
+    let date = chrono::NaiveDateTime::parse_from_str(date, "%Y%m%dT%H%M%SZ").ok()?;
+    Some(date.and_utc().to_rfc3339())
+}
@@ -0,0 +1,294 @@
+//! Support of the Gemini batch API: many requests are submitted as one job that is processed
+//! asynchronously by the provider at a reduced price, the job is polled until it's done.
+use std::{
+    collections::HashMap,
+    time::{Duration, Instant},
+};
+
+use serde_json::{json, Value};
+
+use google_genai::datatypes::GenerationConfig;
+
+use crate::{
+    errors::translator_errors::BatchError,
+    metrics,
+    shutdown::{interrupted_error, is_cancelled, CancellationToken},
+};
+
+use super::{budget::RunUsage, timeout_error, Message};
+
+pub(crate) const BASE_URL: &str = "https://generativelanguage.googleapis.com/v1beta";
+/// Header the API key is sent in, a key in the URL would end up in the errors and the logs
+pub(crate) const API_KEY_HEADER: &str = "x-goog-api-key";
+
+/// Time between two checks of the state of a job
+pub const POLL_INTERVAL: Duration = Duration::from_secs(30);
+
+#[derive(Debug, Clone)]
+/// A single request of a batch job
+pub struct BatchItem {
+    /// unique key the result is returned under
+    pub key: String,
+    /// message sent to the model
+    pub message: Message,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// State of a batch job
+pub enum BatchState {
+    Pending,
+    Running,
+    Succeeded,
+    Failed,
+    Cancelled,
+    Expired,
+    Unknown(String),
+}
+
+impl BatchState {
+    fn from_api(state: &str) -> Self {
+        match state {
+            "BATCH_STATE_PENDING" | "JOB_STATE_PENDING" => BatchState::Pending,
+            "BATCH_STATE_RUNNING" | "JOB_STATE_RUNNING" => BatchState::Running,
+            "BATCH_STATE_SUCCEEDED" | "JOB_STATE_SUCCEEDED" => BatchState::Succeeded,
+            "BATCH_STATE_FAILED" | "JOB_STATE_FAILED" => BatchState::Failed,
+            "BATCH_STATE_CANCELLED" | "JOB_STATE_CANCELLED" => BatchState::Cancelled,
+            "BATCH_STATE_EXPIRED" | "JOB_STATE_EXPIRED" => BatchState::Expired,
+            other => BatchState::Unknown(other.to_string()),
+        }
+    }
+    /// returns true if the job won't change its state anymore
+    pub fn is_finished(&self) -> bool {
+        !matches!(
+            self,
+            BatchState::Pending | BatchState::Running | BatchState::Unknown(_)
+        )
+    }
+}
+
+/// Submits the items as one batch job and returns the name of the job
+pub async fn submit_batch(
+    api_key: &str,
+    model: &str,
+    generation_config: Option<&GenerationConfig>,
+    display_name: &str,
+    items: &[BatchItem],
+) -> Result<String, BatchError> {
+    let requests: Vec<Value> = items
+        .iter()
+        .map(|item| {
+            let mut request = json!({
+                "contents": [{ "role": "user", "parts": [{ "text": item.message.contents }] }]
+            });
+            if let Some(instruction) = &item.message.system_instruction {
+                request["system_instruction"] = json!({ "parts": [{ "text": instruction }] });
+            }
+            if let Some(config) = generation_config {
+                request["generation_config"] = json!(config);
+            }
+            json!({
+                "request": request,
+                "metadata": { "key": item.key }
+            })
+        })
+        .collect();
+    let body = json!({
+        "batch": {
+            "display_name": display_name,
+            "input_config": { "requests": { "requests": requests } }
+        }
+    });
+
+    let url = format!("{}/models/{}:batchGenerateContent", BASE_URL, model);
+    let request = reqwest::Client::new().post(url).json(&body);
+    let response = send(request.header(API_KEY_HEADER, api_key)).await?;
+    response
+        .get("name")
+        .and_then(|n| n.as_str())
+        .map(|n| n.to_string())
+        .ok_or_else(|| BatchError::UnexpectedResponse(response.to_string()))
+}
+
+#[derive(Debug, Clone, Default)]
+/// Answers of a finished batch job
+pub struct BatchResults {
+    /// raw text answers by the keys of the items
+    pub answers: HashMap<String, String>,
+    /// tokens the provider reported for the answers, zero if it reported none
+    pub usage: RunUsage,
+}
+
+/// Returns the state of the job and, if it succeeded, its answers
+pub async fn poll_batch(
+    api_key: &str,
+    name: &str,
+) -> Result<(BatchState, BatchResults), BatchError> {
+    let url = format!("{}/{}", BASE_URL, name);
+    let request = reqwest::Client::new().get(url);
+    let response = send(request.header(API_KEY_HEADER, api_key)).await?;
+
+    let state = field(&response, &["metadata", "state"])
+        .and_then(|s| s.as_str())
+        .map(BatchState::from_api)
+        .unwrap_or(BatchState::Unknown(String::new()));
+    if state != BatchState::Succeeded {
+        return Ok((state, BatchResults::default()));
+    }
+
+    let mut res = BatchResults::default();
+    let inlined = field(
+        &response,
+        &["response", "inlinedResponses", "inlinedResponses"],
+    )
+    .or_else(|| {
+        field(
+            &response,
+            &["response", "inlined_responses", "inlined_responses"],
+        )
+    })
+    .and_then(|v| v.as_array())
+    .ok_or_else(|| BatchError::UnexpectedResponse(response.to_string()))?;
+    for entry in inlined {
+        let key = match field(entry, &["metadata", "key"]).and_then(|k| k.as_str()) {
+            Some(k) => k.to_string(),
+            None => continue,
+        };
+        let text = field(entry, &["response", "candidates"])
+            .and_then(|c| c.get(0))
+            .and_then(|c| field(c, &["content", "parts"]))
+            .and_then(|p| p.get(0))
+            .and_then(|p| p.get("text"))
+            .and_then(|t| t.as_str());
+        if let Some(text) = text {
+            res.answers.insert(key, text.to_string());
+        }
+        let metadata = field(entry, &["response", "usageMetadata"])
+            .or_else(|| field(entry, &["response", "usage_metadata"]));
+        if let Some(metadata) = metadata {
+            res.usage.add(&get_usage(metadata));
+        }
+    }
+    Ok((state, res))
+}
+
+/// Submits the items, waits for the job to finish and returns its answers. Fails if an item has
+/// no answer, stops waiting with an `Interrupted` error once the _cancel_ token is cancelled.
+/// Every request to the provider fails with a `TimedOut` error after the _timeout_, if any.
+pub fn run_batch(
+    api_key: &str,
+    model: &str,
+    generation_config: Option<GenerationConfig>,
+    display_name: &str,
+    items: &[BatchItem],
+    cancel: Option<&CancellationToken>,
+    timeout: Option<Duration>,
+) -> Result<BatchResults, BatchError> {
+    let _span = tracing::info_span!("batch", model, items = items.len()).entered();
+    let rt = tokio::runtime::Runtime::new().map_err(BatchError::IoError)?;
+    rt.block_on(async {
+        let name = with_timeout(
+            timeout,
+            submit_batch(
+                api_key,
+                model,
+                generation_config.as_ref(),
+                display_name,
+                items,
+            ),
+        )
+        .await?;
+        metrics::record_batch_job();
+        tracing::info!(job = %name, "batch job submitted");
+        loop {
+            let (state, results) = with_timeout(timeout, poll_batch(api_key, &name)).await?;
+            tracing::debug!(job = %name, state = ?state, "batch job polled");
+            if state == BatchState::Succeeded {
+                if let Some(item) = items.iter().find(|i| !results.answers.contains_key(&i.key)) {
+                    return Err(BatchError::MissingResult(item.key.clone()));
+                }
+                tracing::info!(
+                    job = %name,
+                    input_tokens = results.usage.input_tokens,
+                    output_tokens = results.usage.output_tokens,
+                    "batch job succeeded"
+                );
+                return Ok(results);
+            }
+            if state.is_finished() {
+                return Err(BatchError::JobNotSucceeded(format!("{:?}", state)));
+            }
+            if is_cancelled(cancel) {
+                // the job keeps running on the provider's side, only the waiting stops
+                return Err(BatchError::IoError(interrupted_error()));
+            }
+            match cancel {
+                Some(token) => {
+                    tokio::select! {
+                        _ = token.cancelled() => {}
+                        _ = tokio::time::sleep(POLL_INTERVAL) => {}
+                    }
+                }
+                None => tokio::time::sleep(POLL_INTERVAL).await,
+            }
+        }
+    })
+}
+
+/// Runs the request to the provider within the _timeout_, if any
+async fn with_timeout<T>(
+    timeout: Option<Duration>,
+    request: impl std::future::Future<Output = Result<T, BatchError>>,
+) -> Result<T, BatchError> {
+    match timeout {
+        Some(timeout) => tokio::time::timeout(timeout, request)
+            .await
+            .map_err(|_| BatchError::IoError(timeout_error("the request to the provider")))?,
+        None => request.await,
+    }
+}
+
+/// Returns the tokens of an answer from its usage metadata
+fn get_usage(metadata: &Value) -> RunUsage {
+    let count = |names: &[&str]| {
+        names
+            .iter()
+            .find_map(|n| metadata.get(*n).and_then(|c| c.as_u64()))
+            .unwrap_or_default()
+    };
+    RunUsage {
+        requests: 1,
+        input_tokens: count(&["promptTokenCount", "prompt_token_count"]),
+        output_tokens: count(&["candidatesTokenCount", "candidates_token_count"]),
+    }
+}
+
+async fn send(request: reqwest::RequestBuilder) -> Result<Value, BatchError> {
+    let start = Instant::now();
+    let res = send_request(request).await;
+    metrics::record_api_call(start, res.is_ok());
+    res
+}
+
+async fn send_request(request: reqwest::RequestBuilder) -> Result<Value, BatchError> {
+    let response = request
+        .send()
+        .await
+        .map_err(|e| BatchError::RequestError(e.without_url().to_string()))?;
+    let status = response.status();
+    let text = response
+        .text()
+        .await
+        .map_err(|e| BatchError::RequestError(e.without_url().to_string()))?;
+    if !status.is_success() {
+        return Err(BatchError::RemoteError {
+            status: status.as_u16(),
+            message: text,
+        });
+    }
+    serde_json::from_str(&text).map_err(|_| BatchError::UnexpectedResponse(text))
+}
+
+/// Follows the path of keys in the JSON value
+fn field<'a>(value: &'a Value, path: &[&str]) -> Option<&'a Value> {
+    path.iter().try_fold(value, |v, key| v.get(key))
+}
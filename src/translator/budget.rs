@@ -0,0 +1,144 @@
+//! Spending cap of a translation run. The tokens of every request (as reported by the provider,
+//! estimated if it reports none) are added to the usage of the run, a request that would take it
+//! over the budget isn't sent and the run stops with a [`BudgetExceeded`] error instead.
+use std::sync::{Arc, Mutex};
+
+use google_genai::datatypes::GenerateContentResponseUsageMetadata;
+
+use crate::{
+    estimate::{cost::ModelPricing, estimate_tokens},
+    translator::{Message, TranslationContext},
+};
+
+#[derive(Debug, Clone, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+/// Limits of the spending of a run, `None` disables a limit
+pub struct RunBudget {
+    /// maximal number of tokens sent to and received from the model
+    #[serde(default)]
+    pub max_tokens: Option<u64>,
+    /// maximal cost in US dollars, ignored if the model has no known rates
+    #[serde(default)]
+    pub max_cost: Option<f64>,
+}
+
+impl RunBudget {
+    pub fn is_limited(&self) -> bool {
+        self.max_tokens.is_some() || self.max_cost.is_some()
+    }
+    /// Returns true if the _usage_ is within the limits
+    pub fn allows(&self, usage: &RunUsage, pricing: Option<&ModelPricing>) -> bool {
+        if self
+            .max_tokens
+            .is_some_and(|max| usage.get_total_tokens() > max)
+        {
+            return false;
+        }
+        match (self.max_cost, pricing) {
+            (Some(max), Some(pricing)) => usage.get_cost(pricing) <= max,
+            _ => true,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+/// Tokens used by the requests of a run
+pub struct RunUsage {
+    pub requests: u64,
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+}
+
+impl RunUsage {
+    pub fn get_total_tokens(&self) -> u64 {
+        self.input_tokens + self.output_tokens
+    }
+    pub fn get_cost(&self, pricing: &ModelPricing) -> f64 {
+        pricing.get_cost(self.input_tokens, self.output_tokens)
+    }
+    pub fn add(&mut self, other: &RunUsage) {
+        self.requests += other.requests;
+        self.input_tokens += other.input_tokens;
+        self.output_tokens += other.output_tokens;
+    }
+}
+
+#[derive(Debug, Clone, Copy, thiserror::Error)]
+#[error("the budget of the run is exhausted after {} tokens", usage.get_total_tokens())]
+/// The error a request that would exceed the budget fails with, wrapped in an `io::Error`
+pub struct BudgetExceeded {
+    /// usage of the run when it stopped
+    pub usage: RunUsage,
+}
+
+#[derive(Debug, Clone, Default)]
+/// The usage of a run, shared by the clones of its [`TranslationContext`]. A new meter starts
+/// a new run at zero, the runs of two projects (or two threads) don't add up.
+pub struct UsageMeter(Arc<Mutex<RunUsage>>);
+
+impl UsageMeter {
+    /// Returns the usage of the run so far
+    pub fn get(&self) -> RunUsage {
+        *self.0.lock().unwrap_or_else(|e| e.into_inner())
+    }
+    /// Adds the tokens of a request to the usage of the run and calls _save_ before the next
+    /// request is recorded, the requests of the run sent in parallel save one after the other
+    pub(crate) fn record<T>(&self, usage: &RunUsage, save: impl FnOnce() -> T) -> T {
+        let mut run = self.0.lock().unwrap_or_else(|e| e.into_inner());
+        run.add(usage);
+        save()
+    }
+}
+
+/// Fails with [`BudgetExceeded`] if sending the _planned_ requests would exceed the budget of
+/// the context
+pub(crate) fn check(ctx: &TranslationContext, planned: &RunUsage) -> std::io::Result<()> {
+    let budget = &ctx.options.budget;
+    if !budget.is_limited() {
+        return Ok(());
+    }
+    let usage = ctx.usage.get();
+    let mut after = usage;
+    after.add(planned);
+    if budget.allows(&after, ctx.pricing.as_ref()) {
+        return Ok(());
+    }
+    Err(std::io::Error::other(BudgetExceeded { usage }))
+}
+
+/// Returns true if the error is a [`BudgetExceeded`] one
+pub fn is_budget_exceeded(e: &std::io::Error) -> bool {
+    e.get_ref()
+        .is_some_and(|inner| inner.is::<BudgetExceeded>())
+}
+
+/// Returns the usage the provider reported for a request, `None` if it reported none
+pub(crate) fn from_metadata(metadata: &GenerateContentResponseUsageMetadata) -> Option<RunUsage> {
+    if metadata.prompt_token_count.is_none() && metadata.candidates_token_count.is_none() {
+        return None;
+    }
+    Some(RunUsage {
+        requests: 1,
+        input_tokens: metadata.prompt_token_count.unwrap_or_default().max(0) as u64,
+        output_tokens: metadata.candidates_token_count.unwrap_or_default().max(0) as u64,
+    })
+}
+
+/// Returns the estimated usage of sending the message, the answer is estimated as long as the
+/// text to translate
+pub(crate) fn plan_message(message: &Message, contents: &str) -> RunUsage {
+    RunUsage {
+        requests: 1,
+        input_tokens: estimate_message_tokens(message),
+        output_tokens: estimate_tokens(contents),
+    }
+}
+
+/// Returns the estimated number of tokens of the message
+pub(crate) fn estimate_message_tokens(message: &Message) -> u64 {
+    estimate_tokens(&message.contents)
+        + message
+            .system_instruction
+            .as_deref()
+            .map(estimate_tokens)
+            .unwrap_or_default()
+}
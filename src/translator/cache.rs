@@ -0,0 +1,86 @@
+//! Cache of the translated chunks in the state directory of the project. A chunk is keyed by its
+//! text, the target language, the model and the whole message sent for it, so rerunning an
+//! interrupted translation or translating identical content doesn't ask the model again.
+use std::path::{Path, PathBuf};
+
+use crate::{helper::content_hash, metrics, state, Language};
+
+use super::{Message, TranslationContext};
+
+/// Name of the cache directory in the state directory
+const CACHE_DIR: &str = "cache";
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+/// A cached translation of a chunk
+pub struct CacheEntry {
+    /// model that translated the chunk
+    pub model: String,
+    pub translation: String,
+}
+
+/// Returns the path to the cache directory of the project
+pub fn get_cache_dir(project_root: &Path) -> PathBuf {
+    state::get_state_dir(project_root).join(CACHE_DIR)
+}
+
+/// Removes all the cached translations of the project
+pub fn clear_cache(project_root: &Path) -> std::io::Result<()> {
+    match std::fs::remove_dir_all(get_cache_dir(project_root)) {
+        Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e),
+        _ => Ok(()),
+    }
+}
+
+/// Returns the key of the translation of the _chunk_ sent as the _message_, the message carries
+/// the prompt, the glossary and the context of the chunk
+pub(crate) fn cache_key(
+    chunk: &str,
+    tgt_lang: &Language,
+    ctx: &TranslationContext,
+    message: &Message,
+) -> String {
+    let prompt = format!(
+        "{}\0{}\0{}",
+        ctx.model,
+        message.system_instruction.as_deref().unwrap_or_default(),
+        message.contents
+    );
+    format!(
+        "{}-{}-{}",
+        content_hash(chunk),
+        tgt_lang.get_code(),
+        content_hash(&prompt)
+    )
+}
+
+/// Returns the cached translation of the key, `None` if there is none or the cache is disabled
+pub(crate) fn lookup(ctx: &TranslationContext, key: &str) -> Option<String> {
+    let root = get_cache_root(ctx)?;
+    let entry: Option<CacheEntry> = state::read_json(&get_entry_path(root, key));
+    metrics::record_cache_lookup(entry.is_some());
+    entry.map(|e| e.translation)
+}
+
+/// Caches the translation under the key, does nothing if the cache is disabled
+pub(crate) fn store(ctx: &TranslationContext, key: &str, translation: &str) -> std::io::Result<()> {
+    let Some(root) = get_cache_root(ctx) else {
+        return Ok(());
+    };
+    let entry = CacheEntry {
+        model: ctx.model.clone(),
+        translation: translation.to_string(),
+    };
+    state::write_json(&get_entry_path(root, key), &entry)
+}
+
+/// Returns the project root if the cache is enabled for the context
+fn get_cache_root(ctx: &TranslationContext) -> Option<&Path> {
+    if ctx.options.no_cache {
+        return None;
+    }
+    ctx.project_root.as_deref()
+}
+
+fn get_entry_path(project_root: &Path, key: &str) -> PathBuf {
+    get_cache_dir(project_root).join(format!("{}.json", key))
+}
@@ -0,0 +1,542 @@
+//! Splitting of the texts into the chunks sent to the model. The boundaries of the chunks of
+//! every translated file are persisted, so a rerun on a partly changed file splits the unchanged
+//! parts exactly as before and only the changed parts get new boundaries.
+use std::{
+    collections::HashMap,
+    path::Path,
+    sync::{Arc, OnceLock, RwLock},
+};
+
+use crate::{format::handler_for_path, helper::content_hash, state};
+
+use super::TranslationContext;
+
+/// Name of the file with the boundaries in the state directory
+const CHUNKS_FILE: &str = "chunks.json";
+
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+/// A chunk of a previous run
+pub struct ChunkBoundary {
+    /// number of lines of the chunk
+    pub lines: usize,
+    /// hash of the text of the chunk
+    pub hash: String,
+}
+
+/// A way to split a text into the chunks sent to the model. The built-in strategies are
+/// [`LineChunker`], [`MarkdownChunker`], [`LatexChunker`] and [`SentenceChunker`], others can be
+/// added with [`register_chunking_strategy`] and selected per file extension in the
+/// [`super::TranslatorOptions`].
+pub trait ChunkingStrategy: Send + Sync {
+    /// name the strategy is selected by in the config
+    fn name(&self) -> &str;
+    /// Splits the lines (with their line ends) into chunks of about _lines_per_chunk_ lines.
+    /// Concatenating the chunks must give back the lines.
+    fn split(&self, lines: &[&str], lines_per_chunk: usize) -> Vec<String>;
+}
+
+/// Cuts after any line, code blocks are kept whole
+pub struct LineChunker;
+
+impl ChunkingStrategy for LineChunker {
+    fn name(&self) -> &str {
+        "lines"
+    }
+    fn split(&self, lines: &[&str], lines_per_chunk: usize) -> Vec<String> {
+        split_by_rank(lines, lines_per_chunk, &get_cuttable_lines(lines), |_| 0)
+    }
+}
+
+/// Cuts preferably before a heading, then between paragraphs, then between list items, so the
+/// sentences and the lists stay in one chunk. Code blocks are kept whole.
+pub struct MarkdownChunker;
+
+impl ChunkingStrategy for MarkdownChunker {
+    fn name(&self) -> &str {
+        "markdown"
+    }
+    fn split(&self, lines: &[&str], lines_per_chunk: usize) -> Vec<String> {
+        split_by_rank(lines, lines_per_chunk, &get_cuttable_lines(lines), |i| {
+            markdown_cut_rank(lines, i)
+        })
+    }
+}
+
+/// Cuts preferably before a sectioning command, then between paragraphs, never inside of an
+/// environment or a display math block, so math and figures stay whole
+pub struct LatexChunker;
+
+impl ChunkingStrategy for LatexChunker {
+    fn name(&self) -> &str {
+        "latex"
+    }
+    fn split(&self, lines: &[&str], lines_per_chunk: usize) -> Vec<String> {
+        split_by_rank(
+            lines,
+            lines_per_chunk,
+            &get_latex_cuttable_lines(lines),
+            |i| latex_cut_rank(lines, i),
+        )
+    }
+}
+
+/// Cuts preferably between paragraphs, then after a line ending a sentence, so the sentences of
+/// plain texts aren't cut at the seams of the chunks
+pub struct SentenceChunker;
+
+impl ChunkingStrategy for SentenceChunker {
+    fn name(&self) -> &str {
+        "sentences"
+    }
+    fn split(&self, lines: &[&str], lines_per_chunk: usize) -> Vec<String> {
+        split_by_rank(lines, lines_per_chunk, &get_cuttable_lines(lines), |i| {
+            sentence_cut_rank(lines, i)
+        })
+    }
+}
+
+/// The strategies that can be selected in the config, by their names
+static STRATEGIES: OnceLock<RwLock<Vec<Arc<dyn ChunkingStrategy>>>> = OnceLock::new();
+
+fn get_strategies() -> &'static RwLock<Vec<Arc<dyn ChunkingStrategy>>> {
+    STRATEGIES.get_or_init(|| {
+        RwLock::new(vec![
+            Arc::new(LineChunker),
+            Arc::new(MarkdownChunker),
+            Arc::new(LatexChunker),
+            Arc::new(SentenceChunker),
+        ])
+    })
+}
+
+/// Registers the strategy so it can be selected by its name in the config, it replaces a
+/// strategy registered under the same name (including a built-in one)
+pub fn register_chunking_strategy(strategy: Arc<dyn ChunkingStrategy>) {
+    let mut strategies = get_strategies().write().unwrap_or_else(|e| e.into_inner());
+    strategies.retain(|s| s.name() != strategy.name());
+    strategies.push(strategy);
+}
+
+/// Returns the strategy registered under the name
+pub fn get_chunking_strategy(name: &str) -> Option<Arc<dyn ChunkingStrategy>> {
+    let strategies = get_strategies().read().unwrap_or_else(|e| e.into_inner());
+    strategies.iter().find(|s| s.name() == name).cloned()
+}
+
+/// Returns the built-in strategy for the file by its format
+pub fn default_strategy_for_path(path: &Path) -> Arc<dyn ChunkingStrategy> {
+    let ext = get_extension(path);
+    if ext == "tex" {
+        return Arc::new(LatexChunker);
+    }
+    match handler_for_path(path).name() {
+        "markdown" => Arc::new(MarkdownChunker),
+        _ if matches!(ext.as_str(), "" | "txt" | "text") => Arc::new(SentenceChunker),
+        _ => Arc::new(LineChunker),
+    }
+}
+
+/// Returns the strategy selected for the extension of the file in _by_extension_ (extensions
+/// without the dot → names of strategies), the default one if none is selected or the selected
+/// one isn't registered
+pub fn strategy_for_path(
+    path: &Path,
+    by_extension: &HashMap<String, String>,
+) -> Arc<dyn ChunkingStrategy> {
+    by_extension
+        .get(&get_extension(path))
+        .and_then(|name| get_chunking_strategy(name))
+        .unwrap_or_else(|| default_strategy_for_path(path))
+}
+
+fn get_extension(path: &Path) -> String {
+    path.extension()
+        .map(|e| e.to_string_lossy().to_lowercase())
+        .unwrap_or_default()
+}
+
+/// Splits the text into chunks with the strategy. The chunks of _previous_ that are still found
+/// in the text, in the same order, are kept as they are and the rest of the text is split around
+/// them. Concatenating the chunks gives back the text.
+pub fn split_into_chunks(
+    text: &str,
+    lines_per_chunk: usize,
+    previous: &[ChunkBoundary],
+    strategy: &dyn ChunkingStrategy,
+) -> Vec<String> {
+    let lines: Vec<&str> = text.split_inclusive('\n').collect();
+    let mut res = Vec::new();
+    let mut pos = 0;
+    let split = |lines: &[&str], res: &mut Vec<String>| {
+        if !lines.is_empty() {
+            res.extend(strategy.split(lines, lines_per_chunk));
+        }
+    };
+    for boundary in previous {
+        // a file that shrank can't hold the chunk anymore
+        if boundary.lines == 0 || boundary.lines > lines.len() - pos {
+            continue;
+        }
+        let found = (pos..=lines.len().saturating_sub(boundary.lines)).find(|start| {
+            content_hash(&lines[*start..*start + boundary.lines].concat()) == boundary.hash
+        });
+        if let Some(start) = found {
+            split(&lines[pos..start], &mut res);
+            res.push(lines[start..start + boundary.lines].concat());
+            pos = start + boundary.lines;
+        }
+    }
+    split(&lines[pos..], &mut res);
+    res
+}
+
+/// Splits the lines into chunks of at most _lines_per_chunk_ lines, a chunk only starts at a
+/// line that is _cuttable_ (one value per line and one for the end) and preferably at the line
+/// with the highest _rank_ (0 means no preference). A building block for the strategies.
+pub fn split_by_rank(
+    lines: &[&str],
+    lines_per_chunk: usize,
+    cuttable: &[bool],
+    rank: impl Fn(usize) -> u8,
+) -> Vec<String> {
+    let lines_per_chunk = lines_per_chunk.max(1);
+    let mut res = Vec::new();
+    let mut start = 0;
+    while lines.len() - start > lines_per_chunk {
+        let end = start + lines_per_chunk;
+        // the best cut in the second half of the chunk, then in the first one, the chunks
+        // don't get too small unless there's no other way to keep a structure whole
+        let best_cut = |range: std::ops::Range<usize>| {
+            range
+                .filter(|i| cuttable[*i])
+                .map(|i| (rank(i), i))
+                .filter(|(rank, _)| *rank > 0)
+                .max()
+                .map(|(_, i)| i)
+        };
+        let middle = start + (lines_per_chunk / 2).max(1);
+        let cut = best_cut(middle..end + 1)
+            .or_else(|| best_cut(start + 1..middle))
+            // the last line the chunk may end at, a code block (or an environment) longer than
+            // a chunk gets a chunk of its own
+            .or_else(|| (start + 1..end + 1).rev().find(|i| cuttable[*i]))
+            .or_else(|| (end + 1..lines.len()).find(|i| cuttable[*i]))
+            .unwrap_or(lines.len());
+        res.push(lines[start..cut].concat());
+        start = cut;
+    }
+    if start < lines.len() {
+        res.push(lines[start..].concat());
+    }
+    res
+}
+
+/// Returns for every line whether a chunk may start at it, the lines of code blocks (fenced ones
+/// and indented ones) may not, as a code block cut in two gets corrupted by the translation
+fn get_cuttable_lines(lines: &[&str]) -> Vec<bool> {
+    let mut res = vec![true; lines.len() + 1];
+    let mut fence: Option<(char, usize)> = None;
+    let mut indented_block = false;
+    for (i, line) in lines.iter().enumerate() {
+        let trimmed = line.trim();
+        if let Some((marker, len)) = fence {
+            res[i] = false;
+            let closing = trimmed.chars().take_while(|c| *c == marker).count();
+            if closing >= len && closing == trimmed.chars().count() {
+                fence = None;
+            }
+            continue;
+        }
+        let marker = trimmed.chars().next().filter(|c| *c == '`' || *c == '~');
+        if let Some(marker) = marker {
+            let len = trimmed.chars().take_while(|c| *c == marker).count();
+            if len >= 3 {
+                fence = Some((marker, len));
+                indented_block = false;
+                continue;
+            }
+        }
+        let indented = line.starts_with("    ") || line.starts_with('\t');
+        if indented_block {
+            // blank lines followed by more code belong to the block
+            indented_block = indented || (trimmed.is_empty() && next_is_indented(lines, i));
+            res[i] = !indented_block;
+        } else if indented && (i == 0 || lines[i - 1].trim().is_empty()) {
+            indented_block = true;
+        }
+    }
+    res
+}
+
+/// returns true if the first non blank line after the line _i_ is indented as code
+fn next_is_indented(lines: &[&str], i: usize) -> bool {
+    lines[i + 1..]
+        .iter()
+        .find(|l| !l.trim().is_empty())
+        .is_some_and(|l| l.starts_with("    ") || l.starts_with('\t'))
+}
+
+/// Returns for every line of a LaTeX text whether a chunk may start at it, the lines inside of
+/// an environment (except `document`) or of a display math block may not
+fn get_latex_cuttable_lines(lines: &[&str]) -> Vec<bool> {
+    let mut res = vec![true; lines.len() + 1];
+    let mut depth: usize = 0;
+    let mut in_dollar_math = false;
+    for (i, line) in lines.iter().enumerate() {
+        res[i] = depth == 0 && !in_dollar_math;
+        let code = strip_latex_comment(line);
+        let mut rest = code;
+        while let Some(pos) = rest.find(['\\', '$']) {
+            rest = &rest[pos..];
+            if rest.starts_with("$$") {
+                in_dollar_math = !in_dollar_math;
+                rest = &rest[2..];
+            } else if let Some(env) = latex_env_name(rest, "\\begin{") {
+                depth += (env != "document") as usize;
+                rest = &rest[1..];
+            } else if let Some(env) = latex_env_name(rest, "\\end{") {
+                if env != "document" {
+                    depth = depth.saturating_sub(1);
+                }
+                rest = &rest[1..];
+            } else if rest.starts_with("\\[") {
+                depth += 1;
+                rest = &rest[2..];
+            } else if rest.starts_with("\\]") {
+                depth = depth.saturating_sub(1);
+                rest = &rest[2..];
+            } else {
+                // an escaped character (e.g. `\$`) or a lone dollar of inline math
+                rest = &rest[rest.chars().take(2).map(|c| c.len_utf8()).sum::<usize>()..];
+            }
+        }
+    }
+    res
+}
+
+/// Returns the line without its comment, a `%` not escaped by a backslash starts one
+fn strip_latex_comment(line: &str) -> &str {
+    let mut escaped = false;
+    for (pos, c) in line.char_indices() {
+        match c {
+            '%' if !escaped => return &line[..pos],
+            '\\' => escaped = !escaped,
+            _ => escaped = false,
+        }
+    }
+    line
+}
+
+/// Returns the name of the environment if _text_ starts with the _command_ (`\begin{` or
+/// `\end{`)
+fn latex_env_name<'a>(text: &'a str, command: &str) -> Option<&'a str> {
+    let rest = text.strip_prefix(command)?;
+    rest.find('}').map(|end| &rest[..end])
+}
+
+/// Returns how good it is to start a chunk at the line _i_ of a LaTeX text: 3 before a
+/// sectioning command, 2 between paragraphs, 1 before an environment, 0 otherwise
+fn latex_cut_rank(lines: &[&str], i: usize) -> u8 {
+    const SECTIONS: [&str; 7] = [
+        "\\part",
+        "\\chapter",
+        "\\section",
+        "\\subsection",
+        "\\subsubsection",
+        "\\paragraph",
+        "\\subparagraph",
+    ];
+    let line = lines[i].trim_start();
+    if SECTIONS.iter().any(|s| {
+        line.strip_prefix(s)
+            .is_some_and(|rest| rest.starts_with(['{', '*', '[']))
+    }) {
+        return 3;
+    }
+    if line.trim().is_empty() {
+        return 0;
+    }
+    if i > 0 && lines[i - 1].trim().is_empty() {
+        return 2;
+    }
+    if line.starts_with("\\begin{") {
+        return 1;
+    }
+    0
+}
+
+/// Returns how good it is to start a chunk at the line _i_ of a plain text: 2 between
+/// paragraphs, 1 after a line ending a sentence, 0 inside of a sentence
+fn sentence_cut_rank(lines: &[&str], i: usize) -> u8 {
+    if i == 0 || lines[i].trim().is_empty() {
+        return 0;
+    }
+    let prev = lines[i - 1];
+    if prev.trim().is_empty() {
+        return 2;
+    }
+    ends_sentence(prev) as u8
+}
+
+/// returns true if the text ends with the end of a sentence, an abbreviation or an initial
+/// followed by a dot doesn't end one
+pub(crate) fn ends_sentence(text: &str) -> bool {
+    const ABBREVIATIONS: [&str; 9] = [
+        "e.g.", "i.e.", "mr.", "mrs.", "dr.", "vs.", "cf.", "fig.", "no.",
+    ];
+    let text = text
+        .trim_end()
+        .trim_end_matches(['"', '\'', ')', ']', '»', '”', '’']);
+    let last = match text.chars().last() {
+        Some(c) => c,
+        None => return false,
+    };
+    if matches!(last, '!' | '?' | '…' | '。' | '！' | '？') {
+        return true;
+    }
+    if last != '.' {
+        return false;
+    }
+    let word = text
+        .rsplit(char::is_whitespace)
+        .next()
+        .unwrap_or("")
+        .to_lowercase();
+    let is_initial = word.chars().count() == 2 && word.starts_with(char::is_alphabetic);
+    !is_initial && !ABBREVIATIONS.contains(&word.as_str())
+}
+
+/// Returns how good it is to start a chunk at the line _i_ of a markdown text: 3 before a
+/// heading, 2 between paragraphs, 1 between list items, 0 inside of a paragraph or an item
+fn markdown_cut_rank(lines: &[&str], i: usize) -> u8 {
+    let line = lines[i].trim_end();
+    let prev_blank = i > 0 && lines[i - 1].trim().is_empty();
+    let is_setext_heading = !line.trim().is_empty()
+        && lines.get(i + 1).is_some_and(|next| {
+            let next = next.trim();
+            !next.is_empty() && (next.chars().all(|c| c == '=') || next.chars().all(|c| c == '-'))
+        });
+    if is_atx_heading(line) || (is_setext_heading && (i == 0 || prev_blank)) {
+        return 3;
+    }
+    if line.trim().is_empty() {
+        return 0;
+    }
+    let indented = line.starts_with("    ") || line.starts_with('\t');
+    if is_list_item(line) {
+        return 1;
+    }
+    if prev_blank && !indented {
+        return 2;
+    }
+    0
+}
+
+fn is_atx_heading(line: &str) -> bool {
+    let trimmed = line.trim_start_matches(' ');
+    let level = trimmed.chars().take_while(|c| *c == '#').count();
+    (1..=6).contains(&level)
+        && trimmed[level..]
+            .chars()
+            .next()
+            .is_none_or(|c| c == ' ' || c == '\t')
+}
+
+fn is_list_item(line: &str) -> bool {
+    let trimmed = line.trim_start();
+    if let Some(rest) = trimmed.strip_prefix(['-', '*', '+']) {
+        return rest.starts_with(' ');
+    }
+    let digits = trimmed.chars().take_while(|c| c.is_ascii_digit()).count();
+    digits > 0 && (trimmed[digits..].starts_with(". ") || trimmed[digits..].starts_with(") "))
+}
+
+/// Returns the boundaries of the chunks to be persisted
+pub fn get_boundaries(chunks: &[String]) -> Vec<ChunkBoundary> {
+    chunks
+        .iter()
+        .map(|c| ChunkBoundary {
+            lines: c.split_inclusive('\n').count(),
+            hash: content_hash(c),
+        })
+        .collect()
+}
+
+#[derive(Debug, Clone, Default, serde::Serialize, serde::Deserialize)]
+/// Chunk boundaries of the files of a project, by the path relative to the project root
+pub struct ChunkStore {
+    files: HashMap<String, Vec<ChunkBoundary>>,
+}
+
+impl ChunkStore {
+    /// Loads the boundaries of the project, empty if there are none yet
+    pub fn load(project_root: &Path) -> Self {
+        state::read_json(&state::get_state_dir(project_root).join(CHUNKS_FILE)).unwrap_or_default()
+    }
+    pub fn save(&self, project_root: &Path) -> std::io::Result<()> {
+        state::write_json(&state::get_state_dir(project_root).join(CHUNKS_FILE), self)
+    }
+    pub fn get_boundaries_as_ref(&self, file: &str) -> Option<&Vec<ChunkBoundary>> {
+        self.files.get(file)
+    }
+    pub fn set_boundaries(&mut self, file: &str, boundaries: Vec<ChunkBoundary>) {
+        self.files.insert(file.to_string(), boundaries);
+    }
+}
+
+/// Splits the text of the file being translated into chunks reusing the boundaries of the
+/// previous run and persists the new ones. Without a project or a file the text is just split.
+pub(crate) fn plan_chunks(text: &str, ctx: &TranslationContext) -> std::io::Result<Vec<String>> {
+    let lines_per_chunk = super::LINES_PER_CHUNK;
+    let strategy: Arc<dyn ChunkingStrategy> = match &ctx.file {
+        Some(file) => strategy_for_path(file, &ctx.options.chunking),
+        None => Arc::new(LineChunker),
+    };
+    let strategy = strategy.as_ref();
+    let (root, key) = match (&ctx.project_root, &ctx.file) {
+        (Some(root), Some(file)) => match file.strip_prefix(root) {
+            Ok(rel) => (root, rel.to_string_lossy().replace('\\', "/")),
+            Err(_) => return Ok(split_into_chunks(text, lines_per_chunk, &[], strategy)),
+        },
+        _ => return Ok(split_into_chunks(text, lines_per_chunk, &[], strategy)),
+    };
+
+    let mut store = ChunkStore::load(root);
+    let previous = store
+        .get_boundaries_as_ref(&key)
+        .cloned()
+        .unwrap_or_default();
+    let chunks = split_into_chunks(text, lines_per_chunk, &previous, strategy);
+    let boundaries = get_boundaries(&chunks);
+    if boundaries != previous {
+        store.set_boundaries(&key, boundaries);
+        store.save(root)?;
+    }
+    Ok(chunks)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keeps_the_chunks_of_the_previous_split() {
+        let text = "a\nb\nc\nd\ne\n";
+        let chunks = split_into_chunks(text, 2, &[], &LineChunker);
+        assert_eq!(chunks, vec!["a\nb\n", "c\nd\n", "e\n"]);
+
+        let edited = "a\nb\nx\nc\nd\ne\n";
+        let chunks = split_into_chunks(edited, 2, &get_boundaries(&chunks), &LineChunker);
+        assert_eq!(chunks.concat(), edited);
+        assert!(chunks.contains(&"c\nd\n".to_string()));
+    }
+
+    #[test]
+    fn rechunks_a_shortened_file() {
+        let text = "a\nb\nc\nd\ne\nf\n";
+        let boundaries = get_boundaries(&split_into_chunks(text, 6, &[], &LineChunker));
+        let chunks = split_into_chunks("a\nb\n", 6, &boundaries, &LineChunker);
+        assert_eq!(chunks, vec!["a\nb\n"]);
+        assert!(split_into_chunks("", 6, &boundaries, &LineChunker).is_empty());
+    }
+}
@@ -0,0 +1,86 @@
+//! Health check of the translation provider: a minimal authenticated request that doesn't
+//! consume any tokens, so a bad setup is reported before a long run starts.
+use std::time::{Duration, Instant};
+
+use crate::{errors::translator_errors::CredentialError, metrics};
+
+use super::{
+    batch::{API_KEY_HEADER, BASE_URL},
+    API_KEY_VAR,
+};
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+/// A provider the files can be translated with
+pub enum Provider {
+    #[default]
+    Gemini,
+}
+
+impl Provider {
+    pub fn get_name(&self) -> &'static str {
+        match self {
+            Provider::Gemini => "Gemini",
+        }
+    }
+    /// returns the environment variable the API key of the provider is read from
+    pub fn get_key_var(&self) -> &'static str {
+        match self {
+            Provider::Gemini => API_KEY_VAR,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+/// Result of a successful credential check
+pub struct CredentialReport {
+    pub provider: Provider,
+    /// time the provider took to answer
+    pub latency: Duration,
+}
+
+/// Sends a minimal authenticated request (listing one model) to the provider and reports why it
+/// failed: a missing or rejected key, a wrong endpoint, an exhausted quota or a network problem
+pub fn check_credentials(provider: Provider) -> Result<CredentialReport, CredentialError> {
+    let key_var = provider.get_key_var();
+    let api_key = match std::env::var(key_var) {
+        Ok(k) if !k.trim().is_empty() => k,
+        _ => return Err(CredentialError::MissingKey(key_var)),
+    };
+    let url = match provider {
+        Provider::Gemini => format!("{}/models", BASE_URL),
+    };
+
+    let rt =
+        tokio::runtime::Runtime::new().map_err(|e| CredentialError::Unreachable(e.to_string()))?;
+    let start = Instant::now();
+    let res = rt.block_on(send_check(&url, &api_key));
+    metrics::record_api_call(start, res.is_ok());
+    res.map(|_| CredentialReport {
+        provider,
+        latency: start.elapsed(),
+    })
+}
+
+async fn send_check(url: &str, api_key: &str) -> Result<(), CredentialError> {
+    let response = reqwest::Client::new()
+        .get(url)
+        .header(API_KEY_HEADER, api_key)
+        .query(&[("pageSize", "1")])
+        .send()
+        .await
+        .map_err(|e| CredentialError::Unreachable(e.without_url().to_string()))?;
+    let status = response.status().as_u16();
+    if response.status().is_success() {
+        return Ok(());
+    }
+    let message = response.text().await.unwrap_or_default();
+    Err(match status {
+        // Gemini answers a bad key with 400 and the API_KEY_INVALID reason
+        400 if message.contains("API_KEY_INVALID") => CredentialError::InvalidKey(message),
+        401 => CredentialError::InvalidKey(message),
+        403 => CredentialError::PermissionDenied(message),
+        404 => CredentialError::WrongEndpoint(url.to_string()),
+        429 => CredentialError::QuotaExhausted(message),
+        _ => CredentialError::RemoteError { status, message },
+    })
+}
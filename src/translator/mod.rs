@@ -3,96 +3,1561 @@
 //!
 //!
 
-use std::{io::Write, thread, time::Duration};
+use std::{
+    collections::HashMap,
+    io::Write,
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::{Duration, Instant},
+};
 
 use crate::{
-    helper::{divide_into_chunks, extract_translated_from_response, read_string_file},
+    errors::translator_errors::{BatchError, CredentialError, ResponseError},
+    estimate::{
+        cost::{get_model_pricing, ModelPricing},
+        estimate_tokens,
+    },
+    format::{handler_for_path, protect, reinsert, Reinserter},
+    glossary::Glossary,
+    helper::{
+        content_hash, extract_translated_from_response, read_string_file, write_atomic, AtomicFile,
+        OutputExtractor,
+    },
+    merge, metrics,
+    progress::{ProgressEvent, ProgressHandler},
+    qa::{check_segment, QaFinding},
+    shutdown::{interrupted_error, is_cancelled, CancellationToken},
+    state::{backups, checkpoints::Checkpoint, usage::ProjectUsage},
+    translation_memory::{RunIndexes, TranslationMemory, DEFAULT_FUZZY_THRESHOLD},
     Language,
 };
-use google_genai::datatypes::{Content, GenerateContentParameters, Part};
+use budget::{RunBudget, RunUsage, UsageMeter};
+use chunks::plan_chunks;
+use futures_util::StreamExt;
+use google_genai::datatypes::{
+    Content, GenerateContentConfig, GenerateContentParameters, GenerateContentReq,
+    GenerationConfig, Part,
+};
+pub use health::{check_credentials, Provider};
+use prompt::{format_examples, render_prompt, ExamplePair, PromptVariables};
+use rate_limit::RateLimit;
+use schedule::Schedule;
 use tokio::runtime::Runtime;
 
-fn get_default_prompt() -> String {
-    read_string_file("/Users/dobbikov/Desktop/stage/prompts/prompt4")
+pub mod batch;
+pub mod budget;
+pub mod cache;
+pub mod chunks;
+pub mod health;
+pub mod prompt;
+pub mod rate_limit;
+pub mod schedule;
+
+/// Model used for the translation when the project doesn't set one
+pub const DEFAULT_MODEL: &str = "gemini-2.0-flash";
+
+/// Environment variable the Gemini API key is read from
+pub const API_KEY_VAR: &str = "GOOGLE_API_KEY";
+
+pub(crate) const LINES_PER_CHUNK: usize = 50;
+
+/// Maximum number of characters of a document the summary is generated from
+pub const SUMMARY_INPUT_CHARS: usize = 40_000;
+
+/// Maximal number of near matches of the translation memory sent with a chunk
+const MAX_FUZZY_MATCHES: usize = 3;
+
+/// Number of retries of a chunk whose answer has no `<output>` tag by default
+pub const DEFAULT_OUTPUT_RETRIES: u32 = 2;
+
+/// Appended to the message when a chunk is requested again after a malformed answer
+const OUTPUT_REMINDER: &str = "\nYour previous answer to this request had no <output> tag. \
+     Answer with the translation inside of <output></output> and nothing else.";
+
+/// Introduces the findings of the quality checks when a chunk is requested again after them
+const QA_FINDINGS_INTRO: &str = "\nYour previous translation of this document failed the \
+     following checks, translate it again fixing them:\n";
+
+/// Number of lines of the previous chunk sent as context by default
+pub const DEFAULT_CONTEXT_LINES: usize = 10;
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+/// The chunk translated before the current one, its end is sent with the current chunk so the
+/// terms and the sentences continue across the chunk boundary
+pub struct PreviousChunk {
+    pub source: String,
+    /// the translation, empty if it isn't known yet (e.g. in batch mode)
+    pub translation: String,
+}
+
+#[derive(Debug, Clone, Default, serde::Serialize, serde::Deserialize)]
+/// Options of the translator stored in the project config
+pub struct TranslatorOptions {
+    /// if true, the translation is streamed and written to the target file as it arrives
+    #[serde(default)]
+    pub streaming: bool,
+    /// limit of requests, shared by all the projects using the same API key
+    #[serde(default)]
+    pub rate_limit: RateLimit,
+    /// if true, `translate_all` submits all the chunks as one batch job, which is cheaper but
+    /// may take hours to complete
+    #[serde(default)]
+    pub batch: bool,
+    /// when the requests may be sent, by default at any time
+    #[serde(default)]
+    pub schedule: Schedule,
+    /// sampling temperature, `None` keeps the model's default (0 is best for documentation)
+    #[serde(default)]
+    pub temperature: Option<f64>,
+    /// nucleus sampling probability, `None` keeps the model's default
+    #[serde(default)]
+    pub top_p: Option<f64>,
+    /// maximum number of tokens in an answer, `None` keeps the model's default
+    #[serde(default)]
+    pub max_output_tokens: Option<i64>,
+    /// if true, the translation instructions are sent as a system instruction, separately from
+    /// the document, instead of being prepended to it
+    #[serde(default)]
+    pub system_instruction: bool,
+    /// number of lines at the end of the previous chunk (and of its translation) sent with the
+    /// next chunk for continuity, `None` uses [`DEFAULT_CONTEXT_LINES`], 0 disables it
+    #[serde(default)]
+    pub context_lines: Option<usize>,
+    /// if true, a short summary of a file split into several chunks is generated first and sent
+    /// with every chunk, so the model knows the topic and the register of the whole document
+    #[serde(default)]
+    pub summary: bool,
+    /// chunking strategies by file extension (without the dot), e.g. `rst` → `sentences`, see
+    /// [`chunks::ChunkingStrategy`]. The other files use the strategy of their format.
+    #[serde(default)]
+    pub chunking: HashMap<String, String>,
+    /// number of times a chunk is requested again when the answer has no `<output>` tag, `None`
+    /// uses [`DEFAULT_OUTPUT_RETRIES`]
+    #[serde(default)]
+    pub output_retries: Option<u32>,
+    /// number of times a chunk is requested again when its translation fails the quality checks
+    /// (placeholders, numbers, URLs, glossary terms, Markdown structure), the findings are sent
+    /// with the new request. `None` doesn't check the chunks. Not used when streaming.
+    #[serde(default)]
+    pub qa_retries: Option<u32>,
+    /// don't use the cache of the translated chunks in the state directory of the project
+    #[serde(default)]
+    pub no_cache: bool,
+    /// number of previous versions of a target file saved in the state directory of the project
+    /// before a translation overwrites it, `None` doesn't save them
+    #[serde(default)]
+    pub backups: Option<usize>,
+    /// don't use the translation memory of the project, see [`crate::translation_memory`]
+    #[serde(default)]
+    pub no_memory: bool,
+    /// minimal similarity of the near matches of the translation memory sent as reference
+    /// translations, `None` uses [`DEFAULT_FUZZY_THRESHOLD`]
+    #[serde(default)]
+    pub fuzzy_threshold: Option<f64>,
+    /// limits of the tokens and the cost of a run, see [`budget`]
+    #[serde(default)]
+    pub budget: RunBudget,
+    /// maximal duration of a request to the model in seconds, a stuck request fails with a
+    /// `TimedOut` error instead of hanging. `None` waits as long as the provider does.
+    #[serde(default)]
+    pub request_timeout_secs: Option<u64>,
+    /// maximal duration of the translation of a file in seconds, checked before every chunk and
+    /// bounding the requests, `None` for no limit
+    #[serde(default)]
+    pub file_timeout_secs: Option<u64>,
 }
 
-pub(crate) fn put_lang_into_prompt(prompt: &str, lang: &Language) -> String {
-    let lang_str: &str = (*lang).clone().into();
+impl TranslatorOptions {
+    /// Returns the generation parameters set in the options
+    fn get_generate_content_config(&self) -> GenerateContentConfig {
+        GenerateContentConfig {
+            temperature: self.temperature,
+            top_p: self.top_p,
+            max_output_tokens: self.max_output_tokens,
+            ..Default::default()
+        }
+    }
+}
 
-    prompt.replace("[TARGET_LANGUAGE]", lang_str)
+#[derive(Debug, Clone)]
+/// Everything the translator needs to know about the project to translate its files
+pub struct TranslationContext {
+    /// name of the model to use
+    pub model: String,
+    pub options: TranslatorOptions,
+    /// the prompt template, see [`prompt::DEFAULT_PROMPT`]
+    pub prompt: String,
+    /// language of the translated files, `None` if unknown
+    pub source_language: Option<Language>,
+    pub glossary: Glossary,
+    /// the file being translated, `None` if the text doesn't come from a file
+    pub file: Option<PathBuf>,
+    /// root of the project the files belong to, its state (e.g. the chunk boundaries of the
+    /// previous runs) is used if set
+    pub project_root: Option<PathBuf>,
+    /// directory of the translation memory shared by the projects of the workspace, used with
+    /// the one of the project
+    pub shared_memory: Option<PathBuf>,
+    /// example translations into the target language included in the prompt
+    pub examples: Vec<ExamplePair>,
+    /// summary of the whole document sent with every chunk, see [`summarize_document`]
+    pub summary: Option<String>,
+    /// rates of the model, the cost limit of the budget is checked with them
+    pub pricing: Option<ModelPricing>,
+    /// told about every translated chunk
+    pub progress: Option<Arc<dyn ProgressHandler>>,
+    /// the translation stops before the next chunk once it's cancelled
+    pub cancel: Option<CancellationToken>,
+    /// end of the time limit of the file being translated, see
+    /// [`TranslatorOptions::file_timeout_secs`]
+    pub deadline: Option<Instant>,
+    /// language every chunk is translated into first and from it into the target language,
+    /// `None` to translate the chunks directly. The batch mode doesn't support it.
+    pub pivot: Option<Language>,
+    /// prompt template of the translation into the pivot language, `None` for
+    /// [`TranslationContext::prompt`]. The prompt of a target language doesn't apply to it.
+    pub pivot_prompt: Option<String>,
+    /// the tokens used by the run, checked against [`TranslatorOptions::budget`]
+    pub usage: UsageMeter,
+    /// the translation memory read by the run to look for the near matches of the chunks
+    pub memory_indexes: RunIndexes,
+}
+
+impl TranslationContext {
+    /// Returns the same context for the translation of the given file
+    pub fn for_file(&self, path: &Path) -> Self {
+        let timeout = self.options.file_timeout_secs.map(Duration::from_secs);
+        Self {
+            file: Some(path.to_path_buf()),
+            deadline: timeout.map(|t| Instant::now() + t),
+            ..self.clone()
+        }
+    }
+    /// Returns the time a request may take: the request timeout of the options, shortened to
+    /// what is left of the time limit of the file. `None` if there is no limit.
+    fn request_timeout(&self) -> Option<Duration> {
+        let request = self.options.request_timeout_secs.map(Duration::from_secs);
+        let left = self
+            .deadline
+            .map(|d| d.saturating_duration_since(Instant::now()));
+        match (request, left) {
+            (Some(request), Some(left)) => Some(request.min(left)),
+            (request, left) => request.or(left),
+        }
+    }
+    /// Fails with a `TimedOut` error if the time limit of the file is over
+    fn check_deadline(&self) -> std::io::Result<()> {
+        match self.deadline {
+            Some(deadline) if Instant::now() >= deadline => Err(timeout_error("the file")),
+            _ => Ok(()),
+        }
+    }
+    /// returns true if a shutdown was requested or the token of the context was cancelled
+    pub fn is_cancelled(&self) -> bool {
+        is_cancelled(self.cancel.as_ref())
+    }
+    /// Reports the translation of the _chunk_-th of the _chunks_ of the file to the progress
+    /// handler
+    fn report_chunk(&self, tgt_lang: &Language, chunk: usize, chunks: usize) {
+        if let Some(progress) = &self.progress {
+            progress.on_progress(&ProgressEvent::ChunkCompleted {
+                file: self.file.as_deref().unwrap_or(Path::new("")),
+                lang: tgt_lang,
+                chunk,
+                chunks,
+            });
+        }
+    }
+    /// Returns the prompt rendered for the translation into _tgt_lang_, the template itself if it
+    /// can't be rendered
+    pub fn get_rendered_prompt(&self, tgt_lang: &Language) -> String {
+        render_prompt(&self.prompt, &self.get_prompt_variables(tgt_lang))
+            .unwrap_or_else(|_| self.prompt.clone())
+    }
+    /// Returns the values of the prompt placeholders for the translation into _tgt_lang_
+    fn get_prompt_variables(&self, tgt_lang: &Language) -> PromptVariables {
+        let mut vars = PromptVariables::new(tgt_lang.clone());
+        vars.source_language = self.source_language.clone();
+        vars.glossary = Some(
+            self.glossary
+                .format_for_prompt(self.source_language.clone(), tgt_lang.clone()),
+        );
+        vars.examples = Some(format_examples(&self.examples));
+        if let Some(file) = &self.file {
+            vars.file_name = file.file_name().map(|n| n.to_string_lossy().into_owned());
+            vars.file_format = Some(handler_for_path(file).name().to_string());
+        }
+        vars
+    }
+    /// Returns the hash of what the translations into _tgt_lang_ depend on besides the source:
+    /// the prompt, the glossary and the examples. A segment of the translation memory translated
+    /// with another one isn't reused.
+    pub(crate) fn get_memory_context(&self, tgt_lang: &Language) -> String {
+        let vars = self.get_prompt_variables(tgt_lang);
+        content_hash(&format!(
+            "{}\0{}\0{}",
+            self.prompt,
+            vars.glossary.unwrap_or_default(),
+            vars.examples.unwrap_or_default()
+        ))
+    }
+}
+
+impl Default for TranslationContext {
+    fn default() -> Self {
+        Self {
+            model: DEFAULT_MODEL.to_string(),
+            options: TranslatorOptions::default(),
+            prompt: prompt::DEFAULT_PROMPT.to_string(),
+            source_language: None,
+            glossary: Glossary::new(),
+            file: None,
+            project_root: None,
+            shared_memory: None,
+            examples: Vec::new(),
+            summary: None,
+            pricing: get_model_pricing(DEFAULT_MODEL, &[]),
+            progress: None,
+            cancel: None,
+            deadline: None,
+            pivot: None,
+            pivot_prompt: None,
+            usage: UsageMeter::default(),
+            memory_indexes: RunIndexes::default(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// A message sent to the model
+pub struct Message {
+    /// instructions sent apart from the contents, `None` if they are a part of the contents
+    pub system_instruction: Option<String>,
+    pub contents: String,
+}
+
+#[derive(Debug, Clone, Copy)]
+/// State of a streamed translation of a file
+pub struct StreamProgress {
+    /// index of the chunk being translated
+    pub chunk: usize,
+    /// number of chunks in the file
+    pub chunks: usize,
+    /// bytes written to the target file so far
+    pub bytes_written: usize,
 }
 
 pub fn translate_file_to_file(
     from_path: impl Into<std::path::PathBuf>,
     to_path: impl Into<std::path::PathBuf>,
     tgt_lang: &Language,
+    ctx: &TranslationContext,
 ) -> std::io::Result<()> {
-    let contents = translate_file(from_path, tgt_lang);
+    if ctx.options.streaming {
+        return translate_file_to_file_streaming(from_path, to_path, tgt_lang, ctx, |_| {});
+    }
+    let contents = translate_file(from_path, tgt_lang, ctx)?;
     let to_path: std::path::PathBuf = to_path.into();
+    backup_target(&to_path, ctx)?;
+    write_atomic(&to_path, contents)?;
+    metrics::record_file_translated();
+    Ok(())
+}
 
-    let mut file = std::fs::OpenOptions::new()
-        .create(true)
-        .write(true)
-        .truncate(true)
-        .open(to_path)?;
+/// Translates the file streaming the response of the model, the translated text is written to
+/// the target file as soon as it arrives and _on_progress_ is called after every write.
+pub fn translate_file_to_file_streaming<F>(
+    from_path: impl Into<std::path::PathBuf>,
+    to_path: impl Into<std::path::PathBuf>,
+    tgt_lang: &Language,
+    ctx: &TranslationContext,
+    mut on_progress: F,
+) -> std::io::Result<()>
+where
+    F: FnMut(&StreamProgress),
+{
+    let from_path: std::path::PathBuf = from_path.into();
+    let to_path: std::path::PathBuf = to_path.into();
+    let span = file_span(&from_path, tgt_lang);
+    let _entered = span.enter();
+    let start = Instant::now();
+    let ctx = &ctx.for_file(&from_path);
+    let contents = read_string_file(&from_path);
+    let handler = handler_for_path(&from_path);
+    let (text, protected) = protect(handler.as_ref(), &contents);
+
+    backup_target(&to_path, ctx)?;
+    // the file is replaced once complete, an interrupted run leaves the previous version
+    let mut file = AtomicFile::create(&to_path)?;
+    let mut reinserter = Reinserter::new(&protected);
+    let chunks = plan_chunks(&text, ctx)?;
+    span.record("chunks", chunks.len());
+    let mut checkpoint = load_checkpoint(ctx, &text, tgt_lang, chunks.len());
+    let ctx = &with_checkpoint_summary(ctx, &text, chunks.len(), checkpoint.as_mut())?;
+    let mut pivot = PivotHop::new(ctx, tgt_lang);
+    let mut progress = StreamProgress {
+        chunk: 0,
+        chunks: chunks.len(),
+        bytes_written: 0,
+    };
+    let mut previous: Option<PreviousChunk> = None;
+    let mut segments = Vec::new();
+
+    for (id, chunk) in chunks.iter().enumerate() {
+        if ctx.is_cancelled() {
+            return Err(interrupted_error());
+        }
+        ctx.check_deadline()?;
+        let _chunk = tracing::debug_span!("chunk", index = id, chunks = chunks.len()).entered();
+        progress.chunk = id;
+        let mut write_piece = |piece: &str| -> std::io::Result<()> {
+            let ready = reinserter.push(piece).map_err(invalid_data)?;
+            file.write_all(ready.as_bytes())?;
+            progress.bytes_written += ready.len();
+            on_progress(&progress);
+            Ok(())
+        };
+        let translation = match checkpoint.as_mut().and_then(|c| c.get(id, chunk)) {
+            Some(translation) => {
+                tracing::debug!("chunk restored from the checkpoint");
+                write_piece(&translation)?;
+                PivotHop::skip(pivot.as_mut());
+                translation
+            }
+            None => {
+                let mut translation = String::new();
+                let (text, hop_previous) = match pivot.as_mut() {
+                    Some(hop) => hop.translate(chunk, previous.as_ref())?,
+                    None => (chunk.clone(), previous.clone()),
+                };
+                let hop_ctx = pivot.as_ref().map_or(ctx, |hop| &hop.ctx);
+                translate_chunk_streaming(
+                    &text,
+                    tgt_lang,
+                    hop_ctx,
+                    hop_previous.as_ref(),
+                    &mut |piece| {
+                        translation.push_str(piece);
+                        write_piece(piece)
+                    },
+                )?;
+                save_checkpoint(ctx, tgt_lang, checkpoint.as_mut(), chunk, &translation)?;
+                translation
+            }
+        };
+        let segment = PreviousChunk {
+            source: chunk.clone(),
+            translation,
+        };
+        segments.push(segment.clone());
+        previous = Some(segment);
+        ctx.report_chunk(tgt_lang, id, chunks.len());
+    }
+    let rest = reinserter.finish().map_err(invalid_data)?;
+    file.write_all(rest.as_bytes())?;
+    file.commit()?;
+    remove_checkpoint(ctx, tgt_lang, checkpoint.as_ref())?;
+    progress.bytes_written += rest.len();
+    on_progress(&progress);
+    merge::record_base(ctx, tgt_lang.clone(), &segments, &protected)?;
+    metrics::record_file_translated();
+    record_file_duration(&span, start);
+    Ok(())
+}
 
-    file.write_fmt(format_args!("{}", contents))?;
+/// Returns the span of the translation of the file, its duration is recorded by
+/// [`record_file_duration`]
+fn file_span(path: &Path, tgt_lang: &Language) -> tracing::Span {
+    tracing::info_span!(
+        "translate_file",
+        file = %path.display(),
+        lang = ?tgt_lang,
+        chunks = tracing::field::Empty,
+        duration_ms = tracing::field::Empty,
+    )
+}
+
+fn record_file_duration(span: &tracing::Span, start: Instant) {
+    let duration_ms = start.elapsed().as_millis() as u64;
+    span.record("duration_ms", duration_ms);
+    tracing::info!(duration_ms, "file translated");
+}
+
+/// Saves the previous version of the target file before it's overwritten if the backups are
+/// enabled, only the files of a project are backed up
+fn backup_target(to_path: &Path, ctx: &TranslationContext) -> std::io::Result<()> {
+    if let (Some(keep), Some(root)) = (ctx.options.backups, &ctx.project_root) {
+        backups::backup(root, to_path, keep)?;
+    }
     Ok(())
 }
 
-pub fn translate_file(path: impl Into<std::path::PathBuf>, tgt_lang: &Language) -> String {
+/// Returns the error of a request or a file that took longer than its time limit
+pub fn timeout_error(what: &str) -> std::io::Error {
+    std::io::Error::new(
+        std::io::ErrorKind::TimedOut,
+        format!("{} took longer than its time limit", what),
+    )
+}
+
+/// Runs the request to the model within the _timeout_, if any
+async fn with_timeout<T>(
+    timeout: Option<Duration>,
+    request: impl std::future::Future<Output = std::io::Result<T>>,
+) -> std::io::Result<T> {
+    match timeout {
+        Some(timeout) => tokio::time::timeout(timeout, request)
+            .await
+            .map_err(|_| timeout_error("the request to the model"))?,
+        None => request.await,
+    }
+}
+
+fn invalid_data<E>(e: E) -> std::io::Error
+where
+    E: Into<Box<dyn std::error::Error + Send + Sync>>,
+{
+    std::io::Error::new(std::io::ErrorKind::InvalidData, e)
+}
+
+/// Translates the file, the regions protected by its format (e.g. code blocks) are kept as is
+pub fn translate_file(
+    path: impl Into<std::path::PathBuf>,
+    tgt_lang: &Language,
+    ctx: &TranslationContext,
+) -> std::io::Result<String> {
     let path: std::path::PathBuf = path.into();
-    let contents = read_string_file(path);
-    translate_contents(&contents, tgt_lang)
+    let span = file_span(&path, tgt_lang);
+    let _entered = span.enter();
+    let start = Instant::now();
+    let contents = read_string_file(&path);
+    let handler = handler_for_path(&path);
+    let (text, protected) = protect(handler.as_ref(), &contents);
+    let ctx = &ctx.for_file(&path);
+    let segments = translate_segments(&text, tgt_lang, ctx)?;
+    let translated: String = segments.iter().map(|s| s.translation.as_str()).collect();
+    let res = reinsert(&translated, &protected).map_err(invalid_data)?;
+    merge::record_base(ctx, tgt_lang.clone(), &segments, &protected)?;
+    record_file_duration(&span, start);
+    Ok(res)
+}
+
+/// Translates the contents chunk by chunk. Stops with an `Interrupted` error before the next
+/// chunk if a shutdown was requested.
+pub fn translate_contents(
+    contents: &str,
+    tgt_lang: &Language,
+    ctx: &TranslationContext,
+) -> std::io::Result<String> {
+    Ok(translate_segments(contents, tgt_lang, ctx)?
+        .into_iter()
+        .map(|chunk| chunk.translation)
+        .collect())
+}
+
+/// Translates the contents chunk by chunk like [`translate_contents`], returns every chunk with
+/// its translation
+pub fn translate_segments(
+    contents: &str,
+    tgt_lang: &Language,
+    ctx: &TranslationContext,
+) -> std::io::Result<Vec<PreviousChunk>> {
+    translate_segments_keeping(contents, tgt_lang, ctx, &HashMap::new())
 }
 
-pub fn translate_contents(contents: &str, tgt_lang: &Language) -> String {
-    let mut res = String::new();
+/// Same as [`translate_segments`], the chunks of _reviewed_ (by their index) aren't translated
+/// again: their translation is kept as long as their source is the same
+pub fn translate_segments_keeping(
+    contents: &str,
+    tgt_lang: &Language,
+    ctx: &TranslationContext,
+    reviewed: &HashMap<usize, PreviousChunk>,
+) -> std::io::Result<Vec<PreviousChunk>> {
+    let mut res: Vec<PreviousChunk> = Vec::new();
 
-    const LINES_PER_CHUNK: usize = 50;
+    let chunks = plan_chunks(contents, ctx)?;
+    tracing::Span::current().record("chunks", chunks.len());
+    let mut checkpoint = load_checkpoint(ctx, contents, tgt_lang, chunks.len());
+    let ctx = &with_checkpoint_summary(ctx, contents, chunks.len(), checkpoint.as_mut())?;
+    let mut pivot = PivotHop::new(ctx, tgt_lang);
+    let count = chunks.len();
+    for (id, chunk) in chunks.into_iter().enumerate() {
+        if ctx.is_cancelled() {
+            return Err(interrupted_error());
+        }
+        ctx.check_deadline()?;
+        let _chunk = tracing::debug_span!("chunk", index = id, chunks = count).entered();
+        let restored = match reviewed.get(&id).filter(|r| r.source == chunk) {
+            Some(reviewed) => {
+                tracing::debug!("reviewed chunk kept");
+                Some(reviewed.translation.clone())
+            }
+            None => checkpoint
+                .as_mut()
+                .and_then(|c| c.get(id, &chunk))
+                .inspect(|_| tracing::debug!("chunk restored from the checkpoint")),
+        };
+        let tr_ch = match restored {
+            Some(translation) => {
+                PivotHop::skip(pivot.as_mut());
+                translation
+            }
+            None => {
+                let translation = match pivot.as_mut() {
+                    Some(hop) => {
+                        let (text, previous) = hop.translate(&chunk, res.last())?;
+                        translate_chunk(&text, tgt_lang, &hop.ctx, previous.as_ref())?
+                    }
+                    None => translate_chunk(&chunk, tgt_lang, ctx, res.last())?,
+                };
+                save_checkpoint(ctx, tgt_lang, checkpoint.as_mut(), &chunk, &translation)?;
+                translation
+            }
+        };
+        res.push(PreviousChunk {
+            source: chunk,
+            translation: tr_ch,
+        });
+        ctx.report_chunk(tgt_lang, id, count);
+    }
+    remove_checkpoint(ctx, tgt_lang, checkpoint.as_ref())?;
+    Ok(res)
+}
 
-    let chunks = divide_into_chunks(contents.to_string(), LINES_PER_CHUNK);
-    for chunk in chunks {
-        let tr_ch = translate_chunk(&chunk, tgt_lang);
-        res.push_str(&tr_ch);
+/// Translates all the files as one batch job. Every pair is a (source path, target path).
+pub fn translate_files_batch(
+    files: &[(std::path::PathBuf, std::path::PathBuf)],
+    tgt_lang: &Language,
+    ctx: &TranslationContext,
+) -> Result<(), BatchError> {
+    if ctx.pivot.is_some() {
+        return Err(BatchError::PivotNotSupported);
     }
+    let mut items = Vec::new();
+    let mut prepared = Vec::new();
+    let mut cached = HashMap::new();
+    let mut cache_keys = HashMap::new();
+    let mut planned = RunUsage::default();
+    for (file_id, (from_path, _)) in files.iter().enumerate() {
+        let contents = std::fs::read_to_string(from_path).map_err(BatchError::IoError)?;
+        let handler = handler_for_path(from_path);
+        let (text, protected) = protect(handler.as_ref(), &contents);
+        let file_ctx = ctx.for_file(from_path);
+        let chunks = plan_chunks(&text, &file_ctx).map_err(BatchError::IoError)?;
+        // the summary is asked for directly, the chunks can't wait for another batch job
+        let file_ctx = with_summary(&file_ctx, &text, chunks.len()).map_err(BatchError::IoError)?;
+        for (chunk_id, chunk) in chunks.iter().enumerate() {
+            // the chunks are translated independently, only the source can be carried over
+            let previous = chunk_id.checked_sub(1).map(|id| PreviousChunk {
+                source: chunks[id].clone(),
+                translation: String::new(),
+            });
+            // the known chunks aren't sent again
+            if let Some(translation) = recall(chunk, tgt_lang, &file_ctx) {
+                cached.insert(format!("{}:{}", file_id, chunk_id), translation);
+                continue;
+            }
+            let cache_key = get_cache_key(chunk, tgt_lang, &file_ctx, previous.as_ref())
+                .map_err(BatchError::IoError)?;
+            if let Some(translation) = cache::lookup(&file_ctx, &cache_key) {
+                cached.insert(format!("{}:{}", file_id, chunk_id), translation);
+                continue;
+            }
+            let message = build_message(chunk, tgt_lang, &file_ctx, previous.as_ref())
+                .map_err(BatchError::IoError)?;
+            let usage = budget::plan_message(&message, chunk);
+            planned.requests += usage.requests;
+            planned.input_tokens += usage.input_tokens;
+            planned.output_tokens += usage.output_tokens;
+            items.push(batch::BatchItem {
+                key: format!("{}:{}", file_id, chunk_id),
+                message,
+            });
+            cache_keys.insert(format!("{}:{}", file_id, chunk_id), cache_key);
+        }
+        prepared.push((chunks, protected, file_ctx));
+    }
+    if prepared.is_empty() {
+        return Ok(());
+    }
+
+    let results = if items.is_empty() {
+        HashMap::new()
+    } else {
+        budget::check(ctx, &planned).map_err(BatchError::IoError)?;
+        let api_key = get_api_key().map_err(BatchError::IoError)?;
+        wait_for_turn(&api_key, ctx).map_err(BatchError::IoError)?;
+        let lang_name: &str = tgt_lang.get_name();
+        let results = batch::run_batch(
+            &api_key,
+            &ctx.model,
+            to_generation_config(&ctx.options.get_generate_content_config()),
+            &format!("translate-dir {}", lang_name),
+            &items,
+            ctx.cancel.as_ref(),
+            ctx.options.request_timeout_secs.map(Duration::from_secs),
+        )?;
+        let usage = match results.usage.requests {
+            0 => RunUsage {
+                output_tokens: results.answers.values().map(|r| estimate_tokens(r)).sum(),
+                ..planned
+            },
+            _ => results.usage,
+        };
+        record_usage(ctx, usage);
+        results.answers
+    };
+
+    for (file_id, ((_, to_path), (chunks, protected, file_ctx))) in
+        files.iter().zip(prepared.iter()).enumerate()
+    {
+        let mut segments = Vec::new();
+        for (chunk_id, chunk) in chunks.iter().enumerate() {
+            let key = format!("{}:{}", file_id, chunk_id);
+            if let Some(translation) = cached.get(&key) {
+                segments.push(PreviousChunk {
+                    source: chunk.clone(),
+                    translation: translation.clone(),
+                });
+                continue;
+            }
+            let response = results
+                .get(&key)
+                .ok_or_else(|| BatchError::MissingResult(key.clone()))?;
+            let chunk_translation = match extract_translated_from_response(response.clone()) {
+                Ok(chunk_translation)
+                    if check_chunk(chunk, &chunk_translation, tgt_lang, file_ctx).is_empty() =>
+                {
+                    remember(
+                        chunk,
+                        tgt_lang,
+                        file_ctx,
+                        &cache_keys[&key],
+                        &chunk_translation,
+                    )
+                    .map_err(BatchError::IoError)?;
+                    chunk_translation
+                }
+                // a malformed answer or one failing the quality checks is asked for again
+                // directly instead of failing the batch
+                _ => {
+                    let previous = chunk_id.checked_sub(1).map(|id| PreviousChunk {
+                        source: chunks[id].clone(),
+                        translation: String::new(),
+                    });
+                    translate_chunk(chunk, tgt_lang, file_ctx, previous.as_ref())
+                        .map_err(BatchError::IoError)?
+                }
+            };
+            segments.push(PreviousChunk {
+                source: chunk.clone(),
+                translation: chunk_translation,
+            });
+        }
+        let translated: String = segments.iter().map(|s| s.translation.as_str()).collect();
+        let contents =
+            reinsert(&translated, protected).map_err(|e| BatchError::IoError(invalid_data(e)))?;
+        backup_target(to_path, file_ctx).map_err(BatchError::IoError)?;
+        write_atomic(to_path, contents).map_err(BatchError::IoError)?;
+        merge::record_base(file_ctx, tgt_lang.clone(), &segments, protected)
+            .map_err(BatchError::IoError)?;
+        metrics::record_file_translated();
+    }
+    Ok(())
+}
+
+/// Asks the model for translations of the terms, returns the suggested translation of every term
+/// the model answered for
+pub fn suggest_term_translations(
+    terms: &[String],
+    src_lang: &Language,
+    tgt_lang: &Language,
+    ctx: &TranslationContext,
+) -> std::io::Result<HashMap<String, String>> {
+    if terms.is_empty() {
+        return Ok(HashMap::new());
+    }
+    let src_name: &str = src_lang.get_name();
+    let tgt_name: &str = tgt_lang.get_name();
+    let mut contents = format!(
+        "Translate the following terms of technical documentation from {} into {}. Use the \
+         translation established in the field, keep a term as is if it isn't translated in \
+         practice. Answer with one line per term, the term and its translation separated by a \
+         tab, inside of an <output> tag.\n<terms>\n",
+        src_name, tgt_name
+    );
+    for term in terms {
+        contents.push_str(term);
+        contents.push('\n');
+    }
+    contents.push_str("</terms>");
+    let message = Message {
+        system_instruction: None,
+        contents,
+    };
+
+    let rt = Runtime::new()?;
+    let response = rt.block_on(async { ask_gemini_model(message, ctx).await })?;
+    Ok(extract_translated_from_response(response)
+        .map_err(invalid_data)?
+        .lines()
+        .filter_map(|line| line.split_once('\t'))
+        .map(|(term, translation)| (term.trim().to_string(), translation.trim().to_string()))
+        .filter(|(term, translation)| terms.contains(term) && !translation.is_empty())
+        .collect())
+}
+
+/// Asks the model for a short summary of the document (title, topic, register) that is sent with
+/// every chunk of it. Only the first [`SUMMARY_INPUT_CHARS`] characters are summarized.
+pub fn summarize_document(text: &str, ctx: &TranslationContext) -> std::io::Result<String> {
+    let end = text
+        .char_indices()
+        .nth(SUMMARY_INPUT_CHARS)
+        .map(|(i, _)| i)
+        .unwrap_or(text.len());
+    let message = Message {
+        system_instruction: None,
+        contents: format!(
+            "Summarize the following document in at most five sentences, in its own language. \
+             Give its title, its topic, its audience and its register (formal or informal, how \
+             the reader is addressed). Ignore the [[PROTECTED_n]] placeholders. Answer inside of \
+             an <output> tag.\n<document>{}\n</document>",
+            &text[..end]
+        ),
+    };
+
+    let rt = Runtime::new()?;
+    let response = rt.block_on(async { ask_gemini_model(message, ctx).await })?;
+    // the summary only helps the translation, a malformed one is left out
+    Ok(extract_translated_from_response(response)
+        .unwrap_or_default()
+        .trim()
+        .to_string())
+}
+
+/// Returns the context with the summary of the document if the summary is enabled and the
+/// document has more than one chunk, a short document is its own global context
+fn with_summary(
+    ctx: &TranslationContext,
+    text: &str,
+    chunks: usize,
+) -> std::io::Result<TranslationContext> {
+    let mut ctx = ctx.clone();
+    if ctx.options.summary && ctx.summary.is_none() && chunks > 1 {
+        let summary = summarize_document(text, &ctx)?;
+        ctx.summary = Some(summary).filter(|s| !s.is_empty());
+    }
+    Ok(ctx)
+}
+
+/// The first hop of the translation through a pivot language, see [`TranslationContext::pivot`]:
+/// every chunk is translated into the pivot language, the pivot text is translated into the
+/// target language with [`PivotHop::ctx`]. Both hops are cached like any other chunk.
+struct PivotHop {
+    pivot: Language,
+    /// context of the first hop, without the prompt and the examples of the target language
+    first: TranslationContext,
+    /// context of the second hop, its source language is the pivot language and its glossary
+    /// keeps the entries of the source language
+    ctx: TranslationContext,
+    /// the previous chunk with its translation into the pivot language
+    previous: Option<PreviousChunk>,
+}
+
+impl PivotHop {
+    /// Returns the first hop of the translation into _tgt_lang_ with the context, `None` if it
+    /// has no pivot language
+    fn new(ctx: &TranslationContext, tgt_lang: &Language) -> Option<Self> {
+        let pivot = ctx.pivot.clone()?;
+        Some(Self {
+            pivot: pivot.clone(),
+            first: TranslationContext {
+                prompt: ctx
+                    .pivot_prompt
+                    .clone()
+                    .unwrap_or_else(|| ctx.prompt.clone()),
+                examples: Vec::new(),
+                pivot: None,
+                ..ctx.clone()
+            },
+            ctx: TranslationContext {
+                source_language: Some(pivot.clone()),
+                glossary: ctx.glossary.for_pivot(
+                    ctx.source_language.clone(),
+                    pivot,
+                    tgt_lang.clone(),
+                ),
+                pivot: None,
+                ..ctx.clone()
+            },
+            previous: None,
+        })
+    }
+    /// Translates the chunk into the pivot language. Returns the pivot text and the previous
+    /// chunk of the second hop: the pivot text of the previous chunk with its translation, taken
+    /// from _previous_.
+    fn translate(
+        &mut self,
+        chunk: &str,
+        previous: Option<&PreviousChunk>,
+    ) -> std::io::Result<(String, Option<PreviousChunk>)> {
+        let text = translate_chunk(chunk, &self.pivot, &self.first, self.previous.as_ref())?;
+        let hop_previous = match (&self.previous, previous) {
+            (Some(pivot), Some(previous)) => Some(PreviousChunk {
+                source: pivot.translation.clone(),
+                translation: previous.translation.clone(),
+            }),
+            _ => None,
+        };
+        self.previous = Some(PreviousChunk {
+            source: chunk.to_string(),
+            translation: text.clone(),
+        });
+        Ok((text, hop_previous))
+    }
+    /// Forgets the previous chunk when a chunk is restored without its pivot text
+    fn skip(hop: Option<&mut Self>) {
+        if let Some(hop) = hop {
+            hop.previous = None;
+        }
+    }
+}
+
+/// Loads the checkpoint of the file of the context, `None` if the file isn't a part of a project
+/// or has a single chunk
+fn load_checkpoint(
+    ctx: &TranslationContext,
+    text: &str,
+    tgt_lang: &Language,
+    chunks: usize,
+) -> Option<Checkpoint> {
+    let root = ctx.project_root.as_deref()?;
+    let file = ctx.file.as_deref()?;
+    (chunks > 1).then(|| Checkpoint::load(root, file, tgt_lang.clone(), text, &ctx.model))
+}
+
+/// Returns the context with the summary of the document like [`with_summary`], the summary of
+/// the checkpoint is reused instead of asking for a new one and a new one is kept in it
+fn with_checkpoint_summary(
+    ctx: &TranslationContext,
+    text: &str,
+    chunks: usize,
+    checkpoint: Option<&mut Checkpoint>,
+) -> std::io::Result<TranslationContext> {
+    let Some(checkpoint) = checkpoint else {
+        return with_summary(ctx, text, chunks);
+    };
+    let mut ctx = ctx.clone();
+    if ctx.summary.is_none() {
+        ctx.summary = checkpoint.summary.clone();
+    }
+    let ctx = with_summary(&ctx, text, chunks)?;
+    checkpoint.summary = ctx.summary.clone();
+    Ok(ctx)
+}
+
+/// Adds the translated chunk to the checkpoint, if any, and saves it
+fn save_checkpoint(
+    ctx: &TranslationContext,
+    tgt_lang: &Language,
+    checkpoint: Option<&mut Checkpoint>,
+    source: &str,
+    translation: &str,
+) -> std::io::Result<()> {
+    if let (Some(checkpoint), Some(root), Some(file)) = (checkpoint, &ctx.project_root, &ctx.file) {
+        checkpoint.push(source, translation);
+        checkpoint.save(root, file, tgt_lang.clone())?;
+    }
+    Ok(())
+}
+
+/// Removes the checkpoint of the file once its translation is complete
+fn remove_checkpoint(
+    ctx: &TranslationContext,
+    tgt_lang: &Language,
+    checkpoint: Option<&Checkpoint>,
+) -> std::io::Result<()> {
+    if let (Some(_), Some(root), Some(file)) = (checkpoint, &ctx.project_root, &ctx.file) {
+        Checkpoint::remove(root, file, tgt_lang.clone())?;
+    }
+    Ok(())
+}
+
+/// Formats the end of the previous chunk as the context of the next request
+fn format_previous_chunk(previous: &PreviousChunk, lines: usize) -> String {
+    let tail = |text: &str| {
+        let all: Vec<&str> = text.trim_end().lines().collect();
+        all[all.len().saturating_sub(lines)..].join("\n")
+    };
+    let mut res = String::from(
+        "<previous_context>\nThe end of the previous part of the document, only to keep the terms \
+         and the sentences consistent, don't translate it again.\n",
+    );
+    res.push_str(&format!(
+        "<source>\n{}\n</source>\n",
+        tail(&previous.source)
+    ));
+    if !previous.translation.trim().is_empty() {
+        res.push_str(&format!(
+            "<translation>\n{}\n</translation>\n",
+            tail(&previous.translation)
+        ));
+    }
+    res.push_str("</previous_context>\n");
     res
 }
 
-pub fn translate_chunk(contents: &str, tgt_lang: &Language) -> String {
+/// Formats the near matches of the chunk in the translation memory as reference translations,
+/// empty if there are none
+fn format_fuzzy_matches(
+    contents: &str,
+    tgt_lang: &Language,
+    ctx: &TranslationContext,
+) -> std::io::Result<String> {
+    let Some(memory) = open_memory(contents, ctx) else {
+        return Ok(String::new());
+    };
+    let threshold = ctx
+        .options
+        .fuzzy_threshold
+        .unwrap_or(DEFAULT_FUZZY_THRESHOLD);
+    let matches = ctx.memory_indexes.fuzzy_matches(
+        &memory,
+        contents,
+        tgt_lang.clone(),
+        threshold,
+        MAX_FUZZY_MATCHES,
+    )?;
+    if matches.is_empty() {
+        return Ok(String::new());
+    }
+    let mut res = String::from(
+        "<reference_translations>\nEarlier translations of similar texts, reuse their wording \
+         where the text below is the same and translate the differences.\n",
+    );
+    for m in matches {
+        res.push_str(&format!(
+            "<reference similarity=\"{:.0}%\">\n<source>\n{}\n</source>\n<translation>\n{}\n\
+             </translation>\n</reference>\n",
+            m.similarity * 100.0,
+            m.entry.source.trim_end(),
+            m.entry.translation.trim_end()
+        ));
+    }
+    res.push_str("</reference_translations>\n");
+    Ok(res)
+}
+
+/// Builds the message sent to the model to translate the chunk
+fn build_message(
+    contents: &str,
+    tgt_lang: &Language,
+    ctx: &TranslationContext,
+    previous: Option<&PreviousChunk>,
+) -> std::io::Result<Message> {
+    let references = format_fuzzy_matches(contents, tgt_lang, ctx)?;
+    build_message_with(contents, tgt_lang, ctx, previous, &references)
+}
+
+/// Returns the cache key of the chunk. The key is of the message without the near matches of the
+/// translation memory, so the cache is looked up before the memory is scanned for them.
+fn get_cache_key(
+    contents: &str,
+    tgt_lang: &Language,
+    ctx: &TranslationContext,
+    previous: Option<&PreviousChunk>,
+) -> std::io::Result<String> {
+    let message = build_message_with(contents, tgt_lang, ctx, previous, "")?;
+    Ok(cache::cache_key(contents, tgt_lang, ctx, &message))
+}
+
+/// Builds the message of the chunk with the _references_ translations of similar texts
+fn build_message_with(
+    contents: &str,
+    tgt_lang: &Language,
+    ctx: &TranslationContext,
+    previous: Option<&PreviousChunk>,
+    references: &str,
+) -> std::io::Result<Message> {
     let mut fin_mess = String::new();
-    let prompt = put_lang_into_prompt(&get_default_prompt(), tgt_lang);
-    fin_mess.push_str(&prompt);
+    let vars = ctx.get_prompt_variables(tgt_lang);
+    let mut prompt = render_prompt(&ctx.prompt, &vars).map_err(invalid_data)?;
+    // a template that doesn't place the glossary itself still gets it
+    let glossary = vars.glossary.as_deref().unwrap_or("");
+    if !ctx.prompt.contains("[GLOSSARY]") && !glossary.is_empty() {
+        prompt.push_str("\nUse the following approved translations of terms:\n");
+        prompt.push_str(glossary);
+        prompt.push('\n');
+    }
+    let examples = vars.examples.as_deref().unwrap_or("");
+    if !ctx.prompt.contains("[EXAMPLES]") && !examples.is_empty() {
+        prompt.push_str("\nTranslate in the style of the following examples:\n");
+        prompt.push_str(examples);
+    }
+    let system_instruction = if ctx.options.system_instruction {
+        Some(prompt)
+    } else {
+        fin_mess.push_str(&prompt);
+        None
+    };
+    if let Some(summary) = &ctx.summary {
+        fin_mess.push_str(&format!(
+            "<document_summary>\nThe summary of the whole document the text below is a part of, \
+             don't translate it.\n{}\n</document_summary>\n",
+            summary
+        ));
+    }
+    fin_mess.push_str(references);
+    let context_lines = ctx.options.context_lines.unwrap_or(DEFAULT_CONTEXT_LINES);
+    if let Some(previous) = previous.filter(|_| context_lines > 0) {
+        fin_mess.push_str(&format_previous_chunk(previous, context_lines));
+    }
     fin_mess.push_str("<document>");
     fin_mess.push_str(contents);
     fin_mess.push_str("\n</document>");
+    Ok(Message {
+        system_instruction,
+        contents: fin_mess,
+    })
+}
 
-    let rt = Runtime::new().unwrap();
-    let gen_resp = rt.block_on(async { ask_gemini_model(fin_mess).await });
+/// Translates the chunk, the end of the _previous_ chunk is sent with it as context
+pub fn translate_chunk(
+    contents: &str,
+    tgt_lang: &Language,
+    ctx: &TranslationContext,
+    previous: Option<&PreviousChunk>,
+) -> std::io::Result<String> {
+    if let Some(translation) = recall(contents, tgt_lang, ctx) {
+        tracing::debug!("chunk found in the translation memory");
+        return Ok(translation);
+    }
+    let key = get_cache_key(contents, tgt_lang, ctx, previous)?;
+    if let Some(translation) = cache::lookup(ctx, &key) {
+        tracing::debug!("chunk found in the cache");
+        return Ok(translation);
+    }
+    let fin_mess = build_message(contents, tgt_lang, ctx, previous)?;
 
-    let translated = extract_translated_from_response(gen_resp);
-    translated
+    let rt = Runtime::new()?;
+    let mut attempt = 0;
+    let mut qa_attempt = 0;
+    let mut findings: Vec<QaFinding> = Vec::new();
+    loop {
+        let message = with_findings(&with_reminder(&fin_mess, attempt), &findings);
+        budget::check(ctx, &budget::plan_message(&message, contents))?;
+        let gen_resp = rt.block_on(async { ask_gemini_model(message, ctx).await })?;
+        let translation = match extract_translated_from_response(gen_resp) {
+            Ok(translation) => translation,
+            Err(e) if attempt >= get_output_retries(ctx) => return Err(invalid_data(e)),
+            Err(e) => {
+                tracing::warn!(attempt, error = %e, "malformed answer, the chunk is requested again");
+                attempt += 1;
+                continue;
+            }
+        };
+        findings = check_chunk(contents, &translation, tgt_lang, ctx);
+        if findings.is_empty() {
+            remember(contents, tgt_lang, ctx, &key, &translation)?;
+            return Ok(translation);
+        }
+        if qa_attempt >= ctx.options.qa_retries.unwrap_or_default() {
+            let messages: Vec<&str> = findings.iter().map(|f| f.message.as_str()).collect();
+            return Err(invalid_data(ResponseError::QaFailed(messages.join("; "))));
+        }
+        tracing::warn!(
+            attempt = qa_attempt,
+            findings = findings.len(),
+            "the translation failed the checks, the chunk is requested again"
+        );
+        qa_attempt += 1;
+        attempt = 0;
+    }
 }
 
-pub async fn ask_gemini_model(message: String) -> String {
-    let api_key =
-        std::env::var("GOOGLE_API_KEY").expect("GOOGLEAI_API_KEY environment variable must be set");
+/// Runs the quality checks on the translation of the chunk, returns no findings if the checks
+/// are disabled
+fn check_chunk(
+    contents: &str,
+    translation: &str,
+    tgt_lang: &Language,
+    ctx: &TranslationContext,
+) -> Vec<QaFinding> {
+    if ctx.options.qa_retries.is_none() {
+        return Vec::new();
+    }
+    let entries = match ctx.source_language.clone() {
+        Some(source) => ctx.glossary.entries_for_pair(source, tgt_lang.clone()),
+        None => Vec::new(),
+    };
+    let file = ctx.file.as_deref().unwrap_or(Path::new(""));
+    check_segment(file, contents, translation, tgt_lang.clone(), &entries)
+}
+
+/// Returns the message with the _findings_ of the quality checks of the previous translation
+fn with_findings(message: &Message, findings: &[QaFinding]) -> Message {
+    let mut message = message.clone();
+    if !findings.is_empty() {
+        message.contents.push_str(QA_FINDINGS_INTRO);
+        for finding in findings {
+            message
+                .contents
+                .push_str(&format!("- {}\n", finding.message));
+        }
+    }
+    message
+}
 
+/// Returns the translation memory the chunk is looked up in and recorded to, `None` if the memory
+/// is disabled, there's no project or the chunk is blank
+fn open_memory(contents: &str, ctx: &TranslationContext) -> Option<TranslationMemory> {
+    if ctx.options.no_memory || contents.trim().is_empty() {
+        return None;
+    }
+    let memory = TranslationMemory::open(ctx.project_root.as_deref()?);
+    Some(match &ctx.shared_memory {
+        Some(shared) => memory.with_shared(shared.clone()),
+        None => memory,
+    })
+}
+
+/// Returns the translation of the chunk recorded in the translation memory of the project, `None`
+/// if there is none or the memory is disabled
+fn recall(contents: &str, tgt_lang: &Language, ctx: &TranslationContext) -> Option<String> {
+    let memory = open_memory(contents, ctx)?;
+    let translation = memory.recall(
+        contents,
+        tgt_lang.clone(),
+        &ctx.get_memory_context(tgt_lang),
+    );
+    metrics::record_memory_lookup(translation.is_some());
+    translation
+}
+
+/// Records the translation of the chunk in the cache and the translation memory of the project
+fn remember(
+    contents: &str,
+    tgt_lang: &Language,
+    ctx: &TranslationContext,
+    cache_key: &str,
+    translation: &str,
+) -> std::io::Result<()> {
+    cache::store(ctx, cache_key, translation)?;
+    if let Some(memory) = open_memory(contents, ctx) {
+        let entry = memory.record(
+            contents,
+            translation,
+            ctx.source_language.clone(),
+            tgt_lang.clone(),
+            Some(ctx.get_memory_context(tgt_lang)),
+        )?;
+        ctx.memory_indexes.insert(entry);
+    }
+    Ok(())
+}
+
+fn get_output_retries(ctx: &TranslationContext) -> u32 {
+    ctx.options.output_retries.unwrap_or(DEFAULT_OUTPUT_RETRIES)
+}
+
+/// Returns the message of the _attempt_ to translate the chunk, the retries after a malformed
+/// answer get a stricter instruction
+fn with_reminder(message: &Message, attempt: u32) -> Message {
+    let mut message = message.clone();
+    if attempt > 0 {
+        message.contents.push_str(OUTPUT_REMINDER);
+    }
+    message
+}
+
+/// Translates the chunk streaming the response, _on_text_ receives the translated text piece by
+/// piece
+pub fn translate_chunk_streaming(
+    contents: &str,
+    tgt_lang: &Language,
+    ctx: &TranslationContext,
+    previous: Option<&PreviousChunk>,
+    on_text: &mut dyn FnMut(&str) -> std::io::Result<()>,
+) -> std::io::Result<()> {
+    if let Some(translation) = recall(contents, tgt_lang, ctx) {
+        tracing::debug!("chunk found in the translation memory");
+        return on_text(&translation);
+    }
+    let key = get_cache_key(contents, tgt_lang, ctx, previous)?;
+    if let Some(translation) = cache::lookup(ctx, &key) {
+        tracing::debug!("chunk found in the cache");
+        return on_text(&translation);
+    }
+    let fin_mess = build_message(contents, tgt_lang, ctx, previous)?;
+
+    let rt = Runtime::new()?;
+    let mut attempt = 0;
+    loop {
+        let message = with_reminder(&fin_mess, attempt);
+        budget::check(ctx, &budget::plan_message(&message, contents))?;
+        let mut extractor = OutputExtractor::new();
+        let mut translation = String::new();
+        rt.block_on(async {
+            ask_gemini_model_stream(message, ctx, |delta| {
+                let text = extractor.push(delta);
+                translation.push_str(&text);
+                on_text(&text)
+            })
+            .await
+        })?;
+        // nothing was passed on without an output tag, the chunk can be requested again
+        if extractor.has_output() {
+            return remember(contents, tgt_lang, ctx, &key, &translation);
+        }
+        if attempt >= get_output_retries(ctx) {
+            return Err(invalid_data(ResponseError::MissingOutput));
+        }
+        tracing::warn!(
+            attempt,
+            "no output in the answer, the chunk is requested again"
+        );
+        attempt += 1;
+    }
+}
+
+fn build_request(message: Message, ctx: &TranslationContext) -> GenerateContentReq {
     let params = GenerateContentParameters::default()
         .contents(vec![Content {
-            parts: Some(vec![Part::default().text(message)]),
+            parts: Some(vec![Part::default().text(message.contents)]),
             role: Some("user".to_string()),
         }])
-        .model("gemini-2.0-flash");
+        .model(ctx.model.as_str())
+        .config(ctx.options.get_generate_content_config());
 
-    let request = google_genai::datatypes::GenerateContentReq::default()
+    let mut request = GenerateContentReq::default()
         .contents(params.contents.unwrap())
         .model(params.model.unwrap());
+    if let Some(instruction) = message.system_instruction {
+        request = request.system_instruction(Content {
+            parts: Some(vec![Part::default().text(instruction)]),
+            role: None,
+        });
+    }
+    match params.config.and_then(|c| to_generation_config(&c)) {
+        Some(generation_config) => request.generation_config(generation_config),
+        None => request,
+    }
+}
+
+/// Converts the generation parameters to the request format, `None` if none is set
+fn to_generation_config(config: &GenerateContentConfig) -> Option<GenerationConfig> {
+    if config.temperature.is_none() && config.top_p.is_none() && config.max_output_tokens.is_none()
+    {
+        return None;
+    }
+    Some(GenerationConfig {
+        temperature: config.temperature,
+        top_p: config.top_p,
+        max_output_tokens: config.max_output_tokens,
+        ..Default::default()
+    })
+}
+
+/// Blocks until the schedule and the rate limit allow to send a request, fails with an
+/// `Interrupted` error if the run is cancelled meanwhile
+fn wait_for_turn(api_key: &str, ctx: &TranslationContext) -> std::io::Result<()> {
+    let start = Instant::now();
+    schedule::wait_for_schedule(&ctx.options.schedule, ctx.cancel.as_ref())?;
+    rate_limit::acquire(api_key, &ctx.options.rate_limit, ctx.cancel.as_ref())?;
+    metrics::record_throttling(start.elapsed());
+    Ok(())
+}
+
+/// Adds the tokens of a request to the usage of the run and to the one of the project
+fn record_usage(ctx: &TranslationContext, usage: RunUsage) {
+    tracing::debug!(
+        input_tokens = usage.input_tokens,
+        output_tokens = usage.output_tokens,
+        "tokens used"
+    );
+    let saved = ctx.usage.record(&usage, || match &ctx.project_root {
+        Some(root) => ProjectUsage::record(root, &ctx.model, &usage),
+        None => Ok(()),
+    });
+    // the usage is informative, failing to write it doesn't fail the translation
+    if let Err(e) = saved {
+        tracing::warn!(error = %e, "the usage of the project couldn't be recorded");
+    }
+}
+
+/// Reports the request to the model, in the span of the chunk it was sent for
+fn trace_request<E: std::fmt::Display>(
+    ctx: &TranslationContext,
+    start: Instant,
+    error: Option<&E>,
+) {
+    let duration_ms = start.elapsed().as_millis() as u64;
+    match error {
+        None => tracing::debug!(model = %ctx.model, duration_ms, "model request"),
+        Some(e) => {
+            tracing::warn!(model = %ctx.model, duration_ms, error = %e, "model request failed")
+        }
+    }
+}
+
+/// Returns the error of the model API as an IO error, the API key the client puts in the URL of
+/// the request is redacted so it never reaches the logs, the reports or the replies of the server
+fn api_error(e: impl std::fmt::Display) -> std::io::Error {
+    std::io::Error::other(redact_api_key(&e.to_string()))
+}
+
+/// Where the API key appears in a request: the `key` query parameter or the header
+const API_KEY_MARKERS: [&str; 3] = ["?key=", "&key=", "x-goog-api-key:"];
+
+/// Replaces the value of every `key` query parameter and `x-goog-api-key` header of the text
+fn redact_api_key(text: &str) -> String {
+    let lowercase = text.to_ascii_lowercase();
+    let mut res = String::with_capacity(text.len());
+    let mut copied = 0;
+    let mut i = 0;
+    while i < text.len() {
+        let Some(marker) = API_KEY_MARKERS
+            .iter()
+            .find(|m| lowercase.as_bytes()[i..].starts_with(m.as_bytes()))
+        else {
+            i += 1;
+            continue;
+        };
+        let mut start = i + marker.len();
+        // the value of a header follows a space
+        if marker.ends_with(':') {
+            start += text[start..].len() - text[start..].trim_start_matches(' ').len();
+        }
+        let end = text[start..]
+            .find(|c: char| matches!(c, '&' | ')' | '"' | ',') || c.is_whitespace())
+            .map_or(text.len(), |e| start + e);
+        if end > start {
+            res.push_str(&text[copied..start]);
+            res.push_str("REDACTED");
+            copied = end;
+        }
+        i = end;
+    }
+    res.push_str(&text[copied..]);
+    res
+}
+
+/// Returns the API key of the provider, fails with a `NotFound` error carrying
+/// [`CredentialError::MissingKey`] if it isn't set
+fn get_api_key() -> std::io::Result<String> {
+    match std::env::var(API_KEY_VAR) {
+        Ok(key) if !key.trim().is_empty() => Ok(key),
+        _ => Err(std::io::Error::new(
+            std::io::ErrorKind::NotFound,
+            CredentialError::MissingKey(API_KEY_VAR),
+        )),
+    }
+}
+
+/// Sends the message to the model and passes every piece of the streamed answer to _on_delta_
+pub async fn ask_gemini_model_stream<F>(
+    message: Message,
+    ctx: &TranslationContext,
+    mut on_delta: F,
+) -> std::io::Result<()>
+where
+    F: FnMut(&str) -> std::io::Result<()>,
+{
+    let api_key = get_api_key()?;
+    let input_tokens = budget::estimate_message_tokens(&message);
+    let request = build_request(message, ctx);
 
-    thread::sleep(Duration::from_secs(5));
-    let response = google_genai::generate_content(&api_key, request)
+    wait_for_turn(&api_key, ctx)?;
+    let start = Instant::now();
+    let mut answer = String::new();
+    let res = with_timeout(
+        ctx.request_timeout(),
+        read_stream(&api_key, request, &mut |delta| {
+            answer.push_str(delta);
+            on_delta(delta)
+        }),
+    )
+    .await;
+    metrics::record_api_call(start, res.is_ok());
+    trace_request(ctx, start, res.as_ref().err());
+    let reported = res.as_ref().ok().copied().flatten();
+    record_usage(
+        ctx,
+        reported.unwrap_or(RunUsage {
+            requests: 1,
+            input_tokens,
+            output_tokens: estimate_tokens(&answer),
+        }),
+    );
+    res.map(|_| ())
+}
+
+async fn read_stream(
+    api_key: &str,
+    request: GenerateContentReq,
+    on_delta: &mut dyn FnMut(&str) -> std::io::Result<()>,
+) -> std::io::Result<Option<RunUsage>> {
+    let mut stream = google_genai::generate_content_stream(api_key, request)
         .await
-        .unwrap();
+        .map_err(api_error)?;
+    let mut usage = None;
+    while let Some(response) = stream.next().await {
+        let response = response.map_err(api_error)?;
+        // every response carries the counts of the answer so far
+        if let Some(reported) = response
+            .usage_metadata
+            .as_ref()
+            .and_then(budget::from_metadata)
+        {
+            usage = Some(reported);
+        }
+        // the end of the stream is reported as a response without candidates, the event
+        // source would reconnect and ask again if we kept polling
+        let candidate = match response.candidates.as_ref().and_then(|v| v.first()) {
+            Some(c) => c,
+            None => break,
+        };
+        let text = candidate
+            .content
+            .as_ref()
+            .and_then(|cnt| cnt.parts.as_ref())
+            .and_then(|v| v.first())
+            .and_then(|part| part.text.as_ref());
+        if let Some(text) = text {
+            on_delta(text)?;
+        }
+        if candidate.finish_reason.is_some() {
+            break;
+        }
+    }
+    Ok(usage)
+}
+
+/// Sends the message to the model and returns its answer, fails with a `TimedOut` error if it
+/// takes longer than the timeout of the context
+pub async fn ask_gemini_model(
+    message: Message,
+    ctx: &TranslationContext,
+) -> std::io::Result<String> {
+    let api_key = get_api_key()?;
+    let input_tokens = budget::estimate_message_tokens(&message);
+    let request = build_request(message, ctx);
+
+    wait_for_turn(&api_key, ctx)?;
+    let start = Instant::now();
+    let response = with_timeout(ctx.request_timeout(), async {
+        google_genai::generate_content(&api_key, request)
+            .await
+            .map_err(api_error)
+    })
+    .await;
+    metrics::record_api_call(start, response.is_ok());
+    trace_request(ctx, start, response.as_ref().err());
+    let response = response?;
+    let reported = response
+        .usage_metadata
+        .as_ref()
+        .and_then(budget::from_metadata);
     let text = response
         .candidates // Option<Vec<Candidate>>
         .as_ref() // Option<&Vec<Candidate>>
@@ -103,9 +1568,43 @@ pub async fn ask_gemini_model(message: String) -> String {
         .and_then(|part| part.text.as_ref())
         .cloned() // we finally need an owned String
         .unwrap_or_default(); // or .ok_or(MyError::MissingText)? for Result<T,E>
+    record_usage(
+        ctx,
+        reported.unwrap_or(RunUsage {
+            requests: 1,
+            input_tokens,
+            output_tokens: estimate_tokens(&text),
+        }),
+    );
 
-    println!("answer:\n\n");
-    println!("{}", &text);
-    return text;
-    String::new()
+    tracing::trace!(answer = %text, "model answer");
+    Ok(text)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn redacts_the_api_key_of_the_url() {
+        let error = "Request failed: error sending request for url \
+            (https://host/v1beta/models/m:generateContent?key=secret123&alt=sse)";
+        let redacted = redact_api_key(error);
+        assert!(!redacted.contains("secret123"));
+        assert!(redacted.contains("?key=REDACTED&alt=sse)"));
+        assert_eq!(redact_api_key("no key here"), "no key here");
+    }
+
+    #[test]
+    fn redacts_only_the_key_parameter_and_header() {
+        let url = "https://host/m?monkey=foo&key=secret123&turkey=bar";
+        assert_eq!(
+            redact_api_key(url),
+            "https://host/m?monkey=foo&key=REDACTED&turkey=bar"
+        );
+        assert_eq!(
+            redact_api_key("headers: {x-goog-api-key: secret123, accept: */*}"),
+            "headers: {x-goog-api-key: REDACTED, accept: */*}"
+        );
+    }
 }
@@ -0,0 +1,209 @@
+//! The prompt the files are translated with. A project can override the default prompt with a
+//! file of its own, the prompt is a template whose placeholders are replaced before sending.
+use std::path::Path;
+
+use crate::{
+    errors::{prompt_errors::PromptError, sandbox_errors::SandboxError},
+    sandbox::confine_path,
+    Language,
+};
+
+/// Prompt used when neither the project nor the library config sets one. `[TARGET_LANGUAGE]` is
+/// replaced with the name of the target language.
+pub const DEFAULT_PROMPT: &str = include_str!("prompts/default.txt");
+
+/// File in the project root that overrides the default prompt when the config doesn't reference
+/// another one
+pub const PROJECT_PROMPT_FILE: &str = "prompt.txt";
+
+/// Placeholders every prompt has to contain
+pub const REQUIRED_PLACEHOLDERS: &[&str] = &["[TARGET_LANGUAGE]"];
+
+/// Returns the prompt template to translate with, the first found of:
+/// - the file referenced by the project config (relative to the project root),
+/// - the [`PROJECT_PROMPT_FILE`] in the project root,
+/// - the file referenced by the library config,
+/// - [`DEFAULT_PROMPT`].
+///
+/// Fails if a referenced file can't be read or the prompt misses a required placeholder.
+pub fn resolve_prompt(
+    project_root: &Path,
+    project_prompt_file: Option<&Path>,
+) -> Result<String, PromptError> {
+    if let Some(file) = project_prompt_file {
+        return load_prompt_file(project_root, file);
+    }
+    let prompt = if project_root.join(PROJECT_PROMPT_FILE).is_file() {
+        std::fs::read_to_string(project_root.join(PROJECT_PROMPT_FILE))
+            .map_err(PromptError::IoError)?
+    } else if let Some(file) = crate::lib_config::load_lib_config().get_prompt_file() {
+        std::fs::read_to_string(file).map_err(PromptError::IoError)?
+    } else {
+        DEFAULT_PROMPT.to_string()
+    };
+    validate_prompt(&prompt)?;
+    Ok(prompt)
+}
+
+/// Reads the prompt template from the file of the project (relative to the project root) and
+/// validates it
+pub fn load_prompt_file(project_root: &Path, file: &Path) -> Result<String, PromptError> {
+    let path = confine_path(project_root, file).map_err(|e| match e {
+        SandboxError::IoError(e) => PromptError::IoError(e),
+        e => PromptError::PathOutsideProject(e),
+    })?;
+    let prompt = std::fs::read_to_string(path).map_err(PromptError::IoError)?;
+    validate_prompt(&prompt)?;
+    Ok(prompt)
+}
+
+/// Verifies that the prompt contains all the [`REQUIRED_PLACEHOLDERS`] and nothing but the
+/// [`KNOWN_PLACEHOLDERS`]
+pub fn validate_prompt(prompt: &str) -> Result<(), PromptError> {
+    if let Some(p) = REQUIRED_PLACEHOLDERS.iter().find(|p| !prompt.contains(**p)) {
+        return Err(PromptError::MissingPlaceholder(p.to_string()));
+    }
+    match find_placeholders(prompt)
+        .into_iter()
+        .find(|p| !KNOWN_PLACEHOLDERS.contains(&p.as_str()))
+    {
+        Some(p) => Err(PromptError::UnknownPlaceholder(p)),
+        None => Ok(()),
+    }
+}
+
+/// Placeholders the prompt template may contain
+pub const KNOWN_PLACEHOLDERS: &[&str] = &[
+    "[TARGET_LANGUAGE]",
+    "[SOURCE_LANGUAGE]",
+    "[FILE_NAME]",
+    "[FILE_FORMAT]",
+    "[GLOSSARY]",
+    "[EXAMPLES]",
+];
+
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+/// An example of a translation shown to the model to steer the tone and the formatting
+pub struct ExamplePair {
+    /// a snippet in the source language
+    pub source: String,
+    /// its translation
+    pub translation: String,
+}
+
+/// Formats the examples for the prompt, empty if there are none
+pub fn format_examples(examples: &[ExamplePair]) -> String {
+    examples
+        .iter()
+        .map(|e| {
+            format!(
+                "<example>\n<source>\n{}\n</source>\n<translation>\n{}\n</translation>\n</example>\n",
+                e.source.trim_end(),
+                e.translation.trim_end()
+            )
+        })
+        .collect()
+}
+
+#[derive(Debug, Clone)]
+/// Values the placeholders of the prompt are replaced with, a value that isn't known is replaced
+/// with an empty string
+pub struct PromptVariables {
+    pub target_language: Language,
+    pub source_language: Option<Language>,
+    /// name of the translated file
+    pub file_name: Option<String>,
+    /// name of the format handler of the file (e.g. `markdown`)
+    pub file_format: Option<String>,
+    /// the glossary entries of the language pair, one per line
+    pub glossary: Option<String>,
+    /// example translations, see [`format_examples`]
+    pub examples: Option<String>,
+}
+
+impl PromptVariables {
+    pub fn new(target_language: Language) -> Self {
+        Self {
+            target_language,
+            source_language: None,
+            file_name: None,
+            file_format: None,
+            glossary: None,
+            examples: None,
+        }
+    }
+    fn get_value(&self, placeholder: &str) -> Option<String> {
+        let res = match placeholder {
+            "[TARGET_LANGUAGE]" => self.target_language.get_name().to_string(),
+            "[SOURCE_LANGUAGE]" => self
+                .source_language
+                .as_ref()
+                .map(|l| l.get_name().to_string())
+                .unwrap_or_default(),
+            "[FILE_NAME]" => self.file_name.clone().unwrap_or_default(),
+            "[FILE_FORMAT]" => self.file_format.clone().unwrap_or_default(),
+            "[GLOSSARY]" => self.glossary.clone().unwrap_or_default(),
+            "[EXAMPLES]" => self.examples.clone().unwrap_or_default(),
+            _ => return None,
+        };
+        Some(res)
+    }
+}
+
+/// Replaces the placeholders of the template with the variables, fails if the template contains
+/// a placeholder that isn't known
+pub fn render_prompt(template: &str, vars: &PromptVariables) -> Result<String, PromptError> {
+    let mut res = String::new();
+    let mut rest = template;
+    while let Some((start, len)) = next_placeholder(rest) {
+        let placeholder = &rest[start..start + len];
+        let value = vars
+            .get_value(placeholder)
+            .ok_or_else(|| PromptError::UnknownPlaceholder(placeholder.to_string()))?;
+        res.push_str(&rest[..start]);
+        res.push_str(&value);
+        rest = &rest[start + len..];
+    }
+    res.push_str(rest);
+    Ok(res)
+}
+
+/// Returns all the placeholders of the template
+fn find_placeholders(template: &str) -> Vec<String> {
+    let mut res = Vec::new();
+    let mut rest = template;
+    while let Some((start, len)) = next_placeholder(rest) {
+        res.push(rest[start..start + len].to_string());
+        rest = &rest[start + len..];
+    }
+    res
+}
+
+/// Finds the next `[UPPER_CASE]` placeholder, returns its start and length. Text in double
+/// brackets, like the `[[PROTECTED_0]]` placeholders of the protected regions, isn't a prompt
+/// placeholder.
+fn next_placeholder(text: &str) -> Option<(usize, usize)> {
+    let bytes = text.as_bytes();
+    let mut id = 0;
+    while let Some(offset) = text[id..].find('[') {
+        let start = id + offset;
+        if bytes.get(start + 1) == Some(&b'[') {
+            // skip the whole double bracket
+            id = text[start..]
+                .find("]]")
+                .map(|end| start + end + 2)
+                .unwrap_or(text.len());
+            continue;
+        }
+        let name_len = bytes[start + 1..]
+            .iter()
+            .take_while(|b| b.is_ascii_uppercase() || b.is_ascii_digit() || **b == b'_')
+            .count();
+        let end = start + 1 + name_len;
+        if name_len > 0 && bytes[start + 1].is_ascii_uppercase() && bytes.get(end) == Some(&b']') {
+            return Some((start, end + 1 - start));
+        }
+        id = start + 1;
+    }
+    None
+}
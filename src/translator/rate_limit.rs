@@ -0,0 +1,136 @@
+//! A rate limiter shared by everything that uses the same API key. Inside of a process every
+//! project shares the same limiter state, optionally the state is also coordinated between
+//! processes through a locked file in a directory of the user.
+use std::{
+    collections::HashMap,
+    io::{Read, Seek, Write},
+    path::PathBuf,
+    sync::{Mutex, OnceLock},
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+use crate::{
+    helper::fnv1a_64,
+    shutdown::{sleep_cancellable, CancellationToken},
+};
+
+/// Maximum number of requests that may wait for their slot, a slot further in the future read
+/// from the shared file (e.g. left by a crashed process) is brought back within it
+const MAX_PENDING: u32 = 100;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+/// Limit of requests sent with one API key
+pub struct RateLimit {
+    /// maximum number of requests per minute
+    pub requests_per_minute: u32,
+    /// if true, the limit is shared with the other processes of the user
+    #[serde(default)]
+    pub cross_process: bool,
+}
+
+impl Default for RateLimit {
+    fn default() -> Self {
+        Self {
+            requests_per_minute: 12,
+            cross_process: false,
+        }
+    }
+}
+
+impl RateLimit {
+    /// returns the minimal time between two requests
+    pub fn get_interval(&self) -> Duration {
+        Duration::from_millis(60_000 / self.requests_per_minute.max(1) as u64)
+    }
+}
+
+/// Time at which the next request is allowed, per API key hash
+static NEXT_SLOTS: OnceLock<Mutex<HashMap<u64, Instant>>> = OnceLock::new();
+
+/// Blocks until a request with the API key is allowed by the limit. If the state shared
+/// between processes can't be accessed, only the limit inside of this process is respected.
+/// The wait stops with an `Interrupted` error once a shutdown is requested or the _cancel_
+/// token is cancelled.
+pub fn acquire(
+    api_key: &str,
+    limit: &RateLimit,
+    cancel: Option<&CancellationToken>,
+) -> std::io::Result<()> {
+    let key_hash = fnv1a_64(api_key.as_bytes());
+    let interval = limit.get_interval();
+    let wait = if limit.cross_process {
+        reserve_slot_cross_process(key_hash, interval)
+            .unwrap_or_else(|_| reserve_slot(key_hash, interval))
+    } else {
+        reserve_slot(key_hash, interval)
+    };
+    sleep_cancellable(wait, cancel)
+}
+
+/// Reserves the next free slot for the key and returns how long to wait for it
+fn reserve_slot(key_hash: u64, interval: Duration) -> Duration {
+    let slots = NEXT_SLOTS.get_or_init(|| Mutex::new(HashMap::new()));
+    // a panic while holding the lock can't leave the map in an invalid state
+    let mut slots = slots.lock().unwrap_or_else(|e| e.into_inner());
+    let now = Instant::now();
+    let slot = slots.get(&key_hash).copied().unwrap_or(now).max(now);
+    slots.insert(key_hash, slot + interval);
+    slot - now
+}
+
+/// Same as [`reserve_slot`] but the state is kept in a file locked for the time of the
+/// reservation, so concurrent processes see each other's reservations
+fn reserve_slot_cross_process(key_hash: u64, interval: Duration) -> std::io::Result<Duration> {
+    let path = get_state_file_path(key_hash)?;
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let mut options = std::fs::OpenOptions::new();
+    options.create(true).truncate(false).read(true).write(true);
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+    let mut file = options.open(path)?;
+    file.lock()?;
+
+    let mut contents = String::new();
+    file.read_to_string(&mut contents)?;
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as u64;
+    let max_slot = now + interval.as_millis() as u64 * MAX_PENDING as u64;
+    let slot = contents
+        .trim()
+        .parse::<u64>()
+        .unwrap_or(now)
+        .clamp(now, max_slot);
+
+    file.set_len(0)?;
+    file.rewind()?;
+    file.write_all((slot + interval.as_millis() as u64).to_string().as_bytes())?;
+    file.unlock()?;
+
+    Ok(Duration::from_millis(slot - now))
+}
+
+/// Returns the path of the file shared by the processes of the user, in the runtime directory
+/// of the user or in its state directory. Fails if the user has neither.
+fn get_state_file_path(key_hash: u64) -> std::io::Result<PathBuf> {
+    let dir = match std::env::var_os("XDG_RUNTIME_DIR") {
+        Some(dir) if !dir.is_empty() => PathBuf::from(dir),
+        _ => match std::env::var_os("XDG_STATE_HOME") {
+            Some(dir) if !dir.is_empty() => PathBuf::from(dir),
+            _ => std::env::var_os("HOME")
+                .map(|home| PathBuf::from(home).join(".local").join("state"))
+                .ok_or_else(|| {
+                    std::io::Error::new(
+                        std::io::ErrorKind::NotFound,
+                        "no runtime or state directory of the user",
+                    )
+                })?,
+        },
+    };
+    Ok(dir
+        .join("translate-dir")
+        .join(format!("{:016x}.ratelimit", key_hash)))
+}
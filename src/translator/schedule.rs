@@ -0,0 +1,136 @@
+//! Scheduling of translation requests: time windows in which requests may be sent and a pacing
+//! limit per hour, so long runs (e.g. in watch mode) can use off-peak quota unattended.
+use std::{
+    collections::VecDeque,
+    sync::{Mutex, OnceLock},
+    time::{Duration, Instant},
+};
+
+use chrono::{NaiveTime, Timelike};
+
+use crate::shutdown::{sleep_cancellable, CancellationToken};
+
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+/// A daily time window in the local time, in the `HH:MM` format. A window whose end is before
+/// its start spans midnight (e.g. 22:00–06:00).
+pub struct TimeWindow {
+    pub start: String,
+    pub end: String,
+}
+
+impl TimeWindow {
+    /// Creates the window, returns `None` if a time isn't in the `HH:MM` format or the window
+    /// is empty
+    pub fn new(start: &str, end: &str) -> Option<Self> {
+        let window = Self {
+            start: start.to_string(),
+            end: end.to_string(),
+        };
+        window.is_valid().then_some(window)
+    }
+
+    /// returns true if both times are valid and the window isn't empty
+    pub fn is_valid(&self) -> bool {
+        match (parse_time(&self.start), parse_time(&self.end)) {
+            (Some(start), Some(end)) => start != end,
+            _ => false,
+        }
+    }
+
+    /// returns true if the time is inside of the window, invalid windows contain nothing
+    pub fn contains(&self, time: NaiveTime) -> bool {
+        let (Some(start), Some(end)) = (parse_time(&self.start), parse_time(&self.end)) else {
+            return false;
+        };
+        if start <= end {
+            start <= time && time < end
+        } else {
+            time >= start || time < end
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+/// When the translation requests may be sent
+pub struct Schedule {
+    /// windows the requests are allowed in, empty means at any time
+    #[serde(default)]
+    pub windows: Vec<TimeWindow>,
+    /// maximum number of requests in any hour, `None` means no limit
+    #[serde(default)]
+    pub max_requests_per_hour: Option<u32>,
+}
+
+impl Schedule {
+    /// returns true if every window of the schedule is valid
+    pub fn is_valid(&self) -> bool {
+        self.windows.iter().all(|w| w.is_valid())
+    }
+}
+
+/// Times of the requests sent during the last hour
+static SENT: OnceLock<Mutex<VecDeque<Instant>>> = OnceLock::new();
+
+/// Blocks until the schedule allows to send a request and records the request. The wait stops
+/// with an `Interrupted` error once a shutdown is requested or the _cancel_ token is cancelled.
+pub fn wait_for_schedule(
+    schedule: &Schedule,
+    cancel: Option<&CancellationToken>,
+) -> std::io::Result<()> {
+    loop {
+        let wait = time_until_window(schedule, chrono::Local::now().time());
+        if wait.is_zero() {
+            break;
+        }
+        sleep_cancellable(wait, cancel)?;
+    }
+
+    let limit = match schedule.max_requests_per_hour {
+        Some(l) => l.max(1) as usize,
+        None => return Ok(()),
+    };
+    let sent = SENT.get_or_init(|| Mutex::new(VecDeque::new()));
+    loop {
+        let wait = {
+            let mut sent = sent.lock().unwrap_or_else(|e| e.into_inner());
+            let now = Instant::now();
+            while sent
+                .front()
+                .is_some_and(|t| now.duration_since(*t) >= Duration::from_secs(3600))
+            {
+                sent.pop_front();
+            }
+            if sent.len() < limit {
+                sent.push_back(now);
+                return Ok(());
+            }
+            Duration::from_secs(3600) - now.duration_since(sent[0])
+        };
+        sleep_cancellable(wait, cancel)?;
+    }
+}
+
+/// Returns how long to wait from _now_ until a window of the schedule opens
+fn time_until_window(schedule: &Schedule, now: NaiveTime) -> Duration {
+    if schedule.windows.is_empty() || schedule.windows.iter().any(|w| w.contains(now)) {
+        return Duration::ZERO;
+    }
+    let now_secs = now.num_seconds_from_midnight() as u64;
+    schedule
+        .windows
+        .iter()
+        .filter(|w| w.is_valid())
+        .filter_map(|w| parse_time(&w.start))
+        .map(|start| {
+            let start_secs = start.num_seconds_from_midnight() as u64;
+            (start_secs + 24 * 3600 - now_secs) % (24 * 3600)
+        })
+        .min()
+        .map(Duration::from_secs)
+        // no valid window at all, nothing to wait for
+        .unwrap_or(Duration::ZERO)
+}
+
+fn parse_time(time: &str) -> Option<NaiveTime> {
+    NaiveTime::parse_from_str(time, "%H:%M").ok()
+}
@@ -0,0 +1,104 @@
+//! Watch mode: the source directory of a project is monitored, the untranslatable files are
+//! synced and the changed translatable files are translated again as soon as they change, see
+//! [`crate::project::Project::watch`]. The watch stops once a shutdown is requested, see
+//! [`crate::shutdown`].
+use std::{
+    collections::BTreeSet,
+    path::PathBuf,
+    sync::mpsc::{Receiver, RecvTimeoutError},
+    time::Duration,
+};
+
+use notify::{EventKind, RecursiveMode, Watcher};
+
+use crate::{change_report::SyncReport, shutdown::is_shutdown_requested, Language};
+
+/// Interval at which a waiting watch checks if a shutdown was requested
+const SHUTDOWN_POLL: Duration = Duration::from_millis(200);
+
+#[derive(Debug, Clone)]
+pub struct WatchOptions {
+    /// time without any change after which the collected changes are handled
+    pub debounce: Duration,
+    /// if true, the changed translatable files are translated into every target language,
+    /// otherwise the changes are only reported
+    pub translate: bool,
+    /// the sync and the translations overwrite the target files edited by hand
+    pub force: bool,
+}
+
+impl Default for WatchOptions {
+    fn default() -> Self {
+        Self {
+            debounce: Duration::from_millis(500),
+            translate: true,
+            force: false,
+        }
+    }
+}
+
+#[derive(Debug)]
+/// What the watch did after a change of the source directory
+pub enum WatchEvent {
+    /// the untranslatable files were synced
+    Synced(SyncReport),
+    /// the translatable file changed, it's queued for translation if it's enabled
+    Changed(PathBuf),
+    /// the changed file was translated into the language
+    Translated { file: PathBuf, lang: Language },
+    /// handling a change failed, the watch goes on
+    Failed {
+        file: Option<PathBuf>,
+        error: String,
+    },
+}
+
+/// Watches directories recursively and collects the changed paths
+pub struct SourceWatcher {
+    // dropping the watcher stops the watch
+    _watcher: notify::RecommendedWatcher,
+    events: Receiver<notify::Result<notify::Event>>,
+}
+
+impl SourceWatcher {
+    pub fn new(dirs: &[PathBuf]) -> notify::Result<Self> {
+        let (sender, events) = std::sync::mpsc::channel();
+        let mut watcher = notify::recommended_watcher(sender)?;
+        for dir in dirs {
+            watcher.watch(dir, RecursiveMode::Recursive)?;
+        }
+        Ok(Self {
+            _watcher: watcher,
+            events,
+        })
+    }
+
+    /// Returns the paths changed since the last call, once no change happened for _debounce_.
+    /// If _block_ is set it waits for the first change, otherwise it returns nothing if there
+    /// is none. Returns nothing once a shutdown is requested.
+    pub fn next_batch(&self, debounce: Duration, block: bool) -> Vec<PathBuf> {
+        let mut changed = BTreeSet::new();
+        loop {
+            if is_shutdown_requested() {
+                return Vec::new();
+            }
+            let timeout = match (changed.is_empty(), block) {
+                (true, true) => SHUTDOWN_POLL,
+                (true, false) => Duration::ZERO,
+                (false, _) => debounce,
+            };
+            match self.events.recv_timeout(timeout) {
+                Ok(Ok(event)) => {
+                    // reading the files (e.g. to translate them) isn't a change
+                    if !matches!(event.kind, EventKind::Access(_)) {
+                        changed.extend(event.paths);
+                    }
+                }
+                // the watcher may miss events (e.g. an overflow), the batch is handled anyway
+                Ok(Err(_)) => {}
+                Err(RecvTimeoutError::Timeout) if changed.is_empty() && block => {}
+                Err(_) => return changed.into_iter().collect(),
+            }
+        }
+    }
+}
@@ -0,0 +1,124 @@
+//! A module that marks translated files as machine translated, either with a sidecar
+//! `<file>.meta.json` or with metadata embedded into the file itself.
+use std::path::{Path, PathBuf};
+
+use crate::{helper::write_atomic, Language};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+/// Where the machine translation metadata is written
+pub enum WatermarkMode {
+    /// a `<file>.meta.json` file next to the translated one
+    Sidecar,
+    /// inside the translated file (HTML meta tag or a comment), falls back to a sidecar for
+    /// formats that can't carry it
+    Embedded,
+    /// both embedded and in a sidecar
+    Both,
+}
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+/// Disclosure metadata attached to a machine translated file
+pub struct TranslationMetadata {
+    pub machine_translated: bool,
+    /// model that produced the translation
+    pub model: String,
+    /// date and time of the translation in RFC 3339 format
+    pub date: String,
+    pub source_language: Language,
+    pub target_language: Language,
+    /// name and version of the tool that produced the translation
+    pub generator: String,
+}
+
+impl TranslationMetadata {
+    pub fn new(model: &str, src_lang: Language, tgt_lang: Language) -> Self {
+        Self {
+            machine_translated: true,
+            model: model.to_string(),
+            date: chrono::Utc::now().to_rfc3339(),
+            source_language: src_lang,
+            target_language: tgt_lang,
+            generator: format!("{} {}", env!("CARGO_PKG_NAME"), env!("CARGO_PKG_VERSION")),
+        }
+    }
+
+    fn as_line(&self) -> String {
+        let src: &str = self.source_language.get_name();
+        let tgt: &str = self.target_language.get_name();
+        format!(
+            "machine translated from {} to {} by {} on {} ({})",
+            src, tgt, self.model, self.date, self.generator
+        )
+    }
+}
+
+/// Returns the path of the sidecar metadata file for the given file
+pub fn get_sidecar_path(path: &Path) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(".meta.json");
+    path.with_file_name(name)
+}
+
+/// Marks the file by given path as machine translated according to the _mode_
+pub fn apply_watermark(
+    path: &Path,
+    mode: WatermarkMode,
+    meta: &TranslationMetadata,
+) -> std::io::Result<()> {
+    let mut sidecar = mode != WatermarkMode::Embedded;
+    if mode != WatermarkMode::Sidecar && !embed_metadata(path, meta)? {
+        sidecar = true;
+    }
+    if sidecar {
+        let serialized = serde_json::to_string_pretty(meta)
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?;
+        write_atomic(&get_sidecar_path(path), serialized)?;
+    }
+    Ok(())
+}
+
+/// Embeds the metadata into the file, returns false if the format of the file isn't supported
+fn embed_metadata(path: &Path, meta: &TranslationMetadata) -> std::io::Result<bool> {
+    let ext = path
+        .extension()
+        .map(|e| e.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+    let contents = std::fs::read_to_string(path)?;
+    let line = meta.as_line();
+
+    let new_contents = match ext.as_str() {
+        "html" | "htm" => {
+            let tag = format!(
+                "<meta name=\"machine-translated\" content=\"{}\">",
+                line.replace('"', "&quot;")
+            );
+            match contents.find("<head>") {
+                Some(id) => {
+                    let at = id + "<head>".len();
+                    format!("{}\n{}{}", &contents[..at], tag, &contents[at..])
+                }
+                None => format!("{}\n{}", tag, contents),
+            }
+        }
+        "md" | "markdown" => {
+            let comment = format!("<!-- {} -->\n", line);
+            // keep the front matter on top, otherwise it's not recognized anymore
+            match front_matter_end(&contents) {
+                Some(at) => format!("{}{}{}", &contents[..at], comment, &contents[at..]),
+                None => format!("{}{}", comment, contents),
+            }
+        }
+        "tex" => format!("% {}\n{}", line, contents),
+        "txt" | "rst" => format!("{}\n\n{}", line, contents),
+        _ => return Ok(false),
+    };
+    write_atomic(path, new_contents)?;
+    Ok(true)
+}
+
+/// Returns the byte offset right after a `---` delimited front matter, if the text has one
+fn front_matter_end(text: &str) -> Option<usize> {
+    let rest = text.strip_prefix("---\n")?;
+    let end = rest.find("\n---\n")?;
+    Some("---\n".len() + end + "\n---\n".len())
+}
@@ -0,0 +1,192 @@
+//! A workspace is a directory (e.g. a monorepo) with several projects under it, every directory
+//! with a `trans_conf.json` is one. The operations of the workspace run on all of its projects:
+//! a failure in one project doesn't stop the others.
+use std::path::{Path, PathBuf};
+
+use crate::{
+    change_report::SyncReport,
+    errors::{
+        project_errors::{SyncFilesError, TranslateFileError},
+        workspace_errors::WorkspaceError,
+    },
+    project::{load, Project},
+    status::ProjectStatus,
+    Language,
+};
+
+/// Name of the config file that marks the root of a project
+const CONFIG_FILE_NAME: &str = "trans_conf.json";
+
+/// The projects found under a root directory
+pub struct Workspace {
+    root: PathBuf,
+    projects: Vec<Project>,
+    /// the directories that couldn't be searched and the projects that couldn't be loaded
+    errors: Vec<WorkspaceError>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+/// State of the translations of every project of the workspace
+pub struct WorkspaceStatus {
+    pub projects: Vec<WorkspaceProjectStatus>,
+    /// the projects whose state couldn't be read, with the error
+    #[serde(default)]
+    pub errors: Vec<(PathBuf, String)>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+/// State of the translations of a project of the workspace
+pub struct WorkspaceProjectStatus {
+    /// root of the project relative to the root of the workspace
+    pub path: PathBuf,
+    pub name: String,
+    pub status: ProjectStatus,
+}
+
+impl WorkspaceStatus {
+    /// returns true if the state of every project was read and every translation is up to
+    /// date or approved
+    pub fn is_clean(&self) -> bool {
+        self.errors.is_empty() && self.projects.iter().all(|p| p.status.is_clean())
+    }
+}
+
+impl Workspace {
+    /// Finds and loads the projects under _root_. The hidden directories aren't searched, nor
+    /// the directories of a project once its config is found. A directory that can't be read
+    /// or a project that can't be loaded is skipped, see [`Workspace::get_errors_as_ref`].
+    pub fn discover(root: PathBuf) -> Result<Self, WorkspaceError> {
+        let root = root.canonicalize().map_err(WorkspaceError::IoError)?;
+        let mut project_roots = Vec::new();
+        let mut errors = Vec::new();
+        find_projects(&root, &mut project_roots, &mut errors);
+        if project_roots.is_empty() && errors.is_empty() {
+            return Err(WorkspaceError::NoProjects);
+        }
+        project_roots.sort();
+        let mut projects = Vec::new();
+        for path in project_roots {
+            match load(path.clone()) {
+                Ok(project) => projects.push(project),
+                Err(e) => errors.push(WorkspaceError::LoadProjectError(path, e)),
+            }
+        }
+        Ok(Self {
+            root,
+            projects,
+            errors,
+        })
+    }
+    pub fn get_root_path(&self) -> &Path {
+        &self.root
+    }
+    pub fn get_projects_as_ref(&self) -> &Vec<Project> {
+        &self.projects
+    }
+    pub fn get_projects_as_mut(&mut self) -> &mut Vec<Project> {
+        &mut self.projects
+    }
+    /// Returns the errors of the discovery: the directories that couldn't be searched and the
+    /// projects that couldn't be loaded, they aren't part of the workspace
+    pub fn get_errors_as_ref(&self) -> &Vec<WorkspaceError> {
+        &self.errors
+    }
+    /// Returns the root of the project relative to the root of the workspace
+    fn relative_path(&self, project: &Project) -> PathBuf {
+        let path = project.get_root_path();
+        path.strip_prefix(&self.root)
+            .map(Path::to_path_buf)
+            .unwrap_or(path)
+    }
+
+    /// Returns the state of the translations of every project, see [`Project::status`]. A
+    /// project whose state can't be read is listed in [`WorkspaceStatus::errors`].
+    pub fn status(&self) -> WorkspaceStatus {
+        let mut status = WorkspaceStatus::default();
+        for project in &self.projects {
+            let path = self.relative_path(project);
+            match project.status() {
+                Ok(project_status) => status.projects.push(WorkspaceProjectStatus {
+                    path,
+                    name: project.get_config_as_ref().get_name(),
+                    status: project_status,
+                }),
+                Err(e) => status.errors.push((path, e.to_string())),
+            }
+        }
+        status
+    }
+
+    /// Syncs the untranslatable files of every project, see [`Project::sync_files`]. Returns the
+    /// result of every project with its root relative to the workspace.
+    pub fn sync_files(
+        &mut self,
+        force: bool,
+    ) -> Vec<(PathBuf, Result<SyncReport, SyncFilesError>)> {
+        let paths: Vec<PathBuf> = self
+            .projects
+            .iter()
+            .map(|p| self.relative_path(p))
+            .collect();
+        paths
+            .into_iter()
+            .zip(self.projects.iter_mut())
+            .map(|(path, project)| (path, project.sync_files(force)))
+            .collect()
+    }
+
+    /// Translates the files of every project into each of its target languages, see
+    /// [`Project::translate_all`]. Returns the result of every project and language with the
+    /// root of the project relative to the workspace.
+    pub fn translate_all(&mut self) -> Vec<(PathBuf, Language, Result<(), TranslateFileError>)> {
+        let paths: Vec<PathBuf> = self
+            .projects
+            .iter()
+            .map(|p| self.relative_path(p))
+            .collect();
+        let mut results = Vec::new();
+        for (path, project) in paths.into_iter().zip(self.projects.iter_mut()) {
+            let langs: Vec<Language> = project
+                .get_config_as_ref()
+                .get_lang_dirs_as_ref()
+                .iter()
+                .map(|d| d.get_lang())
+                .collect();
+            for lang in langs {
+                results.push((path.clone(), lang.clone(), project.translate_all(lang)));
+            }
+        }
+        results
+    }
+}
+
+/// Collects the roots of the projects under _dir_ into _found_, the directories that can't be
+/// read are skipped and collected into _errors_
+fn find_projects(dir: &Path, found: &mut Vec<PathBuf>, errors: &mut Vec<WorkspaceError>) {
+    if dir.join(CONFIG_FILE_NAME).is_file() {
+        found.push(dir.to_path_buf());
+        return;
+    }
+    let entries = match std::fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(e) => {
+            errors.push(WorkspaceError::ReadDirError(dir.to_path_buf(), e));
+            return;
+        }
+    };
+    for entry in entries {
+        let entry = match entry {
+            Ok(entry) => entry,
+            Err(e) => {
+                errors.push(WorkspaceError::ReadDirError(dir.to_path_buf(), e));
+                continue;
+            }
+        };
+        let hidden = entry.file_name().to_string_lossy().starts_with('.');
+        // the links are skipped so a link loop can't be followed
+        if hidden || !entry.file_type().is_ok_and(|t| t.is_dir()) {
+            continue;
+        }
+        find_projects(&entry.path(), found, errors);
+    }
+}
@@ -0,0 +1,32 @@
+use translate_dir_lib::{
+    Language, testing::fixture::ProjectBuilder, translator::TranslatorOptions,
+};
+
+#[test]
+fn a_batch_run_backs_up_the_translation_it_overwrites() {
+    // an empty file has no chunks, nothing is sent to the model
+    let mut project = ProjectBuilder::new("backup")
+        .lang(Language::French)
+        .translatable_file("empty.txt", "")
+        .target_file(Language::French, "empty.txt", "previous translation")
+        .build()
+        .unwrap();
+    project
+        .set_translator_options(TranslatorOptions {
+            batch: true,
+            backups: Some(2),
+            ..Default::default()
+        })
+        .unwrap();
+    project.translate_all(Language::French).unwrap();
+
+    let target = project.path("backup_fr/empty.txt");
+    let backups = project.list_backups(&target).unwrap();
+    assert_eq!(backups.len(), 1);
+    assert_eq!(project.read("backup_fr/empty.txt").unwrap(), "");
+    project.restore_backup(&target, &backups[0]).unwrap();
+    assert_eq!(
+        project.read("backup_fr/empty.txt").unwrap(),
+        "previous translation"
+    );
+}
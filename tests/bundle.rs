@@ -0,0 +1,92 @@
+use std::path::Path;
+
+use translate_dir_lib::{
+    Language,
+    bundle::{Bundle, BundleOptions},
+    errors::{bundle_errors::BundleError, sandbox_errors::SandboxError},
+    project::import_bundle,
+    testing::fixture::ProjectBuilder,
+};
+
+#[cfg(unix)]
+#[test]
+fn a_bundle_writing_through_a_symlink_is_not_imported() {
+    let project = ProjectBuilder::new("bundle")
+        .lang(Language::French)
+        .file("notes.txt", "notes")
+        .build()
+        .unwrap();
+    let bundle_path = project.path("project.bundle");
+    project
+        .export_bundle(&bundle_path, &BundleOptions::default())
+        .unwrap();
+    let mut bundle = Bundle::read(&bundle_path).unwrap();
+    bundle.add(Path::new("link/escaped.txt"), b"escaped");
+    bundle.write(&bundle_path).unwrap();
+
+    let outside = project.path("outside");
+    let dest = project.path("imported");
+    std::fs::create_dir_all(&outside).unwrap();
+    std::fs::create_dir_all(&dest).unwrap();
+    std::os::unix::fs::symlink(&outside, dest.join("link")).unwrap();
+
+    assert!(matches!(
+        import_bundle(&bundle_path, dest.clone()),
+        Err(BundleError::PathOutsideProject(SandboxError::SymlinkEscape))
+    ));
+    assert!(!outside.join("escaped.txt").exists());
+    // the entries checked before the escaping one aren't written either
+    assert!(!dest.join("trans_conf.json").exists());
+}
+
+#[test]
+fn a_rejected_bundle_leaves_no_destination_behind() {
+    let project = ProjectBuilder::new("bundle")
+        .lang(Language::French)
+        .file("notes.txt", "notes")
+        .build()
+        .unwrap();
+    let bundle_path = project.path("project.bundle");
+    project
+        .export_bundle(&bundle_path, &BundleOptions::default())
+        .unwrap();
+    let mut bundle = Bundle::read(&bundle_path).unwrap();
+    bundle.add(Path::new("../escaped.txt"), b"escaped");
+    bundle.write(&bundle_path).unwrap();
+
+    let dest = project.path("imported");
+    assert!(matches!(
+        import_bundle(&bundle_path, dest.clone()),
+        Err(BundleError::PathOutsideProject(_))
+    ));
+    assert!(!dest.exists());
+}
+
+#[test]
+fn a_bundle_does_not_overwrite_an_existing_file() {
+    let project = ProjectBuilder::new("bundle")
+        .lang(Language::French)
+        .file("notes.txt", "notes")
+        .build()
+        .unwrap();
+    let bundle_path = project.path("project.bundle");
+    project
+        .export_bundle(&bundle_path, &BundleOptions::default())
+        .unwrap();
+    let mut bundle = Bundle::read(&bundle_path).unwrap();
+    bundle.add(Path::new("src/notes.txt"), b"theirs");
+    bundle.write(&bundle_path).unwrap();
+
+    let dest = project.path("imported");
+    std::fs::create_dir_all(dest.join("src")).unwrap();
+    std::fs::write(dest.join("src/notes.txt"), "mine").unwrap();
+    assert!(matches!(
+        import_bundle(&bundle_path, dest.clone()),
+        Err(BundleError::WouldOverwrite(_))
+    ));
+    assert_eq!(
+        std::fs::read_to_string(dest.join("src/notes.txt")).unwrap(),
+        "mine"
+    );
+    assert!(!dest.join("trans_conf.json").exists());
+}
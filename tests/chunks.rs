@@ -0,0 +1,41 @@
+use std::{collections::HashMap, path::Path};
+
+use translate_dir_lib::{
+    Language,
+    testing::fixture::ProjectBuilder,
+    translator::chunks::{split_into_chunks, strategy_for_path},
+};
+
+fn numbered_lines(count: usize) -> String {
+    (0..count).map(|i| format!("line {}\n", i)).collect()
+}
+
+#[test]
+fn a_long_file_is_split_into_several_requests() {
+    let project = ProjectBuilder::new("chunks")
+        .lang(Language::French)
+        .translatable_file("short.txt", numbered_lines(10))
+        .translatable_file("long.txt", numbered_lines(120))
+        .build()
+        .unwrap();
+    let estimate = project.estimate_translation(Language::French).unwrap();
+    let chunks = |name: &str| {
+        estimate
+            .files
+            .iter()
+            .find(|f| f.file.ends_with(name))
+            .map(|f| f.chunks)
+    };
+    assert_eq!(chunks("short.txt"), Some(1));
+    assert_eq!(chunks("long.txt"), Some(3));
+}
+
+#[test]
+fn the_chunks_give_back_the_text() {
+    let text = numbered_lines(25);
+    let strategy = strategy_for_path(Path::new("notes.txt"), &HashMap::new());
+    let chunks = split_into_chunks(&text, 10, &[], strategy.as_ref());
+    assert_eq!(chunks.len(), 3);
+    assert!(chunks.iter().all(|c| c.lines().count() <= 10));
+    assert_eq!(chunks.concat(), text);
+}
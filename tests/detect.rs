@@ -0,0 +1,42 @@
+use translate_dir_lib::{
+    Language, detect::SAMPLE_CHARS, project_config::SymlinkPolicy,
+    testing::fixture::ProjectBuilder,
+};
+
+const TEXT: &str = "The cat is on the table and the dog is in the garden with the children.\n";
+
+#[cfg(unix)]
+#[test]
+fn a_link_loop_is_sampled_once() {
+    let mut project = ProjectBuilder::new("detect")
+        .lang(Language::French)
+        .file("docs/intro.txt", TEXT.repeat(3))
+        .build()
+        .unwrap();
+    std::os::unix::fs::symlink("..", project.path("src/docs/up")).unwrap();
+    project.set_symlink_policy(SymlinkPolicy::Follow).unwrap();
+
+    assert_eq!(
+        project.detect_source_language("src").unwrap(),
+        Some(Language::English)
+    );
+}
+
+#[test]
+fn only_the_start_of_a_large_file_is_read() {
+    // the limit cuts the multi-byte character in two, the file is still a text
+    let mut contents = TEXT.repeat(SAMPLE_CHARS / TEXT.len() + 1);
+    contents.truncate(SAMPLE_CHARS - 1);
+    contents.push('é');
+    contents.push_str(TEXT);
+    let project = ProjectBuilder::new("detect")
+        .lang(Language::French)
+        .file("big.txt", contents)
+        .build()
+        .unwrap();
+
+    assert_eq!(
+        project.detect_source_language("src").unwrap(),
+        Some(Language::English)
+    );
+}
@@ -0,0 +1,66 @@
+use translate_dir_lib::{
+    Language, errors::exchange_errors::ExchangeError, testing::fixture::ProjectBuilder,
+};
+
+/// An XLIFF document from _src_lang_ into French of the files with their translation, `None` for
+/// an untranslated one
+fn xliff(src_lang: &str, files: &[(&str, &str, Option<&str>)]) -> String {
+    let mut res = format!(
+        "<xliff xmlns=\"urn:oasis:names:tc:xliff:document:2.0\" version=\"2.1\" \
+         srcLang=\"{}\" trgLang=\"fr\">\n",
+        src_lang
+    );
+    for (id, (original, source, target)) in files.iter().enumerate() {
+        res.push_str(&format!(
+            "<file id=\"f{}\" original=\"{}\"><unit id=\"u1\"><segment><source>{}</source>",
+            id + 1,
+            original,
+            source
+        ));
+        if let Some(target) = target {
+            res.push_str(&format!("<target>{}</target>", target));
+        }
+        res.push_str("</segment></unit></file>\n");
+    }
+    res.push_str("</xliff>\n");
+    res
+}
+
+#[test]
+fn nothing_is_imported_from_a_document_with_an_untranslated_file() {
+    let project = ProjectBuilder::new("exchange")
+        .lang(Language::French)
+        .translatable_file("a.txt", "Hello")
+        .translatable_file("b.txt", "Bye")
+        .build()
+        .unwrap();
+    let doc = project.path("review.xlf");
+    let files = [("a.txt", "Hello", Some("Bonjour")), ("b.txt", "Bye", None)];
+    std::fs::write(&doc, xliff("en", &files)).unwrap();
+
+    assert!(matches!(
+        project.import_xliff(&doc),
+        Err(ExchangeError::MissingTarget(_))
+    ));
+    let tgt_file = project
+        .get_config_as_ref()
+        .get_tgt_file_path(&project.path("src/a.txt"), &Language::French)
+        .unwrap();
+    assert!(!tgt_file.exists());
+}
+
+#[test]
+fn a_document_from_another_source_language_is_rejected() {
+    let project = ProjectBuilder::new("exchange")
+        .lang(Language::French)
+        .translatable_file("a.txt", "Hello")
+        .build()
+        .unwrap();
+    let doc = project.path("review.xlf");
+    std::fs::write(&doc, xliff("de", &[("a.txt", "Hallo", Some("Bonjour"))])).unwrap();
+
+    assert!(matches!(
+        project.import_xliff(&doc),
+        Err(ExchangeError::SourceLanguageMismatch(code)) if code == "de"
+    ));
+}
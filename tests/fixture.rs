@@ -0,0 +1,25 @@
+use translate_dir_lib::{
+    Language, errors::fixture_errors::FixtureError, testing::fixture::ProjectBuilder,
+};
+
+#[test]
+fn target_files_go_to_the_directory_of_the_language() {
+    let project = ProjectBuilder::new("site")
+        .lang_dir(Language::French, "content/fr")
+        .target_file(Language::French, "notes.txt", "notes")
+        .build()
+        .unwrap();
+    assert_eq!(project.read("content/fr/notes.txt").unwrap(), "notes");
+}
+
+#[test]
+fn a_target_file_needs_its_language() {
+    let res = ProjectBuilder::new("site")
+        .lang(Language::French)
+        .target_file(Language::German, "notes.txt", "notes")
+        .build();
+    assert!(matches!(
+        res,
+        Err(FixtureError::LangNotInProject(Language::German))
+    ));
+}
@@ -0,0 +1,64 @@
+use translate_dir_lib::{
+    Language,
+    errors::exchange_errors::ExchangeError,
+    exchange::{
+        Workflow,
+        po::{PoEntry, write_po},
+    },
+    testing::fixture::ProjectBuilder,
+};
+
+fn entry(file: &str, msgid: &str, msgstr: &str) -> PoEntry {
+    PoEntry {
+        file: file.to_string(),
+        index: 0,
+        msgid: msgid.to_string(),
+        msgstr: msgstr.to_string(),
+        fuzzy: false,
+    }
+}
+
+#[test]
+fn nothing_is_compiled_if_a_file_is_incomplete() {
+    let project = ProjectBuilder::new("po")
+        .lang(Language::French)
+        .translatable_file("a.txt", "Hello")
+        .translatable_file("b.txt", "World")
+        .synced()
+        .build()
+        .unwrap();
+    let entries = [
+        entry("a.txt", "Hello", "Bonjour"),
+        entry("b.txt", "World", ""),
+    ];
+    let po_path = project.get_po_path(Some(Language::French));
+    std::fs::create_dir_all(po_path.parent().unwrap()).unwrap();
+    std::fs::write(&po_path, write_po("po", Some(Language::French), &entries)).unwrap();
+
+    let res = project.compile_po(Language::French);
+    assert!(matches!(res, Err(ExchangeError::MissingTarget(_))));
+    assert!(!project.path("po_fr/a.txt").exists());
+}
+
+#[test]
+fn the_files_in_the_po_file_are_not_missing() {
+    let mut project = ProjectBuilder::new("po")
+        .lang(Language::French)
+        .translatable_file("a.txt", "Hello")
+        .translatable_file("b.txt", "World")
+        .synced()
+        .build()
+        .unwrap();
+    project.set_workflow(Workflow::Gettext).unwrap();
+    // an entry still waiting for its review counts as a translation too
+    let entries = [
+        entry("a.txt", "Hello", "Bonjour"),
+        entry("b.txt", "World", ""),
+    ];
+    let po_path = project.get_po_path(Some(Language::French));
+    std::fs::create_dir_all(po_path.parent().unwrap()).unwrap();
+    std::fs::write(&po_path, write_po("po", Some(Language::French), &entries)).unwrap();
+
+    let translated = project.translate_missing(Language::French).unwrap();
+    assert!(translated.is_empty());
+}
@@ -0,0 +1,109 @@
+use translate_dir_lib::{
+    Language,
+    errors::{language_errors::LanguageError, project_errors::AddLanguageError},
+    testing::fixture::ProjectBuilder,
+};
+
+#[test]
+fn parses_the_names_and_the_codes() {
+    for name in ["French", "french", "français", "fr"] {
+        assert_eq!(name.parse::<Language>(), Ok(Language::French), "{}", name);
+    }
+    assert_eq!(Language::from_code("fr-CA"), Some(Language::French));
+    assert_eq!(Language::from_code("pt_BR"), Some(Language::Portuguese));
+}
+
+#[test]
+fn the_displayed_language_is_parsed_back() {
+    let hawaiian = Language::custom("haw", "Hawaiian", None).unwrap();
+    assert_eq!(hawaiian.to_string(), "haw:Hawaiian");
+    for lang in Language::all().into_iter().chain([hawaiian]) {
+        assert_eq!(
+            lang.to_string().parse::<Language>(),
+            Ok(lang.clone()),
+            "{}",
+            lang
+        );
+    }
+    assert_eq!(
+        "Klingon".parse::<Language>(),
+        Err(LanguageError::UnknownLanguage("Klingon".into()))
+    );
+}
+
+#[test]
+fn lists_the_built_in_languages() {
+    let all = Language::all();
+    assert_eq!(all.len(), 15);
+    assert!(all.contains(&Language::French));
+    assert!(all.contains(&Language::Ukrainian));
+    assert!(
+        all.iter()
+            .all(|l| Language::from_code(l.get_code()) == Some(l.clone()))
+    );
+}
+
+#[test]
+fn a_custom_language_does_not_shadow_a_built_in_one() {
+    let canadian: Language = "fr-CA:Canadian French".parse().unwrap();
+    assert_eq!(canadian.get_code(), "fr-CA");
+    assert_eq!(canadian.get_dir_suffix(), "_fr-ca");
+    assert_eq!(Language::from_code("fr-CA"), Some(Language::French));
+    assert_eq!("fr".parse::<Language>(), Ok(Language::French));
+    assert_eq!(
+        Language::custom("fr", "French", None),
+        Err(LanguageError::BuiltInCode("fr".into()))
+    );
+}
+
+#[test]
+fn the_code_and_the_suffix_of_a_custom_language_are_checked() {
+    assert_eq!(
+        Language::custom("not a code", "Name", None),
+        Err(LanguageError::InvalidCode("not a code".into()))
+    );
+    assert_eq!(
+        Language::custom("haw", "Hawaiian", Some("/../haw")),
+        Err(LanguageError::InvalidSuffix("/../haw".into()))
+    );
+    assert_eq!(
+        Language::custom("haw", " ", None),
+        Err(LanguageError::EmptyName)
+    );
+}
+
+#[test]
+fn the_project_resolves_the_codes_of_its_languages() {
+    let canadian = Language::custom("fr-CA", "Canadian French", None).unwrap();
+    let mut project = ProjectBuilder::new("langs")
+        .lang(Language::French)
+        .lang(canadian.clone())
+        .build()
+        .unwrap();
+    let conf = project.get_config_as_ref();
+    assert_eq!(conf.get_lang_by_code("fr-ca"), Some(canadian));
+    assert_eq!(conf.get_lang_by_code("fr-FR"), Some(Language::French));
+
+    // another language with the same code would be ambiguous
+    let other = Language::custom("fr-CA", "Quebec French", Some("_qc")).unwrap();
+    assert!(matches!(
+        project.add_lang(other),
+        Err(AddLanguageError::LangAlreadyInTheProj)
+    ));
+}
+
+#[test]
+fn a_custom_language_is_planned_by_its_code() {
+    let mut project = ProjectBuilder::new("langs")
+        .translatable_file("a.txt", "Hello")
+        .build()
+        .unwrap();
+    project.add_lang("haw:Hawaiian".parse().unwrap()).unwrap();
+
+    // the code alone doesn't carry the name of the language of the project
+    let parsed: Language = "haw".parse().unwrap();
+    assert!(project.plan_translate(parsed).is_err());
+    let lang = project.get_config_as_ref().get_lang_by_code("haw").unwrap();
+    assert_eq!(lang.get_name(), "Hawaiian");
+    assert_eq!(project.plan_translate(lang).unwrap().files.len(), 1);
+}
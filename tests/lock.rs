@@ -0,0 +1,60 @@
+use translate_dir_lib::{
+    Language,
+    errors::{lock_errors::LockError, project_errors::RemoveLangaugeError},
+    lock::ProjectLock,
+    project::load,
+    testing::fixture::ProjectBuilder,
+};
+
+#[test]
+fn a_change_keeps_the_changes_of_another_instance() {
+    let mut first = ProjectBuilder::new("lock")
+        .lang(Language::French)
+        .build()
+        .unwrap();
+    let mut second = load(first.root().to_path_buf()).unwrap();
+
+    first.set_model("first-model").unwrap();
+    second.set_change_report(true).unwrap();
+
+    let reloaded = load(first.root().to_path_buf()).unwrap();
+    let config = reloaded.get_config_as_ref();
+    assert_eq!(config.get_model(), "first-model");
+    assert!(config.get_change_report());
+}
+
+#[test]
+fn the_lock_is_released_after_each_operation() {
+    let mut project = ProjectBuilder::new("lock")
+        .lang(Language::French)
+        .build()
+        .unwrap();
+    let mut other = load(project.root().to_path_buf()).unwrap();
+    project.add_lang_and_sync(Language::German, None).unwrap();
+    other.sync_files(false).unwrap();
+}
+
+#[test]
+fn another_instance_is_refused_while_the_lock_is_held() {
+    let project = ProjectBuilder::new("lock")
+        .lang(Language::French)
+        .build()
+        .unwrap();
+    let mut other = load(project.root().to_path_buf()).unwrap();
+
+    let held = ProjectLock::open(project.root()).unwrap();
+    let guard = held.acquire().unwrap();
+    assert!(matches!(
+        ProjectLock::open(project.root()).unwrap().acquire(),
+        Err(LockError::Locked)
+    ));
+    assert!(matches!(
+        other.remove_lang(Language::French),
+        Err(RemoveLangaugeError::Locked(LockError::Locked))
+    ));
+    assert!(other.empty_trash().is_err());
+    assert!(other.reset_usage().is_err());
+
+    drop(guard);
+    other.empty_trash().unwrap();
+}
@@ -0,0 +1,119 @@
+use translate_dir_lib::{
+    Language,
+    testing::fixture::ProjectBuilder,
+    translation_memory::{MemoryIndex, TranslationMemory},
+};
+
+#[test]
+fn the_segments_of_a_project_are_shared_with_the_workspace() {
+    let first = ProjectBuilder::new("memory").build().unwrap();
+    let second = ProjectBuilder::new("memory").build().unwrap();
+    let shared = first.path("shared");
+    let first_memory = TranslationMemory::open(first.root()).with_shared(shared.clone());
+    let second_memory = TranslationMemory::open(second.root()).with_shared(shared);
+
+    first_memory
+        .record(
+            "Hello",
+            "Bonjour",
+            Some(Language::English),
+            Language::French,
+            None,
+        )
+        .unwrap();
+    let entry = second_memory.lookup("Hello", Language::French).unwrap();
+    assert_eq!(entry.translation, "Bonjour");
+
+    // the project's own translation overrides the shared one
+    TranslationMemory::open(second.root())
+        .record(
+            "Hello",
+            "Salut",
+            Some(Language::English),
+            Language::French,
+            None,
+        )
+        .unwrap();
+    let entries = second_memory.entries(Language::French).unwrap();
+    assert_eq!(entries.len(), 1);
+    assert_eq!(entries[0].translation, "Salut");
+    assert_eq!(
+        second_memory
+            .lookup("Hello", Language::French)
+            .unwrap()
+            .translation,
+        "Salut"
+    );
+}
+
+#[test]
+fn a_chunk_is_put_together_from_the_translations_of_its_sentences() {
+    let project = ProjectBuilder::new("memory").build().unwrap();
+    let memory = TranslationMemory::open(project.root());
+    for (source, translation) in [("Hello.", "Bonjour."), ("How are you?", "Comment ça va ?")] {
+        memory
+            .record(source, translation, None, Language::French, None)
+            .unwrap();
+    }
+
+    assert_eq!(
+        memory
+            .recall("Hello. How are you?\n\nHello.\n", Language::French, "")
+            .as_deref(),
+        Some("Bonjour. Comment ça va ?\n\nBonjour.\n")
+    );
+    // every sentence must be known
+    assert_eq!(memory.recall("Hello. Goodbye.", Language::French, ""), None);
+}
+
+#[test]
+fn a_machine_translation_is_not_recalled_once_its_prompt_changed() {
+    let project = ProjectBuilder::new("memory").build().unwrap();
+    let memory = TranslationMemory::open(project.root());
+    let context = Some("prompt-a".to_string());
+    memory
+        .record("Hello", "Bonjour", None, Language::French, context)
+        .unwrap();
+
+    assert_eq!(
+        memory
+            .recall("Hello", Language::French, "prompt-a")
+            .as_deref(),
+        Some("Bonjour")
+    );
+    assert_eq!(memory.recall("Hello", Language::French, "prompt-b"), None);
+}
+
+#[test]
+fn the_index_finds_the_near_matches_of_a_similar_length() {
+    let project = ProjectBuilder::new("memory").build().unwrap();
+    let memory = TranslationMemory::open(project.root());
+    let sources = [
+        "one two three four five six seven eight nine ten",
+        "one two three four five six seven eight nine eleven",
+        "one two three",
+    ];
+    for source in sources {
+        memory
+            .record(source, "traduction", None, Language::French, None)
+            .unwrap();
+    }
+    let mut index = memory.index(Language::French).unwrap();
+
+    let matches = index.fuzzy_matches(sources[0], 0.9, 5);
+    assert_eq!(matches.len(), 1);
+    assert_eq!(matches[0].entry.source, sources[1]);
+
+    // an entry recorded later replaces the one of the same source
+    let entry = memory
+        .record(sources[1], "autre", None, Language::French, None)
+        .unwrap();
+    index.insert(entry);
+    let matches = index.fuzzy_matches(sources[0], 0.9, 5);
+    assert_eq!(matches.len(), 1);
+    assert_eq!(matches[0].entry.translation, "autre");
+    assert_eq!(
+        MemoryIndex::default().fuzzy_matches(sources[0], 0.9, 5),
+        Vec::new()
+    );
+}
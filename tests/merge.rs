@@ -0,0 +1,20 @@
+use translate_dir_lib::{Language, testing::fixture::ProjectBuilder};
+
+#[test]
+fn an_edited_translation_of_an_unchanged_source_is_kept() {
+    let mut project = ProjectBuilder::new("merge")
+        .lang(Language::French)
+        .translatable_file("notes.txt", "Hello\n")
+        .translation(Language::French, "notes.txt", "Bonjour\n")
+        .build()
+        .unwrap();
+    std::fs::write(project.path("merge_fr/notes.txt"), "Bonjour à tous\n").unwrap();
+
+    let source = project.path("src/notes.txt");
+    let report = project.merge_file(source, Language::French).unwrap();
+    assert_eq!((report.kept, report.retranslated), (1, 0));
+    assert_eq!(
+        project.read("merge_fr/notes.txt").unwrap(),
+        "Bonjour à tous\n"
+    );
+}
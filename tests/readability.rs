@@ -0,0 +1,23 @@
+use std::path::Path;
+
+use translate_dir_lib::{
+    Language,
+    qa::{
+        QaFindingKind,
+        readability::{ReadabilityThresholds, Register, check_readability},
+    },
+};
+
+#[test]
+fn a_capitalized_informal_address_is_found_in_german() {
+    let thresholds = ReadabilityThresholds {
+        register: Some(Register::Formal),
+        ..Default::default()
+    };
+    let text = "Du findest die Datei hier. Dein Ordner ist leer.";
+
+    let findings = check_readability(Path::new("a.md"), text, Language::German, &thresholds);
+    assert_eq!(findings.len(), 1);
+    assert_eq!(findings[0].kind, QaFindingKind::RegisterMismatch);
+    assert!(findings[0].message.starts_with("2 informal"));
+}
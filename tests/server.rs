@@ -0,0 +1,36 @@
+use translate_dir_lib::{
+    Language, project::load, server::Server, testing::fixture::ProjectBuilder,
+};
+
+#[test]
+fn a_null_id_gets_a_response() {
+    let project = ProjectBuilder::new("server")
+        .lang(Language::French)
+        .build()
+        .unwrap();
+    let mut server = Server::new(load(project.root().to_path_buf()).unwrap());
+
+    let response = server
+        .handle_line(r#"{"jsonrpc": "2.0", "id": null, "method": "status"}"#)
+        .unwrap();
+    assert!(response.contains(r#""id":null"#), "{}", response);
+    // without an id it's a notification
+    assert_eq!(
+        server.handle_line(r#"{"jsonrpc": "2.0", "method": "status"}"#),
+        None
+    );
+}
+
+#[cfg(unix)]
+#[test]
+fn a_file_is_not_replaced_by_the_socket() {
+    let project = ProjectBuilder::new("server")
+        .lang(Language::French)
+        .file("notes.txt", "notes")
+        .build()
+        .unwrap();
+    let mut server = Server::new(load(project.root().to_path_buf()).unwrap());
+
+    assert!(server.serve_unix(&project.path("src/notes.txt")).is_err());
+    assert_eq!(project.read("src/notes.txt").unwrap(), "notes");
+}
@@ -0,0 +1,54 @@
+use std::path::PathBuf;
+
+use translate_dir_lib::{
+    Language, errors::project_errors::SetSourceDirError, testing::fixture::ProjectBuilder,
+};
+
+#[test]
+fn the_status_covers_the_extra_source_directories() {
+    let mut project = ProjectBuilder::new("sources")
+        .lang(Language::French)
+        .file("notes.txt", "notes")
+        .build()
+        .unwrap();
+    std::fs::create_dir(project.path("guides")).unwrap();
+    std::fs::write(project.path("guides/setup.txt"), "setup").unwrap();
+    project.add_source_dir("guides").unwrap();
+
+    let paths: Vec<PathBuf> = project
+        .get_files_status()
+        .unwrap()
+        .into_iter()
+        .map(|status| status.path)
+        .collect();
+    assert_eq!(
+        paths,
+        vec![
+            PathBuf::from("guides/setup.txt"),
+            PathBuf::from("notes.txt")
+        ]
+    );
+}
+
+#[test]
+fn the_source_directory_cannot_mix_with_an_extra_one() {
+    let mut project = ProjectBuilder::new("sources")
+        .lang(Language::French)
+        .file("notes.txt", "notes")
+        .build()
+        .unwrap();
+    std::fs::create_dir(project.path("guides")).unwrap();
+    project.add_source_dir("guides").unwrap();
+    std::fs::create_dir_all(project.path("docs/guides")).unwrap();
+
+    // the mirror of `guides` would mix with the `guides` of the new source directory
+    assert!(matches!(
+        project.set_source_dir("docs", Language::German),
+        Err(SetSourceDirError::NameConflict(name)) if name == "guides"
+    ));
+    std::fs::create_dir(project.path("guides/more")).unwrap();
+    assert!(matches!(
+        project.set_source_dir("guides/more", Language::German),
+        Err(SetSourceDirError::AlreadyInProject)
+    ));
+}
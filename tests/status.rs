@@ -0,0 +1,50 @@
+use std::path::Path;
+
+use translate_dir_lib::{
+    Language,
+    project::Project,
+    status::{FileState, TranslationState},
+    testing::fixture::ProjectBuilder,
+};
+
+fn get_state(project: &Project, path: &str) -> FileState {
+    let files = project.get_files_status().unwrap();
+    let status = files.iter().find(|f| f.path == Path::new(path)).unwrap();
+    status.langs[0].state
+}
+
+#[test]
+fn a_copy_is_stale_when_its_contents_differ_from_the_source() {
+    let project = ProjectBuilder::new("status")
+        .lang(Language::French)
+        .file("notes.txt", "notes")
+        .synced()
+        .build()
+        .unwrap();
+    assert_eq!(get_state(&project, "notes.txt"), FileState::Translated);
+
+    std::fs::write(project.path("src/notes.txt"), "new notes").unwrap();
+    // the copy is written after the source but it's still of the old source
+    let tgt_dir = project
+        .get_config_as_ref()
+        .get_tgt_dir_path_by_lang(&Language::French)
+        .unwrap();
+    std::fs::write(tgt_dir.join("notes.txt"), "notes").unwrap();
+    assert_eq!(get_state(&project, "notes.txt"), FileState::Stale);
+}
+
+#[test]
+fn the_report_and_the_status_agree_on_an_untracked_translation() {
+    let project = ProjectBuilder::new("status")
+        .lang(Language::French)
+        .translatable_file("intro.txt", "Hello")
+        .translation(Language::French, "intro.txt", "Bonjour")
+        .build()
+        .unwrap();
+
+    // the project has no record of translating the file, it's not of the current source
+    assert_eq!(get_state(&project, "intro.txt"), FileState::Stale);
+    let status = project.status().unwrap();
+    assert_eq!(status.entries[0].state, TranslationState::Untracked);
+    assert!(!status.is_clean());
+}
@@ -0,0 +1,80 @@
+use translate_dir_lib::{
+    Language, helper::glob_match, project_config::AssetMode, testing::fixture::ProjectBuilder,
+};
+
+/// Edits a source asset between two syncs in the given asset mode, the second sync must not
+/// take the linked target for a file edited by hand
+fn edit_then_sync(mode: AssetMode) {
+    let mut project = ProjectBuilder::new("assets")
+        .lang(Language::French)
+        .file("img/logo.svg", "<svg/>")
+        .build()
+        .unwrap();
+    project.set_asset_mode(mode).unwrap();
+    project.sync_files(false).unwrap();
+
+    std::fs::write(project.path("src/img/logo.svg"), "<svg>edited</svg>").unwrap();
+    project.sync_files(false).unwrap();
+    assert_eq!(
+        project.read("assets_fr/img/logo.svg").unwrap(),
+        "<svg>edited</svg>"
+    );
+}
+
+#[test]
+fn edited_source_syncs_with_symlinks() {
+    edit_then_sync(AssetMode::Symlink);
+}
+
+#[test]
+fn edited_source_syncs_with_hardlinks() {
+    edit_then_sync(AssetMode::Hardlink);
+}
+
+#[test]
+fn edited_copy_is_not_overwritten() {
+    let mut project = ProjectBuilder::new("copies")
+        .lang(Language::French)
+        .file("notes.txt", "source")
+        .synced()
+        .build()
+        .unwrap();
+    std::fs::write(project.path("copies_fr/notes.txt"), "edited by hand").unwrap();
+    std::fs::write(project.path("src/notes.txt"), "new source").unwrap();
+    assert!(project.sync_files(false).is_err());
+    project.sync_files(true).unwrap();
+    assert_eq!(project.read("copies_fr/notes.txt").unwrap(), "new source");
+}
+
+#[test]
+fn excluded_paths_are_not_synced() {
+    let mut project = ProjectBuilder::new("exclude")
+        .lang(Language::French)
+        .file("notes.txt", "notes")
+        .file("debug.log", "log")
+        .file("build/out/app.bin", "bin")
+        .build()
+        .unwrap();
+    let patterns = vec!["*.log".to_string(), "build/**".to_string()];
+    assert!(glob_match("build/**", "build/out/app.bin"));
+    assert!(!glob_match("*.log", "logs/notes.txt"));
+    project.set_exclude_patterns(patterns).unwrap();
+    project.sync_files(false).unwrap();
+    assert!(project.path("exclude_fr/notes.txt").exists());
+    assert!(!project.path("exclude_fr/debug.log").exists());
+    assert!(!project.path("exclude_fr/build/out/app.bin").exists());
+}
+
+#[test]
+fn a_language_is_added_and_synced_into_its_directory() {
+    let mut project = ProjectBuilder::new("added")
+        .lang(Language::French)
+        .file("img/logo.svg", "<svg/>")
+        .build()
+        .unwrap();
+    let report = project
+        .add_lang_and_sync(Language::German, Some("content/de"))
+        .unwrap();
+    assert_eq!(report.copied, vec![project.path("content/de/img/logo.svg")]);
+    assert!(!project.path("added_fr/img/logo.svg").exists());
+}
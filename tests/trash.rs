@@ -0,0 +1,47 @@
+use translate_dir_lib::{Language, testing::fixture::ProjectBuilder};
+
+#[test]
+fn an_untracked_file_is_restored_from_the_trash() {
+    let mut project = ProjectBuilder::new("trash")
+        .lang(Language::French)
+        .file("notes.txt", "notes")
+        .target_file(Language::French, "old.txt", "old")
+        .build()
+        .unwrap();
+    project.set_use_trash(true).unwrap();
+    let report = project.sync_files(false).unwrap();
+    assert_eq!(report.removed, vec![project.path("trash_fr/old.txt")]);
+    assert!(!project.path("trash_fr/old.txt").exists());
+
+    let entries = project.list_trash().unwrap();
+    assert_eq!(entries.len(), 1);
+    let restored = project.restore_trash(&entries[0]).unwrap();
+    assert_eq!(restored, vec![project.path("trash_fr/old.txt")]);
+    assert_eq!(project.read("trash_fr/old.txt").unwrap(), "old");
+    assert!(project.list_trash().unwrap().is_empty());
+}
+
+#[test]
+fn only_a_listed_entry_is_restored() {
+    let project = ProjectBuilder::new("trash")
+        .lang(Language::French)
+        .build()
+        .unwrap();
+    assert!(project.restore_trash("../../elsewhere").is_err());
+}
+
+#[test]
+fn replaced_copies_are_not_kept_in_the_trash() {
+    let mut project = ProjectBuilder::new("trash")
+        .lang(Language::French)
+        .file("notes.txt", "notes")
+        .build()
+        .unwrap();
+    project.set_use_trash(true).unwrap();
+    project.sync_files(false).unwrap();
+
+    std::fs::write(project.path("src/notes.txt"), "new notes").unwrap();
+    project.sync_files(false).unwrap();
+    assert_eq!(project.read("trash_fr/notes.txt").unwrap(), "new notes");
+    assert!(project.list_trash().unwrap().is_empty());
+}
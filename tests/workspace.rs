@@ -0,0 +1,30 @@
+use translate_dir_lib::{
+    Language, errors::workspace_errors::WorkspaceError, project::init,
+    testing::fixture::ProjectBuilder, workspace::Workspace,
+};
+
+#[test]
+fn a_broken_project_does_not_hide_the_others() {
+    let project = ProjectBuilder::new("workspace")
+        .lang(Language::French)
+        .build()
+        .unwrap();
+    let packages = project.path("packages");
+    std::fs::create_dir_all(packages.join("good")).unwrap();
+    init("good", packages.join("good")).unwrap();
+    std::fs::create_dir_all(packages.join("broken")).unwrap();
+    std::fs::write(packages.join("broken/trans_conf.json"), "{").unwrap();
+
+    let workspace = Workspace::discover(packages).unwrap();
+    assert_eq!(workspace.get_projects_as_ref().len(), 1);
+    assert!(matches!(
+        workspace.get_errors_as_ref().as_slice(),
+        [WorkspaceError::LoadProjectError(path, _)] if path.ends_with("broken")
+    ));
+
+    // the project without a source directory has no status
+    let status = workspace.status();
+    assert!(status.projects.is_empty());
+    assert_eq!(status.errors.len(), 1);
+    assert!(!status.is_clean());
+}